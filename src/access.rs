@@ -0,0 +1,179 @@
+//! Access to any key by path.
+
+use crate::{
+    DESKTOP_ENTRY_GROUP, DesktopEntry, DesktopEntryError, Entry, KeyValues, Locale, RawGroups,
+    Result, escape_value,
+};
+
+impl DesktopEntry {
+    /// Gets the value of any key, with escape sequences decoded.
+    ///
+    /// Keys are addressed by path: `Key` for a key of `[Desktop Entry]` and
+    /// `Group/Key` for a key of another group, such as
+    /// `Desktop Action new-window/Name`. Standard keys, unknown keys, and
+    /// keys of additional groups are all read from the entry as it would be
+    /// serialized, so lists come back as `a;b`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, Locale};
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nStartupWMClass=app-main\n\
+    ///      Comment=Does things\nComment[de]=Macht Dinge\nActions=new;\n\n\
+    ///      [Desktop Action new]\nName=New\nExec=app --new\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(entry.get("StartupWMClass").as_deref(), Some("app-main"));
+    /// assert_eq!(entry.get("Desktop Action new/Name").as_deref(), Some("New"));
+    /// assert_eq!(
+    ///     entry.get_localized("Comment", &Locale::from_string("de_AT")).as_deref(),
+    ///     Some("Macht Dinge")
+    /// );
+    ///
+    /// entry.set("X-MyKey", "on").unwrap();
+    /// entry.set("Terminal", "true").unwrap();
+    /// entry.set("Desktop Action new/Exec", "app --new-window").unwrap();
+    /// assert_eq!(entry.unknown_string("X-MyKey").as_deref(), Some("on"));
+    /// assert_eq!(entry.terminal, Some(true));
+    /// assert!(entry.set("Type", "Service").is_err());
+    /// ```
+    pub fn get(&self, path: &str) -> Option<String> {
+        let (group, key) = split_path(path);
+        let groups = self.raw_groups().ok()?;
+        KeyValues(groups.get(group)?).string(key)
+    }
+
+    /// Gets the value of any key resolved for a locale, falling back to the
+    /// unlocalized value.
+    ///
+    /// See [`get`](Self::get) for how keys are addressed.
+    pub fn get_localized(&self, path: &str, locale: &Locale) -> Option<String> {
+        let (group, key) = split_path(path);
+        let groups = self.raw_groups().ok()?;
+        KeyValues(groups.get(group)?).localestring(key, locale)
+    }
+
+    /// Sets the unlocalized value of any key, keeping its translations.
+    ///
+    /// The group is created if it does not exist. Values of standard keys
+    /// are parsed into their fields as [`parse`](Self::parse) does, so
+    /// `set("Terminal", "true")` sets [`terminal`](Self::terminal). See
+    /// [`get`](Self::get) for how keys are addressed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::InvalidValue`] if the path does not name
+    /// a valid group and key, or if `Type` is set to an unknown type; the
+    /// entry is then left untouched.
+    pub fn set(&mut self, path: &str, value: impl Into<String>) -> Result<()> {
+        self.set_entry(path, None, value.into())
+    }
+
+    /// Sets the translation of any key for a locale.
+    ///
+    /// See [`set`](Self::set).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::InvalidValue`] if the path does not name
+    /// a valid group and key.
+    pub fn set_localized(
+        &mut self,
+        path: &str,
+        locale: Locale,
+        value: impl Into<String>,
+    ) -> Result<()> {
+        self.set_entry(path, Some(locale), value.into())
+    }
+
+    /// Removes a key of any group with all of its translations, returning
+    /// whether it existed.
+    ///
+    /// Removing a standard key clears its field; removing the last key of a
+    /// group keeps the empty group. Use [`remove_group`](Self::remove_group)
+    /// or [`remove_action`](Self::remove_action) to drop whole groups.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::MissingRequiredKey`] when removing `Type`
+    /// or `Name` from `[Desktop Entry]`; the entry is then left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nTerminal=false\n\
+    ///      X-Tag=a\nX-Tag[de]=b\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert!(entry.remove_key("Desktop Entry", "Terminal").unwrap());
+    /// assert!(entry.remove_key("Desktop Entry", "X-Tag").unwrap());
+    /// assert!(!entry.remove_key("Desktop Entry", "X-Tag").unwrap());
+    /// assert!(entry.remove_key("Desktop Entry", "Name").is_err());
+    /// assert_eq!(entry.terminal, None);
+    /// assert!(entry.unknown_keys.is_empty());
+    /// ```
+    pub fn remove_key(&mut self, group: &str, key: &str) -> Result<bool> {
+        let mut removed = false;
+        self.edit_raw_groups(|groups| {
+            removed = groups
+                .get_mut(group)
+                .is_some_and(|entries| entries.remove(key).is_some());
+            Ok(())
+        })?;
+        Ok(removed)
+    }
+
+    fn set_entry(&mut self, path: &str, locale: Option<Locale>, value: String) -> Result<()> {
+        let (group, key) = split_path(path);
+        if !is_valid_group_name(group) || !is_valid_key(key) {
+            return Err(DesktopEntryError::InvalidValue(
+                path.to_string(),
+                "not a valid group and key".to_string(),
+            ));
+        }
+
+        self.edit_raw_groups(|groups: &mut RawGroups| {
+            let entries = groups
+                .entry(group.to_string())
+                .or_default()
+                .entry(key.to_string())
+                .or_default();
+            entries.retain(|entry| entry.locale != locale);
+            let entry = Entry {
+                key: key.to_string(),
+                locale,
+                value: escape_value(&value),
+                span: None,
+            };
+            if entry.locale.is_none() {
+                entries.insert(0, entry);
+            } else {
+                entries.push(entry);
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Splits a key path into its group and key. Keys cannot contain `/`, so
+/// the group is everything before the last one.
+fn split_path(path: &str) -> (&str, &str) {
+    path.rsplit_once('/').unwrap_or((DESKTOP_ENTRY_GROUP, path))
+}
+
+/// Whether a key name is made of `A-Za-z0-9-` only.
+pub(crate) fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Whether a group name can be written in a group header.
+pub(crate) fn is_valid_group_name(group: &str) -> bool {
+    !group.is_empty() && !group.contains(['[', ']']) && !group.chars().any(char::is_control)
+}
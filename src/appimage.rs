@@ -0,0 +1,272 @@
+//! Desktop integration of AppImages.
+//!
+//! An AppImage carries its desktop file and icon at the root of the SquashFS
+//! image appended to its runtime. [`extract`] reads them without running the
+//! AppImage and points the command lines of the entry at the AppImage, so
+//! the entry can be installed as is.
+//!
+//! Only type 2 AppImages are supported; the obsolete ISO 9660 based type 1
+//! is rejected.
+//!
+//! Requires the `appimage` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use xdg_desktop_entry::appimage;
+//!
+//! let app = appimage::extract("/home/user/Apps/Krita-x86_64.AppImage").unwrap();
+//! println!("{}", app.entry.exec.as_deref().unwrap());
+//! if let Some(icon) = &app.icon {
+//!     std::fs::write(format!("/tmp/{}", icon.file_name), &icon.data).unwrap();
+//! }
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+use backhand::{FilesystemReader, InnerNode, Node, SquashfsFileReader};
+
+use crate::exec::{quote_arg, quote_word, split_words};
+use crate::{ACTION_GROUP_PREFIX, DesktopEntry, Entry, Result, escape_value};
+
+/// Icon formats looked up next to the desktop file, in order of preference.
+const ICON_EXTENSIONS: &[&str] = &["png", "svg", "svgz", "xpm"];
+
+/// Symbolic links followed when resolving the icon.
+const MAX_SYMLINKS: usize = 8;
+
+/// The desktop entry and icon of an AppImage.
+#[derive(Debug, Clone)]
+pub struct AppImage {
+    /// Absolute path of the AppImage.
+    pub path: PathBuf,
+    /// File name of the embedded desktop file, e.g. `org.kde.krita.desktop`.
+    pub desktop_file_name: String,
+    /// The embedded entry, with `Exec` and `TryExec` pointing at the AppImage.
+    pub entry: DesktopEntry,
+    /// The embedded icon, if any.
+    pub icon: Option<AppImageIcon>,
+}
+
+/// An icon embedded in an AppImage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppImageIcon {
+    /// File name to install the icon under: the entry's `Icon` name with the
+    /// extension of the icon format, e.g. `krita.png`.
+    pub file_name: String,
+    /// Contents of the icon file.
+    pub data: Vec<u8>,
+}
+
+/// Extracts the desktop entry and icon of an AppImage.
+///
+/// The program of every `Exec` line, including those of actions, is
+/// replaced by the absolute path of the AppImage, keeping the arguments,
+/// and `TryExec` is set to that path. The icon is the file named by the
+/// `Icon` key next to the desktop file, or else `.DirIcon`.
+///
+/// # Errors
+///
+/// Returns an I/O error if the file cannot be read or is not a type 2
+/// AppImage, and a parse error if the embedded desktop file is invalid.
+pub fn extract(path: impl AsRef<Path>) -> Result<AppImage> {
+    let path = std::path::absolute(path.as_ref())?;
+    let mut file = File::open(&path)?;
+    let offset = squashfs_offset(&mut file)?;
+    let filesystem = FilesystemReader::from_reader_with_offset(BufReader::new(file), offset)
+        .map_err(io::Error::other)?;
+
+    let desktop_file = filesystem
+        .files()
+        .find(|node| {
+            node.fullpath.parent() == Some(Path::new("/"))
+                && node
+                    .fullpath
+                    .extension()
+                    .is_some_and(|extension| extension == "desktop")
+        })
+        .ok_or_else(|| invalid_appimage("no desktop file at the root of the image"))?;
+    let desktop_file_name = desktop_file
+        .fullpath
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let content = String::from_utf8(read_file(&filesystem, desktop_file)?)
+        .map_err(|_| crate::DesktopEntryError::InvalidUtf8)?;
+    let mut entry = DesktopEntry::parse(&content)?;
+
+    let icon = extract_icon(&filesystem, &entry)?;
+    point_at_appimage(&mut entry, &path)?;
+
+    Ok(AppImage {
+        path,
+        desktop_file_name,
+        entry,
+        icon,
+    })
+}
+
+/// Finds where the SquashFS image starts: right after the section header
+/// table of the ELF runtime.
+fn squashfs_offset(file: &mut File) -> Result<u64> {
+    let mut header = [0u8; 64];
+    file.read_exact(&mut header)
+        .map_err(|_| invalid_appimage("file too short"))?;
+    if &header[..4] != b"\x7fELF" {
+        return Err(invalid_appimage("not an ELF executable"));
+    }
+    if &header[8..11] == b"AI\x01" {
+        return Err(invalid_appimage("type 1 AppImages are not supported"));
+    }
+
+    let little_endian = header[5] == 1;
+    let read = |range: std::ops::Range<usize>| {
+        let bytes = &header[range];
+        let fold = |value: u64, byte: &u8| value << 8 | u64::from(*byte);
+        if little_endian {
+            bytes.iter().rev().fold(0, fold)
+        } else {
+            bytes.iter().fold(0, fold)
+        }
+    };
+    let (shoff, shentsize, shnum) = match header[4] {
+        1 => (read(0x20..0x24), read(0x2E..0x30), read(0x30..0x32)),
+        2 => (read(0x28..0x30), read(0x3A..0x3C), read(0x3C..0x3E)),
+        _ => return Err(invalid_appimage("unknown ELF class")),
+    };
+    shentsize
+        .checked_mul(shnum)
+        .and_then(|size| shoff.checked_add(size))
+        .ok_or_else(|| invalid_appimage("section header table out of range"))
+}
+
+/// Replaces the program of the command lines with the AppImage.
+fn point_at_appimage(entry: &mut DesktopEntry, appimage: &Path) -> Result<()> {
+    let appimage = appimage.to_string_lossy();
+    let rewrite = |exec: &str| -> Result<String> {
+        let args = split_words(exec)?;
+        Ok(std::iter::once(quote_arg(&appimage))
+            .chain(args.iter().skip(1).map(|arg| quote_word(arg)))
+            .collect::<Vec<_>>()
+            .join(" "))
+    };
+
+    if let Some(exec) = &entry.exec {
+        entry.exec = Some(rewrite(exec)?);
+    }
+    entry.try_exec = Some(appimage.to_string());
+    for (name, group) in &mut entry.additional_groups {
+        if !name.starts_with(ACTION_GROUP_PREFIX) {
+            continue;
+        }
+        if let Some(exec) = group.get_string("Exec") {
+            let value = escape_value(&rewrite(&exec)?);
+            group.entries.insert(
+                "Exec".to_string(),
+                vec![Entry {
+                    key: "Exec".to_string(),
+                    locale: None,
+                    value,
+                    span: None,
+                }],
+            );
+        }
+    }
+    Ok(())
+}
+
+fn extract_icon(
+    filesystem: &FilesystemReader,
+    entry: &DesktopEntry,
+) -> Result<Option<AppImageIcon>> {
+    let name = entry
+        .icon
+        .as_ref()
+        .map(|icon| icon.default.to_string())
+        .filter(|name| !name.is_empty() && !name.contains('/'));
+
+    if let Some(name) = &name {
+        for extension in ICON_EXTENSIONS {
+            let candidate = Path::new("/").join(format!("{name}.{extension}"));
+            if let Some(node) = resolve(filesystem, &candidate) {
+                return Ok(Some(AppImageIcon {
+                    file_name: format!("{name}.{extension}"),
+                    data: read_file(filesystem, node)?,
+                }));
+            }
+        }
+    }
+
+    let Some(node) = resolve(filesystem, Path::new("/.DirIcon")) else {
+        return Ok(None);
+    };
+    let data = read_file(filesystem, node)?;
+    let extension = if data.starts_with(b"\x89PNG") {
+        "png"
+    } else if data.starts_with(b"\x1f\x8b") {
+        "svgz"
+    } else if data.starts_with(b"/* XPM */") {
+        "xpm"
+    } else {
+        "svg"
+    };
+    let stem = name.unwrap_or_else(|| "icon".to_string());
+    Ok(Some(AppImageIcon {
+        file_name: format!("{stem}.{extension}"),
+        data,
+    }))
+}
+
+/// Finds a regular file, following symbolic links within the image.
+fn resolve<'a>(
+    filesystem: &'a FilesystemReader,
+    path: &Path,
+) -> Option<&'a Node<SquashfsFileReader>> {
+    let mut path = path.to_path_buf();
+    for _ in 0..MAX_SYMLINKS {
+        let node = filesystem.files().find(|node| node.fullpath == path)?;
+        match &node.inner {
+            InnerNode::File(_) => return Some(node),
+            InnerNode::Symlink(symlink) => {
+                path = normalize(&path.parent()?.join(&symlink.link));
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Resolves `.` and `..` in an absolute path inside the image.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::from("/");
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::Normal(part) => normalized.push(part),
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+    normalized
+}
+
+fn read_file(filesystem: &FilesystemReader, node: &Node<SquashfsFileReader>) -> Result<Vec<u8>> {
+    let InnerNode::File(file) = &node.inner else {
+        return Err(invalid_appimage("not a regular file"));
+    };
+    let mut data = Vec::new();
+    filesystem.file(file).reader().read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn invalid_appimage(reason: &str) -> crate::DesktopEntryError {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid AppImage: {reason}"),
+    )
+    .into()
+}
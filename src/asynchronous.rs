@@ -0,0 +1,100 @@
+//! Asynchronous parsing and discovery on the tokio runtime.
+//!
+//! These mirror the blocking functions of the crate, reading files with
+//! `tokio::fs` so GUI shells built on an async runtime can call them from
+//! their tasks directly. With the `dbus` feature,
+//! `DesktopEntry::launch_dbus_async` and
+//! `DesktopEntry::launch_dbus_action_async` activate applications without
+//! blocking as well.
+//!
+//! Requires the `async` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # async fn example() {
+//! use xdg_desktop_entry::asynchronous;
+//!
+//! for (id, _, entry) in asynchronous::find_all_desktop_entries().await {
+//!     println!("{}: {}", id, entry.name.default);
+//! }
+//! # }
+//! ```
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::discovery::{self, DESKTOP_EXTENSION, DesktopFileId};
+use crate::{DesktopEntry, Result};
+
+impl DesktopEntry {
+    /// Parses a desktop entry file without blocking.
+    ///
+    /// See [`parse_file`](Self::parse_file).
+    pub async fn parse_file_async(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = tokio::fs::read_to_string(path).await?;
+        let mut entry = Self::parse(&content)?;
+        entry.source_path = Some(path.to_path_buf());
+        Ok(entry)
+    }
+}
+
+/// Finds all installed desktop entries without blocking.
+///
+/// See [`discovery::find_all_desktop_entries`].
+pub async fn find_all_desktop_entries() -> Vec<(DesktopFileId, PathBuf, DesktopEntry)> {
+    find_desktop_entries_in(discovery::application_dirs()).await
+}
+
+/// Finds the desktop entries in the given `applications` directories, most
+/// important first, without blocking.
+///
+/// Follows the precedence and shadowing rules of
+/// [`ApplicationIter`](discovery::ApplicationIter).
+pub async fn find_desktop_entries_in(
+    dirs: impl IntoIterator<Item = PathBuf>,
+) -> Vec<(DesktopFileId, PathBuf, DesktopEntry)> {
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+    for dir in dirs {
+        let mut files = collect_desktop_files(&dir).await;
+        files.sort();
+        for (id, path) in files {
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            match DesktopEntry::parse_file_async(&path).await {
+                Ok(entry) if entry.hidden != Some(true) => found.push((id, path, entry)),
+                _ => continue,
+            }
+        }
+    }
+    found
+}
+
+/// Lists the desktop files below `root` with their IDs.
+async fn collect_desktop_files(root: &Path) -> Vec<(DesktopFileId, PathBuf)> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(mut read_dir) = tokio::fs::read_dir(&dir).await else {
+            continue;
+        };
+        while let Ok(Some(dir_entry)) = read_dir.next_entry().await {
+            let path = dir_entry.path();
+            if tokio::fs::metadata(&path)
+                .await
+                .is_ok_and(|metadata| metadata.is_dir())
+            {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == DESKTOP_EXTENSION)
+                && let Ok(relative) = path.strip_prefix(root)
+                && let Some(id) = DesktopFileId::from_relative_path(relative)
+            {
+                files.push((id, path));
+            }
+        }
+    }
+    files
+}
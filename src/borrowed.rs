@@ -0,0 +1,205 @@
+//! Zero-copy parsing of desktop entries.
+//!
+//! A [`DesktopEntryRef`] borrows every group name, key, locale, and value
+//! from the parsed text instead of copying them into owned strings and
+//! hash maps. Launchers scanning whole application directories at startup
+//! can read the keys they need from it and convert only the entries they
+//! keep with [`DesktopEntryRef::to_owned_entry`].
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::borrowed::DesktopEntryRef;
+//! use xdg_desktop_entry::{DesktopEntryType, Locale};
+//!
+//! let content = "[Desktop Entry]\nType=Application\nName=Viewer\nName[de]=Betrachter\nExec=viewer %f\n";
+//! let entry = DesktopEntryRef::parse(content).unwrap();
+//!
+//! assert_eq!(entry.entry_type(), DesktopEntryType::Application);
+//! assert_eq!(entry.get("Exec"), Some("viewer %f"));
+//! assert_eq!(entry.name().get(&Locale::from_string("de_AT")), "Betrachter");
+//! ```
+
+use std::borrow::Cow;
+
+use crate::events::{EntryEvent, EntryEvents};
+use crate::{
+    DESKTOP_ENTRY_GROUP, DesktopEntry, DesktopEntryError, DesktopEntryType, Locale, Localized,
+    Result, unescape_value,
+};
+
+/// A key-value pair borrowed from the parsed text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryRef<'a> {
+    /// Name of the key, without the locale
+    pub key: &'a str,
+    /// Locale in brackets, if any
+    pub locale: Option<&'a str>,
+    /// Raw value, with escape sequences as written
+    pub value: &'a str,
+}
+
+/// A group borrowed from the parsed text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupRef<'a> {
+    /// Name of the group, without the brackets
+    pub name: &'a str,
+    /// Key-value pairs in file order
+    pub entries: Vec<EntryRef<'a>>,
+}
+
+impl<'a> GroupRef<'a> {
+    /// Gets the raw, unlocalized value of a key.
+    ///
+    /// When a key appears more than once, the first value is returned, as
+    /// [`DesktopEntry::parse`] does.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.key == key && entry.locale.is_none())
+            .map(|entry| entry.value)
+    }
+
+    /// Gets a `string` key, with escape sequences decoded.
+    ///
+    /// Values without escape sequences are borrowed.
+    pub fn get_string(&self, key: &str) -> Option<Cow<'a, str>> {
+        self.get(key).map(unescape)
+    }
+
+    /// Gets the raw values of a localized key, or `None` if it has no
+    /// unlocalized value.
+    ///
+    /// The result applies the spec's matching rules with
+    /// [`Localized::get`].
+    pub fn localized(&self, key: &str) -> Option<Localized<&'a str>> {
+        let mut localized = Localized::new(self.get(key)?);
+        for entry in &self.entries {
+            if entry.key == key
+                && let Some(locale) = entry.locale
+            {
+                localized
+                    .localized
+                    .entry(Locale::from_string(locale))
+                    .or_insert(entry.value);
+            }
+        }
+        Some(localized)
+    }
+}
+
+/// A desktop entry borrowing its values from the parsed text.
+///
+/// Parsing checks the structure of the file and the required `Type` and
+/// `Name` keys, like [`DesktopEntry::parse`] with the default options;
+/// other keys are not interpreted until they are read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopEntryRef<'a> {
+    content: &'a str,
+    entry_type: DesktopEntryType,
+    groups: Vec<GroupRef<'a>>,
+}
+
+impl<'a> DesktopEntryRef<'a> {
+    /// Parses a desktop entry without copying its values.
+    pub fn parse(content: &'a str) -> Result<Self> {
+        let mut groups: Vec<GroupRef<'a>> = Vec::new();
+        for event in EntryEvents::new(content) {
+            match event? {
+                EntryEvent::GroupStart { name, .. } => {
+                    if groups.iter().any(|group| group.name == name) {
+                        return Err(DesktopEntryError::DuplicateGroup(name.to_string()));
+                    }
+                    groups.push(GroupRef {
+                        name,
+                        entries: Vec::new(),
+                    });
+                }
+                EntryEvent::KeyValue {
+                    key, locale, value, ..
+                } => {
+                    // Keys before the first group are reported by the events
+                    if let Some(group) = groups.last_mut() {
+                        group.entries.push(EntryRef { key, locale, value });
+                    }
+                }
+                EntryEvent::Comment { .. } => {}
+            }
+        }
+
+        let desktop_entry = groups
+            .iter()
+            .find(|group| group.name == DESKTOP_ENTRY_GROUP)
+            .ok_or(DesktopEntryError::MissingDesktopEntryGroup)?;
+        let type_value = desktop_entry
+            .get("Type")
+            .ok_or_else(|| DesktopEntryError::MissingRequiredKey("Type".to_string()))?;
+        let entry_type: DesktopEntryType = type_value.parse()?;
+        if desktop_entry.get("Name").is_none() {
+            return Err(DesktopEntryError::MissingRequiredKey("Name".to_string()));
+        }
+
+        Ok(Self {
+            content,
+            entry_type,
+            groups,
+        })
+    }
+
+    /// The parsed text.
+    pub fn content(&self) -> &'a str {
+        self.content
+    }
+
+    /// The type of the entry.
+    pub fn entry_type(&self) -> DesktopEntryType {
+        self.entry_type
+    }
+
+    /// All groups in file order, including `[Desktop Entry]`.
+    pub fn groups(&self) -> &[GroupRef<'a>] {
+        &self.groups
+    }
+
+    /// Gets a group by name.
+    pub fn group(&self, name: &str) -> Option<&GroupRef<'a>> {
+        self.groups.iter().find(|group| group.name == name)
+    }
+
+    /// The `[Desktop Entry]` group.
+    pub fn desktop_entry(&self) -> &GroupRef<'a> {
+        // Checked by `parse`
+        self.group(DESKTOP_ENTRY_GROUP).unwrap()
+    }
+
+    /// Gets the raw, unlocalized value of a key of `[Desktop Entry]`.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.desktop_entry().get(key)
+    }
+
+    /// Gets a `string` key of `[Desktop Entry]`, with escape sequences
+    /// decoded.
+    pub fn get_string(&self, key: &str) -> Option<Cow<'a, str>> {
+        self.desktop_entry().get_string(key)
+    }
+
+    /// The raw `Name` values of the entry.
+    pub fn name(&self) -> Localized<&'a str> {
+        // Checked by `parse`
+        self.desktop_entry().localized("Name").unwrap()
+    }
+
+    /// Parses the text into an owned [`DesktopEntry`].
+    pub fn to_owned_entry(&self) -> Result<DesktopEntry> {
+        DesktopEntry::parse(self.content)
+    }
+}
+
+/// Decodes escape sequences, borrowing values without any.
+fn unescape(value: &str) -> Cow<'_, str> {
+    if value.contains('\\') {
+        Cow::Owned(unescape_value(value))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
@@ -0,0 +1,253 @@
+//! Fluent construction of desktop entries.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::{DesktopEntry, DesktopEntryType};
+//!
+//! let entry = DesktopEntry::builder(DesktopEntryType::Application)
+//!     .name("Foo")
+//!     .exec("foo %U")
+//!     .categories(["Utility"])
+//!     .terminal(false)
+//!     .action("new-window", "New Window", Some("foo --new-window"))
+//!     .build()
+//!     .unwrap();
+//!
+//! assert_eq!(entry.name.default, "Foo");
+//! assert_eq!(entry.actions.as_deref(), Some(&["new-window".to_string()][..]));
+//!
+//! // A link without a URL is rejected
+//! assert!(DesktopEntry::builder(DesktopEntryType::Link).name("Home").build().is_err());
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    ACTION_GROUP_PREFIX, DesktopEntry, DesktopEntryError, DesktopEntryType, Entry, Group,
+    IconString, Locale, LocalizedString, LocalizedStringList, Result, escape_value,
+};
+
+/// Builder for [`DesktopEntry`], created by [`DesktopEntry::builder`].
+///
+/// [`build`](Self::build) checks the entry with
+/// [`DesktopEntry::validate`], so requirements depending on the type, such
+/// as `URL` for links, are enforced.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct DesktopEntryBuilder {
+    entry: DesktopEntry,
+    has_name: bool,
+}
+
+impl DesktopEntryBuilder {
+    /// Starts an entry of the given type.
+    pub fn new(entry_type: DesktopEntryType) -> Self {
+        Self {
+            entry: DesktopEntry::new(entry_type, LocalizedString::new("")),
+            has_name: false,
+        }
+    }
+
+    /// Sets the name.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.entry.name.default = name.into();
+        self.has_name = true;
+        self
+    }
+
+    /// Adds a translation of the name.
+    pub fn localized_name(mut self, locale: Locale, name: impl Into<String>) -> Self {
+        self.entry.name.add_localized(locale, name.into());
+        self
+    }
+
+    /// Sets the generic name.
+    pub fn generic_name(mut self, generic_name: impl Into<String>) -> Self {
+        self.entry.generic_name = Some(LocalizedString::new(generic_name));
+        self
+    }
+
+    /// Sets the tooltip.
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.entry.comment = Some(LocalizedString::new(comment));
+        self
+    }
+
+    /// Sets the icon name or path.
+    pub fn icon(mut self, icon: impl Into<String>) -> Self {
+        self.entry.icon = Some(IconString::new(icon));
+        self
+    }
+
+    /// Sets the specification version the entry conforms to.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.entry.version = Some(version.into());
+        self
+    }
+
+    /// Sets `NoDisplay`.
+    pub fn no_display(mut self, no_display: bool) -> Self {
+        self.entry.no_display = Some(no_display);
+        self
+    }
+
+    /// Sets `Hidden`.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.entry.hidden = Some(hidden);
+        self
+    }
+
+    /// Sets the desktops that should display the entry.
+    pub fn only_show_in<I: IntoIterator<Item = S>, S: Into<String>>(mut self, desktops: I) -> Self {
+        self.entry.only_show_in = Some(collect(desktops));
+        self
+    }
+
+    /// Sets the desktops that should not display the entry.
+    pub fn not_show_in<I: IntoIterator<Item = S>, S: Into<String>>(mut self, desktops: I) -> Self {
+        self.entry.not_show_in = Some(collect(desktops));
+        self
+    }
+
+    /// Sets `DBusActivatable`.
+    pub fn dbus_activatable(mut self, dbus_activatable: bool) -> Self {
+        self.entry.dbus_activatable = Some(dbus_activatable);
+        self
+    }
+
+    /// Sets the program checked for to decide whether the application is
+    /// installed.
+    pub fn try_exec(mut self, try_exec: impl Into<String>) -> Self {
+        self.entry.try_exec = Some(try_exec.into());
+        self
+    }
+
+    /// Sets the command line.
+    pub fn exec(mut self, exec: impl Into<String>) -> Self {
+        self.entry.exec = Some(exec.into());
+        self
+    }
+
+    /// Sets the working directory.
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.entry.path = Some(path.into());
+        self
+    }
+
+    /// Sets `Terminal`.
+    pub fn terminal(mut self, terminal: bool) -> Self {
+        self.entry.terminal = Some(terminal);
+        self
+    }
+
+    /// Adds an action with its `[Desktop Action <id>]` group.
+    pub fn action(
+        mut self,
+        id: impl Into<String>,
+        name: impl Into<String>,
+        exec: Option<&str>,
+    ) -> Self {
+        let id = id.into();
+        let group_name = format!("{}{}", ACTION_GROUP_PREFIX, id);
+
+        let mut entries = HashMap::new();
+        let values = [
+            ("Name", Some(name.into())),
+            ("Exec", exec.map(String::from)),
+        ];
+        for (key, value) in values {
+            if let Some(value) = value {
+                let entry = Entry {
+                    key: key.to_string(),
+                    locale: None,
+                    value: escape_value(&value),
+                    span: None,
+                };
+                entries.insert(key.to_string(), vec![entry]);
+            }
+        }
+        self.entry.additional_groups.insert(
+            group_name.clone(),
+            Group {
+                name: group_name,
+                entries,
+                header_span: None,
+            },
+        );
+        self.entry.actions.get_or_insert_with(Vec::new).push(id);
+        self
+    }
+
+    /// Sets the supported MIME types.
+    pub fn mime_types<I: IntoIterator<Item = S>, S: Into<String>>(mut self, mime_types: I) -> Self {
+        self.entry.mime_type = Some(collect(mime_types));
+        self
+    }
+
+    /// Sets the menu categories.
+    pub fn categories<I: IntoIterator<Item = S>, S: Into<String>>(mut self, categories: I) -> Self {
+        self.entry.categories = Some(collect(categories));
+        self
+    }
+
+    /// Sets the implemented interfaces.
+    pub fn implements<I: IntoIterator<Item = S>, S: Into<String>>(mut self, interfaces: I) -> Self {
+        self.entry.implements = Some(collect(interfaces));
+        self
+    }
+
+    /// Sets the search keywords.
+    pub fn keywords<I: IntoIterator<Item = S>, S: Into<String>>(mut self, keywords: I) -> Self {
+        self.entry.keywords = Some(LocalizedStringList::new(collect(keywords)));
+        self
+    }
+
+    /// Sets `StartupNotify`.
+    pub fn startup_notify(mut self, startup_notify: bool) -> Self {
+        self.entry.startup_notify = Some(startup_notify);
+        self
+    }
+
+    /// Sets the WM class the application's windows are expected to have.
+    pub fn startup_wm_class(mut self, wm_class: impl Into<String>) -> Self {
+        self.entry.startup_wm_class = Some(wm_class.into());
+        self
+    }
+
+    /// Sets the target URL of a link.
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.entry.url = Some(url.into());
+        self
+    }
+
+    /// Sets `PrefersNonDefaultGPU`.
+    pub fn prefers_non_default_gpu(mut self, prefers: bool) -> Self {
+        self.entry.prefers_non_default_gpu = Some(prefers);
+        self
+    }
+
+    /// Sets `SingleMainWindow`.
+    pub fn single_main_window(mut self, single: bool) -> Self {
+        self.entry.single_main_window = Some(single);
+        self
+    }
+
+    /// Finishes the entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::MissingRequiredKey`] if no name was set,
+    /// or the error of [`DesktopEntry::validate`].
+    pub fn build(self) -> Result<DesktopEntry> {
+        if !self.has_name {
+            return Err(DesktopEntryError::MissingRequiredKey("Name".to_string()));
+        }
+        self.entry.validate()?;
+        Ok(self.entry)
+    }
+}
+
+fn collect<I: IntoIterator<Item = S>, S: Into<String>>(values: I) -> Vec<String> {
+    values.into_iter().map(Into::into).collect()
+}
@@ -0,0 +1,801 @@
+//! Persistent binary cache of an [`AppDatabase`].
+//!
+//! The cache stores every key of every entry as offsets into a single,
+//! deduplicated string table. Reading a cache validates the file once and then
+//! serves `&str` slices straight out of the file contents, so looking up a few
+//! keys of thousands of cached entries allocates nothing. With the `mmap`
+//! feature, `AppCache::open_mapped` memory-maps the file rather than
+//! reading it into memory.
+//!
+//! # Format
+//!
+//! All integers are little-endian `u32`s.
+//!
+//! ```text
+//! header:  magic "XDGDECCH", version, entry count, key count, string table size
+//! entries: id offset, id length, first key index, key count
+//! keys:    group, key, locale, and value, each as an offset and a length
+//! strings: UTF-8 string table
+//! ```
+//!
+//! A locale offset of `u32::MAX` marks an unlocalized key.
+//!
+//! [`ApplicationCache`] builds on this format to keep the parsed entries of
+//! the applications directories across runs, keyed by path, size, and
+//! modification time.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{self, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::database::AppDatabase;
+use crate::discovery;
+use crate::findings::{RefreshSummary, modified_nanos};
+use crate::{DesktopEntry, DesktopEntryError, Locale, Result};
+
+const MAGIC: &[u8; 8] = b"XDGDECCH";
+const FORMAT_VERSION: u32 = 1;
+const HEADER_LEN: usize = 8 + 4 * 4;
+const ENTRY_RECORD_LEN: usize = 4 * 4;
+const KEY_RECORD_LEN: usize = 8 * 4;
+const NO_LOCALE: u32 = u32::MAX;
+const APPLICATIONS_MAGIC: &[u8; 8] = b"XDGDEAPP";
+const APPLICATIONS_HEADER_LEN: usize = 8 + 2 * 4;
+const NO_MODIFIED: u128 = u128::MAX;
+
+/// Offset and length of a string in the string table.
+#[derive(Debug, Clone, Copy)]
+struct StrRef {
+    offset: u32,
+    len: u32,
+}
+
+/// Backing bytes of a cache.
+#[derive(Debug)]
+enum Storage {
+    Owned(Vec<u8>),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl Deref for Storage {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Owned(bytes) => bytes,
+            #[cfg(feature = "mmap")]
+            Self::Mapped(map) => map,
+        }
+    }
+}
+
+/// A read-only application cache.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::DesktopEntry;
+/// use xdg_desktop_entry::cache::AppCache;
+/// use xdg_desktop_entry::database::AppDatabase;
+///
+/// let mut database = AppDatabase::new();
+/// database.insert(
+///     "org.example.App.desktop",
+///     DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=App\nExec=app\n").unwrap(),
+/// );
+///
+/// let mut bytes = Vec::new();
+/// AppCache::write(&database, &mut bytes).unwrap();
+///
+/// let cache = AppCache::from_bytes(bytes).unwrap();
+/// let entry = cache.get("org.example.App.desktop").unwrap();
+/// assert_eq!(entry.get("Desktop Entry", "Exec"), Some("app"));
+/// ```
+#[derive(Debug)]
+pub struct AppCache {
+    storage: Storage,
+    entry_count: usize,
+    key_count: usize,
+}
+
+impl AppCache {
+    /// Opens a cache file, reading it into memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid cache.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_bytes(fs::read(path)?)
+    }
+
+    /// Opens a cache file by memory-mapping it.
+    ///
+    /// The file is validated once, here; its strings are then served
+    /// without further checks.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified in place or truncated, by this or any
+    /// other process, while the cache is alive. Replacing it with a new file,
+    /// as [`AppCache::write_file`] does, is fine.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid cache.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn open_mapped(path: impl AsRef<Path>) -> Result<Self> {
+        let file = fs::File::open(path)?;
+        // SAFETY: the caller guarantees that the file is not modified while
+        // the map is alive.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Self::from_storage(Storage::Mapped(map))
+    }
+
+    /// Loads a cache from bytes previously produced by [`AppCache::write`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not a valid cache.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        Self::from_storage(Storage::Owned(bytes))
+    }
+
+    fn from_storage(storage: Storage) -> Result<Self> {
+        let bytes: &[u8] = &storage;
+        if bytes.len() < HEADER_LEN || &bytes[..8] != MAGIC {
+            return Err(invalid_cache("bad header"));
+        }
+        if read_u32(bytes, 8) != FORMAT_VERSION {
+            return Err(invalid_cache("unsupported format version"));
+        }
+
+        let entry_count = read_u32(bytes, 12) as usize;
+        let key_count = read_u32(bytes, 16) as usize;
+        let strings_len = read_u32(bytes, 20) as usize;
+        let expected_len = entry_count
+            .checked_mul(ENTRY_RECORD_LEN)
+            .zip(key_count.checked_mul(KEY_RECORD_LEN))
+            .and_then(|(entries, keys)| HEADER_LEN.checked_add(entries)?.checked_add(keys))
+            .and_then(|len| len.checked_add(strings_len));
+        if expected_len != Some(bytes.len()) {
+            return Err(invalid_cache("truncated file"));
+        }
+
+        let cache = Self {
+            storage,
+            entry_count,
+            key_count,
+        };
+        cache.check()?;
+        Ok(cache)
+    }
+
+    /// Checks every record against the string table so accessors never fail.
+    fn check(&self) -> Result<()> {
+        let strings = std::str::from_utf8(self.string_bytes())
+            .map_err(|_| invalid_cache("string table is not valid UTF-8"))?;
+        let check_ref = |r: StrRef| {
+            let start = r.offset as usize;
+            let end = start + r.len as usize;
+            if end <= strings.len()
+                && strings.is_char_boundary(start)
+                && strings.is_char_boundary(end)
+            {
+                Ok(())
+            } else {
+                Err(invalid_cache("string reference out of bounds"))
+            }
+        };
+
+        for index in 0..self.entry_count {
+            let (id, first_key, key_count) = self.entry_record(index);
+            check_ref(id)?;
+            if first_key
+                .checked_add(key_count)
+                .is_none_or(|end| end > self.key_count)
+            {
+                return Err(invalid_cache("key range out of bounds"));
+            }
+            if index > 0 && self.str_at(self.entry_record(index - 1).0) >= self.str_at(id) {
+                return Err(invalid_cache("entries are not sorted"));
+            }
+        }
+        for index in 0..self.key_count {
+            let [group, key, locale, value] = self.key_record(index);
+            check_ref(group)?;
+            check_ref(key)?;
+            check_ref(value)?;
+            if locale.offset != NO_LOCALE {
+                check_ref(locale)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a cache of the database.
+    pub fn write<W: Write>(database: &AppDatabase, writer: &mut W) -> io::Result<()> {
+        let mut strings = StringTable::default();
+        let mut entries = Vec::new();
+        let mut keys = Vec::new();
+
+        for (id, entry) in database.iter() {
+            let first_key = keys.len() as u32;
+            let groups = entry
+                .raw_groups()
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            let mut groups: Vec<_> = groups.into_iter().collect();
+            groups.sort_by(|a, b| a.0.cmp(&b.0));
+            for (group, data) in groups {
+                let mut data: Vec<_> = data.into_iter().collect();
+                data.sort_by(|a, b| a.0.cmp(&b.0));
+                for raw in data.into_iter().flat_map(|(_, raw)| raw) {
+                    let locale = match &raw.locale {
+                        Some(locale) => strings.intern(&locale.to_string_repr()),
+                        None => StrRef {
+                            offset: NO_LOCALE,
+                            len: 0,
+                        },
+                    };
+                    keys.push([
+                        strings.intern(&group),
+                        strings.intern(&raw.key),
+                        locale,
+                        strings.intern(&raw.value),
+                    ]);
+                }
+            }
+            entries.push((strings.intern(id), first_key, keys.len() as u32 - first_key));
+        }
+
+        writer.write_all(MAGIC)?;
+        for value in [
+            FORMAT_VERSION,
+            entries.len() as u32,
+            keys.len() as u32,
+            strings.bytes.len() as u32,
+        ] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        for (id, first_key, key_count) in entries {
+            for value in [id.offset, id.len, first_key, key_count] {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        for refs in keys {
+            for r in refs {
+                writer.write_all(&r.offset.to_le_bytes())?;
+                writer.write_all(&r.len.to_le_bytes())?;
+            }
+        }
+        writer.write_all(&strings.bytes)
+    }
+
+    /// Writes a cache of the database to a file.
+    ///
+    /// The cache is written to a synced temporary file that is then renamed
+    /// over `path`, so readers mapping the old cache are unaffected.
+    pub fn write_file(database: &AppDatabase, path: impl AsRef<Path>) -> Result<()> {
+        crate::write_atomically(path.as_ref(), |mut writer| {
+            Self::write(database, &mut writer)
+        })?;
+        Ok(())
+    }
+
+    /// Number of cached entries.
+    pub fn len(&self) -> usize {
+        self.entry_count
+    }
+
+    /// Whether the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entry_count == 0
+    }
+
+    /// Gets a cached entry by desktop file ID.
+    pub fn get(&self, id: &str) -> Option<CachedEntry<'_>> {
+        let mut low = 0;
+        let mut high = self.entry_count;
+        while low < high {
+            let mid = (low + high) / 2;
+            match self.str_at(self.entry_record(mid).0).cmp(id) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Some(self.entry(mid)),
+            }
+        }
+        None
+    }
+
+    /// Iterates over all cached entries in desktop file ID order.
+    pub fn entries(&self) -> impl Iterator<Item = CachedEntry<'_>> {
+        (0..self.entry_count).map(|index| self.entry(index))
+    }
+
+    /// Parses every cached entry back into an [`AppDatabase`].
+    pub fn to_database(&self) -> Result<AppDatabase> {
+        let mut database = AppDatabase::new();
+        for entry in self.entries() {
+            database.insert(entry.id(), entry.to_desktop_entry()?);
+        }
+        Ok(database)
+    }
+
+    fn entry(&self, index: usize) -> CachedEntry<'_> {
+        let (id, first_key, key_count) = self.entry_record(index);
+        CachedEntry {
+            cache: self,
+            id: self.str_at(id),
+            keys: first_key..first_key + key_count,
+        }
+    }
+
+    fn entry_record(&self, index: usize) -> (StrRef, usize, usize) {
+        let base = HEADER_LEN + index * ENTRY_RECORD_LEN;
+        let bytes: &[u8] = &self.storage;
+        (
+            StrRef {
+                offset: read_u32(bytes, base),
+                len: read_u32(bytes, base + 4),
+            },
+            read_u32(bytes, base + 8) as usize,
+            read_u32(bytes, base + 12) as usize,
+        )
+    }
+
+    fn key_record(&self, index: usize) -> [StrRef; 4] {
+        let base = HEADER_LEN + self.entry_count * ENTRY_RECORD_LEN + index * KEY_RECORD_LEN;
+        let bytes: &[u8] = &self.storage;
+        std::array::from_fn(|field| StrRef {
+            offset: read_u32(bytes, base + field * 8),
+            len: read_u32(bytes, base + field * 8 + 4),
+        })
+    }
+
+    fn string_bytes(&self) -> &[u8] {
+        let start =
+            HEADER_LEN + self.entry_count * ENTRY_RECORD_LEN + self.key_count * KEY_RECORD_LEN;
+        &self.storage[start..]
+    }
+
+    fn str_at(&self, r: StrRef) -> &str {
+        let bytes = &self.string_bytes()[r.offset as usize..(r.offset + r.len) as usize];
+        // SAFETY: `check` verified that the string table is valid UTF-8 and
+        // that every reference starts and ends on a character boundary; the
+        // bytes are owned or, per `open_mapped`, never modified.
+        unsafe { std::str::from_utf8_unchecked(bytes) }
+    }
+}
+
+/// A view of one entry inside an [`AppCache`].
+///
+/// All strings are borrowed from the cache.
+#[derive(Debug, Clone)]
+pub struct CachedEntry<'a> {
+    cache: &'a AppCache,
+    id: &'a str,
+    keys: std::ops::Range<usize>,
+}
+
+impl<'a> CachedEntry<'a> {
+    /// Desktop file ID of the entry.
+    pub fn id(&self) -> &'a str {
+        self.id
+    }
+
+    /// Gets the raw, unlocalized value of a key in a group.
+    pub fn get(&self, group: &str, key: &str) -> Option<&'a str> {
+        self.keys()
+            .find(|k| k.group == group && k.key == key && k.locale.is_none())
+            .map(|k| k.value)
+    }
+
+    /// Gets the raw value of a key for a locale, without fallback.
+    pub fn get_localized(&self, group: &str, key: &str, locale: &Locale) -> Option<&'a str> {
+        let locale = locale.to_string_repr();
+        self.keys()
+            .find(|k| k.group == group && k.key == key && k.locale == Some(locale.as_str()))
+            .map(|k| k.value)
+    }
+
+    /// Iterates over every key of every group of the entry.
+    pub fn keys(&self) -> impl Iterator<Item = CachedKey<'a>> + use<'a> {
+        let cache = self.cache;
+        self.keys.clone().map(move |index| {
+            let [group, key, locale, value] = cache.key_record(index);
+            CachedKey {
+                group: cache.str_at(group),
+                key: cache.str_at(key),
+                locale: (locale.offset != NO_LOCALE).then(|| cache.str_at(locale)),
+                value: cache.str_at(value),
+            }
+        })
+    }
+
+    /// Parses the cached keys into a full [`DesktopEntry`].
+    pub fn to_desktop_entry(&self) -> Result<DesktopEntry> {
+        let mut content = String::new();
+        let mut current_group = None;
+        for key in self.keys() {
+            if current_group != Some(key.group) {
+                content.push_str(&format!("[{}]\n", key.group));
+                current_group = Some(key.group);
+            }
+            match key.locale {
+                Some(locale) => {
+                    content.push_str(&format!("{}[{}]={}\n", key.key, locale, key.value))
+                }
+                None => content.push_str(&format!("{}={}\n", key.key, key.value)),
+            }
+        }
+        DesktopEntry::parse(&content)
+    }
+}
+
+/// Size and modification time of a cached file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<u128>,
+}
+
+impl FileStamp {
+    fn of(metadata: &fs::Metadata) -> Self {
+        Self {
+            len: metadata.len(),
+            modified: modified_nanos(metadata),
+        }
+    }
+
+    /// Whether both stamps are known to describe the same contents.
+    fn matches(&self, other: &Self) -> bool {
+        self.len == other.len && self.modified.is_some() && self.modified == other.modified
+    }
+}
+
+#[derive(Debug)]
+struct CachedFile {
+    stamp: FileStamp,
+    /// Parsed on first access for files loaded from disk
+    entry: OnceLock<Option<DesktopEntry>>,
+}
+
+/// Parsed desktop entries keyed by path, size, and modification time.
+///
+/// Refreshing the cache only parses files that are new or whose size or
+/// modification time changed. Saved caches store the entries in the
+/// [`AppCache`] format, and entries loaded from disk are only parsed when
+/// first accessed, so a launcher starting with an up-to-date cache parses
+/// nothing but the entries it shows.
+///
+/// # Examples
+///
+/// ```no_run
+/// use xdg_desktop_entry::cache::ApplicationCache;
+///
+/// let mut cache = ApplicationCache::load_default();
+/// let summary = cache.refresh_installed();
+/// if summary.added.len() + summary.changed.len() + summary.removed.len() > 0 {
+///     cache.save_default().unwrap();
+/// }
+/// for (path, entry) in cache.iter() {
+///     println!("{}: {}", path.display(), entry.name.default);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ApplicationCache {
+    files: BTreeMap<PathBuf, CachedFile>,
+    stored: Option<AppCache>,
+}
+
+impl ApplicationCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The default cache file,
+    /// `$XDG_CACHE_HOME/xdg-desktop-entry/applications.cache`.
+    pub fn default_path() -> Option<PathBuf> {
+        discovery::cache_home().map(|dir| dir.join("xdg-desktop-entry/applications.cache"))
+    }
+
+    /// Loads the cache from [`default_path`](Self::default_path), starting
+    /// empty if it is missing or invalid.
+    pub fn load_default() -> Self {
+        Self::default_path()
+            .and_then(|path| Self::load(path).ok())
+            .unwrap_or_default()
+    }
+
+    /// Loads a cache saved with [`save`](Self::save).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid cache.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let mut bytes = fs::read(path)?;
+        if bytes.len() < APPLICATIONS_HEADER_LEN || &bytes[..8] != APPLICATIONS_MAGIC {
+            return Err(invalid_cache("bad header"));
+        }
+        if read_u32(&bytes, 8) != FORMAT_VERSION {
+            return Err(invalid_cache("unsupported format version"));
+        }
+
+        let mut files = BTreeMap::new();
+        let mut offset = APPLICATIONS_HEADER_LEN;
+        for _ in 0..read_u32(&bytes, 12) {
+            let path_len = read_bytes(&bytes, offset, 4).map(|b| read_u32(b, 0) as usize)?;
+            let path = read_bytes(&bytes, offset + 4, path_len)?;
+            let path =
+                std::str::from_utf8(path).map_err(|_| invalid_cache("path is not valid UTF-8"))?;
+            let stamp = read_bytes(&bytes, offset + 4 + path_len, 24)?;
+            let modified = u128::from_le_bytes(stamp[8..].try_into().unwrap());
+            let stamp = FileStamp {
+                len: u64::from_le_bytes(stamp[..8].try_into().unwrap()),
+                modified: (modified != NO_MODIFIED).then_some(modified),
+            };
+            files.insert(
+                PathBuf::from(path),
+                CachedFile {
+                    stamp,
+                    entry: OnceLock::new(),
+                },
+            );
+            offset += 4 + path_len + 24;
+        }
+
+        let stored = AppCache::from_bytes(bytes.split_off(offset))?;
+        Ok(Self {
+            files,
+            stored: Some(stored),
+        })
+    }
+
+    /// Saves the cache to [`default_path`](Self::default_path), creating
+    /// its directory.
+    pub fn save_default(&self) -> Result<()> {
+        let path = Self::default_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no cache directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        self.save(path)
+    }
+
+    /// Saves the cache to a file.
+    ///
+    /// Every entry is parsed first. The cache is written to a synced
+    /// temporary file that is then renamed over `path`. Files whose path is
+    /// not valid UTF-8 are not saved.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut database = AppDatabase::new();
+        let mut header = Vec::new();
+        header.extend_from_slice(APPLICATIONS_MAGIC);
+        header.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+
+        let mut records = Vec::new();
+        for (file_path, file) in &self.files {
+            let (Some(id), Some(entry)) = (file_path.to_str(), self.entry(file_path, file)) else {
+                continue;
+            };
+            database.insert(id, entry.clone());
+            records.extend_from_slice(&(id.len() as u32).to_le_bytes());
+            records.extend_from_slice(id.as_bytes());
+            records.extend_from_slice(&file.stamp.len.to_le_bytes());
+            records.extend_from_slice(&file.stamp.modified.unwrap_or(NO_MODIFIED).to_le_bytes());
+        }
+        header.extend_from_slice(&(database.len() as u32).to_le_bytes());
+
+        crate::write_atomically(path, |mut writer| {
+            writer.write_all(&header)?;
+            writer.write_all(&records)?;
+            AppCache::write(&database, &mut writer)
+        })?;
+        Ok(())
+    }
+
+    /// Parses a file unless its size and modification time are unchanged.
+    ///
+    /// Returns whether the file was (re-)parsed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, and drops the file from the cache, if it cannot be
+    /// read or parsed.
+    pub fn update(&mut self, path: impl AsRef<Path>) -> Result<bool> {
+        let path = path.as_ref();
+        let parsed = fs::metadata(path)
+            .map_err(DesktopEntryError::from)
+            .and_then(|metadata| {
+                let stamp = FileStamp::of(&metadata);
+                if self
+                    .files
+                    .get(path)
+                    .is_some_and(|known| known.stamp.matches(&stamp))
+                {
+                    return Ok(None);
+                }
+                Ok(Some((stamp, DesktopEntry::parse_file(path)?)))
+            });
+
+        match parsed {
+            Ok(None) => Ok(false),
+            Ok(Some((stamp, entry))) => {
+                self.files.insert(
+                    path.to_path_buf(),
+                    CachedFile {
+                        stamp,
+                        entry: OnceLock::from(Some(entry)),
+                    },
+                );
+                Ok(true)
+            }
+            Err(err) => {
+                self.files.remove(path);
+                Err(err)
+            }
+        }
+    }
+
+    /// Brings the cache in line with a set of files.
+    ///
+    /// New and changed files are parsed, unchanged files keep their entries,
+    /// and files no longer in the set (or no longer readable or valid) are
+    /// dropped.
+    pub fn refresh<P: AsRef<Path>>(
+        &mut self,
+        paths: impl IntoIterator<Item = P>,
+    ) -> RefreshSummary {
+        let mut summary = RefreshSummary::default();
+        let mut seen = Vec::new();
+
+        for path in paths {
+            let path = path.as_ref();
+            let known = self.files.contains_key(path);
+            match self.update(path) {
+                Ok(true) if known => summary.changed.push(path.to_path_buf()),
+                Ok(true) => summary.added.push(path.to_path_buf()),
+                Ok(false) => summary.unchanged += 1,
+                Err(_) => continue,
+            }
+            seen.push(path.to_path_buf());
+        }
+
+        seen.sort();
+        let removed: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| seen.binary_search(path).is_err())
+            .cloned()
+            .collect();
+        for path in &removed {
+            self.files.remove(path);
+        }
+        summary.removed = removed;
+        summary
+    }
+
+    /// Refreshes the cache from every desktop file in the
+    /// [`application_dirs`](discovery::application_dirs), including files
+    /// shadowed by a more important directory.
+    pub fn refresh_installed(&mut self) -> RefreshSummary {
+        let mut files = Vec::new();
+        for dir in discovery::application_dirs() {
+            discovery::collect_desktop_files(&dir, &dir, &mut files);
+        }
+        self.refresh(files.into_iter().map(|(_, path)| path))
+    }
+
+    /// Drops a file from the cache, so it is parsed again on the next
+    /// refresh. Returns whether it was cached.
+    pub fn invalidate(&mut self, path: impl AsRef<Path>) -> bool {
+        self.files.remove(path.as_ref()).is_some()
+    }
+
+    /// Drops every file from the cache.
+    pub fn clear(&mut self) {
+        self.files.clear();
+        self.stored = None;
+    }
+
+    /// Whether a file is cached with its current size and modification time.
+    pub fn is_fresh(&self, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref();
+        self.files.get(path).is_some_and(|file| {
+            fs::metadata(path).is_ok_and(|metadata| file.stamp.matches(&FileStamp::of(&metadata)))
+        })
+    }
+
+    /// Gets the cached entry of a file.
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&DesktopEntry> {
+        let path = path.as_ref();
+        self.entry(path, self.files.get(path)?)
+    }
+
+    /// Iterates over all cached entries in path order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &DesktopEntry)> {
+        self.files
+            .iter()
+            .filter_map(|(path, file)| Some((path.as_path(), self.entry(path, file)?)))
+    }
+
+    /// Number of cached files.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether the cache holds no files.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn entry<'a>(&'a self, path: &Path, file: &'a CachedFile) -> Option<&'a DesktopEntry> {
+        file.entry
+            .get_or_init(|| {
+                let cached = self.stored.as_ref()?.get(path.to_str()?)?;
+                let mut entry = cached.to_desktop_entry().ok()?;
+                entry.source_path = Some(path.to_path_buf());
+                Some(entry)
+            })
+            .as_ref()
+    }
+}
+
+/// A key stored in an [`AppCache`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachedKey<'a> {
+    /// Name of the group holding the key
+    pub group: &'a str,
+    /// The key name (without locale suffix)
+    pub key: &'a str,
+    /// The locale suffix, if any
+    pub locale: Option<&'a str>,
+    /// The raw value
+    pub value: &'a str,
+}
+
+/// Deduplicating builder of the string table.
+#[derive(Default)]
+struct StringTable {
+    bytes: Vec<u8>,
+    offsets: HashMap<String, StrRef>,
+}
+
+impl StringTable {
+    fn intern(&mut self, s: &str) -> StrRef {
+        if let Some(r) = self.offsets.get(s) {
+            return *r;
+        }
+        let r = StrRef {
+            offset: self.bytes.len() as u32,
+            len: s.len() as u32,
+        };
+        self.bytes.extend_from_slice(s.as_bytes());
+        self.offsets.insert(s.to_string(), r);
+        r
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_bytes(bytes: &[u8], offset: usize, len: usize) -> Result<&[u8]> {
+    offset
+        .checked_add(len)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or_else(|| invalid_cache("truncated file"))
+}
+
+fn invalid_cache(reason: &str) -> DesktopEntryError {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid application cache: {}", reason),
+    )
+    .into()
+}
@@ -0,0 +1,245 @@
+//! The category registry of the Desktop Menu Specification.
+//!
+//! `Categories` values place applications in menus. The specification
+//! registers main categories, additional categories that refine them, and
+//! reserved categories for desktop-specific components. Names outside the
+//! registry must be prefixed with `X-`.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::categories::{Category, CategoryKind};
+//!
+//! let category = Category::parse("WebBrowser").unwrap();
+//! assert_eq!(category.kind(), CategoryKind::Additional);
+//! assert_eq!(category.main_categories(), [Category::Network]);
+//!
+//! assert_eq!(Category::parse("2DGraphics"), Some(Category::Graphics2D));
+//! assert_eq!(Category::parse("Browser"), None);
+//! ```
+
+use std::fmt;
+
+/// Kind of a registered category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CategoryKind {
+    /// A main category; each entry should have at least one
+    Main,
+    /// An additional category, refining main categories
+    Additional,
+    /// A category reserved for desktop-specific components, which requires
+    /// `OnlyShowIn`
+    Reserved,
+}
+
+/// Defines [`Category`] from the rows of the registry: the variant, the
+/// registered name, the kind, and the main categories one of which should
+/// accompany the category.
+macro_rules! categories {
+    ($($variant:ident = $name:literal, $kind:ident, [$($main:ident),*];)*) => {
+        /// A category registered by the Desktop Menu Specification.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Category {
+            $(
+                #[doc = concat!("`", $name, "`")]
+                $variant,
+            )*
+        }
+
+        impl Category {
+            /// Every registered category, main categories first.
+            pub const ALL: &[Category] = &[$(Self::$variant),*];
+
+            /// Gets the category with the given registered name.
+            pub fn parse(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// The registered name of the category.
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+
+            /// Whether the category is a main, additional, or reserved one.
+            pub fn kind(&self) -> CategoryKind {
+                match self {
+                    $(Self::$variant => CategoryKind::$kind,)*
+                }
+            }
+
+            /// The main categories one of which should be listed along with
+            /// this category; empty if the category stands on its own.
+            pub fn main_categories(&self) -> &'static [Category] {
+                match self {
+                    $(Self::$variant => &[$(Self::$main),*],)*
+                }
+            }
+        }
+    };
+}
+
+categories! {
+    AudioVideo = "AudioVideo", Main, [];
+    Audio = "Audio", Main, [AudioVideo];
+    Video = "Video", Main, [AudioVideo];
+    Development = "Development", Main, [];
+    Education = "Education", Main, [];
+    Game = "Game", Main, [];
+    Graphics = "Graphics", Main, [];
+    Network = "Network", Main, [];
+    Office = "Office", Main, [];
+    Science = "Science", Main, [];
+    Settings = "Settings", Main, [];
+    System = "System", Main, [];
+    Utility = "Utility", Main, [];
+
+    Building = "Building", Additional, [Development];
+    Debugger = "Debugger", Additional, [Development];
+    Ide = "IDE", Additional, [Development];
+    GuiDesigner = "GUIDesigner", Additional, [Development];
+    Profiling = "Profiling", Additional, [Development];
+    RevisionControl = "RevisionControl", Additional, [Development];
+    Translation = "Translation", Additional, [Development];
+    Calendar = "Calendar", Additional, [Office];
+    ContactManagement = "ContactManagement", Additional, [Office];
+    Database = "Database", Additional, [Office, Development, AudioVideo];
+    Dictionary = "Dictionary", Additional, [Office, Utility];
+    Chart = "Chart", Additional, [Office];
+    Email = "Email", Additional, [Office, Network];
+    Finance = "Finance", Additional, [Office];
+    FlowChart = "FlowChart", Additional, [Office];
+    Pda = "PDA", Additional, [Office];
+    ProjectManagement = "ProjectManagement", Additional, [Office, Development];
+    Presentation = "Presentation", Additional, [Office];
+    Spreadsheet = "Spreadsheet", Additional, [Office];
+    WordProcessor = "WordProcessor", Additional, [Office];
+    Graphics2D = "2DGraphics", Additional, [Graphics];
+    VectorGraphics = "VectorGraphics", Additional, [Graphics];
+    RasterGraphics = "RasterGraphics", Additional, [Graphics];
+    Graphics3D = "3DGraphics", Additional, [Graphics];
+    Scanning = "Scanning", Additional, [Graphics];
+    Ocr = "OCR", Additional, [Graphics];
+    Photography = "Photography", Additional, [Graphics, Office];
+    Publishing = "Publishing", Additional, [Graphics, Office];
+    Viewer = "Viewer", Additional, [Graphics, Office];
+    TextTools = "TextTools", Additional, [Utility];
+    DesktopSettings = "DesktopSettings", Additional, [Settings];
+    HardwareSettings = "HardwareSettings", Additional, [Settings];
+    Printing = "Printing", Additional, [Settings];
+    PackageManager = "PackageManager", Additional, [Settings];
+    Dialup = "Dialup", Additional, [Network];
+    InstantMessaging = "InstantMessaging", Additional, [Network];
+    Chat = "Chat", Additional, [Network];
+    IrcClient = "IRCClient", Additional, [Network];
+    Feed = "Feed", Additional, [Network];
+    FileTransfer = "FileTransfer", Additional, [Network];
+    HamRadio = "HamRadio", Additional, [Network, Audio];
+    News = "News", Additional, [Network];
+    P2p = "P2P", Additional, [Network];
+    RemoteAccess = "RemoteAccess", Additional, [Network];
+    Telephony = "Telephony", Additional, [Network];
+    TelephonyTools = "TelephonyTools", Additional, [Utility];
+    VideoConference = "VideoConference", Additional, [Network];
+    WebBrowser = "WebBrowser", Additional, [Network];
+    WebDevelopment = "WebDevelopment", Additional, [Network, Development];
+    Midi = "Midi", Additional, [AudioVideo, Audio];
+    Mixer = "Mixer", Additional, [AudioVideo, Audio];
+    Sequencer = "Sequencer", Additional, [AudioVideo, Audio];
+    Tuner = "Tuner", Additional, [AudioVideo, Audio];
+    Tv = "TV", Additional, [AudioVideo, Video];
+    AudioVideoEditing = "AudioVideoEditing", Additional, [Audio, Video, AudioVideo];
+    Player = "Player", Additional, [Audio, Video, AudioVideo];
+    Recorder = "Recorder", Additional, [Audio, Video, AudioVideo];
+    DiscBurning = "DiscBurning", Additional, [AudioVideo];
+    ActionGame = "ActionGame", Additional, [Game];
+    AdventureGame = "AdventureGame", Additional, [Game];
+    ArcadeGame = "ArcadeGame", Additional, [Game];
+    BoardGame = "BoardGame", Additional, [Game];
+    BlocksGame = "BlocksGame", Additional, [Game];
+    CardGame = "CardGame", Additional, [Game];
+    KidsGame = "KidsGame", Additional, [Game];
+    LogicGame = "LogicGame", Additional, [Game];
+    RolePlaying = "RolePlaying", Additional, [Game];
+    Shooter = "Shooter", Additional, [Game];
+    Simulation = "Simulation", Additional, [Game];
+    SportsGame = "SportsGame", Additional, [Game];
+    StrategyGame = "StrategyGame", Additional, [Game];
+    Art = "Art", Additional, [Education, Science];
+    Construction = "Construction", Additional, [Education, Science];
+    Music = "Music", Additional, [AudioVideo, Education];
+    Languages = "Languages", Additional, [Education, Science];
+    ArtificialIntelligence = "ArtificialIntelligence", Additional, [Education, Science];
+    Astronomy = "Astronomy", Additional, [Education, Science];
+    Biology = "Biology", Additional, [Education, Science];
+    Chemistry = "Chemistry", Additional, [Education, Science];
+    ComputerScience = "ComputerScience", Additional, [Education, Science];
+    DataVisualization = "DataVisualization", Additional, [Education, Science];
+    Economy = "Economy", Additional, [Education, Science];
+    Electricity = "Electricity", Additional, [Education, Science];
+    Geography = "Geography", Additional, [Education, Science];
+    Geology = "Geology", Additional, [Education, Science];
+    Geoscience = "Geoscience", Additional, [Education, Science];
+    History = "History", Additional, [Education, Science];
+    Humanities = "Humanities", Additional, [Education, Science];
+    ImageProcessing = "ImageProcessing", Additional, [Education, Science];
+    Literature = "Literature", Additional, [Education, Science];
+    Maps = "Maps", Additional, [Education, Science, Utility];
+    Math = "Math", Additional, [Education, Science];
+    NumericalAnalysis = "NumericalAnalysis", Additional, [Education, Science];
+    MedicalSoftware = "MedicalSoftware", Additional, [Education, Science];
+    Physics = "Physics", Additional, [Education, Science];
+    Robotics = "Robotics", Additional, [Education, Science];
+    Spirituality = "Spirituality", Additional, [Education, Science, Utility];
+    Sports = "Sports", Additional, [Education, Science];
+    ParallelComputing = "ParallelComputing", Additional, [Education, Science];
+    Amusement = "Amusement", Additional, [];
+    Archiving = "Archiving", Additional, [Utility];
+    Compression = "Compression", Additional, [Utility];
+    Electronics = "Electronics", Additional, [];
+    Emulator = "Emulator", Additional, [System, Game];
+    Engineering = "Engineering", Additional, [];
+    FileTools = "FileTools", Additional, [Utility, System];
+    FileManager = "FileManager", Additional, [System];
+    TerminalEmulator = "TerminalEmulator", Additional, [System];
+    Filesystem = "Filesystem", Additional, [System];
+    Monitor = "Monitor", Additional, [System];
+    Security = "Security", Additional, [Settings, System];
+    Accessibility = "Accessibility", Additional, [Settings, Utility];
+    Calculator = "Calculator", Additional, [Utility];
+    Clock = "Clock", Additional, [Utility];
+    TextEditor = "TextEditor", Additional, [Utility];
+    Documentation = "Documentation", Additional, [];
+    Adult = "Adult", Additional, [];
+    Core = "Core", Additional, [];
+    Kde = "KDE", Additional, [];
+    Gnome = "GNOME", Additional, [];
+    Xfce = "XFCE", Additional, [];
+    Dde = "DDE", Additional, [];
+    Gtk = "GTK", Additional, [];
+    Qt = "Qt", Additional, [];
+    Motif = "Motif", Additional, [];
+    Java = "Java", Additional, [];
+    ConsoleOnly = "ConsoleOnly", Additional, [];
+
+    Screensaver = "Screensaver", Reserved, [];
+    TrayIcon = "TrayIcon", Reserved, [];
+    Applet = "Applet", Reserved, [];
+    Shell = "Shell", Reserved, [];
+}
+
+impl AsRef<str> for Category {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
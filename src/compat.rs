@@ -0,0 +1,48 @@
+//! Compatibility with the 0.1 API.
+//!
+//! The 0.1 release exposed every item at the crate root. As the crate is split
+//! into modules and its error and locale types evolve, this module keeps the
+//! 0.1 names and signatures available, so downstream code can switch its
+//! imports to `xdg_desktop_entry::compat` and migrate one call site at a time.
+//!
+//! Items that change shape are re-added here with a `#[deprecated]` note
+//! pointing to their replacement, and are kept for at least one release
+//! cycle after the change.
+//!
+//! Requires the `compat-0` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::compat::{DesktopEntry, DesktopEntryType, Locale};
+//!
+//! let entry = DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=App\nExec=app\n")
+//!     .unwrap();
+//! assert_eq!(entry.entry_type, DesktopEntryType::Application);
+//! assert_eq!(entry.name.get(&Locale::from_string("de_DE")), "App");
+//! ```
+
+pub use crate::{
+    Comment, DesktopEntry, DesktopEntryError, DesktopEntryType, Entry, Group, IconString, Locale,
+    LocalizedString, LocalizedStringList, Result,
+};
+
+/// Parses a type string, as the inherent `DesktopEntryType::from_str` did in
+/// 0.1.
+#[deprecated(
+    since = "0.2.0",
+    note = "use `DesktopEntryType::parse`, or `str::parse` for an error on unknown types"
+)]
+pub fn desktop_entry_type_from_str(s: &str) -> Option<DesktopEntryType> {
+    DesktopEntryType::parse(s)
+}
+
+/// Creates an I/O error from a message, as `DesktopEntryError::Io(String)`
+/// did in 0.1.
+#[deprecated(
+    since = "0.2.0",
+    note = "`DesktopEntryError::Io` now keeps the `io::Error`; convert one with `From`"
+)]
+pub fn io_error(message: impl Into<String>) -> DesktopEntryError {
+    std::io::Error::other(message.into()).into()
+}
@@ -0,0 +1,252 @@
+//! In-memory database of application entries.
+
+use std::collections::BTreeMap;
+
+use crate::json::quote;
+use crate::{DesktopEntry, DesktopEntryType, Locale};
+
+/// URL schemes a typical desktop session is expected to have handlers for.
+pub const COMMON_URL_SCHEMES: &[&str] = &["http", "https", "mailto", "ftp"];
+
+/// MIME type prefix used to associate URL schemes with applications.
+pub const SCHEME_HANDLER_PREFIX: &str = "x-scheme-handler/";
+
+/// A collection of desktop entries keyed by desktop file ID.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::DesktopEntry;
+/// use xdg_desktop_entry::database::AppDatabase;
+///
+/// let mut database = AppDatabase::new();
+/// database.insert(
+///     "org.example.App.desktop",
+///     DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=App\nExec=app\n").unwrap(),
+/// );
+///
+/// assert_eq!(database.len(), 1);
+/// assert!(database.get("org.example.App.desktop").is_some());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AppDatabase {
+    entries: BTreeMap<String, DesktopEntry>,
+}
+
+impl AppDatabase {
+    /// Creates an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an entry, returning the entry previously stored under the same ID.
+    pub fn insert(&mut self, id: impl Into<String>, entry: DesktopEntry) -> Option<DesktopEntry> {
+        self.entries.insert(id.into(), entry)
+    }
+
+    /// Removes an entry by desktop file ID.
+    pub fn remove(&mut self, id: &str) -> Option<DesktopEntry> {
+        self.entries.remove(id)
+    }
+
+    /// Gets an entry by desktop file ID.
+    pub fn get(&self, id: &str) -> Option<&DesktopEntry> {
+        self.entries.get(id)
+    }
+
+    /// Number of entries in the database.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the database holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over all entries in desktop file ID order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &DesktopEntry)> {
+        self.entries.iter().map(|(id, entry)| (id.as_str(), entry))
+    }
+
+    /// Reports the global MIME association picture, checking the URL schemes
+    /// in [`COMMON_URL_SCHEMES`].
+    ///
+    /// See [`mime_conflicts_for_schemes`](Self::mime_conflicts_for_schemes).
+    pub fn mime_conflicts(&self) -> MimeConflictReport {
+        self.mime_conflicts_for_schemes(COMMON_URL_SCHEMES)
+    }
+
+    /// Reports MIME types claimed by more than one application and the given
+    /// URL schemes that no application handles.
+    ///
+    /// Only applications that are not `Hidden` take part, since hidden entries
+    /// are considered deleted.
+    pub fn mime_conflicts_for_schemes(&self, schemes: &[&str]) -> MimeConflictReport {
+        let mut claims: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+
+        for (id, entry) in self.iter() {
+            if entry.entry_type != DesktopEntryType::Application || entry.hidden == Some(true) {
+                continue;
+            }
+            for mime_type in entry.mime_type.iter().flatten() {
+                let ids = claims.entry(mime_type).or_default();
+                if !ids.iter().any(|claimed| claimed == id) {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+
+        let unhandled_schemes = schemes
+            .iter()
+            .filter(|scheme| {
+                !claims.contains_key(format!("{}{}", SCHEME_HANDLER_PREFIX, scheme).as_str())
+            })
+            .map(|scheme| scheme.to_string())
+            .collect();
+
+        let contested = claims
+            .into_iter()
+            .filter(|(_, ids)| ids.len() > 1)
+            .map(|(mime_type, ids)| (mime_type.to_string(), ids))
+            .collect();
+
+        MimeConflictReport {
+            contested,
+            unhandled_schemes,
+        }
+    }
+}
+
+impl AppDatabase {
+    /// Exports a manifest of the applications visible in the given desktop
+    /// environments, resolved for one locale.
+    ///
+    /// Each application is described by its desktop file ID, localized name,
+    /// localized icon name or path, `Exec` command line, and categories.
+    /// Applications that are `Hidden`, `NoDisplay`, or excluded by
+    /// `OnlyShowIn`/`NotShowIn` are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::database::{AppDatabase, ExportFormat};
+    /// use xdg_desktop_entry::{DesktopEntry, Locale};
+    ///
+    /// let mut database = AppDatabase::new();
+    /// database.insert(
+    ///     "app.desktop",
+    ///     DesktopEntry::parse(
+    ///         "[Desktop Entry]\nType=Application\nName=App\nName[de]=Anwendung\nExec=app\n",
+    ///     )
+    ///     .unwrap(),
+    /// );
+    ///
+    /// let csv = database.export(&Locale::from_string("de_DE"), &["GNOME"], ExportFormat::Csv);
+    /// assert_eq!(csv, "id,name,icon,exec,categories\r\napp.desktop,Anwendung,,app,\r\n");
+    /// ```
+    pub fn export(
+        &self,
+        locale: &Locale,
+        current_desktops: &[impl AsRef<str>],
+        format: ExportFormat,
+    ) -> String {
+        let rows: Vec<ExportRow> = self
+            .iter()
+            .filter(|(_, entry)| {
+                entry.entry_type == DesktopEntryType::Application
+                    && entry.should_show(current_desktops)
+            })
+            .map(|(id, entry)| ExportRow {
+                id,
+                name: entry.name.get(locale),
+                icon: entry.icon.as_ref().map(|icon| icon.get(locale)),
+                exec: entry.exec.as_deref(),
+                categories: entry.categories.as_deref().unwrap_or_default(),
+            })
+            .collect();
+
+        match format {
+            ExportFormat::Json => export_json(&rows),
+            ExportFormat::Csv => export_csv(&rows),
+        }
+    }
+}
+
+/// Format of [`AppDatabase::export`] manifests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A JSON array with one object per application, with `id`, `name`,
+    /// `icon`, `exec`, and `categories` members (`icon` and `exec` may be
+    /// `null`)
+    Json,
+    /// RFC 4180 CSV with a header row; categories are joined with `;`
+    Csv,
+}
+
+struct ExportRow<'a> {
+    id: &'a str,
+    name: &'a str,
+    icon: Option<&'a str>,
+    exec: Option<&'a str>,
+    categories: &'a [String],
+}
+
+fn export_json(rows: &[ExportRow]) -> String {
+    let optional = |value: Option<&str>| value.map_or_else(|| "null".to_string(), quote);
+
+    let objects: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let categories: Vec<String> = row.categories.iter().map(|c| quote(c)).collect();
+            format!(
+                "  {{\"id\": {}, \"name\": {}, \"icon\": {}, \"exec\": {}, \"categories\": [{}]}}",
+                quote(row.id),
+                quote(row.name),
+                optional(row.icon),
+                optional(row.exec),
+                categories.join(", ")
+            )
+        })
+        .collect();
+
+    if objects.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    }
+}
+
+fn export_csv(rows: &[ExportRow]) -> String {
+    let field = |value: &str| {
+        if value.contains([',', '"', '\r', '\n']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    };
+
+    let mut out = String::from("id,name,icon,exec,categories\r\n");
+    for row in rows {
+        let fields = [
+            field(row.id),
+            field(row.name),
+            field(row.icon.unwrap_or_default()),
+            field(row.exec.unwrap_or_default()),
+            field(&row.categories.join(";")),
+        ];
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Result of [`AppDatabase::mime_conflicts`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MimeConflictReport {
+    /// MIME types claimed by more than one application, with the desktop file
+    /// IDs of the claiming applications
+    pub contested: BTreeMap<String, Vec<String>>,
+    /// URL schemes without any `x-scheme-handler/<scheme>` handler
+    pub unhandled_schemes: Vec<String>,
+}
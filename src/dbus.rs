@@ -0,0 +1,210 @@
+//! D-Bus activation of applications.
+//!
+//! Entries with `DBusActivatable=true` are started by calling the
+//! `org.freedesktop.Application` interface at the application's well-known
+//! bus name, which the session bus activates on demand. The bus name is the
+//! desktop file ID without `.desktop`, and the object path is derived from
+//! it with [`dbus_object_path`].
+//!
+//! Requires the `dbus` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use xdg_desktop_entry::DesktopEntry;
+//! use xdg_desktop_entry::dbus::PlatformData;
+//!
+//! let entry =
+//!     DesktopEntry::parse_file("/usr/share/applications/org.gnome.TextEditor.desktop").unwrap();
+//! entry
+//!     .launch_dbus(&["/home/user/notes.txt"], &PlatformData::default())
+//!     .unwrap();
+//! ```
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::Value;
+
+use crate::launch::{LaunchMethod, dbus_object_path};
+use crate::{DesktopEntry, DesktopEntryError, Result};
+
+/// Interface implemented by D-Bus activatable applications.
+pub const APPLICATION_INTERFACE: &str = "org.freedesktop.Application";
+
+/// The `platform-data` passed along with activation requests.
+///
+/// Lets the launched application take focus from the launcher: X11
+/// applications read the startup notification ID, Wayland ones the XDG
+/// activation token.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlatformData {
+    /// Startup notification ID, sent as `desktop-startup-id`
+    pub desktop_startup_id: Option<String>,
+    /// XDG activation token, sent as `activation-token`
+    pub activation_token: Option<String>,
+}
+
+impl PlatformData {
+    fn to_dict(&self) -> HashMap<&'static str, Value<'_>> {
+        let mut dict = HashMap::new();
+        if let Some(id) = &self.desktop_startup_id {
+            dict.insert("desktop-startup-id", Value::from(id.as_str()));
+        }
+        if let Some(token) = &self.activation_token {
+            dict.insert("activation-token", Value::from(token.as_str()));
+        }
+        dict
+    }
+}
+
+impl DesktopEntry {
+    /// Activates this application over D-Bus, opening files or URIs.
+    ///
+    /// Calls `Activate` without targets and `Open` otherwise. Local paths are
+    /// converted to `file://` URIs; other targets are passed as given.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::ValidationError`] if the entry is not
+    /// D-Bus activatable or was not read from a desktop file, whose name
+    /// gives the bus name, and an I/O error if the session bus is
+    /// unreachable or the call fails.
+    pub fn launch_dbus(
+        &self,
+        files: &[impl AsRef<str>],
+        platform_data: &PlatformData,
+    ) -> Result<()> {
+        let proxy = self.application_proxy()?;
+        let platform_data = platform_data.to_dict();
+        let reply = if files.is_empty() {
+            proxy.call_method("Activate", &(platform_data,))
+        } else {
+            let uris: Vec<String> = files
+                .iter()
+                .map(|file| target_to_uri(file.as_ref()))
+                .collect();
+            proxy.call_method("Open", &(uris, platform_data))
+        };
+        reply.map_err(dbus_error)?;
+        Ok(())
+    }
+
+    /// Asks this application to run one of its actions over D-Bus, with
+    /// `ActivateAction`.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`launch_dbus`](Self::launch_dbus), or with
+    /// [`DesktopEntryError::ValidationError`] if the action is not listed in
+    /// `Actions` or has no group.
+    pub fn launch_dbus_action(&self, action: &str, platform_data: &PlatformData) -> Result<()> {
+        self.action_group(action)?;
+        let proxy = self.application_proxy()?;
+        let parameters: Vec<Value<'_>> = Vec::new();
+        proxy
+            .call_method(
+                "ActivateAction",
+                &(action, parameters, platform_data.to_dict()),
+            )
+            .map_err(dbus_error)?;
+        Ok(())
+    }
+
+    /// Activates this application over D-Bus without blocking.
+    ///
+    /// See [`launch_dbus`](Self::launch_dbus). Requires the `async` feature.
+    #[cfg(feature = "async")]
+    pub async fn launch_dbus_async(
+        &self,
+        files: &[impl AsRef<str>],
+        platform_data: &PlatformData,
+    ) -> Result<()> {
+        let (name, path) = self.application_address()?;
+        let connection = zbus::Connection::session().await.map_err(dbus_error)?;
+        let proxy = zbus::Proxy::new_owned(connection, name, path, APPLICATION_INTERFACE)
+            .await
+            .map_err(dbus_error)?;
+        let platform_data = platform_data.to_dict();
+        let reply = if files.is_empty() {
+            proxy.call_method("Activate", &(platform_data,)).await
+        } else {
+            let uris: Vec<String> = files
+                .iter()
+                .map(|file| target_to_uri(file.as_ref()))
+                .collect();
+            proxy.call_method("Open", &(uris, platform_data)).await
+        };
+        reply.map_err(dbus_error)?;
+        Ok(())
+    }
+
+    /// Asks this application to run one of its actions over D-Bus without
+    /// blocking.
+    ///
+    /// See [`launch_dbus_action`](Self::launch_dbus_action). Requires the
+    /// `async` feature.
+    #[cfg(feature = "async")]
+    pub async fn launch_dbus_action_async(
+        &self,
+        action: &str,
+        platform_data: &PlatformData,
+    ) -> Result<()> {
+        self.action_group(action)?;
+        let (name, path) = self.application_address()?;
+        let connection = zbus::Connection::session().await.map_err(dbus_error)?;
+        let proxy = zbus::Proxy::new_owned(connection, name, path, APPLICATION_INTERFACE)
+            .await
+            .map_err(dbus_error)?;
+        let parameters: Vec<Value<'_>> = Vec::new();
+        proxy
+            .call_method(
+                "ActivateAction",
+                &(action, parameters, platform_data.to_dict()),
+            )
+            .await
+            .map_err(dbus_error)?;
+        Ok(())
+    }
+
+    /// A proxy for the `org.freedesktop.Application` object of this entry.
+    fn application_proxy(&self) -> Result<Proxy<'static>> {
+        let (name, path) = self.application_address()?;
+        let connection = Connection::session().map_err(dbus_error)?;
+        Proxy::new_owned(connection, name, path, APPLICATION_INTERFACE).map_err(dbus_error)
+    }
+
+    /// The bus name and object path of this entry's application.
+    fn application_address(&self) -> Result<(String, String)> {
+        let LaunchMethod::DBusActivation { name, .. } = self.effective_launch_method()? else {
+            return Err(DesktopEntryError::ValidationError(
+                "Entry is not D-Bus activatable, or its desktop file is unknown".to_string(),
+            ));
+        };
+        let path = dbus_object_path(&name);
+        Ok((name, path))
+    }
+}
+
+pub(crate) fn dbus_error(err: zbus::Error) -> DesktopEntryError {
+    io::Error::other(err).into()
+}
+
+/// Converts a local path to a `file://` URI, leaving URIs as given.
+pub(crate) fn target_to_uri(target: &str) -> String {
+    if crate::discovery::uri_scheme(target).is_some() {
+        return target.to_string();
+    }
+    let path = std::path::absolute(Path::new(target)).unwrap_or_else(|_| target.into());
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
@@ -0,0 +1,256 @@
+//! Comparison of desktop entries by content.
+//!
+//! Two files can describe the same entry while differing in layout: key
+//! order, comments, blank lines, or the spelling of list values.
+//! [`DesktopEntry::semantic_eq`] ignores those differences and
+//! [`DesktopEntry::diff`] lists the keys that actually differ, group by
+//! group and locale by locale. The stricter `==` of [`DesktopEntry`] keeps
+//! comments and the spelling of values, but still ignores key order.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::DesktopEntry;
+//! use xdg_desktop_entry::diff::ChangeKind;
+//!
+//! let old = DesktopEntry::parse(
+//!     "[Desktop Entry]\nType=Application\nName=Editor\nExec=editor\nCategories=Utility;TextEditor\n",
+//! )
+//! .unwrap();
+//! let new = DesktopEntry::parse(
+//!     "# Edited\n[Desktop Entry]\nName=Editor\nType=Application\nCategories=Utility;TextEditor;\n\
+//!      Exec=editor %F\nName[de]=Texteditor\n",
+//! )
+//! .unwrap();
+//!
+//! let changes = old.diff(&new);
+//! assert_eq!(changes.len(), 2);
+//! assert_eq!(changes[0].key, "Exec");
+//! assert!(matches!(&changes[0].kind, ChangeKind::Changed { new, .. } if new == "editor %F"));
+//! assert_eq!(changes[1].key, "Name");
+//! assert!(matches!(changes[1].kind, ChangeKind::Added(_)));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{Hash, Hasher};
+
+use crate::{DesktopEntry, Locale, RawGroups};
+
+/// A key whose value differs between two entries.
+///
+/// Returned by [`DesktopEntry::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryChange {
+    /// Group holding the key
+    pub group: String,
+    /// Key name
+    pub key: String,
+    /// Locale of the value, `None` for the unlocalized value
+    pub locale: Option<Locale>,
+    /// How the value changed
+    pub kind: ChangeKind,
+}
+
+/// How the value of a key changed, with raw values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The key is only in the other entry
+    Added(String),
+    /// The key is only in this entry
+    Removed(String),
+    /// The key has different values
+    Changed {
+        /// Value in this entry
+        old: String,
+        /// Value in the other entry
+        new: String,
+    },
+}
+
+/// Flattened values, keyed by group, key, and locale.
+type Values = BTreeMap<(String, String, String), (Option<Locale>, String)>;
+
+impl DesktopEntry {
+    /// Whether two entries have the same content, ignoring comments, blank
+    /// lines, and the order of groups and keys.
+    ///
+    /// Values of known keys are compared in their canonical form, so
+    /// `Categories=A;B` equals `Categories=A;B;`; other values are compared
+    /// as written.
+    pub fn semantic_eq(&self, other: &DesktopEntry) -> bool {
+        match (self.raw_groups(), other.raw_groups()) {
+            (Ok(groups), Ok(other_groups)) => {
+                group_names(&groups) == group_names(&other_groups)
+                    && flatten(&groups) == flatten(&other_groups)
+            }
+            _ => self.canonical_string() == other.canonical_string(),
+        }
+    }
+
+    /// Lists the keys that differ from another entry, sorted by group, key,
+    /// and locale.
+    ///
+    /// Each locale of a key is compared separately. Like
+    /// [`semantic_eq`](Self::semantic_eq), layout is ignored. Groups without
+    /// keys do not show up; keys of groups only one entry has are all added
+    /// or removed.
+    pub fn diff(&self, other: &DesktopEntry) -> Vec<EntryChange> {
+        let old = self
+            .raw_groups()
+            .map(|groups| flatten(&groups))
+            .unwrap_or_default();
+        let new = other
+            .raw_groups()
+            .map(|groups| flatten(&groups))
+            .unwrap_or_default();
+
+        let slots: BTreeSet<_> = old.keys().chain(new.keys()).collect();
+        slots
+            .into_iter()
+            .filter_map(|slot| {
+                let (group, key, _) = slot;
+                let (locale, kind) = match (old.get(slot), new.get(slot)) {
+                    (Some((locale, old)), Some((_, new))) if old != new => (
+                        locale.clone(),
+                        ChangeKind::Changed {
+                            old: old.clone(),
+                            new: new.clone(),
+                        },
+                    ),
+                    (Some((locale, old)), None) => {
+                        (locale.clone(), ChangeKind::Removed(old.clone()))
+                    }
+                    (None, Some((locale, new))) => (locale.clone(), ChangeKind::Added(new.clone())),
+                    _ => return None,
+                };
+                Some(EntryChange {
+                    group: group.clone(),
+                    key: key.clone(),
+                    locale,
+                    kind,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Compares entries field by field, including unknown keys, additional
+/// groups, and the text of comments, but not
+/// [`source_path`](DesktopEntry::source_path),
+/// [`layout`](DesktopEntry::layout), or the line numbers of comments: an
+/// entry equals a copy of itself loaded from another file or laid out
+/// differently.
+///
+/// Use [`semantic_eq`](DesktopEntry::semantic_eq) to also ignore comments and the
+/// spelling of values.
+impl PartialEq for DesktopEntry {
+    fn eq(&self, other: &Self) -> bool {
+        let Self {
+            entry_type,
+            name,
+            url,
+            version,
+            generic_name,
+            no_display,
+            comment,
+            icon,
+            hidden,
+            only_show_in,
+            not_show_in,
+            dbus_activatable,
+            try_exec,
+            exec,
+            path,
+            terminal,
+            actions,
+            mime_type,
+            categories,
+            implements,
+            keywords,
+            startup_notify,
+            startup_wm_class,
+            prefers_non_default_gpu,
+            single_main_window,
+            additional_groups,
+            unknown_keys,
+            comments,
+            source_path: _,
+            layout: _,
+        } = self;
+        *entry_type == other.entry_type
+            && *name == other.name
+            && *url == other.url
+            && *version == other.version
+            && *generic_name == other.generic_name
+            && *no_display == other.no_display
+            && *comment == other.comment
+            && *icon == other.icon
+            && *hidden == other.hidden
+            && *only_show_in == other.only_show_in
+            && *not_show_in == other.not_show_in
+            && *dbus_activatable == other.dbus_activatable
+            && *try_exec == other.try_exec
+            && *exec == other.exec
+            && *path == other.path
+            && *terminal == other.terminal
+            && *actions == other.actions
+            && *mime_type == other.mime_type
+            && *categories == other.categories
+            && *implements == other.implements
+            && *keywords == other.keywords
+            && *startup_notify == other.startup_notify
+            && *startup_wm_class == other.startup_wm_class
+            && *prefers_non_default_gpu == other.prefers_non_default_gpu
+            && *single_main_window == other.single_main_window
+            && *additional_groups == other.additional_groups
+            && *unknown_keys == other.unknown_keys
+            && comments.len() == other.comments.len()
+            && comments
+                .iter()
+                .zip(&other.comments)
+                .all(|(a, b)| a.content == b.content && a.is_blank == b.is_blank)
+    }
+}
+
+impl Eq for DesktopEntry {}
+
+/// Hashes the groups, keys, and values in sorted order, and the text of
+/// comments, consistently with [`PartialEq`].
+impl Hash for DesktopEntry {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.raw_groups() {
+            Ok(groups) => {
+                group_names(&groups).hash(state);
+                flatten(&groups).hash(state);
+            }
+            Err(_) => self.name.default.hash(state),
+        }
+        for comment in &self.comments {
+            (&comment.content, comment.is_blank).hash(state);
+        }
+    }
+}
+
+fn group_names(groups: &RawGroups) -> BTreeSet<&str> {
+    groups.keys().map(String::as_str).collect()
+}
+
+fn flatten(groups: &RawGroups) -> Values {
+    let mut values = Values::new();
+    for (group, keys) in groups {
+        for (key, entries) in keys {
+            for entry in entries {
+                let locale = entry
+                    .locale
+                    .as_ref()
+                    .map(Locale::to_string_repr)
+                    .unwrap_or_default();
+                values.insert(
+                    (group.clone(), key.clone(), locale),
+                    (entry.locale.clone(), entry.value.clone()),
+                );
+            }
+        }
+    }
+    values
+}
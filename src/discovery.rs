@@ -0,0 +1,734 @@
+//! Discovery of installed desktop entries.
+//!
+//! Applications are installed in the `applications` subdirectory of the XDG
+//! data directories. `$XDG_DATA_HOME` takes precedence over
+//! `$XDG_DATA_DIRS`, which are listed from most to least important; an entry
+//! shadows every entry with the same desktop file ID in less important
+//! directories.
+//!
+//! # Specification Reference
+//!
+//! Section 2: "File naming"
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+
+use crate::database::SCHEME_HANDLER_PREFIX;
+use crate::launch::LaunchOptions;
+use crate::mimeapps::MimeApps;
+use crate::mimeinfo::MimeInfoCache;
+use crate::{DesktopEntry, Parser, RawGroups, Result};
+
+/// Default for `$XDG_DATA_DIRS` when it is unset or empty.
+const DEFAULT_DATA_DIRS: &str = "/usr/local/share:/usr/share";
+
+/// Default for `$XDG_CONFIG_DIRS` when it is unset or empty.
+const DEFAULT_CONFIG_DIRS: &str = "/etc/xdg";
+
+/// Extension of desktop entry files.
+pub(crate) const DESKTOP_EXTENSION: &str = "desktop";
+
+/// The identifier of a desktop entry, e.g. `org.example.App.desktop`.
+///
+/// Computed from the path of the file relative to the `applications`
+/// directory it was found in, with subdirectory separators replaced by
+/// dashes.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DesktopFileId(String);
+
+impl DesktopFileId {
+    /// Creates an ID from its string form.
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    /// Computes the ID of a file from its path relative to an `applications`
+    /// directory (e.g. `kde4/kate.desktop` becomes `kde4-kate.desktop`).
+    ///
+    /// Returns `None` if the path is empty or not relative, or if a
+    /// component is not valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::discovery::DesktopFileId;
+    ///
+    /// let id = DesktopFileId::from_relative_path("kde4/kate.desktop").unwrap();
+    /// assert_eq!(id.as_str(), "kde4-kate.desktop");
+    /// ```
+    pub fn from_relative_path(path: impl AsRef<Path>) -> Option<Self> {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            return None;
+        }
+        let components = path
+            .components()
+            .map(|component| match component {
+                std::path::Component::Normal(name) => name.to_str(),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if components.is_empty() {
+            return None;
+        }
+        Some(Self(components.join("-")))
+    }
+
+    /// Computes the ID of a file inside an `applications` directory.
+    ///
+    /// Returns `None` if `path` is not below `applications_dir`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::discovery::DesktopFileId;
+    ///
+    /// let id = DesktopFileId::from_path(
+    ///     "/usr/share/applications/kde4/kate.desktop",
+    ///     "/usr/share/applications",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(id.as_str(), "kde4-kate.desktop");
+    /// ```
+    pub fn from_path(path: impl AsRef<Path>, applications_dir: impl AsRef<Path>) -> Option<Self> {
+        Self::from_relative_path(path.as_ref().strip_prefix(applications_dir).ok()?)
+    }
+
+    /// Computes the ID of an installed file, using the first of
+    /// [`application_dirs`] containing it.
+    pub fn from_installed_path(path: impl AsRef<Path>) -> Option<Self> {
+        application_dirs()
+            .iter()
+            .find_map(|dir| Self::from_path(path.as_ref(), dir))
+    }
+
+    /// Finds the file providing this ID in [`application_dirs`].
+    ///
+    /// See [`resolve_in`](Self::resolve_in).
+    pub fn resolve(&self) -> Option<PathBuf> {
+        self.resolve_in(&application_dirs())
+    }
+
+    /// Finds the file providing this ID in the given `applications`
+    /// directories, most important first.
+    ///
+    /// Since dashes may stand for subdirectories, `kde4-kate.desktop` matches
+    /// both `kde4-kate.desktop` and `kde4/kate.desktop`; within a directory,
+    /// the file with the fewest subdirectories wins.
+    ///
+    /// IDs that are not file names, such as absolute paths or IDs with `/`,
+    /// `\0`, `.` or `..` parts, never match, so an ID read from a file cannot
+    /// point outside the directories.
+    pub fn resolve_in(&self, dirs: &[impl AsRef<Path>]) -> Option<PathBuf> {
+        if !self.is_resolvable() {
+            return None;
+        }
+        dirs.iter()
+            .find_map(|dir| resolve_below(dir.as_ref(), &self.0))
+    }
+
+    /// Finds every file providing this ID in the given `applications`
+    /// directories, one per directory, most important first.
+    ///
+    /// See [`resolve_in`](Self::resolve_in).
+    pub fn resolve_all_in(&self, dirs: &[impl AsRef<Path>]) -> Vec<PathBuf> {
+        if !self.is_resolvable() {
+            return Vec::new();
+        }
+        dirs.iter()
+            .filter_map(|dir| resolve_below(dir.as_ref(), &self.0))
+            .collect()
+    }
+
+    /// Whether the ID can only name files below an `applications`
+    /// directory, with each dash possibly standing for a subdirectory.
+    fn is_resolvable(&self) -> bool {
+        !self.0.is_empty()
+            && !Path::new(&self.0).is_absolute()
+            && !self.0.contains(['/', '\0'])
+            && self.0.split('-').all(|part| part != "." && part != "..")
+    }
+
+    /// The D-Bus well-known name of the application, which is the ID
+    /// without the `.desktop` extension.
+    ///
+    /// # Specification Reference
+    ///
+    /// Section 8: "D-Bus Activation"
+    pub fn bus_name(&self) -> &str {
+        self.0.strip_suffix(".desktop").unwrap_or(&self.0)
+    }
+
+    /// The ID as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for DesktopFileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for DesktopFileId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// How an entry combines with an entry of the same desktop file ID in a
+/// less important directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MergeMode {
+    /// The more important entry replaces the other entirely, as the
+    /// specification requires
+    #[default]
+    Shadow,
+    /// The keys of the more important entry replace the same keys of the
+    /// other, with all their translations, and other keys are kept
+    Overlay,
+}
+
+impl DesktopEntry {
+    /// Resolves this entry overridden by an entry with the same desktop file
+    /// ID from a more important directory.
+    ///
+    /// The override shadows this entry entirely; `None` means the override
+    /// is `Hidden=true`, which deletes the application.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let system = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\n",
+    /// )
+    /// .unwrap();
+    /// let user = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nHidden=true\n",
+    /// )
+    /// .unwrap();
+    /// assert!(system.merged_with(&user).is_none());
+    /// ```
+    pub fn merged_with(&self, override_entry: &DesktopEntry) -> Option<DesktopEntry> {
+        self.merged_with_mode(override_entry, MergeMode::Shadow)
+    }
+
+    /// Resolves this entry overridden by another, as [`merged_with`] does,
+    /// in the given mode.
+    ///
+    /// In [`MergeMode::Overlay`], keys of additional groups are overlaid
+    /// too, and the comments of this entry are kept. The result is `None`
+    /// if it is `Hidden=true`. If the overlaid keys do not form a valid
+    /// entry, the override is used as is.
+    ///
+    /// [`merged_with`]: Self::merged_with
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    /// use xdg_desktop_entry::discovery::MergeMode;
+    ///
+    /// let system = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nCategories=Utility;\n",
+    /// )
+    /// .unwrap();
+    /// let user = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=My App\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let merged = system.merged_with_mode(&user, MergeMode::Overlay).unwrap();
+    /// assert_eq!(merged.name.default, "My App");
+    /// assert_eq!(merged.exec.as_deref(), Some("app"));
+    /// ```
+    pub fn merged_with_mode(
+        &self,
+        override_entry: &DesktopEntry,
+        mode: MergeMode,
+    ) -> Option<DesktopEntry> {
+        if override_entry.hidden == Some(true) {
+            return None;
+        }
+        let merged = match mode {
+            MergeMode::Shadow => override_entry.clone(),
+            MergeMode::Overlay => self
+                .overlay(override_entry)
+                .unwrap_or_else(|_| override_entry.clone()),
+        };
+        (merged.hidden != Some(true)).then_some(merged)
+    }
+
+    fn overlay(&self, override_entry: &DesktopEntry) -> Result<DesktopEntry> {
+        let mut groups = self.raw_groups()?;
+        overlay_groups(&mut groups, override_entry.raw_groups()?);
+        let mut merged = Parser::new("").build_entry(groups, self.comments.clone())?;
+        merged.source_path = override_entry.source_path.clone();
+        Ok(merged)
+    }
+}
+
+/// Replaces keys of `base` with those of `over`, with all their
+/// translations.
+fn overlay_groups(base: &mut RawGroups, over: RawGroups) {
+    for (name, keys) in over {
+        base.entry(name).or_default().extend(keys);
+    }
+}
+
+/// The XDG data directories, most important first.
+///
+/// `$XDG_DATA_HOME` (default `~/.local/share`) followed by
+/// `$XDG_DATA_DIRS` (default `/usr/local/share:/usr/share`). Relative paths
+/// are ignored, as required by the XDG Base Directory Specification.
+pub fn data_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    dirs.extend(data_home());
+
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| DEFAULT_DATA_DIRS.to_string());
+    dirs.extend(
+        data_dirs
+            .split(':')
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute()),
+    );
+
+    dirs
+}
+
+/// The XDG data directory of the user: `$XDG_DATA_HOME`, defaulting to
+/// `~/.local/share`.
+pub fn data_home() -> Option<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+}
+
+/// The XDG configuration directories, most important first.
+///
+/// `$XDG_CONFIG_HOME` (default `~/.config`) followed by `$XDG_CONFIG_DIRS`
+/// (default `/etc/xdg`). Relative paths are ignored.
+pub fn config_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+    dirs.extend(config_home);
+
+    let config_dirs = std::env::var("XDG_CONFIG_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| DEFAULT_CONFIG_DIRS.to_string());
+    dirs.extend(
+        config_dirs
+            .split(':')
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute()),
+    );
+
+    dirs
+}
+
+/// The XDG cache directory: `$XDG_CACHE_HOME`, defaulting to `~/.cache`.
+pub fn cache_home() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+}
+
+/// The `applications` directories, most important first.
+pub fn application_dirs() -> Vec<PathBuf> {
+    data_dirs()
+        .into_iter()
+        .map(|dir| dir.join("applications"))
+        .collect()
+}
+
+/// The `desktop-directories` directories holding the `.directory` entries
+/// of menus, most important first.
+pub fn directory_dirs() -> Vec<PathBuf> {
+    data_dirs()
+        .into_iter()
+        .map(|dir| dir.join("desktop-directories"))
+        .collect()
+}
+
+/// The base directories of icon themes, most important first.
+///
+/// `~/.icons`, the `icons` directory of each [data directory](data_dirs),
+/// and `/usr/share/pixmaps`, as listed by the Icon Theme Specification.
+pub fn icon_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    dirs.extend(std::env::var_os("HOME").map(|home| Path::new(&home).join(".icons")));
+    dirs.extend(data_dirs().into_iter().map(|dir| dir.join("icons")));
+    dirs.push(PathBuf::from("/usr/share/pixmaps"));
+    dirs
+}
+
+/// Iterator over the installed desktop entries.
+///
+/// Yields each desktop file ID once, from the most important directory
+/// providing it, together with the file path and the parsed entry. Files
+/// that fail to parse or are marked `Hidden=true` still shadow less
+/// important files with the same ID, but are not yielded.
+///
+/// # Examples
+///
+/// ```no_run
+/// use xdg_desktop_entry::discovery::ApplicationIter;
+///
+/// for (id, path, entry) in ApplicationIter::new() {
+///     println!("{} ({}): {}", id, path.display(), entry.name.default);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ApplicationIter {
+    dirs: std::vec::IntoIter<PathBuf>,
+    pending: std::vec::IntoIter<(DesktopFileId, PathBuf)>,
+    seen: HashSet<DesktopFileId>,
+}
+
+impl ApplicationIter {
+    /// Iterates over the entries in [`application_dirs`].
+    pub fn new() -> Self {
+        Self::with_dirs(application_dirs())
+    }
+
+    /// Iterates over the entries in the given `applications` directories,
+    /// most important first.
+    pub fn with_dirs(dirs: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            dirs: dirs.into_iter().collect::<Vec<_>>().into_iter(),
+            pending: Vec::new().into_iter(),
+            seen: HashSet::new(),
+        }
+    }
+}
+
+impl Default for ApplicationIter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Iterator for ApplicationIter {
+    type Item = (DesktopFileId, PathBuf, DesktopEntry);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some((id, path)) = self.pending.next() else {
+                let dir = self.dirs.next()?;
+                let mut files = Vec::new();
+                collect_desktop_files(&dir, &dir, &mut files);
+                files.sort();
+                self.pending = files.into_iter();
+                continue;
+            };
+
+            if !self.seen.insert(id.clone()) {
+                continue;
+            }
+            match DesktopEntry::parse_file(&path) {
+                Ok(entry) if entry.hidden != Some(true) => return Some((id, path, entry)),
+                _ => continue,
+            }
+        }
+    }
+}
+
+/// Finds all installed desktop entries.
+///
+/// See [`ApplicationIter`].
+pub fn find_all_desktop_entries() -> Vec<(DesktopFileId, PathBuf, DesktopEntry)> {
+    ApplicationIter::new().collect()
+}
+
+/// The installed applications together with the user's MIME associations.
+///
+/// Entries are parsed on demand, so queries only read the files they need.
+///
+/// # Examples
+///
+/// ```no_run
+/// use xdg_desktop_entry::discovery::Applications;
+///
+/// let applications = Applications::new();
+/// for (id, _, entry) in applications.handlers_for_mime("image/png") {
+///     println!("{}: {}", id, entry.name.default);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Applications {
+    dirs: Vec<PathBuf>,
+    mime_apps: MimeApps,
+}
+
+impl Applications {
+    /// Uses the entries in [`application_dirs`] and the associations of
+    /// [`MimeApps::load`].
+    pub fn new() -> Self {
+        Self::with_dirs(application_dirs(), MimeApps::load())
+    }
+
+    /// Uses the entries in the given `applications` directories, most
+    /// important first, and the given associations.
+    pub fn with_dirs(dirs: Vec<PathBuf>, mime_apps: MimeApps) -> Self {
+        Self { dirs, mime_apps }
+    }
+
+    /// The MIME associations.
+    pub fn mime_apps(&self) -> &MimeApps {
+        &self.mime_apps
+    }
+
+    /// Iterates over the installed entries.
+    pub fn iter(&self) -> ApplicationIter {
+        ApplicationIter::with_dirs(self.dirs.clone())
+    }
+
+    /// Finds and parses the entry with the given ID.
+    ///
+    /// Returns `None` if the file is missing, unparsable, or `Hidden`.
+    pub fn get(&self, id: &DesktopFileId) -> Option<(PathBuf, DesktopEntry)> {
+        self.get_merged(id, MergeMode::Shadow)
+    }
+
+    /// Finds the entry with the given ID, combining the files providing it
+    /// in each directory as [`DesktopEntry::merged_with_mode`] does.
+    ///
+    /// In [`MergeMode::Overlay`], the files overriding the least important
+    /// one may be partial, holding only the keys they change. The path is
+    /// that of the most important file. Returns `None` if no file provides
+    /// the ID, a file is unreadable or malformed, or the result is not a
+    /// valid entry or is `Hidden`.
+    pub fn get_merged(
+        &self,
+        id: &DesktopFileId,
+        mode: MergeMode,
+    ) -> Option<(PathBuf, DesktopEntry)> {
+        if mode == MergeMode::Shadow {
+            let path = id.resolve_in(&self.dirs)?;
+            let entry = DesktopEntry::parse_file(&path).ok()?;
+            return (entry.hidden != Some(true)).then_some((path, entry));
+        }
+
+        let paths = id.resolve_all_in(&self.dirs);
+        let path = paths.first()?.clone();
+        let mut groups = RawGroups::new();
+        let mut comments = Vec::new();
+        for path in paths.iter().rev() {
+            let content = std::fs::read_to_string(path).ok()?;
+            let (file_groups, file_comments) = Parser::new(&content).parse_groups().ok()?;
+            overlay_groups(&mut groups, file_groups);
+            comments = file_comments;
+        }
+        let mut entry = Parser::new("").build_entry(groups, comments).ok()?;
+        entry.source_path = Some(path.clone());
+        (entry.hidden != Some(true)).then_some((path, entry))
+    }
+
+    /// The applications able to open a MIME type, most preferred first.
+    ///
+    /// The default application comes first, followed by the added
+    /// associations and then by the applications listing the type in their
+    /// `MimeType` key, in desktop file ID order. Applications whose
+    /// association was removed in a `mimeapps.list` file are left out.
+    ///
+    /// The `mimeinfo.cache` of each directory is used to find the
+    /// applications claiming the type; directories whose cache is missing or
+    /// stale are scanned instead.
+    pub fn handlers_for_mime(
+        &self,
+        mime_type: &str,
+    ) -> Vec<(DesktopFileId, PathBuf, DesktopEntry)> {
+        let mut handlers: Vec<(DesktopFileId, PathBuf, DesktopEntry)> = Vec::new();
+
+        let preferred = self
+            .mime_apps
+            .default_app_for(mime_type)
+            .into_iter()
+            .chain(self.mime_apps.added_associations(mime_type));
+        for id in preferred {
+            if handlers.iter().any(|(handler, _, _)| *handler == id) {
+                continue;
+            }
+            if let Some((path, entry)) = self.get(&id) {
+                handlers.push((id, path, entry));
+            }
+        }
+
+        for id in self.claiming_ids(mime_type) {
+            if self.mime_apps.is_removed(mime_type, &id)
+                || handlers.iter().any(|(handler, _, _)| *handler == id)
+            {
+                continue;
+            }
+            if let Some((path, entry)) = self.get(&id) {
+                handlers.push((id, path, entry));
+            }
+        }
+
+        handlers
+    }
+
+    /// The applications handling URIs with the given scheme (e.g. `https`),
+    /// most preferred first.
+    ///
+    /// Handlers are associated with the `x-scheme-handler/<scheme>` MIME
+    /// type; see [`handlers_for_mime`](Self::handlers_for_mime).
+    pub fn handlers_for_scheme(&self, scheme: &str) -> Vec<(DesktopFileId, PathBuf, DesktopEntry)> {
+        let scheme = scheme.to_ascii_lowercase();
+        self.handlers_for_mime(&format!("{}{}", SCHEME_HANDLER_PREFIX, scheme))
+    }
+
+    /// The preferred application for URIs with the given scheme.
+    pub fn default_handler_for_scheme(
+        &self,
+        scheme: &str,
+    ) -> Option<(DesktopFileId, PathBuf, DesktopEntry)> {
+        self.handlers_for_scheme(scheme).into_iter().next()
+    }
+
+    /// Opens a URI with the preferred handler of its scheme, which receives
+    /// it through its `%u` or `%U` field code.
+    ///
+    /// # Errors
+    ///
+    /// Fails with an [`io::ErrorKind::InvalidInput`](std::io::ErrorKind)
+    /// error if the URI has no scheme, with
+    /// [`io::ErrorKind::NotFound`](std::io::ErrorKind) if no application
+    /// handles it, or with the error of
+    /// [`DesktopEntry::launch`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xdg_desktop_entry::discovery::Applications;
+    ///
+    /// Applications::new().open_uri("https://example.com").unwrap();
+    /// ```
+    pub fn open_uri(&self, uri: &str) -> Result<Child> {
+        let scheme = uri_scheme(uri).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("URI has no scheme: {}", uri),
+            )
+        })?;
+        let (_, _, entry) = self.default_handler_for_scheme(scheme).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no application handles {} URIs", scheme),
+            )
+        })?;
+        entry.launch(&[uri], &LaunchOptions::default())
+    }
+
+    /// The IDs of the entries listing a MIME type, ignoring entries shadowed
+    /// by a more important directory.
+    fn claiming_ids(&self, mime_type: &str) -> BTreeSet<DesktopFileId> {
+        let mut ids = BTreeSet::new();
+        let mut shadowed = HashSet::new();
+        for dir in &self.dirs {
+            let mut files = Vec::new();
+            collect_desktop_files(dir, dir, &mut files);
+
+            let claiming: Vec<DesktopFileId> = match MimeInfoCache::load_fresh(dir) {
+                Some(cache) => cache
+                    .handlers(mime_type)
+                    .iter()
+                    .map(DesktopFileId::new)
+                    .collect(),
+                None => files
+                    .iter()
+                    .filter(|(id, path)| {
+                        !shadowed.contains(id)
+                            && DesktopEntry::parse_file(path)
+                                .is_ok_and(|entry| claims(&entry, mime_type))
+                    })
+                    .map(|(id, _)| id.clone())
+                    .collect(),
+            };
+            ids.extend(claiming.into_iter().filter(|id| !shadowed.contains(id)));
+            shadowed.extend(files.into_iter().map(|(id, _)| id));
+        }
+        ids
+    }
+}
+
+impl Default for Applications {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The scheme of a URI, as defined by RFC 3986.
+pub(crate) fn uri_scheme(uri: &str) -> Option<&str> {
+    let (scheme, _) = uri.split_once(':')?;
+    let mut chars = scheme.chars();
+    (chars.next()?.is_ascii_alphabetic()
+        && chars.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c)))
+    .then_some(scheme)
+}
+
+/// Whether an entry lists a MIME type in its `MimeType` key.
+fn claims(entry: &DesktopEntry, mime_type: &str) -> bool {
+    entry
+        .mime_type
+        .as_ref()
+        .is_some_and(|types| types.iter().any(|t| t == mime_type))
+}
+
+/// Recursively collects the desktop files below `dir` with their IDs.
+///
+/// Unreadable directories are skipped.
+pub(crate) fn collect_desktop_files(
+    root: &Path,
+    dir: &Path,
+    files: &mut Vec<(DesktopFileId, PathBuf)>,
+) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    for dir_entry in read_dir.flatten() {
+        let path = dir_entry.path();
+        if path.is_dir() {
+            collect_desktop_files(root, &path, files);
+        } else if path.extension().is_some_and(|ext| ext == DESKTOP_EXTENSION)
+            && let Ok(relative) = path.strip_prefix(root)
+            && let Some(id) = DesktopFileId::from_relative_path(relative)
+        {
+            files.push((id, path));
+        }
+    }
+}
+
+/// Finds the file for the remainder of an ID below `dir`, trying each dash as
+/// a directory separator.
+fn resolve_below(dir: &Path, rest: &str) -> Option<PathBuf> {
+    let candidate = dir.join(rest);
+    if candidate.is_file() {
+        return Some(candidate);
+    }
+    rest.match_indices('-').find_map(|(i, _)| {
+        let subdir = dir.join(&rest[..i]);
+        if subdir.is_dir() {
+            resolve_below(&subdir, &rest[i + 1..])
+        } else {
+            None
+        }
+    })
+}
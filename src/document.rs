@@ -0,0 +1,363 @@
+//! Incremental parsing for editors.
+//!
+//! A [`Document`] keeps the text of a desktop file along with the result of
+//! parsing it. Editors and language servers apply each change of the text
+//! with [`Document::edit`], which only reparses the groups the change
+//! touches. The entry, diagnostics, and spans are then those
+//! [`DesktopEntry::parse_collecting`] reports for the whole text.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::ParserOptions;
+//! use xdg_desktop_entry::document::{Document, TextEdit};
+//!
+//! let mut document = Document::parse(
+//!     "[Desktop Entry]\nType=Application\nName=App\nExec=app\n",
+//!     &ParserOptions::default(),
+//! );
+//! assert!(document.diagnostics().is_empty());
+//!
+//! let start = document.text().find("Exec").unwrap();
+//! document.edit(&TextEdit::new(start..start + 4, "Bad Key"));
+//! assert_eq!(document.text(), "[Desktop Entry]\nType=Application\nName=App\nBad Key=app\n");
+//! assert_eq!(document.diagnostics()[0].code, "invalid-key-name");
+//! assert_eq!(document.diagnostics()[0].span.unwrap().line, 4);
+//! assert_eq!(document.entry().unwrap().exec, None);
+//! ```
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::layout::Layout;
+use crate::{
+    Comment, DesktopEntry, DesktopEntryError, Diagnostic, EntrySpan, Locale, Parser, ParserOptions,
+    RawGroups, Severity, Span,
+};
+
+/// A change of the text: the bytes in `range` are replaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// Byte range of the replaced text
+    pub range: Range<usize>,
+    /// Text replacing the range
+    pub replacement: String,
+}
+
+impl TextEdit {
+    /// Creates an edit replacing a byte range.
+    pub fn new(range: Range<usize>, replacement: impl Into<String>) -> Self {
+        Self {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+}
+
+/// The text of a desktop file, parsed and kept up to date as it is edited.
+///
+/// Errors are collected rather than returned, as with
+/// [`DesktopEntry::parse_collecting`]. See the
+/// [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct Document {
+    text: String,
+    options: ParserOptions,
+    sections: Vec<Section>,
+    entry: Option<DesktopEntry>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// A group header and the lines up to the next one, or the lines before the
+/// first header, parsed on its own.
+#[derive(Debug, Clone)]
+struct Section {
+    /// Byte range in the text, line endings included
+    range: Range<usize>,
+    first_line: usize,
+    line_count: usize,
+    /// Whether keys come before the first group header, which attaches them
+    /// to a `[Desktop Entry]` group that may continue in a later section
+    orphan_keys: bool,
+    groups: RawGroups,
+    header_spans: HashMap<String, Span>,
+    comments: Vec<Comment>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Document {
+    /// Parses the text of a desktop file.
+    pub fn parse(text: impl Into<String>, options: &ParserOptions) -> Self {
+        let text = text.into();
+        let sections = parse_sections(&text, 0..text.len(), 1, options);
+        let mut document = Self {
+            text,
+            options: options.clone(),
+            sections,
+            entry: None,
+            diagnostics: Vec::new(),
+        };
+        document.merge_orphan_keys();
+        document.rebuild();
+        document
+    }
+
+    /// Applies an edit to the text and reparses the groups it touches.
+    ///
+    /// Groups after the edit are only moved, unless they have diagnostics,
+    /// whose messages may mention line numbers. When keys come before the
+    /// first group header, the whole text is reparsed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range of the edit is out of bounds or does not lie on
+    /// character boundaries, like [`String::replace_range`].
+    pub fn edit(&mut self, edit: &TextEdit) {
+        let TextEdit { range, replacement } = edit;
+        self.text.replace_range(range.clone(), replacement);
+        let byte_delta = replacement.len() as isize - range.len() as isize;
+
+        // The sections touching the edit, and the one before in case the
+        // edit turns a header into a line of that section
+        let Some(last) = self
+            .sections
+            .iter()
+            .rposition(|section| section.range.start <= range.end)
+        else {
+            self.sections = parse_sections(&self.text, 0..self.text.len(), 1, &self.options);
+            self.merge_orphan_keys();
+            self.rebuild();
+            return;
+        };
+        let first = self
+            .sections
+            .iter()
+            .position(|section| section.range.end >= range.start)
+            .unwrap_or(last)
+            .min(last)
+            .saturating_sub(1);
+
+        let start = self.sections[first].range.start;
+        let end = shift(self.sections[last].range.end, byte_delta);
+        let old_lines: usize = self.sections[first..=last]
+            .iter()
+            .map(|section| section.line_count)
+            .sum();
+        let reparsed = parse_sections(
+            &self.text,
+            start..end,
+            self.sections[first].first_line,
+            &self.options,
+        );
+        let new_lines: usize = reparsed.iter().map(|section| section.line_count).sum();
+        let line_delta = new_lines as isize - old_lines as isize;
+        self.sections.splice(first..=last, reparsed.iter().cloned());
+
+        for section in &mut self.sections[first + reparsed.len()..] {
+            if line_delta != 0 && !section.diagnostics.is_empty() {
+                let range =
+                    shift(section.range.start, byte_delta)..shift(section.range.end, byte_delta);
+                let first_line = shift(section.first_line, line_delta);
+                *section = Section::parse(&self.text, range, first_line, &self.options);
+            } else {
+                section.shift(byte_delta, line_delta);
+            }
+        }
+
+        self.merge_orphan_keys();
+        self.rebuild();
+    }
+
+    /// The current text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The options the text is parsed with.
+    pub fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
+    /// The entry of the current text, or `None` if it cannot be built.
+    pub fn entry(&self) -> Option<&DesktopEntry> {
+        self.entry.as_ref()
+    }
+
+    /// The diagnostics of the current text.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Location of a group header in the current text.
+    pub fn group_span(&self, group: &str) -> Option<Span> {
+        self.sections
+            .iter()
+            .find_map(|section| section.header_spans.get(group))
+            .copied()
+    }
+
+    /// Location of a key in the current text.
+    pub fn entry_span(&self, group: &str, key: &str, locale: Option<&Locale>) -> Option<EntrySpan> {
+        self.sections
+            .iter()
+            .find_map(|section| section.groups.get(group))?
+            .get(key)?
+            .iter()
+            .find(|entry| entry.locale.as_ref() == locale)?
+            .span
+    }
+
+    /// Parses the whole text as a single section when keys come before the
+    /// first group header, so they are attached to `[Desktop Entry]` as
+    /// when parsing it at once.
+    fn merge_orphan_keys(&mut self) {
+        if self.sections.len() > 1 && self.sections[0].orphan_keys {
+            self.sections = vec![Section::parse(
+                &self.text,
+                0..self.text.len(),
+                1,
+                &self.options,
+            )];
+        }
+    }
+
+    /// Builds the entry and diagnostics from the sections.
+    fn rebuild(&mut self) {
+        let mut groups: RawGroups = HashMap::new();
+        let mut header_spans = HashMap::new();
+        let mut comments = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        for section in &self.sections {
+            // Comments are collected until the first group starts
+            if groups.is_empty() {
+                comments.extend(section.comments.iter().cloned());
+            }
+            let duplicate = section
+                .groups
+                .keys()
+                .find(|name| groups.contains_key(*name));
+            if let Some(name) = duplicate {
+                // The keys of a duplicate group are skipped
+                diagnostics.extend(
+                    section
+                        .diagnostics
+                        .iter()
+                        .filter(|d| d.span.is_some_and(|span| span.line == section.first_line))
+                        .cloned(),
+                );
+                let header = self.text[section.range.clone()]
+                    .lines()
+                    .next()
+                    .unwrap_or_default();
+                let err = DesktopEntryError::DuplicateGroup(name.clone());
+                diagnostics.push(
+                    Diagnostic::new(Severity::Error, err.code(), err.to_string())
+                        .with_span(Span::line(section.first_line, header)),
+                );
+                continue;
+            }
+            groups.extend(section.groups.clone());
+            header_spans.extend(section.header_spans.clone());
+            diagnostics.extend(section.diagnostics.iter().cloned());
+        }
+
+        let mut parser = Parser::with_options(&self.text, self.options.clone());
+        parser.collect_errors = true;
+        parser.diagnostics = diagnostics;
+        parser.header_spans = header_spans;
+        let entry = parser.build_entry(groups, comments).map(|mut entry| {
+            if !self.options.discard_layout {
+                entry.layout = Some(Layout::new(&self.text, &self.options));
+            }
+            entry
+        });
+        self.entry = parser.finish_collecting(entry);
+        self.diagnostics = parser.diagnostics;
+    }
+}
+
+impl Section {
+    fn parse(text: &str, range: Range<usize>, first_line: usize, options: &ParserOptions) -> Self {
+        let content = &text[range.clone()];
+        let mut parser = Parser::with_options(content, options.clone());
+        parser.collect_errors = true;
+        parser.first_line = first_line;
+        let (groups, comments) = parser.parse_groups().unwrap_or_default();
+        let orphan_keys = content
+            .lines()
+            .map(str::trim)
+            .take_while(|line| !line.starts_with('['))
+            .any(|line| !line.is_empty() && !line.starts_with('#'));
+
+        Self {
+            range,
+            first_line,
+            line_count: content.lines().count(),
+            orphan_keys,
+            groups,
+            header_spans: parser.header_spans,
+            comments,
+            diagnostics: parser.diagnostics,
+        }
+    }
+
+    /// Moves the section by a number of bytes and lines.
+    fn shift(&mut self, bytes: isize, lines: isize) {
+        self.range = shift(self.range.start, bytes)..shift(self.range.end, bytes);
+        if lines == 0 {
+            return;
+        }
+        self.first_line = shift(self.first_line, lines);
+        let shift_span = |span: &mut Span| span.line = shift(span.line, lines);
+        for span in self.header_spans.values_mut() {
+            shift_span(span);
+        }
+        for entry in self
+            .groups
+            .values_mut()
+            .flat_map(|group| group.values_mut().flatten())
+        {
+            if let Some(span) = &mut entry.span {
+                shift_span(&mut span.line);
+                shift_span(&mut span.key);
+                if let Some(locale) = &mut span.locale {
+                    shift_span(locale);
+                }
+                shift_span(&mut span.value);
+            }
+        }
+        for comment in &mut self.comments {
+            comment.line_number = shift(comment.line_number, lines);
+        }
+    }
+}
+
+/// Splits a range of the text into sections at group headers and parses
+/// them.
+fn parse_sections(
+    text: &str,
+    range: Range<usize>,
+    first_line: usize,
+    options: &ParserOptions,
+) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let (mut start, mut start_line) = (range.start, first_line);
+    let (mut offset, mut line_num) = (range.start, first_line);
+    for line in text[range.clone()].split_inclusive('\n') {
+        if offset > start && line.trim_start().starts_with('[') {
+            sections.push(Section::parse(text, start..offset, start_line, options));
+            (start, start_line) = (offset, line_num);
+        }
+        offset += line.len();
+        line_num += 1;
+    }
+    if start < range.end {
+        sections.push(Section::parse(text, start..range.end, start_line, options));
+    }
+    sections
+}
+
+fn shift(value: usize, by: isize) -> usize {
+    value.wrapping_add_signed(by)
+}
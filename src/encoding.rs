@@ -0,0 +1,142 @@
+//! Reading of desktop files in legacy encodings.
+//!
+//! The specification requires UTF-8, but old KDE-era files are often plain
+//! Latin-1, or declare `Encoding=Legacy-Mixed` and store each translation in
+//! the charset of its locale, as in `Name[ru_RU.KOI8-R]=...`. These files can
+//! be read with [`DesktopEntry::parse_bytes`], which transcodes them
+//! according to an [`EncodingPolicy`] and reports the encoding it found, so
+//! tools can rewrite them as UTF-8.
+//!
+//! Requires the `encoding` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::DesktopEntry;
+//! use xdg_desktop_entry::encoding::{DetectedEncoding, EncodingPolicy};
+//!
+//! let latin1 = b"[Desktop Entry]\nType=Application\nName=Caf\xe9\nExec=cafe\n";
+//! assert!(DesktopEntry::parse_bytes(latin1, EncodingPolicy::Utf8).is_err());
+//!
+//! let (entry, detected) = DesktopEntry::parse_bytes(latin1, EncodingPolicy::latin1()).unwrap();
+//! assert_eq!(entry.name.default, "Café");
+//! assert_eq!(detected, DetectedEncoding::Legacy(encoding_rs::WINDOWS_1252));
+//!
+//! // Rewriting the entry produces UTF-8
+//! assert!(entry.serialize().contains("Name=Café\n"));
+//! ```
+
+use encoding_rs::Encoding;
+
+use crate::{DesktopEntry, DesktopEntryError, Locale, Result};
+
+/// How to decode input that is not valid UTF-8.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EncodingPolicy {
+    /// Fail with [`DesktopEntryError::InvalidUtf8`], as the specification
+    /// requires
+    #[default]
+    Utf8,
+    /// Decode the whole file with this encoding
+    Fallback(&'static Encoding),
+    /// Decode each line that is not valid UTF-8 on its own, with the
+    /// charset of its locale if it names one, as in `Name[ru_RU.KOI8-R]`,
+    /// and with this encoding otherwise; for `Encoding=Legacy-Mixed` files
+    LegacyMixed(&'static Encoding),
+}
+
+impl EncodingPolicy {
+    /// Decoding of plain Latin-1 files.
+    ///
+    /// Uses Windows-1252, the superset of Latin-1 that such files are
+    /// usually written in.
+    pub fn latin1() -> Self {
+        Self::Fallback(encoding_rs::WINDOWS_1252)
+    }
+}
+
+/// The encoding a desktop file was found to be in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectedEncoding {
+    /// Valid UTF-8; nothing was transcoded
+    Utf8,
+    /// Decoded as a whole from this encoding
+    Legacy(&'static Encoding),
+    /// Decoded line by line from several encodings
+    Mixed,
+}
+
+impl DetectedEncoding {
+    /// Whether the file needs rewriting to conform to the specification.
+    pub fn is_legacy(self) -> bool {
+        self != Self::Utf8
+    }
+}
+
+/// Decodes the content of a desktop file according to a policy.
+///
+/// # Errors
+///
+/// Returns [`DesktopEntryError::InvalidUtf8`] if the content is not valid
+/// UTF-8 and the policy is [`EncodingPolicy::Utf8`], or if a line is not
+/// valid in the encoding it is decoded with.
+pub fn decode(bytes: &[u8], policy: EncodingPolicy) -> Result<(String, DetectedEncoding)> {
+    if let Ok(content) = std::str::from_utf8(bytes) {
+        return Ok((content.to_string(), DetectedEncoding::Utf8));
+    }
+
+    match policy {
+        EncodingPolicy::Utf8 => Err(DesktopEntryError::InvalidUtf8),
+        EncodingPolicy::Fallback(encoding) => Ok((
+            decode_with(bytes, encoding)?,
+            DetectedEncoding::Legacy(encoding),
+        )),
+        EncodingPolicy::LegacyMixed(fallback) => {
+            let mut content = String::with_capacity(bytes.len());
+            for line in bytes.split_inclusive(|&b| b == b'\n') {
+                match std::str::from_utf8(line) {
+                    Ok(line) => content.push_str(line),
+                    Err(_) => {
+                        let encoding = line_charset(line).unwrap_or(fallback);
+                        content.push_str(&decode_with(line, encoding)?);
+                    }
+                }
+            }
+            Ok((content, DetectedEncoding::Mixed))
+        }
+    }
+}
+
+impl DesktopEntry {
+    /// Parses a desktop file that may not be UTF-8, returning the entry and
+    /// the encoding it was found to be in.
+    ///
+    /// See the [module documentation](crate::encoding) for an example.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::InvalidUtf8`] if the content cannot be
+    /// decoded with the policy (see [`decode`]), or any error
+    /// [`parse`](Self::parse) returns.
+    pub fn parse_bytes(bytes: &[u8], policy: EncodingPolicy) -> Result<(Self, DetectedEncoding)> {
+        let (content, detected) = decode(bytes, policy)?;
+        Ok((Self::parse(&content)?, detected))
+    }
+}
+
+fn decode_with(bytes: &[u8], encoding: &'static Encoding) -> Result<String> {
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .map(|content| content.into_owned())
+        .ok_or(DesktopEntryError::InvalidUtf8)
+}
+
+/// The charset named by the locale of a key-value line, such as `KOI8-R` in
+/// `Name[ru_RU.KOI8-R]=...`.
+fn line_charset(line: &[u8]) -> Option<&'static Encoding> {
+    let eq = line.iter().position(|&b| b == b'=')?;
+    let key = std::str::from_utf8(&line[..eq]).ok()?;
+    let (_, locale) = key.split_once('[')?;
+    let locale = Locale::from_string(locale.trim_end().strip_suffix(']')?);
+    Encoding::for_label(locale.encoding?.as_bytes())
+}
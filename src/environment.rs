@@ -0,0 +1,143 @@
+//! Desktop environments named by `OnlyShowIn`, `NotShowIn`, and
+//! `$XDG_CURRENT_DESKTOP`.
+//!
+//! The Desktop Menu Specification registers the names of desktop
+//! environments; other names must be prefixed with `X-`.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::environment::DesktopEnvironment;
+//!
+//! assert_eq!(DesktopEnvironment::parse("XFCE"), DesktopEnvironment::Xfce);
+//! assert!(DesktopEnvironment::parse("LXQt").is_registered());
+//!
+//! let custom = DesktopEnvironment::parse("X-Phosh");
+//! assert_eq!(custom, DesktopEnvironment::Other("X-Phosh".to_string()));
+//! assert!(!custom.is_registered());
+//! ```
+
+use std::fmt;
+
+/// Defines [`DesktopEnvironment`] from the registered names.
+macro_rules! environments {
+    ($($variant:ident = $name:literal;)*) => {
+        /// A desktop environment, as named in `$XDG_CURRENT_DESKTOP`.
+        ///
+        /// Names are case-sensitive; unregistered ones are kept in
+        /// [`Other`](Self::Other).
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum DesktopEnvironment {
+            $(
+                #[doc = concat!("`", $name, "`")]
+                $variant,
+            )*
+            /// A name the specification does not register
+            Other(String),
+        }
+
+        impl DesktopEnvironment {
+            /// Every registered desktop environment.
+            pub const REGISTERED: &[DesktopEnvironment] = &[$(Self::$variant),*];
+
+            /// Gets the desktop environment with the given name.
+            pub fn parse(name: &str) -> Self {
+                match name {
+                    $($name => Self::$variant,)*
+                    _ => Self::Other(name.to_string()),
+                }
+            }
+
+            /// The name of the desktop environment.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $name,)*
+                    Self::Other(name) => name,
+                }
+            }
+        }
+    };
+}
+
+environments! {
+    Gnome = "GNOME";
+    GnomeClassic = "GNOME-Classic";
+    GnomeFlashback = "GNOME-Flashback";
+    Kde = "KDE";
+    Lxde = "LXDE";
+    Lxqt = "LXQt";
+    Mate = "MATE";
+    Razor = "Razor";
+    Rox = "ROX";
+    Tde = "TDE";
+    Unity = "Unity";
+    Xfce = "XFCE";
+    Ede = "EDE";
+    Cinnamon = "Cinnamon";
+    Pantheon = "Pantheon";
+    Budgie = "Budgie";
+    Enlightenment = "Enlightenment";
+    Dde = "DDE";
+    Endless = "Endless";
+    Old = "Old";
+}
+
+/// The desktop environments named in `$XDG_CURRENT_DESKTOP`, most specific
+/// first.
+///
+/// This is the list to match `OnlyShowIn` and `NotShowIn` against, as
+/// [`DesktopEntry::should_show_in_current_desktop`](crate::DesktopEntry::should_show_in_current_desktop)
+/// does. See [`parse_current_desktop`] for the format.
+pub fn current_desktop() -> Vec<DesktopEnvironment> {
+    parse_current_desktop(&std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default())
+}
+
+/// Parses a value of `$XDG_CURRENT_DESKTOP`.
+///
+/// The variable holds a colon-separated list, so that a derived desktop can
+/// also match entries shown only in its parent, e.g. `ubuntu:GNOME`. Empty
+/// items are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::environment::{DesktopEnvironment, parse_current_desktop};
+///
+/// assert_eq!(
+///     parse_current_desktop("ubuntu:GNOME"),
+///     [DesktopEnvironment::Other("ubuntu".to_string()), DesktopEnvironment::Gnome],
+/// );
+/// assert!(parse_current_desktop("").is_empty());
+/// ```
+pub fn parse_current_desktop(value: &str) -> Vec<DesktopEnvironment> {
+    value
+        .split(':')
+        .filter(|desktop| !desktop.is_empty())
+        .map(DesktopEnvironment::parse)
+        .collect()
+}
+
+impl DesktopEnvironment {
+    /// Whether the name is registered by the specification.
+    pub fn is_registered(&self) -> bool {
+        !matches!(self, Self::Other(_))
+    }
+}
+
+impl AsRef<str> for DesktopEnvironment {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for DesktopEnvironment {
+    fn from(name: &str) -> Self {
+        Self::parse(name)
+    }
+}
+
+impl fmt::Display for DesktopEnvironment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
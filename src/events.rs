@@ -0,0 +1,142 @@
+//! Pull-based parsing of desktop entry files.
+//!
+//! [`EntryEvents`] walks the lines of a file and yields an [`EntryEvent`]
+//! for each group header, key, and comment, borrowing from the input. It
+//! builds no [`DesktopEntry`](crate::DesktopEntry) and does no key-level
+//! validation, so tools that need only a few keys of many files can stop
+//! as soon as they have them.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::events::{EntryEvent, EntryEvents};
+//!
+//! let content = "[Desktop Entry]\nType=Application\nName=Viewer\nName[de]=Betrachter\nExec=viewer %f\n";
+//!
+//! let mut name = None;
+//! let mut exec = None;
+//! for event in EntryEvents::new(content) {
+//!     match event.unwrap() {
+//!         EntryEvent::GroupStart { name: group, .. } if group != "Desktop Entry" => break,
+//!         EntryEvent::KeyValue { key: "Name", locale: None, value, .. } => name = Some(value),
+//!         EntryEvent::KeyValue { key: "Exec", value, .. } => exec = Some(value),
+//!         _ => {}
+//!     }
+//! }
+//! assert_eq!(name, Some("Viewer"));
+//! assert_eq!(exec, Some("viewer %f"));
+//! ```
+
+use std::iter::Enumerate;
+use std::str::Lines;
+
+use crate::{DesktopEntryError, Result, Span};
+
+/// A syntactic element of a desktop entry file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryEvent<'a> {
+    /// A group header, e.g. `[Desktop Entry]`
+    GroupStart {
+        /// Name of the group, without the brackets
+        name: &'a str,
+        /// Location of the header line
+        span: Span,
+    },
+    /// A key-value pair of the current group
+    KeyValue {
+        /// Name of the key, without the locale
+        key: &'a str,
+        /// Locale in brackets, e.g. `de_DE@euro`; parse it with
+        /// [`Locale::from_string`](crate::Locale::from_string)
+        locale: Option<&'a str>,
+        /// Raw value, with escape sequences as written
+        value: &'a str,
+        /// Location of the whole line
+        span: Span,
+    },
+    /// A comment line
+    Comment {
+        /// Text after the `#`
+        content: &'a str,
+        /// Location of the comment line
+        span: Span,
+    },
+}
+
+/// Iterator over the [`EntryEvent`]s of a desktop entry file.
+///
+/// Malformed lines (a header without `]`, a line without `=`, an invalid
+/// key name, or a key before the first group) yield an error, after which
+/// iteration continues with the next line. Blank lines are skipped.
+#[derive(Debug, Clone)]
+pub struct EntryEvents<'a> {
+    lines: Enumerate<Lines<'a>>,
+    in_group: bool,
+}
+
+impl<'a> EntryEvents<'a> {
+    /// Creates an event iterator over `content`.
+    pub fn new(content: &'a str) -> Self {
+        Self {
+            lines: content.lines().enumerate(),
+            in_group: false,
+        }
+    }
+
+    fn key_value(&self, line_num: usize, line: &'a str, span: Span) -> Result<EntryEvent<'a>> {
+        let invalid_line = || DesktopEntryError::InvalidLine(line_num, line.to_string());
+        let (key_part, value) = line.split_once('=').ok_or_else(invalid_line)?;
+        if !self.in_group {
+            return Err(invalid_line());
+        }
+
+        let (key, locale) = match key_part.split_once('[') {
+            Some((key, rest)) => {
+                let (locale, _) = rest.split_once(']').ok_or_else(invalid_line)?;
+                (key.trim(), Some(locale))
+            }
+            None => (key_part.trim(), None),
+        };
+        if !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(DesktopEntryError::InvalidKeyName(line_num, key.to_string()));
+        }
+
+        Ok(EntryEvent::KeyValue {
+            key,
+            locale,
+            value,
+            span,
+        })
+    }
+}
+
+impl<'a> Iterator for EntryEvents<'a> {
+    type Item = Result<EntryEvent<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (index, line) = self.lines.next()?;
+            let line_num = index + 1;
+            let span = Span::line(line_num, line);
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() {
+                continue;
+            }
+            if let Some(content) = trimmed.strip_prefix('#') {
+                return Some(Ok(EntryEvent::Comment { content, span }));
+            }
+            if let Some(header) = trimmed.strip_prefix('[') {
+                let Some(name) = header.strip_suffix(']') else {
+                    return Some(Err(DesktopEntryError::InvalidGroupHeader(
+                        line_num,
+                        line.to_string(),
+                    )));
+                };
+                self.in_group = true;
+                return Some(Ok(EntryEvent::GroupStart { name, span }));
+            }
+            return Some(self.key_value(line_num, line, span));
+        }
+    }
+}
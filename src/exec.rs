@@ -0,0 +1,412 @@
+//! Parsing of the `Exec` key.
+//!
+//! [`ExecString`] splits a command line into the program and its arguments,
+//! undoing the spec's quoting rules, and recognizes the field codes launchers
+//! expand with files, URLs, and entry details.
+//! [`split_args`] and [`quote_arg`] convert between command lines and
+//! their arguments, for building or rewriting them.
+//!
+//! # Specification Reference
+//!
+//! Section 7: "The `Exec` key"
+
+use crate::{DesktopEntryError, Result};
+
+/// Characters that must be backslash-escaped inside a quoted argument.
+const QUOTE_ESCAPABLE: [char; 4] = ['"', '`', '$', '\\'];
+
+/// Characters that require an argument to be quoted.
+const RESERVED: &[char] = &[
+    ' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(', ')',
+    '`',
+];
+
+/// Splits a command line into its words, undoing the spec's quoting rules.
+///
+/// Arguments are separated by spaces and may be quoted in whole with double
+/// quotes, inside which `"`, `` ` ``, `$`, and `\` are escaped with a
+/// backslash. Field codes are left untouched.
+pub(crate) fn split_words(exec: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) if QUOTE_ESCAPABLE.contains(&escaped) => {
+                                current.push(escaped)
+                            }
+                            Some(other) => {
+                                current.push('\\');
+                                current.push(other);
+                            }
+                            None => return Err(unterminated_quote(exec)),
+                        },
+                        Some(other) => current.push(other),
+                        None => return Err(unterminated_quote(exec)),
+                    }
+                }
+            }
+            _ => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_arg {
+        args.push(current);
+    }
+
+    Ok(args)
+}
+
+/// Quotes a word for a command line, the inverse of [`split_words`]. Field
+/// codes and `%%` are kept as they are.
+///
+/// Words without reserved characters are returned unchanged.
+pub(crate) fn quote_word(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(RESERVED) {
+        return arg.to_string();
+    }
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        if QUOTE_ESCAPABLE.contains(&c) {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Splits a command line into its arguments, undoing the spec's quoting
+/// rules and recognizing field codes.
+///
+/// Unlike [`ExecString::parse`], the program is returned as the first
+/// argument, and the placement of field codes is not checked. The command
+/// line must already be unescaped at the string level, as the fields of
+/// [`DesktopEntry`](crate::DesktopEntry) are.
+///
+/// # Errors
+///
+/// Returns [`DesktopEntryError::InvalidValue`] if the command line has an
+/// unterminated quote, an unknown field code, or a trailing `%`.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::exec::{FieldCode, split_args};
+///
+/// let args = split_args(r#""/opt/My App/app" "\$HOME" 100%% %F"#).unwrap();
+/// assert_eq!(args[0].as_literal(), Some("/opt/My App/app"));
+/// assert_eq!(args[1].as_literal(), Some("$HOME"));
+/// assert_eq!(args[2].as_literal(), Some("100%"));
+/// assert_eq!(args[3].as_field_code(), Some(FieldCode::Files));
+/// ```
+pub fn split_args(exec: &str) -> Result<Vec<ExecArg>> {
+    split_words(exec)?
+        .iter()
+        .map(|word| ExecArg::parse(word, exec))
+        .collect()
+}
+
+/// Quotes a literal argument for a command line, so [`split_args`] gives it
+/// back as it was.
+///
+/// Arguments with reserved characters, such as spaces or `$`, are quoted,
+/// escaping `"`, `` ` ``, `$`, and `\` inside the quotes, and `%` is
+/// written as `%%` so it is not read as a field code. The result is the
+/// command line as stored in [`DesktopEntry::exec`](crate::DesktopEntry::exec);
+/// serializing the entry adds the string-level escaping, doubling the
+/// backslashes again.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::exec::{quote_arg, split_args};
+///
+/// assert_eq!(quote_arg("--verbose"), "--verbose");
+/// assert_eq!(quote_arg("/opt/My App/app"), r#""/opt/My App/app""#);
+/// assert_eq!(quote_arg("$HOME"), r#""\$HOME""#);
+/// assert_eq!(quote_arg("100%"), "100%%");
+///
+/// let exec = format!("app {}", quote_arg("50% \"off\""));
+/// assert_eq!(split_args(&exec).unwrap()[1].as_literal(), Some("50% \"off\""));
+/// ```
+pub fn quote_arg(arg: &str) -> String {
+    quote_word(&arg.replace('%', "%%"))
+}
+
+fn unterminated_quote(exec: &str) -> DesktopEntryError {
+    DesktopEntryError::InvalidValue(
+        "Exec".to_string(),
+        format!("unterminated quoted argument in '{}'", exec),
+    )
+}
+
+/// A field code in an `Exec` command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldCode {
+    /// `%f`: a single file name
+    File,
+    /// `%F`: a list of files
+    Files,
+    /// `%u`: a single URL
+    Url,
+    /// `%U`: a list of URLs
+    Urls,
+    /// `%i`: `--icon` followed by the `Icon` key, if any
+    Icon,
+    /// `%c`: the translated `Name` of the entry
+    Name,
+    /// `%k`: the location of the desktop file
+    Location,
+    /// One of the deprecated `%d`, `%D`, `%n`, `%N`, `%v`, or `%m` codes,
+    /// which launchers ignore
+    Deprecated(char),
+}
+
+impl FieldCode {
+    /// Gets the field code for the character following a `%`.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'f' => Some(Self::File),
+            'F' => Some(Self::Files),
+            'u' => Some(Self::Url),
+            'U' => Some(Self::Urls),
+            'i' => Some(Self::Icon),
+            'c' => Some(Self::Name),
+            'k' => Some(Self::Location),
+            'd' | 'D' | 'n' | 'N' | 'v' | 'm' => Some(Self::Deprecated(c)),
+            _ => None,
+        }
+    }
+
+    /// Gets the character following the `%` of this field code.
+    pub fn as_char(&self) -> char {
+        match self {
+            Self::File => 'f',
+            Self::Files => 'F',
+            Self::Url => 'u',
+            Self::Urls => 'U',
+            Self::Icon => 'i',
+            Self::Name => 'c',
+            Self::Location => 'k',
+            Self::Deprecated(c) => *c,
+        }
+    }
+
+    /// Whether the code is replaced by the files or URLs being opened.
+    pub fn is_target(&self) -> bool {
+        matches!(self, Self::File | Self::Files | Self::Url | Self::Urls)
+    }
+}
+
+/// A piece of an [`ExecArg`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgPart {
+    /// Literal text, with `%%` already replaced by `%`
+    Text(String),
+    /// A field code to expand
+    Code(FieldCode),
+}
+
+/// An argument of an `Exec` command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecArg {
+    parts: Vec<ArgPart>,
+}
+
+impl ExecArg {
+    /// The text and field codes making up the argument.
+    pub fn parts(&self) -> &[ArgPart] {
+        &self.parts
+    }
+
+    /// Gets the argument if it contains no field code.
+    pub fn as_literal(&self) -> Option<&str> {
+        match self.parts.as_slice() {
+            [] => Some(""),
+            [ArgPart::Text(text)] => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Gets the field code if it makes up the whole argument.
+    pub fn as_field_code(&self) -> Option<FieldCode> {
+        match self.parts.as_slice() {
+            [ArgPart::Code(code)] => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Iterates over the field codes in the argument.
+    pub fn field_codes(&self) -> impl Iterator<Item = FieldCode> + '_ {
+        self.parts.iter().filter_map(|part| match part {
+            ArgPart::Code(code) => Some(*code),
+            ArgPart::Text(_) => None,
+        })
+    }
+
+    fn parse(arg: &str, exec: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = arg.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                text.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('%') => text.push('%'),
+                Some(code) => {
+                    let code = FieldCode::from_char(code).ok_or_else(|| {
+                        invalid_exec(exec, format!("unknown field code %{}", code))
+                    })?;
+                    if !text.is_empty() {
+                        parts.push(ArgPart::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(ArgPart::Code(code));
+                }
+                None => return Err(invalid_exec(exec, "trailing %".to_string())),
+            }
+        }
+        if !text.is_empty() || parts.is_empty() {
+            parts.push(ArgPart::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+}
+
+/// A parsed `Exec` command line.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::exec::{ExecString, FieldCode};
+///
+/// let exec = ExecString::parse(r#""/opt/My App/app" --name=%c %U"#).unwrap();
+/// assert_eq!(exec.program(), "/opt/My App/app");
+/// assert_eq!(exec.args().len(), 2);
+/// assert_eq!(exec.field_codes(), vec![FieldCode::Name, FieldCode::Urls]);
+/// assert!(exec.accepts_urls() && exec.accepts_multiple());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecString {
+    program: String,
+    args: Vec<ExecArg>,
+}
+
+impl ExecString {
+    /// Parses the value of an `Exec` key.
+    ///
+    /// The value must already be unescaped at the string level, as the
+    /// fields of [`DesktopEntry`](crate::DesktopEntry) are.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::InvalidValue`] if the command line is
+    /// empty, has an unterminated quote or an unknown field code, uses a field
+    /// code in the program, uses `%F` or `%U` inside a larger argument, or
+    /// has more than one of `%f`, `%F`, `%u`, and `%U`.
+    pub fn parse(exec: &str) -> Result<Self> {
+        let mut argv = split_words(exec)?.into_iter();
+        let program = argv
+            .next()
+            .ok_or_else(|| invalid_exec(exec, "empty command line".to_string()))?;
+        if ExecArg::parse(&program, exec)?.as_literal().is_none() {
+            return Err(invalid_exec(
+                exec,
+                "field codes are not allowed in the program".to_string(),
+            ));
+        }
+        let program = program.replace("%%", "%");
+
+        let args = argv
+            .map(|arg| ExecArg::parse(&arg, exec))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut targets = 0;
+        for arg in &args {
+            for code in arg.field_codes() {
+                if matches!(code, FieldCode::Files | FieldCode::Urls)
+                    && arg.as_field_code().is_none()
+                {
+                    return Err(invalid_exec(
+                        exec,
+                        format!("%{} must be an argument on its own", code.as_char()),
+                    ));
+                }
+                if code.is_target() {
+                    targets += 1;
+                }
+            }
+        }
+        if targets > 1 {
+            return Err(invalid_exec(
+                exec,
+                "at most one of %f, %F, %u, and %U may be used".to_string(),
+            ));
+        }
+
+        Ok(Self { program, args })
+    }
+
+    /// The program to run.
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+
+    /// The arguments following the program.
+    pub fn args(&self) -> &[ExecArg] {
+        &self.args
+    }
+
+    /// All field codes in argument order.
+    pub fn field_codes(&self) -> Vec<FieldCode> {
+        self.args.iter().flat_map(ExecArg::field_codes).collect()
+    }
+
+    /// Whether the application accepts local files (`%f` or `%F`).
+    pub fn accepts_files(&self) -> bool {
+        self.has_code(|code| matches!(code, FieldCode::File | FieldCode::Files))
+    }
+
+    /// Whether the application accepts URLs (`%u` or `%U`).
+    pub fn accepts_urls(&self) -> bool {
+        self.has_code(|code| matches!(code, FieldCode::Url | FieldCode::Urls))
+    }
+
+    /// Whether the application accepts several targets at once (`%F` or `%U`).
+    pub fn accepts_multiple(&self) -> bool {
+        self.has_code(|code| matches!(code, FieldCode::Files | FieldCode::Urls))
+    }
+
+    fn has_code(&self, predicate: impl Fn(FieldCode) -> bool) -> bool {
+        self.args
+            .iter()
+            .flat_map(ExecArg::field_codes)
+            .any(predicate)
+    }
+}
+
+fn invalid_exec(exec: &str, reason: String) -> DesktopEntryError {
+    DesktopEntryError::InvalidValue("Exec".to_string(), format!("{} in '{}'", reason, exec))
+}
@@ -0,0 +1,454 @@
+//! Incremental validation with a persistent findings index.
+//!
+//! A [`FindingsIndex`] remembers a fingerprint and the validation findings of
+//! every file it has seen. Refreshing the index only re-validates files whose
+//! fingerprint changed, so an always-on health check does not rescan every
+//! launcher directory on each pass.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::database::AppDatabase;
+use crate::{DesktopEntry, DesktopEntryError, Diagnostic, Result, Severity, lint};
+
+const INDEX_HEADER: &str = "xdg-desktop-entry findings 1";
+
+/// Identifies a version of a file's contents.
+///
+/// Size and modification time are compared first, and a file whose size
+/// and modification time are unchanged is assumed unchanged. When they
+/// differ, the content hash avoids re-validating files that were only
+/// touched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fingerprint {
+    /// File size in bytes
+    pub len: u64,
+    /// Modification time in nanoseconds since the Unix epoch, if known
+    pub modified: Option<u128>,
+    /// FNV-1a hash of the contents
+    pub hash: u64,
+}
+
+impl Fingerprint {
+    /// Computes the fingerprint of a file.
+    pub fn of_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let metadata = fs::metadata(path)?;
+        Ok(Self {
+            len: metadata.len(),
+            modified: modified_nanos(&metadata),
+            hash: fnv1a(&fs::read(path)?),
+        })
+    }
+}
+
+/// A validation finding stored in a [`FindingsIndex`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// How serious the problem is
+    pub severity: Severity,
+    /// Identifier of the rule that produced the finding
+    pub code: String,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
+impl From<&Diagnostic> for Finding {
+    fn from(diagnostic: &Diagnostic) -> Self {
+        Self {
+            severity: diagnostic.severity,
+            code: diagnostic.code.to_string(),
+            message: diagnostic.message.clone(),
+        }
+    }
+}
+
+/// Fingerprint and findings of one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileFindings {
+    /// Fingerprint of the validated contents
+    pub fingerprint: Fingerprint,
+    /// Findings for those contents
+    pub findings: Vec<Finding>,
+}
+
+/// Summary of a refresh of a [`FindingsIndex`] or an
+/// [`ApplicationCache`](crate::cache::ApplicationCache).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefreshSummary {
+    /// Files validated or parsed for the first time
+    pub added: Vec<PathBuf>,
+    /// Files processed again because their contents changed
+    pub changed: Vec<PathBuf>,
+    /// Files dropped from the index
+    pub removed: Vec<PathBuf>,
+    /// Number of files whose previous results were reused
+    pub unchanged: usize,
+}
+
+/// Validation findings of a set of files, keyed by path.
+///
+/// # Examples
+///
+/// ```no_run
+/// use xdg_desktop_entry::findings::FindingsIndex;
+///
+/// let mut index = FindingsIndex::load("findings.idx").unwrap_or_default();
+/// let summary = index
+///     .refresh(["/usr/share/applications/firefox.desktop"])
+///     .unwrap();
+/// println!("re-validated {} files", summary.added.len() + summary.changed.len());
+/// index.save("findings.idx").unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FindingsIndex {
+    files: BTreeMap<PathBuf, FileFindings>,
+}
+
+impl FindingsIndex {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validates a file unless its fingerprint is unchanged.
+    ///
+    /// Returns whether the file was (re-)validated. Files that fail to parse
+    /// get a single `parse-error` finding; files that parse get the findings
+    /// of [`DesktopEntry::validate`] and [`lint::lint`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn update(&mut self, path: impl AsRef<Path>) -> Result<bool> {
+        let path = path.as_ref();
+        let metadata = fs::metadata(path)?;
+
+        if let Some(known) = self.files.get(path)
+            && known.fingerprint.len == metadata.len()
+            && known.fingerprint.modified.is_some()
+            && known.fingerprint.modified == modified_nanos(&metadata)
+        {
+            return Ok(false);
+        }
+
+        let bytes = fs::read(path)?;
+        let fingerprint = Fingerprint {
+            len: metadata.len(),
+            modified: modified_nanos(&metadata),
+            hash: fnv1a(&bytes),
+        };
+        if let Some(known) = self.files.get_mut(path)
+            && known.fingerprint.hash == fingerprint.hash
+            && known.fingerprint.len == fingerprint.len
+        {
+            known.fingerprint = fingerprint;
+            return Ok(false);
+        }
+
+        let findings = validate_bytes(path, bytes);
+        self.files.insert(
+            path.to_path_buf(),
+            FileFindings {
+                fingerprint,
+                findings,
+            },
+        );
+        Ok(true)
+    }
+
+    /// Brings the index in line with a set of files.
+    ///
+    /// New and changed files are validated, unchanged files keep their
+    /// findings, and files no longer in the set (or no longer readable) are
+    /// dropped.
+    pub fn refresh<P: AsRef<Path>>(
+        &mut self,
+        paths: impl IntoIterator<Item = P>,
+    ) -> Result<RefreshSummary> {
+        let mut summary = RefreshSummary::default();
+        let mut seen = Vec::new();
+
+        for path in paths {
+            let path = path.as_ref();
+            let known = self.files.contains_key(path);
+            match self.update(path) {
+                Ok(true) if known => summary.changed.push(path.to_path_buf()),
+                Ok(true) => summary.added.push(path.to_path_buf()),
+                Ok(false) => summary.unchanged += 1,
+                Err(DesktopEntryError::Io(_)) => continue,
+                Err(err) => return Err(err),
+            }
+            seen.push(path.to_path_buf());
+        }
+
+        seen.sort();
+        let removed: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| seen.binary_search(path).is_err())
+            .cloned()
+            .collect();
+        for path in &removed {
+            self.files.remove(path);
+        }
+        summary.removed = removed;
+
+        Ok(summary)
+    }
+
+    /// Refreshes the index from the source files of a database's entries.
+    ///
+    /// Entries without a [`source_path`](DesktopEntry::source_path) are
+    /// ignored.
+    pub fn refresh_database(&mut self, database: &AppDatabase) -> Result<RefreshSummary> {
+        let paths: Vec<PathBuf> = database
+            .iter()
+            .filter_map(|(_, entry)| entry.source_path.clone())
+            .collect();
+        self.refresh(paths)
+    }
+
+    /// Drops a file from the index.
+    pub fn remove(&mut self, path: impl AsRef<Path>) -> Option<FileFindings> {
+        self.files.remove(path.as_ref())
+    }
+
+    /// Gets the findings of a file.
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&FileFindings> {
+        self.files.get(path.as_ref())
+    }
+
+    /// Iterates over all indexed files in path order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &FileFindings)> {
+        self.files.iter().map(|(path, file)| (path.as_path(), file))
+    }
+
+    /// Number of indexed files.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether the index holds no files.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Loads an index saved with [`save`](Self::save).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let file = io::BufReader::new(fs::File::open(path)?);
+        let mut lines = file.lines();
+        if lines.next().transpose()?.as_deref() != Some(INDEX_HEADER) {
+            return Err(invalid_index(1));
+        }
+
+        let mut index = Self::new();
+        let mut current: Option<PathBuf> = None;
+        for (line_num, line) in lines.enumerate() {
+            let line = line?;
+            let line_num = line_num + 2;
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields.as_slice() {
+                ["file", path, len, modified, hash] => {
+                    let fingerprint = Fingerprint {
+                        len: len.parse().map_err(|_| invalid_index(line_num))?,
+                        modified: match *modified {
+                            "-" => None,
+                            nanos => Some(nanos.parse().map_err(|_| invalid_index(line_num))?),
+                        },
+                        hash: u64::from_str_radix(hash, 16).map_err(|_| invalid_index(line_num))?,
+                    };
+                    let path = unescape_path(path).ok_or_else(|| invalid_index(line_num))?;
+                    index.files.insert(
+                        path.clone(),
+                        FileFindings {
+                            fingerprint,
+                            findings: Vec::new(),
+                        },
+                    );
+                    current = Some(path);
+                }
+                ["finding", severity, code, message] => {
+                    let severity = match *severity {
+                        "error" => Severity::Error,
+                        "warning" => Severity::Warning,
+                        "info" => Severity::Info,
+                        _ => return Err(invalid_index(line_num)),
+                    };
+                    let file = current
+                        .as_ref()
+                        .and_then(|path| index.files.get_mut(path))
+                        .ok_or_else(|| invalid_index(line_num))?;
+                    file.findings.push(Finding {
+                        severity,
+                        code: unescape_field(code),
+                        message: unescape_field(message),
+                    });
+                }
+                _ => return Err(invalid_index(line_num)),
+            }
+        }
+        Ok(index)
+    }
+
+    /// Saves the index to a file, replacing it atomically.
+    ///
+    /// Paths are saved byte for byte on Unix, where they need not be UTF-8.
+    /// Elsewhere, paths that are not valid Unicode are left out.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        crate::write_atomically(path.as_ref(), |file| self.write(file))?;
+        Ok(())
+    }
+
+    fn write(&self, file: &mut dyn Write) -> io::Result<()> {
+        writeln!(file, "{}", INDEX_HEADER)?;
+        for (path, entry) in &self.files {
+            let Some(path) = escape_path(path) else {
+                continue;
+            };
+            let fingerprint = &entry.fingerprint;
+            writeln!(
+                file,
+                "file\t{}\t{}\t{}\t{:016x}",
+                path,
+                fingerprint.len,
+                fingerprint
+                    .modified
+                    .map_or_else(|| "-".to_string(), |nanos| nanos.to_string()),
+                fingerprint.hash
+            )?;
+            for finding in &entry.findings {
+                writeln!(
+                    file,
+                    "finding\t{}\t{}\t{}",
+                    finding.severity.as_str(),
+                    escape_field(&finding.code),
+                    escape_field(&finding.message)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn validate_bytes(path: &Path, bytes: Vec<u8>) -> Vec<Finding> {
+    let parsed = String::from_utf8(bytes)
+        .map_err(|_| DesktopEntryError::InvalidUtf8)
+        .and_then(|content| DesktopEntry::parse(&content));
+    let mut entry = match parsed {
+        Ok(entry) => entry,
+        Err(err) => {
+            return vec![Finding {
+                severity: Severity::Error,
+                code: "parse-error".to_string(),
+                message: err.to_string(),
+            }];
+        }
+    };
+    entry.source_path = Some(path.to_path_buf());
+
+    let mut findings = Vec::new();
+    if let Err(err) = entry.validate() {
+        findings.push(Finding {
+            severity: Severity::Error,
+            code: "validation-error".to_string(),
+            message: err.to_string(),
+        });
+    }
+    findings.extend(lint::lint(&entry).iter().map(Finding::from));
+    findings
+}
+
+pub(crate) fn modified_nanos(metadata: &fs::Metadata) -> Option<u128> {
+    let modified = metadata.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+/// 64-bit FNV-1a, stable across Rust releases unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn escape_field(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape_field(value: &str) -> String {
+    String::from_utf8_lossy(&unescape_bytes(value).unwrap_or_default()).into_owned()
+}
+
+/// Escapes a path like a field, writing the bytes of Unix paths that are not
+/// UTF-8 as `\xHH`. Returns `None` for other paths that are not Unicode.
+fn escape_path(path: &Path) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        let mut out = String::new();
+        for chunk in path.as_os_str().as_bytes().utf8_chunks() {
+            out.push_str(&escape_field(chunk.valid()));
+            for byte in chunk.invalid() {
+                out.push_str(&format!("\\x{:02x}", byte));
+            }
+        }
+        Some(out)
+    }
+    #[cfg(not(unix))]
+    {
+        path.to_str().map(escape_field)
+    }
+}
+
+fn unescape_path(value: &str) -> Option<PathBuf> {
+    let bytes = unescape_bytes(value)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Some(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+    }
+    #[cfg(not(unix))]
+    {
+        String::from_utf8(bytes).ok().map(PathBuf::from)
+    }
+}
+
+/// Undoes the escaping of a field, returning `None` for an invalid `\xHH`.
+fn unescape_bytes(value: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\\' => match chars.next() {
+                Some('t') => '\t',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('x') => {
+                    let hex = chars.as_str().get(..2)?;
+                    if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+                        return None;
+                    }
+                    out.push(u8::from_str_radix(hex, 16).ok()?);
+                    chars.nth(1);
+                    continue;
+                }
+                Some(other) => other,
+                None => '\\',
+            },
+            c => c,
+        };
+        out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+    Some(out)
+}
+
+fn invalid_index(line: usize) -> DesktopEntryError {
+    DesktopEntryError::InvalidLine(line, "invalid findings index entry".to_string())
+}
@@ -0,0 +1,221 @@
+//! Flatpak applications.
+//!
+//! Flatpak exports the desktop files of installed applications with an
+//! `X-Flatpak` key naming the application ID and an `Exec` line running
+//! `flatpak run`. [`DesktopEntry::flatpak_app_id`] recognizes both.
+//!
+//! Sandboxed applications can only open the files of the host that were
+//! exported to them through the document portal. `flatpak run
+//! --file-forwarding` does so for the arguments between its `@@` markers;
+//! launchers passing files by other means, such as D-Bus activation, can
+//! use `forward_targets` (with the `dbus` feature) first.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::DesktopEntry;
+//!
+//! let entry = DesktopEntry::parse(
+//!     "[Desktop Entry]\nType=Application\nName=GIMP\n\
+//!      Exec=/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=gimp --file-forwarding org.gimp.GIMP @@ %U @@\n",
+//! )
+//! .unwrap();
+//! assert_eq!(entry.flatpak_app_id().as_deref(), Some("org.gimp.GIMP"));
+//! assert!(entry.uses_flatpak_file_forwarding());
+//! ```
+
+use std::path::Path;
+
+use crate::DesktopEntry;
+use crate::exec::ExecString;
+use crate::launch::is_valid_bus_name;
+
+/// Key naming the application ID in exported desktop files.
+pub const FLATPAK_KEY: &str = "X-Flatpak";
+
+/// Marker delimiting the file arguments forwarded through the document
+/// portal; `@@u` marks URIs.
+const FILE_FORWARDING_MARKERS: &[&str] = &["@@", "@@u"];
+
+impl DesktopEntry {
+    /// The ID of the Flatpak application the entry runs.
+    ///
+    /// Uses the `X-Flatpak` key, or else an `Exec` line running `flatpak run`
+    /// with an application ID or ref (`app/<ID>/<arch>/<branch>`).
+    pub fn flatpak_app_id(&self) -> Option<String> {
+        if let Some(id) = self.unknown_string(FLATPAK_KEY)
+            && is_valid_bus_name(&id)
+        {
+            return Some(id);
+        }
+        let exec = ExecString::parse(self.exec.as_deref()?).ok()?;
+        flatpak_run_args(&exec)?
+            .into_iter()
+            .find(|arg| !arg.starts_with('-') && !FILE_FORWARDING_MARKERS.contains(arg))
+            .and_then(|app| {
+                let id = app
+                    .strip_prefix("app/")
+                    .map_or(app, |r#ref| r#ref.split('/').next().unwrap_or_default());
+                is_valid_bus_name(id).then(|| id.to_string())
+            })
+    }
+
+    /// Whether the `Exec` line runs `flatpak run --file-forwarding`, which
+    /// exports the files between the `@@` markers to the application.
+    pub fn uses_flatpak_file_forwarding(&self) -> bool {
+        self.exec
+            .as_deref()
+            .and_then(|exec| ExecString::parse(exec).ok())
+            .is_some_and(|exec| {
+                flatpak_run_args(&exec).is_some_and(|args| args.contains(&"--file-forwarding"))
+            })
+    }
+}
+
+/// The literal arguments following `flatpak run`, if the command line runs
+/// it.
+fn flatpak_run_args(exec: &ExecString) -> Option<Vec<&str>> {
+    let program = Path::new(exec.program()).file_name()?;
+    if program != "flatpak" {
+        return None;
+    }
+    let mut args = exec.args().iter().map(|arg| arg.as_literal());
+    if args.next()? != Some("run") {
+        return None;
+    }
+    Some(args.flatten().collect())
+}
+
+#[cfg(feature = "dbus")]
+pub use portal::{export_to_document_portal, forward_targets};
+
+#[cfg(feature = "dbus")]
+mod portal {
+    use std::fs::File;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+
+    use zbus::blocking::{Connection, Proxy};
+    use zbus::zvariant::{Fd, OwnedValue};
+
+    use crate::Result;
+    use crate::dbus::{dbus_error, target_to_uri};
+    use crate::launch::file_uri_to_path;
+
+    const DOCUMENTS_NAME: &str = "org.freedesktop.portal.Documents";
+    const DOCUMENTS_PATH: &str = "/org/freedesktop/portal/documents";
+
+    /// `AddFull` flags: reuse existing documents and keep them across
+    /// sessions.
+    const REUSE_EXISTING: u32 = 1;
+    const PERSISTENT: u32 = 2;
+
+    /// Exports files through the document portal and grants an application
+    /// access to them.
+    ///
+    /// Returns the paths of the files inside the portal's FUSE mount, in
+    /// the order given, which the application can open from its sandbox.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if a file cannot be opened, the portal is not
+    /// running, or the call fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xdg_desktop_entry::flatpak;
+    ///
+    /// let paths = flatpak::export_to_document_portal(
+    ///     &["/home/user/photo.png"],
+    ///     "org.gimp.GIMP",
+    ///     true,
+    /// )
+    /// .unwrap();
+    /// println!("{}", paths[0].display());
+    /// ```
+    pub fn export_to_document_portal(
+        paths: &[impl AsRef<Path>],
+        app_id: &str,
+        writable: bool,
+    ) -> Result<Vec<PathBuf>> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        let files = paths
+            .iter()
+            .map(|path| File::open(path.as_ref()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        let fds: Vec<Fd<'_>> = files.iter().map(Fd::from).collect();
+        let mut permissions = vec!["read"];
+        if writable {
+            permissions.push("write");
+        }
+
+        let connection = Connection::session().map_err(dbus_error)?;
+        let proxy = Proxy::new(&connection, DOCUMENTS_NAME, DOCUMENTS_PATH, DOCUMENTS_NAME)
+            .map_err(dbus_error)?;
+        let mount_point: Vec<u8> = proxy.call("GetMountPoint", &()).map_err(dbus_error)?;
+        let mount_point = Path::new(std::ffi::OsStr::from_bytes(
+            mount_point.strip_suffix(&[0]).unwrap_or(&mount_point),
+        ))
+        .to_path_buf();
+        let (doc_ids, _): (Vec<String>, std::collections::HashMap<String, OwnedValue>) = proxy
+            .call(
+                "AddFull",
+                &(fds, REUSE_EXISTING | PERSISTENT, app_id, permissions),
+            )
+            .map_err(dbus_error)?;
+
+        Ok(paths
+            .iter()
+            .zip(doc_ids)
+            .map(|(path, doc_id)| {
+                let name = path.as_ref().file_name().unwrap_or_default();
+                mount_point.join(doc_id).join(name)
+            })
+            .collect())
+    }
+
+    /// Exports the local files among launch targets through the document
+    /// portal, replacing them with their paths inside the portal.
+    ///
+    /// Local paths are replaced by paths and `file://` URIs by URIs; other
+    /// URIs are passed through unchanged.
+    ///
+    /// # Errors
+    ///
+    /// See [`export_to_document_portal`].
+    pub fn forward_targets(
+        targets: &[impl AsRef<str>],
+        app_id: &str,
+        writable: bool,
+    ) -> Result<Vec<String>> {
+        let local: Vec<(usize, String)> = targets
+            .iter()
+            .enumerate()
+            .filter_map(|(index, target)| {
+                let target = target.as_ref();
+                let is_local =
+                    target.starts_with("file://") || crate::discovery::uri_scheme(target).is_none();
+                is_local.then(|| (index, file_uri_to_path(target)))
+            })
+            .collect();
+        let paths: Vec<&str> = local.iter().map(|(_, path)| path.as_str()).collect();
+        let exported = export_to_document_portal(&paths, app_id, writable)?;
+
+        let mut forwarded: Vec<String> = targets
+            .iter()
+            .map(|target| target.as_ref().to_string())
+            .collect();
+        for ((index, _), path) in local.into_iter().zip(exported) {
+            let path = path.to_string_lossy().into_owned();
+            forwarded[index] = if forwarded[index].starts_with("file://") {
+                target_to_uri(&path)
+            } else {
+                path
+            };
+        }
+        Ok(forwarded)
+    }
+}
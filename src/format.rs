@@ -0,0 +1,282 @@
+//! Formatting of desktop files.
+//!
+//! [`format_str`] rewrites the text of a desktop file in a consistent style,
+//! the way `rustfmt` does for Rust code, while keeping its comments:
+//!
+//! - whitespace around `=` and at the start of lines is removed
+//! - list values of `[Desktop Entry]` end with a semicolon
+//! - localized values follow their unlocalized value
+//! - `[Desktop Entry]` comes first, followed by the action groups in the
+//!   order of `Actions`, then by the other groups
+//! - groups are separated by a single blank line, and blank lines inside
+//!   groups are dropped
+//!
+//! Comments inside a group move with the key that follows them.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::format::{FormatOptions, format_str};
+//!
+//! let messy = "[Desktop Action new]\nName=New\nExec=app --new\n\
+//!              [Desktop Entry]\nName[de] = Anwendung\n  Type=Application\n\n\
+//!              Name = App\nExec=app\nActions=new\n";
+//!
+//! assert_eq!(
+//!     format_str(messy, &FormatOptions::default()).unwrap(),
+//!     "[Desktop Entry]\nName=App\nName[de]=Anwendung\nType=Application\nExec=app\n\
+//!      Actions=new;\n\n[Desktop Action new]\nName=New\nExec=app --new\n"
+//! );
+//! ```
+
+use crate::lint::{DEPRECATED_KEYS, LIST_KEYS};
+use crate::{
+    ACTION_GROUP_PREFIX, DESKTOP_ENTRY_GROUP, DesktopEntry, DesktopEntryError, KNOWN_KEYS, Result,
+    split_list,
+};
+
+/// Options for [`format_str`] and [`DesktopEntry::format`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Sort the keys of each group by name, and their localized values by
+    /// locale
+    pub sort_keys: bool,
+    /// Prefix keys of `[Desktop Entry]` that are neither defined by the
+    /// specification nor prefixed with `X-` with `X-`, unless that key is
+    /// already set
+    pub prefix_unknown_keys: bool,
+}
+
+/// A key line with the comment lines before it.
+struct KeyLine<'a> {
+    comments: Vec<&'a str>,
+    key: String,
+    locale: Option<&'a str>,
+    value: &'a str,
+}
+
+/// A group with its key lines and the comments after the last one.
+struct Group<'a> {
+    name: &'a str,
+    keys: Vec<KeyLine<'a>>,
+    trailing_comments: Vec<&'a str>,
+}
+
+/// Formats the text of a desktop file.
+///
+/// See the [module documentation](self) for what is changed.
+///
+/// # Errors
+///
+/// Returns [`DesktopEntryError::InvalidLine`] for lines that are neither
+/// blank, comments, group headers, nor key-value pairs, and for key-value
+/// pairs before the first group header.
+pub fn format_str(content: &str, options: &FormatOptions) -> Result<String> {
+    let mut header: Vec<&str> = Vec::new();
+    let mut groups: Vec<Group> = Vec::new();
+    let mut comments: Vec<&str> = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        let invalid_line = || DesktopEntryError::InvalidLine(index + 1, line.to_string());
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            match groups.last() {
+                None => header.push(trimmed),
+                Some(_) if !trimmed.is_empty() => comments.push(trimmed),
+                Some(_) => {}
+            }
+            continue;
+        }
+
+        if trimmed.starts_with('[') {
+            if !trimmed.ends_with(']') {
+                return Err(invalid_line());
+            }
+            if let Some(group) = groups.last_mut() {
+                group.trailing_comments.append(&mut comments);
+            }
+            groups.push(Group {
+                name: &trimmed[1..trimmed.len() - 1],
+                keys: Vec::new(),
+                trailing_comments: Vec::new(),
+            });
+            continue;
+        }
+
+        let (Some(group), Some((key_part, value))) = (groups.last_mut(), trimmed.split_once('='))
+        else {
+            return Err(invalid_line());
+        };
+        let key_part = key_part.trim_end();
+        let (key, locale) = match key_part.split_once('[') {
+            Some((key, locale)) => (
+                key.trim_end(),
+                Some(locale.strip_suffix(']').ok_or_else(invalid_line)?),
+            ),
+            None => (key_part, None),
+        };
+        group.keys.push(KeyLine {
+            comments: std::mem::take(&mut comments),
+            key: key.to_string(),
+            locale,
+            value: value.trim_start(),
+        });
+    }
+    if let Some(group) = groups.last_mut() {
+        group.trailing_comments.append(&mut comments);
+    }
+
+    // Leading and trailing blank lines of the header are dropped
+    while header.last() == Some(&"") {
+        header.pop();
+    }
+    let start = header.iter().take_while(|line| line.is_empty()).count();
+    let mut output: String = header[start..]
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect();
+
+    for (i, group) in order_groups(groups).into_iter().enumerate() {
+        if i > 0 || !output.is_empty() {
+            output.push('\n');
+        }
+        write_group(&mut output, group, options);
+    }
+    Ok(output)
+}
+
+impl DesktopEntry {
+    /// Serializes the entry formatted with [`format_str`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    /// use xdg_desktop_entry::format::FormatOptions;
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nTooltip=Hi\nCategories=Utility\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let options = FormatOptions {
+    ///     sort_keys: true,
+    ///     prefix_unknown_keys: true,
+    /// };
+    /// assert_eq!(
+    ///     entry.format(&options),
+    ///     "[Desktop Entry]\nCategories=Utility;\nExec=app\nName=App\nType=Application\nX-Tooltip=Hi\n"
+    /// );
+    /// ```
+    pub fn format(&self, options: &FormatOptions) -> String {
+        format_str(&self.serialize(), options).expect("serialized entries are well-formed")
+    }
+}
+
+/// Orders `[Desktop Entry]` first, then the action groups in the order of
+/// `Actions`, then the other groups as they were.
+fn order_groups(mut groups: Vec<Group>) -> Vec<Group> {
+    let actions: Vec<String> = groups
+        .iter()
+        .filter(|group| group.name == DESKTOP_ENTRY_GROUP)
+        .flat_map(|group| &group.keys)
+        .find(|line| line.key == "Actions" && line.locale.is_none())
+        .map(|line| split_list(line.value))
+        .unwrap_or_default();
+    let rank = |group: &Group| {
+        if group.name == DESKTOP_ENTRY_GROUP {
+            return 0;
+        }
+        group
+            .name
+            .strip_prefix(ACTION_GROUP_PREFIX)
+            .and_then(|id| actions.iter().position(|action| action == id))
+            .map_or(actions.len() + 1, |position| position + 1)
+    };
+    groups.sort_by_key(rank);
+    groups
+}
+
+fn write_group(output: &mut String, mut group: Group, options: &FormatOptions) {
+    let is_main = group.name == DESKTOP_ENTRY_GROUP;
+    if is_main && options.prefix_unknown_keys {
+        prefix_unknown_keys(&mut group.keys);
+    }
+
+    // Localized values follow the first value of their key
+    let mut order: Vec<String> = Vec::new();
+    for line in &group.keys {
+        if !order.contains(&line.key) {
+            order.push(line.key.clone());
+        }
+    }
+    if options.sort_keys {
+        order.sort();
+    }
+    let mut keys = group.keys;
+    keys.sort_by(|a, b| {
+        let rank = |line: &KeyLine<'_>| {
+            (
+                order.iter().position(|key| *key == line.key),
+                line.locale.is_some(),
+                line.locale
+                    .filter(|_| options.sort_keys)
+                    .map(str::to_string),
+            )
+        };
+        rank(a).cmp(&rank(b))
+    });
+
+    output.push_str(&format!("[{}]\n", group.name));
+    for line in keys {
+        for comment in line.comments {
+            output.push_str(comment);
+            output.push('\n');
+        }
+        output.push_str(&line.key);
+        if let Some(locale) = line.locale {
+            output.push_str(&format!("[{}]", locale));
+        }
+        output.push('=');
+        output.push_str(line.value);
+        if is_main && LIST_KEYS.contains(&line.key.as_str()) && !is_terminated(line.value) {
+            output.push(';');
+        }
+        output.push('\n');
+    }
+    for comment in group.trailing_comments {
+        output.push_str(comment);
+        output.push('\n');
+    }
+}
+
+/// Renames unknown keys to their `X-` form, when it is not taken.
+fn prefix_unknown_keys(keys: &mut [KeyLine]) {
+    let unknown: Vec<String> = keys
+        .iter()
+        .map(|line| line.key.clone())
+        .filter(|key| {
+            !KNOWN_KEYS.contains(&key.as_str())
+                && !DEPRECATED_KEYS.contains(&key.as_str())
+                && !key.starts_with("X-")
+        })
+        .collect();
+    for key in unknown {
+        let prefixed = format!("X-{}", key);
+        if keys.iter().any(|line| line.key == prefixed) {
+            continue;
+        }
+        for line in keys.iter_mut().filter(|line| line.key == key) {
+            line.key = prefixed.clone();
+        }
+    }
+}
+
+/// Whether a list value is empty or ends with an unescaped semicolon.
+fn is_terminated(value: &str) -> bool {
+    let Some(rest) = value.strip_suffix(';') else {
+        return value.is_empty();
+    };
+    rest.chars().rev().take_while(|c| *c == '\\').count() % 2 == 0
+}
@@ -0,0 +1,177 @@
+//! Typed access to the `X-` keys of GNOME and related projects.
+//!
+//! GNOME Shell, gnome-session, and Phosh read a number of extension keys
+//! from `[Desktop Entry]`. [`GnomeExt`] reads and writes them with their
+//! proper types instead of raw strings.
+//!
+//! Requires the `gnome` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::DesktopEntry;
+//! use xdg_desktop_entry::gnome::GnomeExt;
+//!
+//! let mut entry = DesktopEntry::parse(
+//!     "[Desktop Entry]\nType=Application\nName=Chat\nExec=chat\n\
+//!      X-GNOME-UsesNotifications=true\nX-GNOME-Autostart-Delay=5\n\
+//!      X-Purism-FormFactor=Workstation;Mobile;\n",
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(entry.gnome_uses_notifications(), Some(true));
+//! assert_eq!(entry.gnome_autostart_delay(), Some(5));
+//! assert_eq!(entry.purism_form_factors().unwrap(), ["Workstation", "Mobile"]);
+//!
+//! entry.set_gnome_autostart_enabled(Some(false));
+//! assert!(entry.serialize().contains("X-GNOME-Autostart-enabled=false\n"));
+//! ```
+
+use crate::{DesktopEntry, escape_value, join_list};
+
+/// `X-GNOME-UsesNotifications`
+pub const USES_NOTIFICATIONS: &str = "X-GNOME-UsesNotifications";
+/// `X-GNOME-SingleWindow`
+pub const SINGLE_WINDOW: &str = "X-GNOME-SingleWindow";
+/// `X-GNOME-Autostart-enabled`
+pub const AUTOSTART_ENABLED: &str = "X-GNOME-Autostart-enabled";
+/// `X-GNOME-Autostart-Delay`
+pub const AUTOSTART_DELAY: &str = "X-GNOME-Autostart-Delay";
+/// `X-GNOME-Autostart-Phase`
+pub const AUTOSTART_PHASE: &str = "X-GNOME-Autostart-Phase";
+/// `X-GNOME-AutoRestart`
+pub const AUTO_RESTART: &str = "X-GNOME-AutoRestart";
+/// `X-GNOME-HiddenUnderSystemd`
+pub const HIDDEN_UNDER_SYSTEMD: &str = "X-GNOME-HiddenUnderSystemd";
+/// `X-Purism-FormFactor`
+pub const FORM_FACTOR: &str = "X-Purism-FormFactor";
+
+/// Typed getters and setters for GNOME extension keys.
+///
+/// Getters return `None` when the key is missing or its value does not
+/// have the expected type. Setters replace the key, dropping any
+/// translations, or remove it when given `None`.
+pub trait GnomeExt {
+    /// Whether the application sends notifications, so GNOME Settings lists
+    /// it in the notification preferences (`X-GNOME-UsesNotifications`).
+    fn gnome_uses_notifications(&self) -> Option<bool>;
+
+    /// Sets or clears `X-GNOME-UsesNotifications`.
+    fn set_gnome_uses_notifications(&mut self, value: Option<bool>);
+
+    /// Whether the application only ever opens a single window
+    /// (`X-GNOME-SingleWindow`), the predecessor of `SingleMainWindow`.
+    fn gnome_single_window(&self) -> Option<bool>;
+
+    /// Sets or clears `X-GNOME-SingleWindow`.
+    fn set_gnome_single_window(&mut self, value: Option<bool>);
+
+    /// Whether an autostart entry is enabled
+    /// (`X-GNOME-Autostart-enabled`).
+    fn gnome_autostart_enabled(&self) -> Option<bool>;
+
+    /// Sets or clears `X-GNOME-Autostart-enabled`.
+    fn set_gnome_autostart_enabled(&mut self, value: Option<bool>);
+
+    /// Seconds to wait before starting an autostart entry
+    /// (`X-GNOME-Autostart-Delay`).
+    fn gnome_autostart_delay(&self) -> Option<u32>;
+
+    /// Sets or clears `X-GNOME-Autostart-Delay`.
+    fn set_gnome_autostart_delay(&mut self, seconds: Option<u32>);
+
+    /// The gnome-session phase starting an autostart entry, e.g.
+    /// `Initialization` or `Applications` (`X-GNOME-Autostart-Phase`).
+    fn gnome_autostart_phase(&self) -> Option<String>;
+
+    /// Sets or clears `X-GNOME-Autostart-Phase`.
+    fn set_gnome_autostart_phase(&mut self, phase: Option<&str>);
+
+    /// Whether gnome-session restarts the program when it exits
+    /// (`X-GNOME-AutoRestart`).
+    fn gnome_auto_restart(&self) -> Option<bool>;
+
+    /// Sets or clears `X-GNOME-AutoRestart`.
+    fn set_gnome_auto_restart(&mut self, value: Option<bool>);
+
+    /// Whether gnome-session skips the entry when the session is managed by
+    /// systemd (`X-GNOME-HiddenUnderSystemd`).
+    fn gnome_hidden_under_systemd(&self) -> Option<bool>;
+
+    /// Sets or clears `X-GNOME-HiddenUnderSystemd`.
+    fn set_gnome_hidden_under_systemd(&mut self, value: Option<bool>);
+
+    /// The form factors the application adapts to, e.g. `Workstation` and
+    /// `Mobile` (`X-Purism-FormFactor`).
+    fn purism_form_factors(&self) -> Option<Vec<String>>;
+
+    /// Sets or clears `X-Purism-FormFactor`.
+    fn set_purism_form_factors(&mut self, form_factors: Option<&[&str]>);
+}
+
+impl GnomeExt for DesktopEntry {
+    fn gnome_uses_notifications(&self) -> Option<bool> {
+        self.unknown_bool(USES_NOTIFICATIONS)
+    }
+
+    fn set_gnome_uses_notifications(&mut self, value: Option<bool>) {
+        self.set_unknown_raw(USES_NOTIFICATIONS, value.map(|value| value.to_string()));
+    }
+
+    fn gnome_single_window(&self) -> Option<bool> {
+        self.unknown_bool(SINGLE_WINDOW)
+    }
+
+    fn set_gnome_single_window(&mut self, value: Option<bool>) {
+        self.set_unknown_raw(SINGLE_WINDOW, value.map(|value| value.to_string()));
+    }
+
+    fn gnome_autostart_enabled(&self) -> Option<bool> {
+        self.unknown_bool(AUTOSTART_ENABLED)
+    }
+
+    fn set_gnome_autostart_enabled(&mut self, value: Option<bool>) {
+        self.set_unknown_raw(AUTOSTART_ENABLED, value.map(|value| value.to_string()));
+    }
+
+    fn gnome_autostart_delay(&self) -> Option<u32> {
+        self.unknown_string(AUTOSTART_DELAY)?.trim().parse().ok()
+    }
+
+    fn set_gnome_autostart_delay(&mut self, seconds: Option<u32>) {
+        self.set_unknown_raw(AUTOSTART_DELAY, seconds.map(|seconds| seconds.to_string()));
+    }
+
+    fn gnome_autostart_phase(&self) -> Option<String> {
+        self.unknown_string(AUTOSTART_PHASE)
+    }
+
+    fn set_gnome_autostart_phase(&mut self, phase: Option<&str>) {
+        self.set_unknown_raw(AUTOSTART_PHASE, phase.map(escape_value));
+    }
+
+    fn gnome_auto_restart(&self) -> Option<bool> {
+        self.unknown_bool(AUTO_RESTART)
+    }
+
+    fn set_gnome_auto_restart(&mut self, value: Option<bool>) {
+        self.set_unknown_raw(AUTO_RESTART, value.map(|value| value.to_string()));
+    }
+
+    fn gnome_hidden_under_systemd(&self) -> Option<bool> {
+        self.unknown_bool(HIDDEN_UNDER_SYSTEMD)
+    }
+
+    fn set_gnome_hidden_under_systemd(&mut self, value: Option<bool>) {
+        self.set_unknown_raw(HIDDEN_UNDER_SYSTEMD, value.map(|value| value.to_string()));
+    }
+
+    fn purism_form_factors(&self) -> Option<Vec<String>> {
+        self.unknown_string_list(FORM_FACTOR)
+    }
+
+    fn set_purism_form_factors(&mut self, form_factors: Option<&[&str]>) {
+        let value = form_factors.map(join_list);
+        self.set_unknown_raw(FORM_FACTOR, value);
+    }
+}
@@ -0,0 +1,363 @@
+//! Installation of desktop files, like `desktop-file-install`.
+//!
+//! [`install`] copies a desktop file into an `applications` directory,
+//! applying edits on the way, validating the result, and setting the file
+//! permissions; [`uninstall`] removes it again. Both can rebuild the
+//! `mimeinfo.cache` of the directory so the MIME handlers of the entry are
+//! picked up.
+//!
+//! [`DesktopEntry::create_override`] installs an edited copy of a system
+//! entry in the user's directory, where it shadows the original.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use xdg_desktop_entry::install::{self, InstallOptions};
+//!
+//! let options = InstallOptions {
+//!     vendor: Some("example".to_string()),
+//!     add_categories: vec!["Development".to_string()],
+//!     rebuild_mime_cache: true,
+//!     ..InstallOptions::default()
+//! }
+//! .set_key("NoDisplay", "false");
+//!
+//! let path = install::install("build/editor.desktop", &options).unwrap();
+//! assert!(path.ends_with("applications/example-editor.desktop"));
+//!
+//! install::uninstall("example-editor.desktop", &options).unwrap();
+//! ```
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::discovery::{DESKTOP_EXTENSION, DesktopFileId, data_home};
+use crate::exec::{quote_arg, quote_word, split_words};
+use crate::mimeinfo::MimeInfoCache;
+use crate::{
+    DESKTOP_ENTRY_GROUP, DesktopEntry, DesktopEntryError, Fix, KeyEdit, Result, escape_value,
+};
+
+/// Permissions of installed files unless [`InstallOptions::mode`] is set.
+const DEFAULT_MODE: u32 = 0o644;
+
+/// Options for [`install`] and [`uninstall`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstallOptions {
+    /// The `applications` directory to install into; the user's
+    /// `$XDG_DATA_HOME/applications` if `None`
+    pub dir: Option<PathBuf>,
+    /// Vendor prefixed to the file name with a dash, unless the name already
+    /// starts with it
+    pub vendor: Option<String>,
+    /// Key edits applied in order; removing a missing key is not an error
+    pub edits: Vec<KeyEdit>,
+    /// Categories added to `Categories` if missing
+    pub add_categories: Vec<String>,
+    /// Categories removed from `Categories`
+    pub remove_categories: Vec<String>,
+    /// MIME types added to `MimeType` if missing
+    pub add_mime_types: Vec<String>,
+    /// MIME types removed from `MimeType`
+    pub remove_mime_types: Vec<String>,
+    /// Permissions of the installed file, `0o644` if `None`
+    pub mode: Option<u32>,
+    /// Install the entry even if it does not validate
+    pub skip_validation: bool,
+    /// Rebuild the `mimeinfo.cache` of the directory afterwards
+    pub rebuild_mime_cache: bool,
+}
+
+impl InstallOptions {
+    /// Adds an edit setting a key of the `[Desktop Entry]` group, like
+    /// `desktop-file-install --set-key`.
+    pub fn set_key(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.edits.push(KeyEdit::Set {
+            group: DESKTOP_ENTRY_GROUP.to_string(),
+            key: key.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Adds an edit removing a key of the `[Desktop Entry]` group with its
+    /// translations, like `desktop-file-install --remove-key`.
+    pub fn remove_key(mut self, key: impl Into<String>) -> Self {
+        self.edits.push(KeyEdit::Remove {
+            group: DESKTOP_ENTRY_GROUP.to_string(),
+            key: key.into(),
+        });
+        self
+    }
+
+    /// The directory files are installed into.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no directory is set and the user's data
+    /// directory is unknown.
+    pub fn target_dir(&self) -> Result<PathBuf> {
+        self.dir
+            .clone()
+            .or_else(|| data_home().map(|dir| dir.join("applications")))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no user data directory").into())
+    }
+}
+
+/// Installs a desktop file, returning the path it was installed to.
+///
+/// The file keeps its name, prefixed with [`InstallOptions::vendor`]. See
+/// [`install_entry`] for the rest.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed, does not have the
+/// `.desktop` extension, or cannot be installed.
+pub fn install(path: impl AsRef<Path>, options: &InstallOptions) -> Result<PathBuf> {
+    let path = path.as_ref();
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| invalid_file_name(&path.to_string_lossy()))?;
+    install_entry(&DesktopEntry::parse_file(path)?, file_name, options)
+}
+
+/// Installs an entry under a file name, returning the path it was installed
+/// to.
+///
+/// The edits of the options are applied first, then the category and MIME
+/// type changes. Unless [`InstallOptions::skip_validation`] is set, the
+/// result must [validate](DesktopEntry::validate). The directory is created
+/// if needed and an existing file is replaced.
+///
+/// # Errors
+///
+/// Returns an error if the file name does not have the `.desktop`
+/// extension, an edit fails, the entry does not validate, or the file
+/// cannot be written.
+pub fn install_entry(
+    entry: &DesktopEntry,
+    file_name: &str,
+    options: &InstallOptions,
+) -> Result<PathBuf> {
+    if file_name.contains('/')
+        || Path::new(file_name)
+            .extension()
+            .is_none_or(|extension| extension != DESKTOP_EXTENSION)
+    {
+        return Err(invalid_file_name(file_name));
+    }
+    let file_name = match &options.vendor {
+        Some(vendor) if !file_name.starts_with(&format!("{vendor}-")) => {
+            format!("{vendor}-{file_name}")
+        }
+        _ => file_name.to_string(),
+    };
+
+    let mut entry = entry.clone();
+    for edit in &options.edits {
+        if let KeyEdit::Remove { group, key } = edit
+            && !entry
+                .raw_groups()?
+                .get(group)
+                .is_some_and(|entries| entries.contains_key(key))
+        {
+            continue;
+        }
+        entry.apply_fix(&Fix {
+            description: String::new(),
+            edits: vec![edit.clone()],
+        })?;
+    }
+    edit_list(
+        &mut entry.categories,
+        &options.add_categories,
+        &options.remove_categories,
+    );
+    edit_list(
+        &mut entry.mime_type,
+        &options.add_mime_types,
+        &options.remove_mime_types,
+    );
+    if !options.skip_validation {
+        entry.validate()?;
+    }
+
+    let dir = options.target_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    entry.write_file(&path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = options.mode.unwrap_or(DEFAULT_MODE);
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))?;
+    }
+
+    if options.rebuild_mime_cache {
+        MimeInfoCache::update(&dir)?;
+    }
+    Ok(path)
+}
+
+impl DesktopEntry {
+    /// Writes a copy of the entry to the user's `applications` directory
+    /// under the same desktop file ID, shadowing the installed file, with
+    /// edits applied.
+    ///
+    /// Setting `Hidden=true` this way removes the application for the user,
+    /// as the specification describes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if [`source_path`](Self::source_path) is not in one
+    /// of the [`application_dirs`](crate::discovery::application_dirs), an
+    /// edit fails, the result does not validate, or it cannot be written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xdg_desktop_entry::{DesktopEntry, KeyEdit};
+    ///
+    /// let entry = DesktopEntry::parse_file("/usr/share/applications/firefox.desktop").unwrap();
+    /// let edit = entry.append_exec_args_edit(&["--new-window"]).unwrap();
+    /// entry.create_override(&[edit]).unwrap();
+    ///
+    /// // Hide an application
+    /// entry
+    ///     .create_override(&[KeyEdit::Set {
+    ///         group: "Desktop Entry".to_string(),
+    ///         key: "Hidden".to_string(),
+    ///         value: "true".to_string(),
+    ///     }])
+    ///     .unwrap();
+    /// ```
+    pub fn create_override(&self, edits: &[KeyEdit]) -> Result<PathBuf> {
+        let id = self
+            .source_path
+            .as_deref()
+            .and_then(DesktopFileId::from_installed_path)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "entry is not an installed desktop file",
+                )
+            })?;
+        self.create_override_in(&id, InstallOptions::default().target_dir()?, edits)
+    }
+
+    /// Writes a copy of the entry under a desktop file ID into an
+    /// `applications` directory, with edits applied.
+    ///
+    /// See [`create_override`](Self::create_override).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an edit fails, the result does not validate, or
+    /// it cannot be written.
+    pub fn create_override_in(
+        &self,
+        id: &DesktopFileId,
+        dir: impl AsRef<Path>,
+        edits: &[KeyEdit],
+    ) -> Result<PathBuf> {
+        let options = InstallOptions {
+            dir: Some(dir.as_ref().to_path_buf()),
+            edits: edits.to_vec(),
+            ..InstallOptions::default()
+        };
+        install_entry(self, id.as_str(), &options)
+    }
+
+    /// An edit adding arguments to the `Exec` key, before its field codes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no `Exec` key or it is malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, KeyEdit};
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Browser\nExec=browser %u\n",
+    /// )
+    /// .unwrap();
+    /// let edit = entry.append_exec_args_edit(&["--new-window"]).unwrap();
+    /// assert!(matches!(edit, KeyEdit::Set { value, .. } if value == "browser --new-window %u"));
+    /// ```
+    pub fn append_exec_args_edit(&self, args: &[&str]) -> Result<KeyEdit> {
+        let exec = self
+            .exec
+            .as_deref()
+            .ok_or_else(|| DesktopEntryError::MissingRequiredKey("Exec".to_string()))?;
+        let mut words: Vec<String> = split_words(exec)?
+            .iter()
+            .map(|arg| quote_word(arg))
+            .collect();
+        let position = words
+            .iter()
+            .skip(1)
+            .position(|word| word.len() == 2 && word.starts_with('%'))
+            .map_or(words.len(), |position| position + 1);
+        words.splice(position..position, args.iter().map(|arg| quote_arg(arg)));
+        Ok(KeyEdit::Set {
+            group: DESKTOP_ENTRY_GROUP.to_string(),
+            key: "Exec".to_string(),
+            value: escape_value(&words.join(" ")),
+        })
+    }
+}
+
+/// Removes an installed desktop file by its desktop file ID, returning the
+/// path of the removed file.
+///
+/// Only [`InstallOptions::dir`] (or the user's directory) is searched, so
+/// entries of other directories are never touched. Files in subdirectories
+/// are found as by [`DesktopFileId::resolve_in`].
+///
+/// # Errors
+///
+/// Returns a `NotFound` I/O error if no file provides the ID, or an error
+/// if it cannot be removed.
+pub fn uninstall(id: impl AsRef<str>, options: &InstallOptions) -> Result<PathBuf> {
+    let dir = options.target_dir()?;
+    let id = DesktopFileId::new(id.as_ref());
+    let path = id.resolve_in(std::slice::from_ref(&dir)).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not installed in {}", id, dir.display()),
+        )
+    })?;
+    std::fs::remove_file(&path)?;
+
+    if options.rebuild_mime_cache {
+        MimeInfoCache::update(&dir)?;
+    }
+    Ok(path)
+}
+
+/// Adds missing items to a list key and removes others, dropping the key
+/// when it ends up empty.
+fn edit_list(list: &mut Option<Vec<String>>, add: &[String], remove: &[String]) {
+    if add.is_empty() && remove.is_empty() {
+        return;
+    }
+    let mut items = list.take().unwrap_or_default();
+    for item in add {
+        if !items.contains(item) {
+            items.push(item.clone());
+        }
+    }
+    items.retain(|item| !remove.contains(item));
+    *list = (!items.is_empty()).then_some(items);
+}
+
+fn invalid_file_name(name: &str) -> DesktopEntryError {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("not a desktop file name: {name}"),
+    )
+    .into()
+}
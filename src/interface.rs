@@ -0,0 +1,99 @@
+//! Interface names listed in the `Implements` key.
+//!
+//! Applications declare the interfaces they implement, such as
+//! `org.freedesktop.FileManager1` or a search provider interface, so
+//! desktop shells can find pluggable implementations. Interface names follow
+//! the D-Bus naming rules: reverse-DNS names of at least two dot-separated
+//! elements.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::DesktopEntry;
+//! use xdg_desktop_entry::interface::Interface;
+//!
+//! let file_manager = Interface::parse("org.freedesktop.FileManager1").unwrap();
+//! assert!(Interface::parse("FileManager").is_none());
+//!
+//! let entry = DesktopEntry::parse(
+//!     "[Desktop Entry]\nType=Application\nName=Files\nExec=files\nImplements=org.freedesktop.FileManager1;\n",
+//! )
+//! .unwrap();
+//! assert!(entry.implements_interface(&file_manager));
+//! ```
+
+use std::fmt;
+
+use crate::DesktopEntry;
+
+/// Maximum length of a D-Bus interface name.
+const MAX_LENGTH: usize = 255;
+
+/// A valid interface name, e.g. `org.freedesktop.FileManager1`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interface(String);
+
+impl Interface {
+    /// Checks an interface name.
+    ///
+    /// Returns `None` unless the name has at least two elements separated by
+    /// dots, each made of ASCII letters, digits, and underscores and not
+    /// starting with a digit, and is at most 255 bytes long.
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::is_valid(name).then(|| Self(name.to_string()))
+    }
+
+    /// Whether a name is a valid interface name; see [`parse`](Self::parse).
+    pub fn is_valid(name: &str) -> bool {
+        name.len() <= MAX_LENGTH
+            && name.contains('.')
+            && name.split('.').all(|element| {
+                element
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                    && element
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            })
+    }
+
+    /// The interface name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Interface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Interface {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl DesktopEntry {
+    /// Whether the entry lists an interface in its `Implements` key.
+    ///
+    /// Takes an [`Interface`] or any name.
+    pub fn implements_interface(&self, interface: impl AsRef<str>) -> bool {
+        let interface = interface.as_ref();
+        self.implements
+            .as_ref()
+            .is_some_and(|implements| implements.iter().any(|i| i == interface))
+    }
+
+    /// The valid interface names in the `Implements` key, skipping invalid
+    /// ones.
+    pub fn interfaces(&self) -> Vec<Interface> {
+        self.implements
+            .iter()
+            .flatten()
+            .filter_map(|name| Interface::parse(name))
+            .collect()
+    }
+}
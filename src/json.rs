@@ -0,0 +1,362 @@
+//! The JSON representation of [`DesktopEntry::to_json`] and
+//! [`DesktopEntry::from_json`].
+
+use std::fmt::Write as _;
+
+use crate::layout::{groups_of, value_of};
+use crate::{DesktopEntry, DesktopEntryError, Result, SerializeOptions};
+
+/// Version of the representation, written as `"version"`.
+pub(crate) const JSON_VERSION: u64 = 1;
+
+/// Maximum depth of nested arrays and objects.
+const MAX_DEPTH: usize = 64;
+
+/// Writes the groups of `entry` in canonical order, each key mapping its
+/// locales (`""` for the unlocalized value) to raw values.
+pub(crate) fn to_json(entry: &DesktopEntry) -> String {
+    let canonical = entry.serialize_with(&SerializeOptions {
+        canonical: true,
+        ..SerializeOptions::default()
+    });
+    let mut output = format!("{{\n  \"version\": {},\n  \"groups\": {{", JSON_VERSION);
+
+    for (group_index, (group, lines)) in groups_of(&canonical).iter().enumerate() {
+        // Localized variants follow their key in canonical order
+        let mut keys: Vec<(&str, Vec<(String, &str)>)> = Vec::new();
+        for ((_, key, locale), line) in lines {
+            let locale = locale
+                .as_ref()
+                .map_or_else(String::new, |locale| locale.to_string_repr());
+            match keys.iter_mut().find(|(name, _)| name == key) {
+                Some((_, values)) => values.push((locale, value_of(line))),
+                None => keys.push((key, vec![(locale, value_of(line))])),
+            }
+        }
+
+        let separator = if group_index == 0 { "" } else { "," };
+        let _ = write!(output, "{}\n    {}: {{", separator, quote(group));
+        for (key_index, (key, values)) in keys.iter().enumerate() {
+            let separator = if key_index == 0 { "" } else { "," };
+            let _ = write!(output, "{}\n      {}: {{", separator, quote(key));
+            for (value_index, (locale, value)) in values.iter().enumerate() {
+                let separator = if value_index == 0 { "" } else { ", " };
+                let _ = write!(output, "{}{}: {}", separator, quote(locale), quote(value));
+            }
+            output.push('}');
+        }
+        output.push_str(if keys.is_empty() { "}" } else { "\n    }" });
+    }
+
+    output.push_str("\n  }\n}\n");
+    output
+}
+
+/// Converts the JSON representation back to the text of a desktop entry
+/// file.
+pub(crate) fn to_desktop_file(json: &str) -> Result<String> {
+    let mut parser = JsonParser {
+        input: json,
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.pos < json.len() {
+        return Err(parser.error("trailing characters"));
+    }
+
+    let Value::Object(root) = value else {
+        return Err(invalid("the top-level value must be an object"));
+    };
+    match field(&root, "version") {
+        Some(Value::Number(version)) if *version == JSON_VERSION as f64 => {}
+        Some(_) => return Err(invalid("unsupported version")),
+        None => return Err(invalid("missing \"version\"")),
+    }
+    let Some(Value::Object(groups)) = field(&root, "groups") else {
+        return Err(invalid("\"groups\" must be an object"));
+    };
+
+    let mut content = String::new();
+    for (group, keys) in groups {
+        if group.contains(['[', ']', '\n', '\r']) {
+            return Err(invalid(&format!("invalid group name {}", quote(group))));
+        }
+        let Value::Object(keys) = keys else {
+            return Err(invalid(&format!(
+                "group {} must be an object",
+                quote(group)
+            )));
+        };
+        let _ = writeln!(content, "[{}]", group);
+        for (key, values) in keys {
+            let Value::Object(values) = values else {
+                return Err(invalid(&format!("key {} must be an object", quote(key))));
+            };
+            for (locale, value) in values {
+                let Value::String(value) = value else {
+                    return Err(invalid(&format!(
+                        "values of {} must be strings",
+                        quote(key)
+                    )));
+                };
+                if value.contains(['\n', '\r'])
+                    || locale.contains(['[', ']', '=', '\n', '\r'])
+                    || key.contains(['[', '=', '\n', '\r'])
+                {
+                    return Err(invalid(&format!(
+                        "line breaks and delimiters are not allowed in {}",
+                        quote(key)
+                    )));
+                }
+                if locale.is_empty() {
+                    let _ = writeln!(content, "{}={}", key, value);
+                } else {
+                    let _ = writeln!(content, "{}[{}]={}", key, locale, value);
+                }
+            }
+        }
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+fn invalid(message: &str) -> DesktopEntryError {
+    DesktopEntryError::InvalidJson(message.to_string())
+}
+
+fn field<'a>(object: &'a [(String, Value)], name: &str) -> Option<&'a Value> {
+    object
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value)
+}
+
+/// Quotes a string as a JSON string literal.
+pub(crate) fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A parsed JSON value. Objects keep their keys in order.
+enum Value {
+    Null,
+    Bool,
+    Number(f64),
+    String(String),
+    Array,
+    Object(Vec<(String, Value)>),
+}
+
+/// A recursive descent parser for RFC 8259 JSON.
+struct JsonParser<'a> {
+    input: &'a str,
+    pos: usize,
+    /// Number of arrays and objects being parsed
+    depth: usize,
+}
+
+impl JsonParser<'_> {
+    fn error(&self, message: &str) -> DesktopEntryError {
+        DesktopEntryError::InvalidJson(format!("{} at byte {}", message, self.pos))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() != Some(byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn parse_value(&mut self) -> Result<Value> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(open @ (b'{' | b'[')) => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("values nested too deeply"));
+                }
+                self.depth += 1;
+                let value = if open == b'{' {
+                    self.parse_object()
+                } else {
+                    self.parse_array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some(b'"') => Ok(Value::String(self.parse_string()?)),
+            Some(b't') => self.parse_literal("true", Value::Bool),
+            Some(b'f') => self.parse_literal("false", Value::Bool),
+            Some(b'n') => self.parse_literal("null", Value::Null),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: Value) -> Result<Value> {
+        if !self.input[self.pos..].starts_with(literal) {
+            return Err(self.error("invalid literal"));
+        }
+        self.pos += literal.len();
+        Ok(value)
+    }
+
+    fn parse_number(&mut self) -> Result<Value> {
+        let start = self.pos;
+        while matches!(
+            self.peek(),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.pos += 1;
+        }
+        self.input[start..self.pos]
+            .parse()
+            .map(Value::Number)
+            .map_err(|_| self.error("invalid number"))
+    }
+
+    fn parse_array(&mut self) -> Result<Value> {
+        self.expect(b'[')?;
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Value::Array);
+        }
+        loop {
+            self.parse_value()?;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array);
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Value> {
+        self.expect(b'{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            if fields.iter().any(|(existing, _)| *existing == key) {
+                return Err(self.error(&format!("duplicate key {}", quote(&key))));
+            }
+            self.expect(b':')?;
+            let value = self.parse_value()?;
+            fields.push((key, value));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(fields));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String> {
+        if self.peek() != Some(b'"') {
+            return Err(self.error("expected a string"));
+        }
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            let rest = &self.input[self.pos..];
+            let Some(c) = rest.chars().next() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(value),
+                '\\' => {
+                    let escape = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    match escape {
+                        b'"' => value.push('"'),
+                        b'\\' => value.push('\\'),
+                        b'/' => value.push('/'),
+                        b'b' => value.push('\u{8}'),
+                        b'f' => value.push('\u{c}'),
+                        b'n' => value.push('\n'),
+                        b'r' => value.push('\r'),
+                        b't' => value.push('\t'),
+                        b'u' => value.push(self.parse_unicode_escape()?),
+                        _ => return Err(self.error("invalid escape sequence")),
+                    }
+                }
+                c if c.is_control() => return Err(self.error("unescaped control character")),
+                c => value.push(c),
+            }
+        }
+    }
+
+    /// Parses the digits of a `\u` escape, combining surrogate pairs.
+    fn parse_unicode_escape(&mut self) -> Result<char> {
+        let high = self.parse_hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.input[self.pos..].starts_with("\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 2;
+            let low = self.parse_hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid code point"))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32> {
+        let digits = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| self.error("truncated \\u escape"))?;
+        let code = u32::from_str_radix(digits, 16).map_err(|_| self.error("invalid \\u escape"))?;
+        self.pos += 4;
+        Ok(code)
+    }
+}
@@ -0,0 +1,146 @@
+//! Typed access to the KDE-specific keys.
+//!
+//! KDE Plasma and KIO read a few keys from `[Desktop Entry]` that the
+//! specification does not define. [`KdeExt`] reads and writes them with
+//! their proper types instead of raw strings.
+//!
+//! Requires the `kde` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::DesktopEntry;
+//! use xdg_desktop_entry::kde::KdeExt;
+//!
+//! let mut entry = DesktopEntry::parse(
+//!     "[Desktop Entry]\nType=Application\nName=Dolphin\nExec=dolphin %u\n\
+//!      X-KDE-Protocols=file;sftp;smb;\nInitialPreference=9\n",
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(entry.kde_protocols().unwrap(), ["file", "sftp", "smb"]);
+//! assert_eq!(entry.initial_preference(), Some(9));
+//!
+//! entry.set_kde_run_on_discrete_gpu(Some(true));
+//! assert!(entry.wants_non_default_gpu());
+//! ```
+
+use crate::{DesktopEntry, escape_value, join_list};
+
+/// `X-KDE-RunOnDiscreteGpu`
+pub const RUN_ON_DISCRETE_GPU: &str = "X-KDE-RunOnDiscreteGpu";
+/// `X-KDE-StartupNotify`
+pub const STARTUP_NOTIFY: &str = "X-KDE-StartupNotify";
+/// `X-KDE-SubstituteUID`
+pub const SUBSTITUTE_UID: &str = "X-KDE-SubstituteUID";
+/// `X-KDE-Username`
+pub const USERNAME: &str = "X-KDE-Username";
+/// `X-KDE-Protocols`
+pub const PROTOCOLS: &str = "X-KDE-Protocols";
+/// `InitialPreference`
+pub const INITIAL_PREFERENCE: &str = "InitialPreference";
+
+/// Typed getters and setters for KDE-specific keys.
+///
+/// Getters return `None` when the key is missing or its value does not
+/// have the expected type. Setters replace the key, dropping any
+/// translations, or remove it when given `None`.
+pub trait KdeExt {
+    /// Whether the application should run on the discrete GPU
+    /// (`X-KDE-RunOnDiscreteGpu`).
+    ///
+    /// See [`DesktopEntry::wants_non_default_gpu`], which combines this key
+    /// with `PrefersNonDefaultGPU`.
+    fn kde_run_on_discrete_gpu(&self) -> Option<bool>;
+
+    /// Sets or clears `X-KDE-RunOnDiscreteGpu`.
+    fn set_kde_run_on_discrete_gpu(&mut self, value: Option<bool>);
+
+    /// KDE's older spelling of `StartupNotify` (`X-KDE-StartupNotify`).
+    fn kde_startup_notify(&self) -> Option<bool>;
+
+    /// Sets or clears `X-KDE-StartupNotify`.
+    fn set_kde_startup_notify(&mut self, value: Option<bool>);
+
+    /// Whether the program runs as another user, named by
+    /// [`kde_username`](Self::kde_username) (`X-KDE-SubstituteUID`).
+    fn kde_substitute_uid(&self) -> Option<bool>;
+
+    /// Sets or clears `X-KDE-SubstituteUID`.
+    fn set_kde_substitute_uid(&mut self, value: Option<bool>);
+
+    /// The user to run the program as, `root` if missing
+    /// (`X-KDE-Username`).
+    fn kde_username(&self) -> Option<String>;
+
+    /// Sets or clears `X-KDE-Username`.
+    fn set_kde_username(&mut self, username: Option<&str>);
+
+    /// The KIO protocols the application accepts URLs of, beyond local
+    /// files (`X-KDE-Protocols`).
+    fn kde_protocols(&self) -> Option<Vec<String>>;
+
+    /// Sets or clears `X-KDE-Protocols`.
+    fn set_kde_protocols(&mut self, protocols: Option<&[&str]>);
+
+    /// The rank of the application among the handlers of its MIME types
+    /// when no default is set, higher first (`InitialPreference`).
+    fn initial_preference(&self) -> Option<i32>;
+
+    /// Sets or clears `InitialPreference`.
+    fn set_initial_preference(&mut self, preference: Option<i32>);
+}
+
+impl KdeExt for DesktopEntry {
+    fn kde_run_on_discrete_gpu(&self) -> Option<bool> {
+        self.unknown_bool(RUN_ON_DISCRETE_GPU)
+    }
+
+    fn set_kde_run_on_discrete_gpu(&mut self, value: Option<bool>) {
+        self.set_unknown_raw(RUN_ON_DISCRETE_GPU, value.map(|value| value.to_string()));
+    }
+
+    fn kde_startup_notify(&self) -> Option<bool> {
+        self.unknown_bool(STARTUP_NOTIFY)
+    }
+
+    fn set_kde_startup_notify(&mut self, value: Option<bool>) {
+        self.set_unknown_raw(STARTUP_NOTIFY, value.map(|value| value.to_string()));
+    }
+
+    fn kde_substitute_uid(&self) -> Option<bool> {
+        self.unknown_bool(SUBSTITUTE_UID)
+    }
+
+    fn set_kde_substitute_uid(&mut self, value: Option<bool>) {
+        self.set_unknown_raw(SUBSTITUTE_UID, value.map(|value| value.to_string()));
+    }
+
+    fn kde_username(&self) -> Option<String> {
+        self.unknown_string(USERNAME)
+    }
+
+    fn set_kde_username(&mut self, username: Option<&str>) {
+        self.set_unknown_raw(USERNAME, username.map(escape_value));
+    }
+
+    fn kde_protocols(&self) -> Option<Vec<String>> {
+        self.unknown_string_list(PROTOCOLS)
+    }
+
+    fn set_kde_protocols(&mut self, protocols: Option<&[&str]>) {
+        let value = protocols.map(join_list);
+        self.set_unknown_raw(PROTOCOLS, value);
+    }
+
+    fn initial_preference(&self) -> Option<i32> {
+        self.unknown_string(INITIAL_PREFERENCE)?.trim().parse().ok()
+    }
+
+    fn set_initial_preference(&mut self, preference: Option<i32>) {
+        self.set_unknown_raw(
+            INITIAL_PREFERENCE,
+            preference.map(|preference| preference.to_string()),
+        );
+    }
+}
@@ -0,0 +1,699 @@
+//! Launching desktop entries.
+
+use std::process::{Child, Command, Stdio};
+
+use crate::exec::{ArgPart, ExecString, FieldCode};
+use crate::{
+    ACTION_GROUP_PREFIX, DesktopEntry, DesktopEntryError, DesktopEntryType, Group, Locale, Result,
+};
+
+/// How an application entry should be started.
+///
+/// Returned by [`DesktopEntry::effective_launch_method`](crate::DesktopEntry::effective_launch_method).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchMethod {
+    /// Activate the application over D-Bus.
+    ///
+    /// The spec asks applications to keep an `Exec` key for implementations
+    /// that do not support D-Bus activation, so the command line is provided
+    /// as a fallback for when the session bus is unavailable.
+    DBusActivation {
+        /// Well-known bus name, derived from the desktop file name
+        name: String,
+        /// Command line to use if D-Bus activation is not possible
+        fallback: Option<Vec<String>>,
+    },
+    /// Execute the command line from the `Exec` key.
+    ExecCommand {
+        /// Program and arguments with quoting undone; field codes are left in
+        /// place for the launcher to expand
+        argv: Vec<String>,
+    },
+    /// The entry cannot be launched (not an application, or no `Exec`).
+    None,
+}
+
+/// Derives the D-Bus object path for a well-known bus name.
+///
+/// Dots become slashes, dashes become underscores, and a slash is prefixed
+/// (e.g., "org.example.Foo-Viewer" becomes "/org/example/Foo_Viewer").
+///
+/// # Specification Reference
+///
+/// Section 8: "D-Bus Activation"
+pub fn dbus_object_path(bus_name: &str) -> String {
+    format!("/{}", bus_name.replace('.', "/").replace('-', "_"))
+}
+
+/// Whether a name is a valid D-Bus well-known bus name: at least two
+/// dot-separated elements of ASCII letters, digits, `_`, and `-`, not
+/// starting with a digit, and at most 255 characters in all.
+pub(crate) fn is_valid_bus_name(name: &str) -> bool {
+    name.len() <= 255
+        && name.contains('.')
+        && name.split('.').all(|element| {
+            element
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '-')
+                && element
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+}
+
+/// Environment variable holding the path of the launched desktop file.
+pub const DESKTOP_FILE_ENV: &str = "GIO_LAUNCHED_DESKTOP_FILE";
+
+/// Environment variable holding the PID of the launched process.
+pub const DESKTOP_FILE_PID_ENV: &str = "GIO_LAUNCHED_DESKTOP_FILE_PID";
+
+/// Environment variables describing a launch, as set by GLib's launcher.
+///
+/// Applications and session managers read `GIO_LAUNCHED_DESKTOP_FILE` and
+/// `GIO_LAUNCHED_DESKTOP_FILE_PID` to find the desktop file a process was
+/// started from. Returned by
+/// [`DesktopEntry::to_exec_env_description`](crate::DesktopEntry::to_exec_env_description).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecEnvDescription {
+    /// Absolute path of the desktop file, if the entry was read from one
+    pub desktop_file: Option<std::path::PathBuf>,
+}
+
+impl ExecEnvDescription {
+    /// Environment variables for the launched process with the given PID.
+    ///
+    /// Nothing is exported for entries without a desktop file, matching GLib.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::launch::ExecEnvDescription;
+    ///
+    /// let description = ExecEnvDescription {
+    ///     desktop_file: Some("/usr/share/applications/foo.desktop".into()),
+    /// };
+    /// assert_eq!(
+    ///     description.vars(42),
+    ///     vec![
+    ///         (
+    ///             "GIO_LAUNCHED_DESKTOP_FILE".to_string(),
+    ///             "/usr/share/applications/foo.desktop".to_string()
+    ///         ),
+    ///         ("GIO_LAUNCHED_DESKTOP_FILE_PID".to_string(), "42".to_string()),
+    ///     ]
+    /// );
+    /// ```
+    pub fn vars(&self, pid: u32) -> Vec<(String, String)> {
+        match &self.desktop_file {
+            Some(path) => vec![
+                (
+                    DESKTOP_FILE_ENV.to_string(),
+                    path.to_string_lossy().into_owned(),
+                ),
+                (DESKTOP_FILE_PID_ENV.to_string(), pid.to_string()),
+            ],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Terminal command used for `Terminal=true` applications when
+/// [`find_terminal_emulator`] finds none.
+pub const DEFAULT_TERMINAL: &[&str] = &["xterm", "-e"];
+
+/// Launcher of the user's preferred terminal, from the proposed
+/// `xdg-terminal-exec` specification. It takes the command to run as its
+/// arguments.
+pub const XDG_TERMINAL_EXEC: &str = "xdg-terminal-exec";
+
+/// Terminal emulators tried by [`find_terminal_emulator`], in order, with
+/// the arguments that precede the command to run.
+pub const TERMINAL_EMULATORS: &[&[&str]] = &[
+    &["x-terminal-emulator", "-e"],
+    &["kgx", "-e"],
+    &["gnome-terminal", "--"],
+    &["konsole", "-e"],
+    &["xfce4-terminal", "-x"],
+    &["mate-terminal", "-x"],
+    &["lxterminal", "-e"],
+    &["qterminal", "-e"],
+    &["tilix", "-e"],
+    &["terminator", "-x"],
+    &["alacritty", "-e"],
+    &["kitty"],
+    &["foot"],
+    &["wezterm", "start", "--"],
+    &["urxvt", "-e"],
+    &["st", "-e"],
+    &["xterm", "-e"],
+];
+
+/// Finds a terminal emulator to run `Terminal=true` applications in.
+///
+/// Returns the command prefix to put before the program: `xdg-terminal-exec`
+/// if installed, then the emulator named by `$TERMINAL`, then the first
+/// installed one of [`TERMINAL_EMULATORS`], which starts with Debian's
+/// `x-terminal-emulator` alternative. `$TERMINAL` gets the arguments listed
+/// for it in [`TERMINAL_EMULATORS`], or `-e` if it is not listed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use xdg_desktop_entry::launch::find_terminal_emulator;
+///
+/// if let Some(terminal) = find_terminal_emulator() {
+///     println!("Terminal applications run in {}", terminal[0]);
+/// }
+/// ```
+pub fn find_terminal_emulator() -> Option<Vec<String>> {
+    let to_vec = |command: &[&str]| command.iter().map(|arg| arg.to_string()).collect();
+
+    if crate::is_installed(XDG_TERMINAL_EXEC) {
+        return Some(vec![XDG_TERMINAL_EXEC.to_string()]);
+    }
+
+    if let Ok(terminal) = std::env::var("TERMINAL")
+        && !terminal.is_empty()
+        && crate::is_installed(&terminal)
+    {
+        let name = std::path::Path::new(&terminal)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&terminal);
+        let mut command = vec![terminal.clone()];
+        match TERMINAL_EMULATORS.iter().find(|known| known[0] == name) {
+            Some(known) => command.extend(known[1..].iter().map(|arg| arg.to_string())),
+            None => command.push("-e".to_string()),
+        }
+        return Some(command);
+    }
+
+    TERMINAL_EMULATORS
+        .iter()
+        .find(|command| crate::is_installed(command[0]))
+        .map(|command| to_vec(command))
+}
+
+/// Options for [`DesktopEntry::launch`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchOptions {
+    /// Preferred locales for `%c`, most preferred first
+    pub locales: Vec<Locale>,
+    /// Command prefix running a program in a terminal, for entries with
+    /// `Terminal=true`; found with [`find_terminal_emulator`] if `None`
+    pub terminal: Option<Vec<String>>,
+    /// Run the application in its own systemd scope, through
+    /// `systemd-run --user --scope`, as GNOME and KDE do so that each
+    /// application gets its own cgroup
+    pub systemd_scope: bool,
+}
+
+impl DesktopEntry {
+    /// Builds the command launching this application with files or URLs.
+    ///
+    /// Field codes in `Exec` are expanded: `%f` and `%F` with the targets as
+    /// local paths (`file://` URIs are converted), `%u` and `%U` with the
+    /// targets as given, `%i` with `--icon` and the `Icon` key, `%c` with the
+    /// name translated for [`LaunchOptions::locales`], and `%k` with the
+    /// desktop file location. Codes without a value are dropped, as are
+    /// deprecated codes. Targets are ignored if `Exec` takes none.
+    ///
+    /// `Path` sets the working directory, `Terminal=true` runs the command
+    /// through [`LaunchOptions::terminal`], and `GIO_LAUNCHED_DESKTOP_FILE`
+    /// and `GIO_LAUNCHED_DESKTOP_FILE_PID` are exported when the desktop file
+    /// is known. The PID has to be the child's own, so the program is then
+    /// started through a `sh` that exports its PID and `exec`s it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry is not an application, has no valid
+    /// `Exec` key, or is given several targets while `Exec` accepts only one
+    /// (`%f` or `%u`); launch such entries once per target.
+    ///
+    /// # Specification Reference
+    ///
+    /// Section 7: "The `Exec` key"
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    /// use xdg_desktop_entry::launch::LaunchOptions;
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Viewer\nExec=viewer --title=%c %F\nPath=/tmp\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let command = entry
+    ///     .launch_command(&["a.png", "file:///tmp/b%20c.png"], &LaunchOptions::default())
+    ///     .unwrap();
+    /// assert_eq!(command.get_program(), "viewer");
+    /// let args: Vec<_> = command.get_args().collect();
+    /// assert_eq!(args, ["--title=Viewer", "a.png", "/tmp/b c.png"]);
+    /// assert_eq!(command.get_current_dir(), Some("/tmp".as_ref()));
+    /// ```
+    pub fn launch_command(
+        &self,
+        targets: &[impl AsRef<str>],
+        options: &LaunchOptions,
+    ) -> Result<Command> {
+        if self.entry_type != DesktopEntryType::Application {
+            return Err(DesktopEntryError::ValidationError(format!(
+                "Cannot launch an entry of type {}",
+                self.entry_type.as_str()
+            )));
+        }
+
+        let exec = self.parsed_exec()?;
+        let targets: Vec<&str> = targets.iter().map(AsRef::as_ref).collect();
+        self.command_for(&exec, &targets, options)
+    }
+
+    /// Builds the command running an action of this application.
+    ///
+    /// The action's `Exec` line is expanded like the main one by
+    /// [`launch_command`](Self::launch_command), without targets; `Path`,
+    /// `Terminal`, and the launch environment come from the entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry is not an application, the action is
+    /// not listed in `Actions` or has no group, or the action has no valid
+    /// `Exec` key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    /// use xdg_desktop_entry::launch::LaunchOptions;
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Browser\nExec=browser %u\nActions=private;\n\n\
+    ///      [Desktop Action private]\nName=Private Window\nExec=browser --private-window\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let command = entry.launch_action_command("private", &LaunchOptions::default()).unwrap();
+    /// assert_eq!(command.get_program(), "browser");
+    /// assert_eq!(command.get_args().collect::<Vec<_>>(), ["--private-window"]);
+    /// ```
+    pub fn launch_action_command(&self, action: &str, options: &LaunchOptions) -> Result<Command> {
+        if self.entry_type != DesktopEntryType::Application {
+            return Err(DesktopEntryError::ValidationError(format!(
+                "Cannot launch an entry of type {}",
+                self.entry_type.as_str()
+            )));
+        }
+
+        let group = self.action_group(action)?;
+        let exec = group.get_string("Exec").ok_or_else(|| {
+            DesktopEntryError::MissingRequiredKey(format!("Exec in [{}]", group.name))
+        })?;
+        self.command_for(&ExecString::parse(&exec)?, &[], options)
+    }
+
+    /// Runs an action of this application, as listed in `Actions`.
+    ///
+    /// With the `dbus` feature, `DBusActivatable` applications are asked to
+    /// run the action with `ActivateAction`, like GIO does, and `None` is
+    /// returned; use `launch_dbus_action` to pass platform data. Otherwise
+    /// the action's `Exec` line is spawned as built by
+    /// [`launch_action_command`](Self::launch_action_command).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the action cannot be found, activated, or
+    /// spawned.
+    pub fn launch_action(&self, action: &str, options: &LaunchOptions) -> Result<Option<Child>> {
+        #[cfg(feature = "dbus")]
+        if matches!(
+            self.effective_launch_method()?,
+            LaunchMethod::DBusActivation { .. }
+        ) {
+            self.launch_dbus_action(action, &crate::dbus::PlatformData::default())?;
+            return Ok(None);
+        }
+
+        Ok(Some(self.launch_action_command(action, options)?.spawn()?))
+    }
+
+    /// The group of an action listed in `Actions`.
+    pub(crate) fn action_group(&self, action: &str) -> Result<&Group> {
+        self.actions
+            .iter()
+            .flatten()
+            .any(|id| id == action)
+            .then(|| {
+                self.additional_groups
+                    .get(&format!("{}{}", ACTION_GROUP_PREFIX, action))
+            })
+            .flatten()
+            .ok_or_else(|| {
+                DesktopEntryError::ValidationError(format!("Unknown action '{}'", action))
+            })
+    }
+
+    /// Builds the command running a parsed `Exec` line of this entry.
+    fn command_for(
+        &self,
+        exec: &ExecString,
+        targets: &[&str],
+        options: &LaunchOptions,
+    ) -> Result<Command> {
+        let mut argv = self.expand_exec(exec, targets, options)?;
+
+        if self.terminal == Some(true) {
+            let terminal = match &options.terminal {
+                Some(terminal) => terminal.clone(),
+                None => find_terminal_emulator()
+                    .unwrap_or_else(|| DEFAULT_TERMINAL.iter().map(|s| s.to_string()).collect()),
+            };
+            argv.splice(0..0, terminal);
+        }
+        if options.systemd_scope {
+            argv.splice(0..0, self.systemd_run_prefix(&argv[0]));
+        }
+
+        let description = self.to_exec_env_description();
+        let mut command = match &description.desktop_file {
+            Some(desktop_file) => {
+                let mut command = Command::new("sh");
+                command
+                    .arg("-c")
+                    .arg(format!(
+                        "{}=$$; export {}; exec \"$0\" \"$@\"",
+                        DESKTOP_FILE_PID_ENV, DESKTOP_FILE_PID_ENV
+                    ))
+                    .args(&argv)
+                    .env(DESKTOP_FILE_ENV, desktop_file);
+                command
+            }
+            None => {
+                let mut command = Command::new(&argv[0]);
+                command.args(&argv[1..]);
+                command
+            }
+        };
+
+        if let Some(path) = self.path.as_deref().filter(|path| !path.is_empty()) {
+            command.current_dir(path);
+        }
+
+        Ok(command)
+    }
+
+    /// Launches this application with files or URLs.
+    ///
+    /// See [`launch_command`](Self::launch_command) for how the command is
+    /// built.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command cannot be built or spawned.
+    pub fn launch(&self, targets: &[impl AsRef<str>], options: &LaunchOptions) -> Result<Child> {
+        Ok(self.launch_command(targets, options)?.spawn()?)
+    }
+
+    /// Launches this application detached from the calling process.
+    ///
+    /// Unlike [`launch`](Self::launch), the application keeps running when
+    /// the launcher exits or its session is closed; see [`spawn_detached`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command cannot be built or spawned.
+    pub fn launch_detached(
+        &self,
+        targets: &[impl AsRef<str>],
+        options: &LaunchOptions,
+    ) -> Result<()> {
+        spawn_detached(&mut self.launch_command(targets, options)?)
+    }
+
+    /// The `systemd-run` arguments running a program in a new scope.
+    ///
+    /// Scopes are named `app-<app ID>-<random>.scope`, following the
+    /// systemd conventions for desktop applications. The app ID is the
+    /// desktop file ID without `.desktop`, or the program name if the
+    /// desktop file is unknown.
+    fn systemd_run_prefix(&self, program: &str) -> Vec<String> {
+        let app_id = self
+            .source_path
+            .as_ref()
+            .and_then(
+                |path| match crate::discovery::DesktopFileId::from_installed_path(path) {
+                    Some(id) => Some(id.bus_name().to_string()),
+                    None => Some(path.file_stem()?.to_str()?.to_string()),
+                },
+            )
+            .unwrap_or_else(|| {
+                std::path::Path::new(program)
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or(program)
+                    .to_string()
+            });
+        let random = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.subsec_nanos())
+            ^ std::process::id();
+
+        vec![
+            "systemd-run".to_string(),
+            "--user".to_string(),
+            "--scope".to_string(),
+            "--quiet".to_string(),
+            "--collect".to_string(),
+            "--slice=app.slice".to_string(),
+            format!(
+                "--unit=app-{}-{:08x}.scope",
+                systemd_escape(&app_id),
+                random
+            ),
+            format!("--description={}", self.name.default),
+            "--".to_string(),
+        ]
+    }
+
+    fn expand_exec(
+        &self,
+        exec: &ExecString,
+        targets: &[&str],
+        options: &LaunchOptions,
+    ) -> Result<Vec<String>> {
+        if targets.len() > 1
+            && !exec.accepts_multiple()
+            && exec.field_codes().iter().any(FieldCode::is_target)
+        {
+            return Err(DesktopEntryError::InvalidValue(
+                "Exec".to_string(),
+                format!(
+                    "'{}' accepts a single file or URL, got {}",
+                    self.exec.as_deref().unwrap_or_default(),
+                    targets.len()
+                ),
+            ));
+        }
+
+        let name = self.name.get_with_fallbacks(&options.locales);
+        let icon = self
+            .icon
+            .as_ref()
+            .map(|icon| icon.get_with_fallbacks(&options.locales));
+        let location = self
+            .to_exec_env_description()
+            .desktop_file
+            .map(|path| path.to_string_lossy().into_owned());
+        let paths: Vec<String> = targets
+            .iter()
+            .map(|target| file_uri_to_path(target))
+            .collect();
+
+        let mut argv = vec![exec.program().to_string()];
+        for arg in exec.args() {
+            match arg.as_field_code() {
+                Some(FieldCode::Files) => argv.extend(paths.iter().cloned()),
+                Some(FieldCode::Urls) => argv.extend(targets.iter().map(|t| t.to_string())),
+                Some(FieldCode::Icon) => {
+                    if let Some(icon) = icon.filter(|icon| !icon.is_empty()) {
+                        argv.push("--icon".to_string());
+                        argv.push(icon.to_string());
+                    }
+                }
+                _ => {
+                    let mut expanded = String::new();
+                    let mut has_value = false;
+                    for part in arg.parts() {
+                        let value = match part {
+                            ArgPart::Text(text) => Some(text.as_str()),
+                            ArgPart::Code(FieldCode::File | FieldCode::Files) => {
+                                paths.first().map(String::as_str)
+                            }
+                            ArgPart::Code(FieldCode::Url | FieldCode::Urls) => {
+                                targets.first().copied()
+                            }
+                            ArgPart::Code(FieldCode::Icon) => icon,
+                            ArgPart::Code(FieldCode::Name) => Some(name),
+                            ArgPart::Code(FieldCode::Location) => location.as_deref(),
+                            ArgPart::Code(FieldCode::Deprecated(_)) => None,
+                        };
+                        if let Some(value) = value {
+                            expanded.push_str(value);
+                            has_value |= !value.is_empty() || matches!(part, ArgPart::Text(_));
+                        }
+                    }
+                    if has_value {
+                        argv.push(expanded);
+                    }
+                }
+            }
+        }
+
+        Ok(argv)
+    }
+}
+
+/// Spawns a command detached from the calling process, as launchers do.
+///
+/// On Unix, the command is started through an intermediate process that
+/// exits right away, so the program is reparented to init (or the nearest
+/// subreaper) instead of becoming a zombie of the launcher. It runs in a new
+/// session, without a controlling terminal, so it survives the launcher's
+/// terminal hanging up. Signal dispositions and the signal mask are reset to
+/// their defaults, and file descriptors other than standard output and
+/// error are not inherited; standard input is `/dev/null`.
+///
+/// The process ID of the program is not known, as the spawned child is the
+/// intermediate process.
+///
+/// # Errors
+///
+/// Returns an error if the command cannot be spawned, including when the
+/// program does not exist.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::process::Command;
+/// use xdg_desktop_entry::launch::spawn_detached;
+///
+/// spawn_detached(&mut Command::new("gnome-calculator")).unwrap();
+/// ```
+pub fn spawn_detached(command: &mut Command) -> Result<()> {
+    command.stdin(Stdio::null());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // SAFETY: `detach` only calls async-signal-safe functions
+        unsafe {
+            command.pre_exec(detach);
+        }
+        // Reap the intermediate process, which exits once it has forked
+        command.spawn()?.wait()?;
+    }
+    #[cfg(not(unix))]
+    command.spawn()?;
+
+    Ok(())
+}
+
+/// Runs in the forked child before `exec`: forks again, leaving the parent
+/// to exit, and resets the state inherited from the launcher.
+#[cfg(unix)]
+fn detach() -> std::io::Result<()> {
+    // SAFETY: only async-signal-safe functions are called, on values owned
+    // by this function
+    unsafe {
+        match libc::fork() {
+            -1 => return Err(std::io::Error::last_os_error()),
+            0 => {}
+            _ => libc::_exit(0),
+        }
+        if libc::setsid() == -1 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = libc::SIG_DFL;
+        libc::sigemptyset(&mut action.sa_mask);
+        // Fails harmlessly for SIGKILL, SIGSTOP, and unused numbers
+        for signal in 1..65 {
+            libc::sigaction(signal, &action, std::ptr::null_mut());
+        }
+        let mut mask: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut mask);
+        libc::sigprocmask(libc::SIG_SETMASK, &mask, std::ptr::null_mut());
+
+        // Descriptors are marked close-on-exec rather than closed, as the
+        // one reporting a failed `exec` to the launcher must stay open until
+        // then
+        #[cfg(target_os = "linux")]
+        let marked = libc::syscall(
+            libc::SYS_close_range,
+            3u32,
+            u32::MAX,
+            libc::CLOSE_RANGE_CLOEXEC,
+        ) == 0;
+        #[cfg(not(target_os = "linux"))]
+        let marked = false;
+        if !marked {
+            let mut limit: libc::rlimit = std::mem::zeroed();
+            let max_fd = if libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) == 0 {
+                limit.rlim_cur.min(65536) as libc::c_int
+            } else {
+                1024
+            };
+            for fd in 3..max_fd {
+                libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Escapes a string for use in a systemd unit name, like
+/// `systemd-escape`: characters other than ASCII alphanumerics, `:`, `_`,
+/// and non-leading `.` become `\xNN`.
+fn systemd_escape(value: &str) -> String {
+    let mut escaped = String::new();
+    for (i, byte) in value.bytes().enumerate() {
+        if byte.is_ascii_alphanumeric() || byte == b':' || byte == b'_' || (byte == b'.' && i > 0) {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("\\x{:02x}", byte));
+        }
+    }
+    escaped
+}
+
+/// Converts a `file://` URI to a local path, leaving other targets as given.
+pub(crate) fn file_uri_to_path(target: &str) -> String {
+    let Some(path) = target.strip_prefix("file://") else {
+        return target.to_string();
+    };
+    // Skip an authority such as "localhost"
+    let path = &path[path.find('/').unwrap_or(path.len())..];
+
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,302 @@
+//! Lossless round-trips of parsed desktop entries.
+//!
+//! Entries parsed from text remember the original lines in a [`Layout`].
+//! Serializing such an entry only rewrites the lines of keys whose value
+//! changed: key order, comments inside groups, blank lines, and the spelling
+//! of unchanged values are kept as they were. Removed keys and groups are
+//! dropped, new keys are appended to their group, and new groups are
+//! appended to the file.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::DesktopEntry;
+//!
+//! let content = "[Desktop Entry]\n# Shown in menus\nName=Editor\nType=Application\n\nExec=editor %F\n";
+//!
+//! let mut entry = DesktopEntry::parse(content).unwrap();
+//! entry.exec = Some("editor --new-window %F".to_string());
+//!
+//! assert_eq!(
+//!     entry.serialize(),
+//!     content.replace("Exec=editor %F", "Exec=editor --new-window %F"),
+//! );
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+use crate::{
+    DESKTOP_ENTRY_GROUP, DesktopEntry, KDE_DESKTOP_ENTRY_GROUP, Locale, Parser, ParserOptions,
+    RawGroups, Span,
+};
+
+/// Identifies a value: group name, key, and locale.
+pub(crate) type ValueId = (String, String, Option<Locale>);
+
+/// The lines of a group in canonical serialization order.
+pub(crate) type CanonicalGroup = (String, Vec<(ValueId, String)>);
+
+/// The original text of a parsed desktop entry.
+///
+/// Set by the parsing functions of [`DesktopEntry`]; clear
+/// [`DesktopEntry::layout`] to serialize an entry in canonical form instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    content: String,
+    options: ParserOptions,
+}
+
+impl Layout {
+    pub(crate) fn new(content: &str, options: &ParserOptions) -> Self {
+        Self {
+            content: content.to_string(),
+            options: options.clone(),
+        }
+    }
+
+    /// The original lines.
+    pub(crate) fn lines(&self) -> std::str::Lines<'_> {
+        self.content.lines()
+    }
+
+    /// The groups of the original text, whose entries carry their spans,
+    /// and the spans of the group headers.
+    pub(crate) fn spans(&self) -> (RawGroups, HashMap<String, Span>) {
+        let mut parser = Parser::with_options(&self.content, self.options.clone());
+        parser.collect_errors = true;
+        let groups = parser
+            .parse_groups()
+            .map(|(groups, _)| groups)
+            .unwrap_or_default();
+        (groups, parser.header_spans)
+    }
+
+    /// Writes `entry` over the original lines.
+    ///
+    /// Returns `false` without writing anything if the original text no
+    /// longer parses, in which case the caller falls back to canonical
+    /// serialization.
+    pub(crate) fn write<W: Write>(&self, entry: &DesktopEntry, writer: &mut W) -> io::Result<bool> {
+        let Ok(original) = Parser::with_options(&self.content, self.options.clone()).parse() else {
+            return Ok(false);
+        };
+
+        let old_values: HashMap<ValueId, String> = canonical_groups(&original)
+            .into_iter()
+            .flat_map(|(_, lines)| lines)
+            .map(|(id, line)| (id, value_of(&line).to_string()))
+            .collect();
+        let new_groups = canonical_groups(entry);
+        let new_values: HashMap<&ValueId, &str> = new_groups
+            .iter()
+            .flat_map(|(_, lines)| lines)
+            .map(|(id, line)| (id, value_of(line)))
+            .collect();
+
+        let mut writer = LayoutWriter {
+            writer,
+            new_groups: &new_groups,
+            emitted: HashSet::new(),
+            pending: Vec::new(),
+        };
+
+        // The comments before the first group are part of the entry; write
+        // the edited ones in place of the original lines
+        let replace_header = entry.comments != original.comments;
+        if replace_header {
+            for comment in &entry.comments {
+                if comment.is_blank {
+                    writeln!(writer.writer)?;
+                } else {
+                    writeln!(writer.writer, "#{}", comment.content)?;
+                }
+            }
+        }
+
+        let mut current_group: Option<&str> = None;
+        let mut seen_groups = HashSet::new();
+        let mut dropping = false;
+
+        for line in self.content.lines() {
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                if dropping {
+                    continue;
+                }
+                if current_group.is_none() {
+                    if !replace_header {
+                        writeln!(writer.writer, "{}", line)?;
+                    }
+                } else {
+                    writer.pending.push(line);
+                }
+                continue;
+            }
+
+            if trimmed.starts_with('[') && trimmed.ends_with(']') {
+                let mut name = &trimmed[1..trimmed.len() - 1];
+                let mut line = line;
+                if self.options.accept_kde_desktop_entry_group && name == KDE_DESKTOP_ENTRY_GROUP {
+                    name = DESKTOP_ENTRY_GROUP;
+                    line = "[Desktop Entry]";
+                }
+                if current_group == Some(name) {
+                    // An explicit header after keys attached to it implicitly
+                    writer.flush_pending()?;
+                    writeln!(writer.writer, "{}", line)?;
+                    continue;
+                }
+                if let Some(group) = current_group.filter(|_| !dropping) {
+                    writer.append_new_keys(group)?;
+                }
+
+                // The blank lines separating a removed group go with it
+                dropping = !new_groups.iter().any(|(group, _)| group == name)
+                    || !seen_groups.insert(name.to_string());
+                if !dropping {
+                    writer.flush_pending()?;
+                    writeln!(writer.writer, "{}", line)?;
+                }
+                current_group = Some(name);
+                continue;
+            }
+
+            if dropping {
+                continue;
+            }
+            let Some((key_part, key, locale)) = split_key(line) else {
+                // Kept as it was, like any other line the entry doesn't model
+                writer.flush_pending()?;
+                writeln!(writer.writer, "{}", line)?;
+                continue;
+            };
+            let group = current_group.unwrap_or(DESKTOP_ENTRY_GROUP);
+            if current_group.is_none() {
+                current_group = Some(DESKTOP_ENTRY_GROUP);
+                seen_groups.insert(DESKTOP_ENTRY_GROUP.to_string());
+            }
+            let id = (group.to_string(), key, locale);
+            let Some(&value) = new_values.get(&id) else {
+                // Lines the entry doesn't model, such as invalid values, are
+                // kept; only removed keys are dropped
+                if !old_values.contains_key(&id) {
+                    writer.flush_pending()?;
+                    writeln!(writer.writer, "{}", line)?;
+                }
+                continue;
+            };
+
+            if old_values.get(&id).map(String::as_str) == Some(value) {
+                writer.flush_pending()?;
+                writeln!(writer.writer, "{}", line)?;
+                writer.emitted.insert(id);
+            } else if !writer.emitted.contains(&id) {
+                writer.flush_pending()?;
+                // Keep the spacing after `=` as written
+                let old_value = value_of(line);
+                let spacing = if value.starts_with([' ', '\t']) {
+                    ""
+                } else {
+                    &old_value[..old_value.len() - old_value.trim_start_matches([' ', '\t']).len()]
+                };
+                writeln!(writer.writer, "{}={}{}", key_part, spacing, value)?;
+                writer.emitted.insert(id);
+            }
+        }
+
+        if let Some(group) = current_group.filter(|_| !dropping) {
+            writer.append_new_keys(group)?;
+            writer.flush_pending()?;
+        }
+
+        for (group, lines) in &new_groups {
+            if seen_groups.contains(group) {
+                continue;
+            }
+            writeln!(writer.writer)?;
+            writeln!(writer.writer, "[{}]", group)?;
+            for (_, line) in lines {
+                writeln!(writer.writer, "{}", line)?;
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// Output state while walking the original lines.
+struct LayoutWriter<'a, W> {
+    writer: &'a mut W,
+    new_groups: &'a [CanonicalGroup],
+    emitted: HashSet<ValueId>,
+    /// Blank and comment lines after the last key of the current group, held
+    /// back so new keys are inserted before them
+    pending: Vec<&'a str>,
+}
+
+impl<W: Write> LayoutWriter<'_, W> {
+    fn flush_pending(&mut self) -> io::Result<()> {
+        for line in self.pending.drain(..) {
+            writeln!(self.writer, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// Appends the keys of `group` that were not in the original text.
+    fn append_new_keys(&mut self, group: &str) -> io::Result<()> {
+        let lines = self
+            .new_groups
+            .iter()
+            .filter(|(name, _)| name == group)
+            .flat_map(|(_, lines)| lines);
+        for (id, line) in lines {
+            if !self.emitted.contains(id) {
+                writeln!(self.writer, "{}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits a key line into the key as written, the key name, and the locale.
+fn split_key(line: &str) -> Option<(&str, String, Option<Locale>)> {
+    let key_part = &line[..line.find('=')?];
+    match key_part.find('[') {
+        Some(start) => {
+            let end = key_part.find(']')?;
+            let locale = Locale::from_string(key_part.get(start + 1..end)?);
+            Some((key_part, key_part[..start].trim().to_string(), Some(locale)))
+        }
+        None => Some((key_part, key_part.trim().to_string(), None)),
+    }
+}
+
+pub(crate) fn value_of(line: &str) -> &str {
+    line.split_once('=').map_or("", |(_, value)| value)
+}
+
+/// The groups and key lines of the canonical serialization of `entry`.
+pub(crate) fn canonical_groups(entry: &DesktopEntry) -> Vec<CanonicalGroup> {
+    groups_of(&entry.canonical_string())
+}
+
+/// The groups and key lines of serialized entry text, skipping comments.
+pub(crate) fn groups_of(content: &str) -> Vec<CanonicalGroup> {
+    let mut groups: Vec<CanonicalGroup> = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            groups.push((trimmed[1..trimmed.len() - 1].to_string(), Vec::new()));
+        } else if let Some((group, lines)) = groups.last_mut()
+            && let Some((_, key, locale)) = split_key(line)
+        {
+            lines.push(((group.clone(), key, locale), line.to_string()));
+        }
+    }
+    groups
+}
@@ -4,7 +4,63 @@
 use std::collections::HashMap;
 use std::fmt;
 use std::io::{self, Write};
-use std::path::Path;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+mod access;
+#[cfg(feature = "appimage")]
+pub mod appimage;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+pub mod borrowed;
+pub mod builder;
+pub mod cache;
+pub mod categories;
+#[cfg(feature = "compat-0")]
+pub mod compat;
+pub mod database;
+#[cfg(feature = "dbus")]
+pub mod dbus;
+pub mod diff;
+pub mod discovery;
+pub mod document;
+#[cfg(feature = "encoding")]
+pub mod encoding;
+pub mod environment;
+pub mod events;
+pub mod exec;
+pub mod findings;
+pub mod flatpak;
+pub mod format;
+#[cfg(feature = "gnome")]
+pub mod gnome;
+pub mod install;
+pub mod interface;
+mod json;
+#[cfg(feature = "kde")]
+pub mod kde;
+pub mod launch;
+pub mod layout;
+pub mod lint;
+pub mod menu;
+pub mod mimeapps;
+pub mod mimeinfo;
+pub mod packaging;
+pub mod patch;
+#[cfg(feature = "gettext")]
+pub mod po;
+mod redact;
+pub mod search;
+pub mod search_provider;
+pub mod store;
+mod translations;
+pub mod typed;
+#[cfg(feature = "watch")]
+pub mod watch;
+
+use exec::ExecString;
+use launch::{ExecEnvDescription, LaunchMethod};
 
 // ============================================================================
 // Error Types
@@ -12,27 +68,40 @@ use std::path::Path;
 
 /// Errors that can occur when parsing or validating desktop entry files.
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum DesktopEntryError {
     /// IO error during file reading/writing
-    Io(String),
+    Io(IoError),
     /// File is not valid UTF-8
     InvalidUtf8,
     /// Missing required [Desktop Entry] group
     MissingDesktopEntryGroup,
     /// Duplicate group header
     DuplicateGroup(String),
+    /// Referenced group does not exist
+    MissingGroup(String),
     /// Invalid line format (not a comment, blank, group header, or key=value)
     InvalidLine(usize, String),
     /// Invalid group header format
     InvalidGroupHeader(usize, String),
     /// Invalid key name (must be ASCII A-Za-z0-9-)
     InvalidKeyName(usize, String),
+    /// Key appearing twice with the same locale in a group
+    DuplicateKey(usize, String),
+    /// Key neither defined by the spec nor prefixed with `X-`
+    UnknownKey(String),
     /// Missing required key
     MissingRequiredKey(String),
     /// Invalid value type
     InvalidValue(String, String),
     /// Validation error
     ValidationError(String),
+    /// Malformed JSON representation, see [`DesktopEntry::from_json`]
+    InvalidJson(String),
+    /// Malformed menu file, see [`menu::MenuFile`]
+    InvalidMenu(String),
+    /// Malformed gettext catalog, see `po::Catalog`
+    InvalidPo(String),
 }
 
 impl fmt::Display for DesktopEntryError {
@@ -44,6 +113,7 @@ impl fmt::Display for DesktopEntryError {
                 write!(f, "Missing required [Desktop Entry] group")
             }
             Self::DuplicateGroup(name) => write!(f, "Duplicate group: [{}]", name),
+            Self::MissingGroup(name) => write!(f, "Missing group: [{}]", name),
             Self::InvalidLine(line, content) => {
                 write!(f, "Invalid line {} format: {}", line, content)
             }
@@ -53,26 +123,296 @@ impl fmt::Display for DesktopEntryError {
             Self::InvalidKeyName(line, name) => {
                 write!(f, "Invalid key name at line {}: '{}'", line, name)
             }
+            Self::DuplicateKey(line, key) => {
+                write!(f, "Duplicate key at line {}: '{}'", line, key)
+            }
+            Self::UnknownKey(key) => write!(f, "Unknown key: '{}'", key),
             Self::MissingRequiredKey(key) => write!(f, "Missing required key: {}", key),
             Self::InvalidValue(key, reason) => {
                 write!(f, "Invalid value for key '{}': {}", key, reason)
             }
             Self::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            Self::InvalidJson(msg) => write!(f, "Invalid JSON: {}", msg),
+            Self::InvalidMenu(msg) => write!(f, "Invalid menu file: {}", msg),
+            Self::InvalidPo(msg) => write!(f, "Invalid PO file: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for DesktopEntryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err.get_ref()),
+            _ => None,
         }
     }
 }
 
-impl std::error::Error for DesktopEntryError {}
+impl DesktopEntryError {
+    /// Diagnostic code reported for this error when collecting diagnostics.
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "io-error",
+            Self::InvalidUtf8 => "invalid-utf8",
+            Self::MissingDesktopEntryGroup => "missing-desktop-entry-group",
+            Self::DuplicateGroup(_) => "duplicate-group",
+            Self::MissingGroup(_) => "missing-group",
+            Self::InvalidLine(_, _) => "invalid-line",
+            Self::InvalidGroupHeader(_, _) => "invalid-group-header",
+            Self::InvalidKeyName(_, _) => "invalid-key-name",
+            Self::DuplicateKey(_, _) => "duplicate-key",
+            Self::UnknownKey(_) => "unknown-key",
+            Self::MissingRequiredKey(_) => "missing-required-key",
+            Self::InvalidValue(_, _) => "invalid-value",
+            Self::ValidationError(_) => "validation-error",
+            Self::InvalidJson(_) => "invalid-json",
+            Self::InvalidMenu(_) => "invalid-menu",
+            Self::InvalidPo(_) => "invalid-po",
+        }
+    }
+}
 
 impl From<io::Error> for DesktopEntryError {
     fn from(err: io::Error) -> Self {
-        Self::Io(err.to_string())
+        Self::Io(IoError::from(err))
+    }
+}
+
+/// An [`io::Error`] kept by [`DesktopEntryError::Io`].
+///
+/// The error is shared so that [`DesktopEntryError`] stays `Clone`. Two
+/// errors are equal if they have the same kind and message.
+#[derive(Debug, Clone)]
+pub struct IoError(Arc<io::Error>);
+
+impl IoError {
+    /// The kind of the underlying error.
+    pub fn kind(&self) -> io::ErrorKind {
+        self.0.kind()
+    }
+
+    /// The underlying error.
+    pub fn get_ref(&self) -> &io::Error {
+        &self.0
+    }
+}
+
+impl From<io::Error> for IoError {
+    fn from(err: io::Error) -> Self {
+        Self(Arc::new(err))
+    }
+}
+
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind() == other.kind() && self.0.to_string() == other.0.to_string()
+    }
+}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
     }
 }
 
 /// Result type for desktop entry operations.
 pub type Result<T> = std::result::Result<T, DesktopEntryError>;
 
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+/// Severity of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The file violates the specification
+    Error,
+    /// The file is valid but likely wrong or deprecated
+    Warning,
+    /// A suggestion that does not affect validity
+    Info,
+}
+
+impl Severity {
+    /// Converts the severity to its string representation.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+        }
+    }
+}
+
+/// How thorough [`DesktopEntry::validate_with`] is. Each level includes
+/// the ones before it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValidationLevel {
+    /// Violations of the specification
+    #[default]
+    Spec,
+    /// Entries that are valid but likely wrong or deprecated
+    Warning,
+    /// Missing optional content, such as keywords, translations, or icons
+    Pedantic,
+}
+
+/// A problem found in a desktop entry, such as a lint finding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// How serious the problem is
+    pub severity: Severity,
+    /// Stable identifier of the rule that produced the diagnostic
+    pub code: &'static str,
+    /// Human-readable description of the problem
+    pub message: String,
+    /// Location of the problem in the parsed text, if known
+    pub span: Option<Span>,
+    /// Automatic fix for the problem, if one is known
+    pub fix: Option<Fix>,
+}
+
+/// A location in the text of a desktop entry file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Span {
+    /// Line number, starting at 1
+    pub line: usize,
+    /// Byte column within the line, starting at 1
+    pub column: usize,
+    /// Length in bytes
+    pub len: usize,
+}
+
+impl Span {
+    /// A span covering a whole line.
+    pub fn line(line: usize, content: &str) -> Self {
+        Self {
+            line,
+            column: 1,
+            len: content.len(),
+        }
+    }
+
+    /// The byte range of the span in `text`, or `None` if `text` has fewer
+    /// lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::Span;
+    ///
+    /// let text = "[Desktop Entry]\nName=App\n";
+    /// let span = Span { line: 2, column: 6, len: 3 };
+    /// assert_eq!(&text[span.byte_range(text).unwrap()], "App");
+    /// ```
+    pub fn byte_range(&self, text: &str) -> Option<std::ops::Range<usize>> {
+        let line_start = if self.line <= 1 {
+            0
+        } else {
+            text.match_indices('\n').nth(self.line - 2)?.0 + 1
+        };
+        let start = line_start + self.column.saturating_sub(1);
+        Some(start..start + self.len)
+    }
+}
+
+/// Where the parts of a key-value line are in the parsed text.
+///
+/// For `Name[de]=App`, the key is `Name`, the locale suffix is `[de]`, and
+/// the value is `App`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EntrySpan {
+    /// The whole line
+    pub line: Span,
+    /// The key name, without the locale suffix
+    pub key: Span,
+    /// The locale suffix with its brackets, if any
+    pub locale: Option<Span>,
+    /// The raw value, everything after the `=`
+    pub value: Span,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic without a fix.
+    pub fn new(severity: Severity, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            code,
+            message: message.into(),
+            span: None,
+            fix: None,
+        }
+    }
+
+    /// Attaches a location to the diagnostic.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Attaches an automatic fix to the diagnostic.
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(span) = &self.span {
+            write!(f, "{}:{}: ", span.line, span.column)?;
+        }
+        write!(
+            f,
+            "{}[{}]: {}",
+            self.severity.as_str(),
+            self.code,
+            self.message
+        )
+    }
+}
+
+/// An automatic fix, made of edits applied in order.
+///
+/// Applied with [`DesktopEntry::apply_fix`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    /// Human-readable description of what the fix does
+    pub description: String,
+    /// Edits making up the fix
+    pub edits: Vec<KeyEdit>,
+}
+
+/// A single key-level edit of a desktop entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyEdit {
+    /// Sets the unlocalized value of a key, adding the key if missing
+    Set {
+        /// Group holding the key
+        group: String,
+        /// Key name
+        key: String,
+        /// New raw value
+        value: String,
+    },
+    /// Removes a key with all of its localized variants
+    Remove {
+        /// Group holding the key
+        group: String,
+        /// Key name
+        key: String,
+    },
+    /// Renames a key, keeping all of its localized variants
+    Rename {
+        /// Group holding the key
+        group: String,
+        /// Current key name
+        from: String,
+        /// New key name
+        to: String,
+    },
+}
+
 // ============================================================================
 // Locale
 // ============================================================================
@@ -87,7 +427,7 @@ pub type Result<T> = std::result::Result<T, DesktopEntryError>;
 /// - `en_US` - Language and country
 /// - `sr_YU@Latn` - Language, country, and modifier
 /// - `en_US.UTF-8@euro` - All components
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Locale {
     /// Language code (e.g., "en", "fr", "sr")
     pub lang: String,
@@ -175,21 +515,125 @@ impl Locale {
     }
 
     /// Converts the locale to its string representation.
+    ///
+    /// Same as the [`Display`](fmt::Display) implementation.
     pub fn to_string_repr(&self) -> String {
-        let mut result = self.lang.clone();
+        self.to_string()
+    }
+
+    /// The canonical form of the locale, used for matching.
+    ///
+    /// The language is lowercased and the country uppercased, the encoding
+    /// is dropped, and deprecated codes are replaced by their current ones:
+    /// `no` by `nb`, `iw` by `he`, `in` by `id`, `ji` by `yi`, and legacy
+    /// territories such as `YU` or `UK` by their successors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::Locale;
+    ///
+    /// let locale = Locale::from_string("PT_br.utf8@Euro").normalized();
+    /// assert_eq!(locale.to_string_repr(), "pt_BR@Euro");
+    /// assert_eq!(Locale::from_string("no_NO").normalized(), Locale::from_string("nb_NO"));
+    /// assert_eq!(Locale::from_string("sr_YU").normalized(), Locale::from_string("sr_RS"));
+    /// ```
+    pub fn normalized(&self) -> Self {
+        let lang = self.lang.to_ascii_lowercase();
+        let lang = match lang.as_str() {
+            "no" => "nb".to_string(),
+            "iw" => "he".to_string(),
+            "in" => "id".to_string(),
+            "ji" => "yi".to_string(),
+            _ => lang,
+        };
+        let country = self.country.as_ref().map(|country| {
+            let country = country.to_ascii_uppercase();
+            match country.as_str() {
+                "UK" => "GB".to_string(),
+                "YU" | "CS" => "RS".to_string(),
+                "DD" => "DE".to_string(),
+                "BU" => "MM".to_string(),
+                "TP" => "TL".to_string(),
+                "ZR" => "CD".to_string(),
+                _ => country,
+            }
+        });
+        Self {
+            lang,
+            country,
+            encoding: None,
+            modifier: self.modifier.clone(),
+        }
+    }
+
+    /// The locales tried for this one by the matching rules, most specific
+    /// first.
+    fn fallbacks(&self) -> Vec<Locale> {
+        let mut fallbacks = vec![self.clone()];
+        if self.country.is_some() && self.modifier.is_some() {
+            fallbacks.push(Self {
+                country: None,
+                ..self.clone()
+            });
+        }
+        if self.modifier.is_some() {
+            fallbacks.push(Self {
+                modifier: None,
+                ..self.clone()
+            });
+        }
+        if self.country.is_some() || self.modifier.is_some() {
+            fallbacks.push(Self::new(&self.lang));
+        }
+        fallbacks
+    }
+}
+
+/// The `C` locale, which has no translations.
+impl Default for Locale {
+    fn default() -> Self {
+        Self::new("C")
+    }
+}
+
+/// Formats the locale as `lang_COUNTRY.ENCODING@MODIFIER`.
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.lang)?;
         if let Some(country) = &self.country {
-            result.push('_');
-            result.push_str(country);
+            write!(f, "_{}", country)?;
         }
         if let Some(encoding) = &self.encoding {
-            result.push('.');
-            result.push_str(encoding);
+            write!(f, ".{}", encoding)?;
         }
         if let Some(modifier) = &self.modifier {
-            result.push('@');
-            result.push_str(modifier);
+            write!(f, "@{}", modifier)?;
         }
-        result
+        Ok(())
+    }
+}
+
+/// Parses a locale like [`Locale::from_string`], which accepts any string.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeSet;
+/// use xdg_desktop_entry::Locale;
+///
+/// let locale: Locale = "sr_RS@latin".parse().unwrap();
+/// assert_eq!(locale.to_string(), "sr_RS@latin");
+///
+/// let sorted: BTreeSet<Locale> = ["fr", "de_CH", "de"].iter().map(|l| l.parse().unwrap()).collect();
+/// let sorted: Vec<String> = sorted.iter().map(Locale::to_string).collect();
+/// assert_eq!(sorted, ["de", "de_CH", "fr"]);
+/// ```
+impl std::str::FromStr for Locale {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(Self::from_string(s))
     }
 }
 
@@ -197,82 +641,30 @@ impl Locale {
 // Localized Values
 // ============================================================================
 
-/// Represents a localizable string value.
+/// A value with locale-specific variants.
 ///
 /// Desktop entries support localization by allowing keys to have locale-specific
 /// variants (e.g., `Name[fr]=...`). This struct stores the default value and all
-/// localized variants.
+/// localized variants, and implements the spec's matching rules once for every
+/// kind of localized value.
 ///
 /// # Specification Reference
 ///
 /// Section 5: "Localized values for keys"
 #[derive(Debug, Clone, PartialEq)]
-pub struct LocalizedString {
+pub struct Localized<T> {
     /// The default value (key without locale suffix)
-    pub default: String,
+    pub default: T,
     /// Map of locale to localized value
-    pub localized: HashMap<Locale, String>,
+    pub localized: HashMap<Locale, T>,
 }
 
-impl LocalizedString {
-    /// Creates a new LocalizedString with just a default value.
-    pub fn new(default: impl Into<String>) -> Self {
-        Self {
-            default: default.into(),
-            localized: HashMap::new(),
-        }
-    }
-
-    /// Adds a localized variant.
-    pub fn add_localized(&mut self, locale: Locale, value: String) {
-        self.localized.insert(locale, value);
-    }
-
-    /// Gets the appropriate value for the given locale using the spec's matching rules.
-    ///
-    /// # Matching Rules (Section 5)
-    ///
-    /// 1. Try exact match: `lang_COUNTRY@MODIFIER`
-    /// 2. Try without country: `lang@MODIFIER`
-    /// 3. Try without modifier: `lang_COUNTRY`
-    /// 4. Try just language: `lang`
-    /// 5. Fall back to default
-    pub fn get(&self, locale: &Locale) -> &str {
-        // 1. Try exact match
-        if let Some(value) = self.localized.get(locale) {
-            return value;
-        }
-
-        // 2. Try without country (lang@MODIFIER)
-        if locale.country.is_some() && locale.modifier.is_some() {
-            let mut try_locale = locale.clone();
-            try_locale.country = None;
-            if let Some(value) = self.localized.get(&try_locale) {
-                return value;
-            }
-        }
-
-        // 3. Try without modifier (lang_COUNTRY)
-        if locale.modifier.is_some() {
-            let mut try_locale = locale.clone();
-            try_locale.modifier = None;
-            if let Some(value) = self.localized.get(&try_locale) {
-                return value;
-            }
-        }
-
-        // 4. Try just language
-        if locale.country.is_some() || locale.modifier.is_some() {
-            let try_locale = Locale::new(&locale.lang);
-            if let Some(value) = self.localized.get(&try_locale) {
-                return value;
-            }
-        }
-
-        // 5. Fall back to default
-        &self.default
-    }
-}
+/// Represents a localizable string value.
+///
+/// # Specification Reference
+///
+/// Section 4: "Values of type `localestring`"
+pub type LocalizedString = Localized<String>;
 
 /// Represents an icon name or path, which can also be localized.
 ///
@@ -284,61 +676,7 @@ impl LocalizedString {
 ///
 /// Section 4: "Values of type `iconstring`"
 /// Section 6: "`Icon` key"
-#[derive(Debug, Clone, PartialEq)]
-pub struct IconString {
-    /// The default icon name or path
-    pub default: String,
-    /// Map of locale to localized icon name or path
-    pub localized: HashMap<Locale, String>,
-}
-
-impl IconString {
-    /// Creates a new IconString with just a default value.
-    pub fn new(default: impl Into<String>) -> Self {
-        Self {
-            default: default.into(),
-            localized: HashMap::new(),
-        }
-    }
-
-    /// Adds a localized variant.
-    pub fn add_localized(&mut self, locale: Locale, value: String) {
-        self.localized.insert(locale, value);
-    }
-
-    /// Gets the appropriate icon for the given locale.
-    pub fn get(&self, locale: &Locale) -> &str {
-        // Use the same matching logic as LocalizedString
-        if let Some(value) = self.localized.get(locale) {
-            return value;
-        }
-
-        if locale.country.is_some() && locale.modifier.is_some() {
-            let mut try_locale = locale.clone();
-            try_locale.country = None;
-            if let Some(value) = self.localized.get(&try_locale) {
-                return value;
-            }
-        }
-
-        if locale.modifier.is_some() {
-            let mut try_locale = locale.clone();
-            try_locale.modifier = None;
-            if let Some(value) = self.localized.get(&try_locale) {
-                return value;
-            }
-        }
-
-        if locale.country.is_some() || locale.modifier.is_some() {
-            let try_locale = Locale::new(&locale.lang);
-            if let Some(value) = self.localized.get(&try_locale) {
-                return value;
-            }
-        }
-
-        &self.default
-    }
-}
+pub type IconString = Localized<String>;
 
 /// Represents a list of localized strings (e.g., Keywords).
 ///
@@ -348,58 +686,90 @@ impl IconString {
 /// # Specification Reference
 ///
 /// Section 4: "Some keys can have multiple values"
-#[derive(Debug, Clone, PartialEq)]
-pub struct LocalizedStringList {
-    /// The default list of values
-    pub default: Vec<String>,
-    /// Map of locale to localized list of values
-    pub localized: HashMap<Locale, Vec<String>>,
-}
+pub type LocalizedStringList = Localized<Vec<String>>;
 
-impl LocalizedStringList {
-    /// Creates a new LocalizedStringList with default values.
-    pub fn new(default: Vec<String>) -> Self {
+impl<T> Localized<T> {
+    /// Creates a value without localized variants.
+    pub fn new(default: impl Into<T>) -> Self {
         Self {
-            default,
+            default: default.into(),
             localized: HashMap::new(),
         }
     }
 
     /// Adds a localized variant.
-    pub fn add_localized(&mut self, locale: Locale, values: Vec<String>) {
-        self.localized.insert(locale, values);
+    pub fn add_localized(&mut self, locale: Locale, value: T) {
+        self.localized.insert(locale, value);
     }
 
-    /// Gets the appropriate list for the given locale.
-    pub fn get(&self, locale: &Locale) -> &[String] {
+    /// Applies matching rules 1-4 of [`get`](Self::get), without falling back
+    /// to the default value.
+    ///
+    /// Locales are compared in their [normalized](Locale::normalized) form,
+    /// so `de_de.utf8` finds `Name[de_DE]` and `no` finds `Name[nb]`.
+    pub fn lookup(&self, locale: &Locale) -> Option<&T> {
         if let Some(value) = self.localized.get(locale) {
-            return value;
+            return Some(value);
         }
 
-        if locale.country.is_some() && locale.modifier.is_some() {
-            let mut try_locale = locale.clone();
-            try_locale.country = None;
-            if let Some(value) = self.localized.get(&try_locale) {
-                return value;
+        let mut normalized: Option<Vec<(Locale, &T)>> = None;
+        for candidate in locale.normalized().fallbacks() {
+            if let Some(value) = self.localized.get(&candidate) {
+                return Some(value);
             }
-        }
-
-        if locale.modifier.is_some() {
-            let mut try_locale = locale.clone();
-            try_locale.modifier = None;
-            if let Some(value) = self.localized.get(&try_locale) {
-                return value;
+            let normalized = normalized.get_or_insert_with(|| {
+                self.localized
+                    .iter()
+                    .map(|(locale, value)| (locale.normalized(), value))
+                    .collect()
+            });
+            if let Some((_, value)) = normalized.iter().find(|(locale, _)| *locale == candidate) {
+                return Some(value);
             }
         }
+        None
+    }
+}
 
-        if locale.country.is_some() || locale.modifier.is_some() {
-            let try_locale = Locale::new(&locale.lang);
-            if let Some(value) = self.localized.get(&try_locale) {
-                return value;
-            }
-        }
+impl<T: Deref> Localized<T> {
+    /// Gets the appropriate value for the given locale using the spec's matching rules.
+    ///
+    /// # Matching Rules (Section 5)
+    ///
+    /// 1. Try exact match: `lang_COUNTRY@MODIFIER`
+    /// 2. Try without country: `lang@MODIFIER`
+    /// 3. Try without modifier: `lang_COUNTRY`
+    /// 4. Try just language: `lang`
+    /// 5. Fall back to default
+    pub fn get(&self, locale: &Locale) -> &T::Target {
+        // 5. Fall back to default
+        self.lookup(locale).unwrap_or(&self.default)
+    }
 
-        &self.default
+    /// Gets the value for the first locale of a priority list that matches,
+    /// falling back to the default.
+    ///
+    /// Each locale is matched with the rules of [`get`](Self::get) before
+    /// moving on to the next one, so the list can come straight from
+    /// `$LANGUAGE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{Locale, LocalizedString};
+    ///
+    /// let mut name = LocalizedString::new("Calculator");
+    /// name.add_localized(Locale::from_string("fr"), "Calculatrice".to_string());
+    ///
+    /// let locales: Vec<Locale> = "de:fr_CA:en".split(':').map(Locale::from_string).collect();
+    /// assert_eq!(name.get_with_fallbacks(&locales), "Calculatrice");
+    /// assert_eq!(name.get_with_fallbacks(&[Locale::from_string("de")]), "Calculator");
+    /// ```
+    pub fn get_with_fallbacks(&self, locales: &[Locale]) -> &T::Target {
+        locales
+            .iter()
+            .find_map(|locale| self.lookup(locale))
+            .unwrap_or(&self.default)
     }
 }
 
@@ -424,7 +794,10 @@ pub enum DesktopEntryType {
 
 impl DesktopEntryType {
     /// Parses a type string into a DesktopEntryType.
-    pub fn from_str(s: &str) -> Option<Self> {
+    ///
+    /// Unlike the [`FromStr`](std::str::FromStr) implementation, returns
+    /// `None` for unknown types rather than an error.
+    pub fn parse(s: &str) -> Option<Self> {
         match s {
             "Application" => Some(Self::Application),
             "Link" => Some(Self::Link),
@@ -443,10 +816,148 @@ impl DesktopEntryType {
     }
 }
 
+impl fmt::Display for DesktopEntryType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parses the value of the `Type` key.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::DesktopEntryType;
+///
+/// let entry_type: DesktopEntryType = "Link".parse().unwrap();
+/// assert_eq!(entry_type, DesktopEntryType::Link);
+/// assert_eq!(entry_type.to_string(), "Link");
+/// assert!("link".parse::<DesktopEntryType>().is_err());
+/// ```
+impl std::str::FromStr for DesktopEntryType {
+    type Err = DesktopEntryError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+            .ok_or_else(|| DesktopEntryError::InvalidValue("Type".to_string(), s.to_string()))
+    }
+}
+
+/// A released version of the Desktop Entry Specification.
+///
+/// Declared by the `Version` key, which is the version of the specification
+/// the file conforms to, not the version of the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SpecVersion {
+    /// Version 1.0
+    V1_0,
+    /// Version 1.1
+    V1_1,
+    /// Version 1.2
+    V1_2,
+    /// Version 1.3
+    V1_3,
+    /// Version 1.4
+    V1_4,
+    /// Version 1.5
+    V1_5,
+}
+
+impl SpecVersion {
+    /// The latest version of the specification known to this crate.
+    pub const LATEST: Self = Self::V1_5;
+
+    /// Parses a `Version` value into a known specification version.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "1.0" => Some(Self::V1_0),
+            "1.1" => Some(Self::V1_1),
+            "1.2" => Some(Self::V1_2),
+            "1.3" => Some(Self::V1_3),
+            "1.4" => Some(Self::V1_4),
+            "1.5" => Some(Self::V1_5),
+            _ => None,
+        }
+    }
+
+    /// Converts the version to its string representation.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::V1_0 => "1.0",
+            Self::V1_1 => "1.1",
+            Self::V1_2 => "1.2",
+            Self::V1_3 => "1.3",
+            Self::V1_4 => "1.4",
+            Self::V1_5 => "1.5",
+        }
+    }
+
+    /// The version of the specification that introduced a key of
+    /// `[Desktop Entry]`, or `None` for keys it does not define.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::SpecVersion;
+    ///
+    /// assert_eq!(SpecVersion::of_key("Exec"), Some(SpecVersion::V1_0));
+    /// assert_eq!(SpecVersion::of_key("SingleMainWindow"), Some(SpecVersion::V1_5));
+    /// assert_eq!(SpecVersion::of_key("X-Custom"), None);
+    /// ```
+    pub fn of_key(key: &str) -> Option<Self> {
+        match key {
+            "Actions" | "DBusActivatable" | "Keywords" => Some(Self::V1_1),
+            "Implements" => Some(Self::V1_2),
+            "PrefersNonDefaultGPU" => Some(Self::V1_4),
+            "SingleMainWindow" => Some(Self::V1_5),
+            _ if KNOWN_KEYS.contains(&key) => Some(Self::V1_0),
+            _ => None,
+        }
+    }
+}
+
 // ============================================================================
 // Group and Entry
 // ============================================================================
 
+/// Name of the main group of every desktop entry file.
+pub(crate) const DESKTOP_ENTRY_GROUP: &str = "Desktop Entry";
+
+/// Name of the main group in files written for KDE 1 and 2.
+pub(crate) const KDE_DESKTOP_ENTRY_GROUP: &str = "KDE Desktop Entry";
+
+/// Prefix of the action groups referenced by the `Actions` key.
+pub(crate) const ACTION_GROUP_PREFIX: &str = "Desktop Action ";
+
+/// Keys of `[Desktop Entry]` defined by the specification.
+const KNOWN_KEYS: &[&str] = &[
+    "Type",
+    "Name",
+    "Version",
+    "GenericName",
+    "NoDisplay",
+    "Comment",
+    "Icon",
+    "Hidden",
+    "OnlyShowIn",
+    "NotShowIn",
+    "DBusActivatable",
+    "TryExec",
+    "Exec",
+    "Path",
+    "Terminal",
+    "Actions",
+    "MimeType",
+    "Categories",
+    "Implements",
+    "Keywords",
+    "StartupNotify",
+    "StartupWMClass",
+    "URL",
+    "PrefersNonDefaultGPU",
+    "SingleMainWindow",
+];
+
 /// Represents a comment or blank line in the file.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Comment {
@@ -468,12 +979,173 @@ pub struct Comment {
 ///
 /// Section 3.2: "Group headers"
 /// Section 11: "Additional applications actions"
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Group {
     /// Name of the group (without the brackets)
     pub name: String,
     /// All key-value pairs in this group
     pub entries: HashMap<String, Vec<Entry>>,
+    /// Location of the group header, brackets included, in the parsed text;
+    /// `None` for groups that were not parsed. Ignored when comparing groups.
+    pub header_span: Option<Span>,
+}
+
+impl PartialEq for Group {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.entries == other.entries
+    }
+}
+
+impl Group {
+    /// Gets the raw, unlocalized value of a key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        KeyValues(&self.entries).raw(key)
+    }
+
+    /// Gets a `string` key, with escape sequences decoded.
+    pub fn get_string(&self, key: &str) -> Option<String> {
+        KeyValues(&self.entries).string(key)
+    }
+
+    /// Gets a `boolean` key. Values other than `true` and `false` are
+    /// treated as absent.
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        KeyValues(&self.entries).bool(key)
+    }
+
+    /// Gets a `numeric` key. Values that are not floating point numbers are
+    /// treated as absent.
+    pub fn get_numeric(&self, key: &str) -> Option<f64> {
+        KeyValues(&self.entries).numeric(key)
+    }
+
+    /// Gets a semicolon-separated list of `string` values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, Locale};
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\n\n\
+    ///      [X-Vendor]\nTags=a;b;\nEnabled=true\nScale=1.5\nLabel=Hello\nLabel[de]=Hallo\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let group = &entry.additional_groups["X-Vendor"];
+    /// assert_eq!(group.get_string_list("Tags"), Some(vec!["a".to_string(), "b".to_string()]));
+    /// assert_eq!(group.get_bool("Enabled"), Some(true));
+    /// assert_eq!(group.get_numeric("Scale"), Some(1.5));
+    /// assert_eq!(
+    ///     group.get_localestring("Label", &Locale::from_string("de_AT")).as_deref(),
+    ///     Some("Hallo")
+    /// );
+    /// ```
+    pub fn get_string_list(&self, key: &str) -> Option<Vec<String>> {
+        KeyValues(&self.entries).string_list(key)
+    }
+
+    /// Gets a `localestring` key resolved for a locale, falling back to the
+    /// unlocalized value.
+    pub fn get_localestring(&self, key: &str, locale: &Locale) -> Option<String> {
+        KeyValues(&self.entries).localestring(key, locale)
+    }
+
+    /// Gets a `localestring` key with all of its localized variants.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, Locale};
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\n\n\
+    ///      [Desktop Action new]\nName=New\nName[fr]=Nouveau\nExec=app --new\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let group = &entry.additional_groups["Desktop Action new"];
+    /// let name = group.get_localized_string("Name").unwrap();
+    /// assert_eq!(name.get(&Locale::from_string("fr_FR")), "Nouveau");
+    /// ```
+    pub fn get_localized_string(&self, key: &str) -> Option<LocalizedString> {
+        let mut target = None;
+        Parser::parse_optional_localized_string(&self.entries, key, &mut target);
+        target
+    }
+
+    /// Gets an `iconstring` key with all of its localized variants.
+    pub fn get_icon_string(&self, key: &str) -> Option<IconString> {
+        let mut target = None;
+        Parser::parse_optional_icon_string(&self.entries, key, &mut target);
+        target
+    }
+
+    /// Gets a `localestring(s)` key with all of its localized variants.
+    pub fn get_localized_string_list(&self, key: &str) -> Option<LocalizedStringList> {
+        let mut target = None;
+        Parser::parse_optional_localized_string_list(&self.entries, key, &mut target);
+        target
+    }
+}
+
+/// Typed access to raw entries, shared by [`Group`] and
+/// [`DesktopEntry::unknown_keys`].
+struct KeyValues<'a>(&'a HashMap<String, Vec<Entry>>);
+
+impl<'a> KeyValues<'a> {
+    fn raw(&self, key: &str) -> Option<&'a str> {
+        self.0
+            .get(key)?
+            .iter()
+            .find(|entry| entry.locale.is_none())
+            .map(|entry| entry.value.as_str())
+    }
+
+    fn string(&self, key: &str) -> Option<String> {
+        self.raw(key).map(unescape_value)
+    }
+
+    fn bool(&self, key: &str) -> Option<bool> {
+        match self.raw(key)? {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        }
+    }
+
+    fn numeric(&self, key: &str) -> Option<f64> {
+        self.raw(key)?.trim().parse().ok()
+    }
+
+    fn string_list(&self, key: &str) -> Option<Vec<String>> {
+        self.raw(key).map(split_list)
+    }
+
+    fn localestring(&self, key: &str, locale: &Locale) -> Option<String> {
+        let mut target = None;
+        Parser::parse_optional_localized_string(self.0, key, &mut target);
+        target.map(|value| value.get(locale).to_string())
+    }
+}
+
+/// An application action resolved for display.
+///
+/// Returned by [`DesktopEntry::visible_actions`].
+///
+/// # Specification Reference
+///
+/// Section 11: "Additional applications actions"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopAction {
+    /// Action identifier, as listed in the `Actions` key
+    pub id: String,
+    /// Localized label of the action
+    pub name: String,
+    /// Localized icon name or path, if any
+    pub icon: Option<String>,
+    /// Command line of the action, if any
+    pub exec: Option<String>,
 }
 
 /// Represents a single key-value entry, which may be localized.
@@ -482,14 +1154,24 @@ pub struct Group {
 ///
 /// Section 3.3: "Entries"
 /// Section 5: "Localized values for keys"
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Entry {
     /// The key name (without locale suffix)
     pub key: String,
     /// The locale for this entry (None for the default)
     pub locale: Option<Locale>,
-    /// The raw value as a string
+    /// The raw value as a string, with its escape sequences; see
+    /// [`unescape_value`] and [`split_list`]
     pub value: String,
+    /// Location of the line in the parsed text; `None` for entries that
+    /// were not parsed. Ignored when comparing entries.
+    pub span: Option<EntrySpan>,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.locale == other.locale && self.value == other.value
+    }
 }
 
 // ============================================================================
@@ -718,6 +1400,20 @@ pub struct DesktopEntry {
 
     /// Comments and blank lines (preserved for round-trip serialization)
     pub comments: Vec<Comment>,
+
+    /// Path of the file this entry was parsed from, if any.
+    ///
+    /// Set by [`DesktopEntry::parse_file`]; the desktop file name determines
+    /// the D-Bus name of `DBusActivatable` applications. Not serialized.
+    pub source_path: Option<PathBuf>,
+
+    /// Original text of the file this entry was parsed from, if any.
+    ///
+    /// Serialization rewrites only the lines of changed keys and keeps the
+    /// rest of the file as it was; see [`layout`]. Set to `None` to write
+    /// the entry in canonical form. The layout holds a copy of the file;
+    /// parse with [`ParserOptions::discard_layout`] to leave it out.
+    pub layout: Option<layout::Layout>,
 }
 
 impl DesktopEntry {
@@ -763,9 +1459,18 @@ impl DesktopEntry {
             additional_groups: HashMap::new(),
             unknown_keys: HashMap::new(),
             comments: Vec::new(),
+            source_path: None,
+            layout: None,
         }
     }
 
+    /// Starts building an entry of the given type.
+    ///
+    /// See [`DesktopEntryBuilder`](builder::DesktopEntryBuilder).
+    pub fn builder(entry_type: DesktopEntryType) -> builder::DesktopEntryBuilder {
+        builder::DesktopEntryBuilder::new(entry_type)
+    }
+
     /// Parses a desktop entry file from a string.
     ///
     /// # Examples
@@ -786,6 +1491,62 @@ impl DesktopEntry {
         Parser::new(content).parse()
     }
 
+    /// Parses a desktop entry file from a string with custom options.
+    ///
+    /// Use [`parse_with_diagnostics`](Self::parse_with_diagnostics) to also
+    /// get the warnings reported when recovering from malformed input.
+    pub fn parse_with(content: &str, options: &ParserOptions) -> Result<Self> {
+        Self::parse_with_diagnostics(content, options).map(|(entry, _)| entry)
+    }
+
+    /// Parses a desktop entry file from a string with custom options,
+    /// returning the warnings reported along the way.
+    pub fn parse_with_diagnostics(
+        content: &str,
+        options: &ParserOptions,
+    ) -> Result<(Self, Vec<Diagnostic>)> {
+        let mut parser = Parser::with_options(content, options.clone());
+        let entry = parser.parse()?;
+        Ok((entry, parser.diagnostics))
+    }
+
+    /// Parses a desktop entry file, reporting every problem found instead of
+    /// stopping at the first one.
+    ///
+    /// Malformed lines are reported as errors and skipped, as are the keys
+    /// of groups with malformed or duplicate headers. The entry is `None` if
+    /// it cannot be built from the remaining lines (for example because
+    /// `Type` is missing); otherwise, a failed [`validate`](Self::validate) is
+    /// reported as well. Diagnostics tied to a line carry its [`Span`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, ParserOptions, Severity};
+    ///
+    /// let (entry, diagnostics) = DesktopEntry::parse_collecting(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nBad Key=1\nno equals sign\n",
+    ///     &ParserOptions::default(),
+    /// );
+    ///
+    /// assert_eq!(entry.unwrap().name.default, "App");
+    /// assert_eq!(diagnostics.len(), 2);
+    /// assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+    /// assert_eq!(diagnostics[0].span.unwrap().line, 5);
+    /// assert_eq!(diagnostics[1].code, "invalid-line");
+    /// ```
+    pub fn parse_collecting(
+        content: &str,
+        options: &ParserOptions,
+    ) -> (Option<Self>, Vec<Diagnostic>) {
+        let mut parser = Parser::with_options(content, options.clone());
+        parser.collect_errors = true;
+
+        let entry = parser.parse();
+        let entry = parser.finish_collecting(entry);
+        (entry, parser.diagnostics)
+    }
+
     /// Parses a desktop entry file from a file path.
     ///
     /// # Examples
@@ -796,8 +1557,74 @@ impl DesktopEntry {
     /// let entry = DesktopEntry::parse_file("app.desktop").unwrap();
     /// ```
     pub fn parse_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
         let content = std::fs::read_to_string(path)?;
-        Self::parse(&content)
+        let mut entry = Self::parse(&content)?;
+        entry.source_path = Some(path.to_path_buf());
+        Ok(entry)
+    }
+
+    /// Location of a key in the parsed text, found in the
+    /// [`layout`](Self::layout).
+    ///
+    /// Spans describe the text as it was parsed: they are `None` for keys
+    /// set afterwards, and still point at the original line of edited keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, Locale};
+    ///
+    /// let content = "[Desktop Entry]\nType=Application\nName=App\nName[de]=Anwendung\n";
+    /// let entry = DesktopEntry::parse(content).unwrap();
+    ///
+    /// let span = entry
+    ///     .entry_span("Desktop Entry", "Name", Some(&Locale::from_string("de")))
+    ///     .unwrap();
+    /// assert_eq!(span.line.line, 4);
+    /// assert_eq!(&content[span.locale.unwrap().byte_range(content).unwrap()], "[de]");
+    /// assert_eq!(&content[span.value.byte_range(content).unwrap()], "Anwendung");
+    /// assert!(entry.entry_span("Desktop Entry", "Exec", None).is_none());
+    /// ```
+    pub fn entry_span(&self, group: &str, key: &str, locale: Option<&Locale>) -> Option<EntrySpan> {
+        let (groups, _) = self.layout.as_ref()?.spans();
+        groups
+            .get(group)?
+            .get(key)?
+            .iter()
+            .find(|entry| entry.locale.as_ref() == locale)?
+            .span
+    }
+
+    /// Location of a group header in the parsed text, found in the
+    /// [`layout`](Self::layout).
+    ///
+    /// The header of `[Desktop Entry]` has no span when its keys come before
+    /// any header.
+    pub fn group_span(&self, group: &str) -> Option<Span> {
+        let (_, header_spans) = self.layout.as_ref()?.spans();
+        header_spans.get(group).copied()
+    }
+
+    /// Writes the desktop entry to a file, atomically replacing it.
+    ///
+    /// The entry is written to a temporary file in the same directory, which
+    /// is synced to disk and then renamed over `path`, so a crash leaves
+    /// either the old or the new file in place. An existing file's
+    /// permissions are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let mut entry = DesktopEntry::parse_file("app.desktop").unwrap();
+    /// entry.no_display = Some(true);
+    /// entry.write_file("app.desktop").unwrap();
+    /// ```
+    pub fn write_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_atomically(path.as_ref(), |mut writer| self.write_to(&mut writer))?;
+        Ok(())
     }
 
     /// Serializes the desktop entry to a string.
@@ -825,6 +1652,132 @@ impl DesktopEntry {
 
     /// Writes the desktop entry to a writer.
     pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.write_to_with(writer, &SerializeOptions::default())
+    }
+
+    /// Serializes the desktop entry to a string with the given options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, SerializeOptions};
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nMimeType=text/x-c;text/plain;image/png;\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let options = SerializeOptions {
+    ///     sort_lists: true,
+    ///     annotate_lists_from: Some(3),
+    ///     ..SerializeOptions::default()
+    /// };
+    /// let serialized = entry.serialize_with(&options);
+    /// assert!(serialized.contains("# MimeType: 3 items\nMimeType=image/png;text/plain;text/x-c\n"));
+    /// ```
+    pub fn serialize_with(&self, options: &SerializeOptions) -> String {
+        let mut output = Vec::new();
+        self.write_to_with(&mut output, options).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    /// Writes the desktop entry to a writer with the given options.
+    ///
+    /// Entries with a [`layout`](Self::layout) keep their original text
+    /// when written with the default options; any other options ignore it.
+    pub fn write_to_with<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &SerializeOptions,
+    ) -> io::Result<()> {
+        #[cfg(feature = "unicode")]
+        if options.normalize_unicode {
+            let mut normalized = self.clone();
+            normalized.normalize_unicode();
+            let options = SerializeOptions {
+                normalize_unicode: false,
+                ..options.clone()
+            };
+            return normalized.write_to_with(writer, &options);
+        }
+        if let Some(layout) = &self.layout
+            && *options == SerializeOptions::default()
+            && layout.write(self, writer)?
+        {
+            return Ok(());
+        }
+        self.write_canonical(writer, options)
+    }
+
+    /// Returns the stable JSON representation of the entry.
+    ///
+    /// The output is an object holding a format `"version"` (currently `1`)
+    /// and the `"groups"` of the entry: `Desktop Entry` first, then actions
+    /// and additional groups sorted by name. Each group maps its keys to an
+    /// object of locales, where `""` holds the unlocalized value:
+    ///
+    /// ```json
+    /// {
+    ///   "version": 1,
+    ///   "groups": {
+    ///     "Desktop Entry": {
+    ///       "Name": {"": "Viewer", "de": "Betrachter"}
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// Keys and locales are written in the order of
+    /// [`SerializeOptions::canonical`], so equal entries give equal output.
+    /// Values are the raw strings of the file, so escape sequences such as
+    /// `\s` are kept and lists end with a semicolon. Comments are not
+    /// included. Use [`from_json`](Self::from_json) to read it back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Viewer\nName[de]=Betrachter\nExec=viewer\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let json = entry.to_json();
+    /// assert!(json.contains(r#""Name": {"": "Viewer", "de": "Betrachter"}"#));
+    /// let restored = DesktopEntry::from_json(&json).unwrap();
+    /// assert_eq!(restored.serialize(), entry.serialize());
+    /// ```
+    pub fn to_json(&self) -> String {
+        json::to_json(self)
+    }
+
+    /// Reads an entry from the JSON representation written by
+    /// [`to_json`](Self::to_json).
+    ///
+    /// Malformed JSON, an unsupported `"version"`, and names or values that
+    /// cannot be written to a desktop entry file (such as line breaks) are
+    /// reported as [`DesktopEntryError::InvalidJson`]; the resulting entry
+    /// is then validated like [`parse`](Self::parse) does.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let mut entry = DesktopEntry::parse(&json::to_desktop_file(json)?)?;
+        entry.layout = None;
+        Ok(entry)
+    }
+
+    /// Serializes the entry with the default options, ignoring its layout.
+    pub(crate) fn canonical_string(&self) -> String {
+        let mut output = Vec::new();
+        self.write_canonical(&mut output, &SerializeOptions::default())
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn write_canonical<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &SerializeOptions,
+    ) -> io::Result<()> {
         // Write comments at the beginning
         for comment in &self.comments {
             if comment.is_blank {
@@ -835,27 +1788,49 @@ impl DesktopEntry {
         }
 
         // Write [Desktop Entry] group
-        writeln!(writer, "[Desktop Entry]")?;
+        writeln!(writer, "[{}]", DESKTOP_ENTRY_GROUP)?;
 
         // Type (required)
         writeln!(writer, "Type={}", self.entry_type.as_str())?;
 
         // Version (optional)
-        if let Some(version) = &self.version {
-            writeln!(writer, "Version={}", version)?;
+        let required = self.required_spec_version();
+        match self.spec_version() {
+            Some(declared) if options.bump_version && declared < required => {
+                writeln!(writer, "Version={}", required.as_str())?;
+            }
+            _ => {
+                if let Some(version) = &self.version {
+                    writeln!(writer, "Version={}", escape_value(version))?;
+                }
+            }
         }
 
         // Name (required)
-        writeln!(writer, "Name={}", self.name.default)?;
-        for (locale, value) in &self.name.localized {
-            writeln!(writer, "Name[{}]={}", locale.to_string_repr(), value)?;
+        writeln!(writer, "Name={}", escape_value(&self.name.default))?;
+        for (locale, value) in options.localized(&self.name.localized) {
+            writeln!(
+                writer,
+                "Name[{}]={}",
+                locale.to_string_repr(),
+                escape_value(value)
+            )?;
         }
 
         // GenericName
         if let Some(generic_name) = &self.generic_name {
-            writeln!(writer, "GenericName={}", generic_name.default)?;
-            for (locale, value) in &generic_name.localized {
-                writeln!(writer, "GenericName[{}]={}", locale.to_string_repr(), value)?;
+            writeln!(
+                writer,
+                "GenericName={}",
+                escape_value(&generic_name.default)
+            )?;
+            for (locale, value) in options.localized(&generic_name.localized) {
+                writeln!(
+                    writer,
+                    "GenericName[{}]={}",
+                    locale.to_string_repr(),
+                    escape_value(value)
+                )?;
             }
         }
 
@@ -866,17 +1841,27 @@ impl DesktopEntry {
 
         // Comment
         if let Some(comment) = &self.comment {
-            writeln!(writer, "Comment={}", comment.default)?;
-            for (locale, value) in &comment.localized {
-                writeln!(writer, "Comment[{}]={}", locale.to_string_repr(), value)?;
+            writeln!(writer, "Comment={}", escape_value(&comment.default))?;
+            for (locale, value) in options.localized(&comment.localized) {
+                writeln!(
+                    writer,
+                    "Comment[{}]={}",
+                    locale.to_string_repr(),
+                    escape_value(value)
+                )?;
             }
         }
 
         // Icon
         if let Some(icon) = &self.icon {
-            writeln!(writer, "Icon={}", icon.default)?;
-            for (locale, value) in &icon.localized {
-                writeln!(writer, "Icon[{}]={}", locale.to_string_repr(), value)?;
+            writeln!(writer, "Icon={}", escape_value(&icon.default))?;
+            for (locale, value) in options.localized(&icon.localized) {
+                writeln!(
+                    writer,
+                    "Icon[{}]={}",
+                    locale.to_string_repr(),
+                    escape_value(value)
+                )?;
             }
         }
 
@@ -887,12 +1872,12 @@ impl DesktopEntry {
 
         // OnlyShowIn
         if let Some(only_show_in) = &self.only_show_in {
-            writeln!(writer, "OnlyShowIn={}", only_show_in.join(";"))?;
+            options.write_list(writer, "OnlyShowIn", only_show_in)?;
         }
 
         // NotShowIn
         if let Some(not_show_in) = &self.not_show_in {
-            writeln!(writer, "NotShowIn={}", not_show_in.join(";"))?;
+            options.write_list(writer, "NotShowIn", not_show_in)?;
         }
 
         // DBusActivatable
@@ -902,17 +1887,17 @@ impl DesktopEntry {
 
         // TryExec
         if let Some(try_exec) = &self.try_exec {
-            writeln!(writer, "TryExec={}", try_exec)?;
+            writeln!(writer, "TryExec={}", escape_value(try_exec))?;
         }
 
         // Exec
         if let Some(exec) = &self.exec {
-            writeln!(writer, "Exec={}", exec)?;
+            writeln!(writer, "Exec={}", escape_value(exec))?;
         }
 
         // Path
         if let Some(path) = &self.path {
-            writeln!(writer, "Path={}", path)?;
+            writeln!(writer, "Path={}", escape_value(path))?;
         }
 
         // Terminal
@@ -922,34 +1907,36 @@ impl DesktopEntry {
 
         // Actions
         if let Some(actions) = &self.actions {
-            writeln!(writer, "Actions={}", actions.join(";"))?;
+            let actions: Vec<String> = actions.iter().map(|a| escape_list_item(a)).collect();
+            writeln!(
+                writer,
+                "Actions={}{}",
+                actions.join(";"),
+                options.list_end()
+            )?;
         }
 
         // MimeType
         if let Some(mime_type) = &self.mime_type {
-            writeln!(writer, "MimeType={}", mime_type.join(";"))?;
+            options.write_list(writer, "MimeType", mime_type)?;
         }
 
         // Categories
         if let Some(categories) = &self.categories {
-            writeln!(writer, "Categories={}", categories.join(";"))?;
+            options.write_list(writer, "Categories", categories)?;
         }
 
         // Implements
         if let Some(implements) = &self.implements {
-            writeln!(writer, "Implements={}", implements.join(";"))?;
+            options.write_list(writer, "Implements", implements)?;
         }
 
         // Keywords
         if let Some(keywords) = &self.keywords {
-            writeln!(writer, "Keywords={}", keywords.default.join(";"))?;
-            for (locale, values) in &keywords.localized {
-                writeln!(
-                    writer,
-                    "Keywords[{}]={}",
-                    locale.to_string_repr(),
-                    values.join(";")
-                )?;
+            options.write_list(writer, "Keywords", &keywords.default)?;
+            for (locale, values) in options.localized(&keywords.localized) {
+                let key = format!("Keywords[{}]", locale.to_string_repr());
+                options.write_list(writer, &key, values)?;
             }
         }
 
@@ -960,12 +1947,12 @@ impl DesktopEntry {
 
         // StartupWMClass
         if let Some(startup_wm_class) = &self.startup_wm_class {
-            writeln!(writer, "StartupWMClass={}", startup_wm_class)?;
+            writeln!(writer, "StartupWMClass={}", escape_value(startup_wm_class))?;
         }
 
         // URL (for Link type)
         if let Some(url) = &self.url {
-            writeln!(writer, "URL={}", url)?;
+            writeln!(writer, "URL={}", escape_value(url))?;
         }
 
         // PrefersNonDefaultGPU
@@ -979,189 +1966,1755 @@ impl DesktopEntry {
         }
 
         // Unknown keys (for round-trip)
-        for (key, entries) in &self.unknown_keys {
-            for entry in entries {
-                if let Some(locale) = &entry.locale {
-                    writeln!(
-                        writer,
-                        "{}[{}]={}",
-                        key,
-                        locale.to_string_repr(),
-                        entry.value
-                    )?;
-                } else {
-                    writeln!(writer, "{}={}", key, entry.value)?;
-                }
-            }
+        for entry in options.entries(&self.unknown_keys) {
+            SerializeOptions::write_entry(writer, entry)?;
         }
 
         // Additional groups
-        for (_, group) in &self.additional_groups {
+        let mut groups: Vec<&Group> = self.additional_groups.values().collect();
+        if options.canonical {
+            groups.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        for group in groups {
             writeln!(writer)?;
             writeln!(writer, "[{}]", group.name)?;
-            for (key, entries) in &group.entries {
-                for entry in entries {
-                    if let Some(locale) = &entry.locale {
-                        writeln!(
-                            writer,
-                            "{}[{}]={}",
-                            key,
-                            locale.to_string_repr(),
-                            entry.value
-                        )?;
-                    } else {
-                        writeln!(writer, "{}={}", key, entry.value)?;
-                    }
-                }
+            for entry in options.entries(&group.entries) {
+                SerializeOptions::write_entry(writer, entry)?;
             }
         }
 
         Ok(())
     }
 
-    /// Validates that required fields are present for the entry type.
+    /// Normalizes all localized values to Unicode Normalization Form C.
     ///
-    /// # Errors
+    /// Translation toolchains disagree on composed (NFC) and decomposed (NFD)
+    /// forms, which breaks search matching and deduplication. This normalizes
+    /// `Name`, `GenericName`, `Comment`, and `Keywords` in every locale, and
+    /// every key with localized variants in unknown keys and additional
+    /// groups. It is applied when parsing with
+    /// [`ParserOptions::normalize_unicode`] and when serializing with
+    /// [`SerializeOptions::normalize_unicode`].
     ///
-    /// Returns an error if validation fails.
-    pub fn validate(&self) -> Result<()> {
-        // URL is required for Link type
-        if self.entry_type == DesktopEntryType::Link && self.url.is_none() {
-            return Err(DesktopEntryError::ValidationError(
-                "URL is required for Link type entries".to_string(),
-            ));
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Cafe\u{301}\nExec=cafe\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// entry.normalize_unicode();
+    /// assert_eq!(entry.name.default, "Caf\u{e9}");
+    /// ```
+    #[cfg(feature = "unicode")]
+    pub fn normalize_unicode(&mut self) {
+        use unicode_normalization::UnicodeNormalization;
+
+        fn nfc(value: &mut String) {
+            if !unicode_normalization::is_nfc(value) {
+                *value = value.nfc().collect();
+            }
         }
 
-        // Exec or DBusActivatable is required for Application type
-        if self.entry_type == DesktopEntryType::Application {
-            let has_exec = self.exec.is_some();
-            let is_dbus_activatable = self.dbus_activatable.unwrap_or(false);
+        fn nfc_localized(values: &mut LocalizedString) {
+            nfc(&mut values.default);
+            values.localized.values_mut().for_each(nfc);
+        }
 
-            if !has_exec && !is_dbus_activatable {
-                return Err(DesktopEntryError::ValidationError(
-                    "Either Exec key or DBusActivatable=true is required for Application type"
-                        .to_string(),
-                ));
+        fn nfc_entries(entries: &mut HashMap<String, Vec<Entry>>) {
+            for (key, entries) in entries.iter_mut() {
+                let localizable = matches!(
+                    key.as_str(),
+                    "Name" | "GenericName" | "Comment" | "Keywords"
+                ) || entries.iter().any(|entry| entry.locale.is_some());
+                if localizable {
+                    entries.iter_mut().for_each(|entry| nfc(&mut entry.value));
+                }
             }
         }
 
-        Ok(())
+        nfc_localized(&mut self.name);
+        self.generic_name.iter_mut().for_each(nfc_localized);
+        self.comment.iter_mut().for_each(nfc_localized);
+        if let Some(keywords) = &mut self.keywords {
+            keywords.default.iter_mut().for_each(nfc);
+            keywords.localized.values_mut().flatten().for_each(nfc);
+        }
+        nfc_entries(&mut self.unknown_keys);
+        for group in self.additional_groups.values_mut() {
+            nfc_entries(&mut group.entries);
+        }
     }
-}
 
-// ============================================================================
-// Parser
-// ============================================================================
-
-struct Parser {
-    lines: Vec<String>,
-}
-
-impl Parser {
-    fn new(content: &str) -> Self {
-        Self {
-            lines: content.lines().map(|s| s.to_string()).collect(),
-        }
+    /// Gets the specification version declared by the `Version` key.
+    ///
+    /// Returns `None` if the key is missing or does not name a known version
+    /// of the specification (e.g., when it holds the application's version).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, SpecVersion};
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nVersion=1.5\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(entry.spec_version(), Some(SpecVersion::V1_5));
+    ///
+    /// entry.version = Some("104.2".to_string());
+    /// assert_eq!(entry.spec_version(), None);
+    /// ```
+    pub fn spec_version(&self) -> Option<SpecVersion> {
+        SpecVersion::parse(self.version.as_deref()?)
     }
 
-    fn parse(&mut self) -> Result<DesktopEntry> {
-        let mut groups: HashMap<String, HashMap<String, Vec<Entry>>> = HashMap::new();
-        let mut current_group: Option<String> = None;
-        let mut comments = Vec::new();
-        let mut line_num = 0;
+    /// The oldest version of the specification defining every key the
+    /// entry sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, SerializeOptions, SpecVersion};
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nVersion=1.0\nType=Application\nName=App\nExec=app\n\
+    ///      SingleMainWindow=true\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(entry.spec_version(), Some(SpecVersion::V1_0));
+    /// assert_eq!(entry.required_spec_version(), SpecVersion::V1_5);
+    ///
+    /// let options = SerializeOptions {
+    ///     bump_version: true,
+    ///     ..SerializeOptions::default()
+    /// };
+    /// assert!(entry.serialize_with(&options).contains("Version=1.5\n"));
+    /// ```
+    pub fn required_spec_version(&self) -> SpecVersion {
+        self.application_keys()
+            .into_iter()
+            .filter_map(SpecVersion::of_key)
+            .max()
+            .unwrap_or(SpecVersion::V1_0)
+    }
 
-        // Parse all lines
-        for line in &self.lines {
-            line_num += 1;
-            let trimmed = line.trim();
+    /// The registered categories listed in `Categories`, in order.
+    ///
+    /// Extension (`X-`) and unknown categories are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    /// use xdg_desktop_entry::categories::Category;
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nCategories=Network;X-Custom;WebBrowser;\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(entry.registered_categories(), [Category::Network, Category::WebBrowser]);
+    /// ```
+    pub fn registered_categories(&self) -> Vec<categories::Category> {
+        self.categories
+            .iter()
+            .flatten()
+            .filter_map(|category| categories::Category::parse(category))
+            .collect()
+    }
 
-            // Skip blank lines and comments before first group
-            if trimmed.is_empty() {
-                if current_group.is_none() {
-                    comments.push(Comment {
-                        line_number: line_num,
-                        content: String::new(),
-                        is_blank: true,
-                    });
+    /// Applies an automatic fix to the entry.
+    ///
+    /// Edits are applied in order to the raw keys of the entry, which is then
+    /// rebuilt; comments and the source path are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an edit refers to a missing group or key, or if
+    /// the edited entry is no longer a well-formed desktop entry (e.g., the
+    /// fix removes `Type`). The entry is left unchanged in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, Fix, KeyEdit};
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nFoo=bar\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// entry
+    ///     .apply_fix(&Fix {
+    ///         description: "Prefix Foo with X-".to_string(),
+    ///         edits: vec![KeyEdit::Rename {
+    ///             group: "Desktop Entry".to_string(),
+    ///             from: "Foo".to_string(),
+    ///             to: "X-Foo".to_string(),
+    ///         }],
+    ///     })
+    ///     .unwrap();
+    /// assert!(entry.unknown_keys.contains_key("X-Foo"));
+    /// ```
+    pub fn apply_fix(&mut self, fix: &Fix) -> Result<()> {
+        self.edit_raw_groups(|groups| apply_edits(groups, &fix.edits))
+    }
+
+    /// Edits the raw groups of the entry and rebuilds it from them, keeping
+    /// its comments, source path, and layout. The entry is left untouched if
+    /// the edit fails or the result is not a valid entry.
+    pub(crate) fn edit_raw_groups(
+        &mut self,
+        edit: impl FnOnce(&mut RawGroups) -> Result<()>,
+    ) -> Result<()> {
+        let mut groups = self.raw_groups()?;
+        edit(&mut groups)?;
+
+        let mut edited = Parser::new("").build_entry(groups, self.comments.clone())?;
+        edited.source_path = self.source_path.clone();
+        edited.layout = self.layout.take();
+        *self = edited;
+        Ok(())
+    }
+
+    /// Parses the `Exec` key into the program, its arguments, and field codes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::MissingRequiredKey`] if there is no `Exec`
+    /// key, or [`DesktopEntryError::InvalidValue`] if it is malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Viewer\nExec=viewer %F\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let exec = entry.parsed_exec().unwrap();
+    /// assert_eq!(exec.program(), "viewer");
+    /// assert!(exec.accepts_files() && !exec.accepts_urls());
+    /// ```
+    pub fn parsed_exec(&self) -> Result<ExecString> {
+        let exec = self
+            .exec
+            .as_deref()
+            .ok_or_else(|| DesktopEntryError::MissingRequiredKey("Exec".to_string()))?;
+        ExecString::parse(exec)
+    }
+
+    /// The program run by the `Exec` key: its first argument, unquoted, with
+    /// any field codes removed and `%%` read as `%`.
+    ///
+    /// Unlike [`parsed_exec`](Self::parsed_exec), this does not require the
+    /// rest of the command line to be valid. Returns `None` if there is no
+    /// `Exec` key, its quoting is unbalanced, or it names no program.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Player\n\
+    ///      Exec=\"/opt/My Player/player\" --enqueue %U\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(entry.exec_program().as_deref(), Some("/opt/My Player/player"));
+    ///
+    /// entry.exec = Some("\"unterminated".to_string());
+    /// assert_eq!(entry.exec_program(), None);
+    /// ```
+    pub fn exec_program(&self) -> Option<String> {
+        let argv = exec::split_words(self.exec.as_deref()?).ok()?;
+        let mut program = String::new();
+        let mut chars = argv.first()?.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '%' => {
+                    if chars.next() == Some('%') {
+                        program.push('%');
+                    }
                 }
-                continue;
+                c => program.push(c),
+            }
+        }
+        Some(program).filter(|program| !program.is_empty())
+    }
+
+    /// Determines how this entry should be launched.
+    ///
+    /// Applications with `DBusActivatable=true` are activated over D-Bus at the
+    /// well-known name given by their desktop file name (which requires
+    /// [`source_path`](Self::source_path) to be known), keeping the `Exec`
+    /// command line as a fallback for when the session bus is unavailable.
+    /// Other applications run their `Exec` command line. Links, directories,
+    /// and applications with neither yield [`LaunchMethod::None`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `Exec` key has unbalanced quoting.
+    ///
+    /// # Specification Reference
+    ///
+    /// Section 7: "The `Exec` key"
+    /// Section 8: "D-Bus Activation"
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    /// use xdg_desktop_entry::launch::LaunchMethod;
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Foo\n\
+    ///      Exec=\"/opt/foo viewer/foo\" %F\nDBusActivatable=true\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let argv = vec!["/opt/foo viewer/foo".to_string(), "%F".to_string()];
+    /// assert_eq!(
+    ///     entry.effective_launch_method().unwrap(),
+    ///     LaunchMethod::ExecCommand { argv: argv.clone() }
+    /// );
+    ///
+    /// entry.source_path = Some("/usr/share/applications/org.example.Foo.desktop".into());
+    /// assert_eq!(
+    ///     entry.effective_launch_method().unwrap(),
+    ///     LaunchMethod::DBusActivation {
+    ///         name: "org.example.Foo".to_string(),
+    ///         fallback: Some(argv),
+    ///     }
+    /// );
+    /// ```
+    pub fn effective_launch_method(&self) -> Result<LaunchMethod> {
+        if self.entry_type != DesktopEntryType::Application {
+            return Ok(LaunchMethod::None);
+        }
+
+        let argv = match &self.exec {
+            Some(exec) => Some(exec::split_words(exec)?).filter(|argv| !argv.is_empty()),
+            None => None,
+        };
+
+        let bus_name = self
+            .source_path
+            .as_ref()
+            .and_then(|path| path.file_name()?.to_str()?.strip_suffix(".desktop"));
+
+        match (self.dbus_activatable.unwrap_or(false), bus_name, argv) {
+            (true, Some(name), fallback) => Ok(LaunchMethod::DBusActivation {
+                name: name.to_string(),
+                fallback,
+            }),
+            (_, _, Some(argv)) => Ok(LaunchMethod::ExecCommand { argv }),
+            (_, _, None) => Ok(LaunchMethod::None),
+        }
+    }
+
+    /// Whether the entry is listed in menus of the given desktop environments,
+    /// according to `Hidden`, `NoDisplay`, `OnlyShowIn`, and `NotShowIn`.
+    ///
+    /// `current_desktops` are the names from `$XDG_CURRENT_DESKTOP`, as
+    /// strings or [`DesktopEnvironment`](environment::DesktopEnvironment)s;
+    /// see
+    /// [`should_show_in_current_desktop`](Self::should_show_in_current_desktop)
+    /// to read them from the environment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Settings\nExec=settings\nOnlyShowIn=GNOME;Unity;\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert!(entry.should_show(&["ubuntu", "GNOME"]));
+    /// assert!(!entry.should_show(&["KDE"]));
+    /// ```
+    pub fn should_show(&self, current_desktops: &[impl AsRef<str>]) -> bool {
+        let current_desktops: Vec<_> = current_desktops
+            .iter()
+            .map(|desktop| environment::DesktopEnvironment::parse(desktop.as_ref()))
+            .collect();
+        let in_current_desktop = |list: &Vec<String>| {
+            list.iter().any(|desktop| {
+                current_desktops.contains(&environment::DesktopEnvironment::parse(desktop))
+            })
+        };
+
+        self.hidden != Some(true)
+            && self.no_display != Some(true)
+            && self.only_show_in.as_ref().is_none_or(in_current_desktop)
+            && !self.not_show_in.as_ref().is_some_and(in_current_desktop)
+    }
+
+    /// Whether the entry is listed in menus of the desktop environments named
+    /// in `$XDG_CURRENT_DESKTOP`, as read by
+    /// [`current_desktop`](environment::current_desktop).
+    ///
+    /// With `check_try_exec`, entries whose `TryExec` program is not
+    /// installed are hidden too, as menus are expected to do.
+    pub fn should_show_in_current_desktop(&self, check_try_exec: bool) -> bool {
+        self.should_show(&environment::current_desktop())
+            && (!check_try_exec || self.try_exec_available())
+    }
+
+    /// Whether the application asks to run on the non-default (usually
+    /// discrete) GPU.
+    ///
+    /// `PrefersNonDefaultGPU` takes precedence; entries without it fall back
+    /// to KDE's older `X-KDE-RunOnDiscreteGpu`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Game\nExec=game\nX-KDE-RunOnDiscreteGpu=true\n",
+    /// )
+    /// .unwrap();
+    /// assert!(entry.wants_non_default_gpu());
+    /// ```
+    pub fn wants_non_default_gpu(&self) -> bool {
+        self.prefers_non_default_gpu
+            .or_else(|| self.unknown_bool("X-KDE-RunOnDiscreteGpu"))
+            .unwrap_or(false)
+    }
+
+    /// Whether the program named by `TryExec` is installed.
+    ///
+    /// Absolute paths are checked directly, other names are looked up in
+    /// `$PATH`. Entries without `TryExec` are always available.
+    ///
+    /// # Specification Reference
+    ///
+    /// Section 6: "Recognized desktop entry keys" (TryExec)
+    pub fn try_exec_available(&self) -> bool {
+        let Some(try_exec) = &self.try_exec else {
+            return true;
+        };
+        is_installed(try_exec)
+    }
+
+    /// Lists the actions to show in the given desktop environments.
+    ///
+    /// Actions are returned in `Actions` order with their `Name` and `Icon`
+    /// resolved for the first matching locale of `locales`. Actions without a
+    /// `[Desktop Action <id>]` group or a `Name` are skipped, as required by
+    /// the spec. Although not part of the spec, some desktops honor
+    /// `OnlyShowIn` and `NotShowIn` keys inside action groups; they are
+    /// matched against `current_desktops` like their main group counterparts.
+    ///
+    /// # Specification Reference
+    ///
+    /// Section 11: "Additional applications actions"
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, Locale};
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nActions=new;settings;\n\n\
+    ///      [Desktop Action new]\nName=New Window\nName[de]=Neues Fenster\nExec=app --new\n\n\
+    ///      [Desktop Action settings]\nName=Settings\nExec=app --settings\nOnlyShowIn=KDE;\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let actions = entry.visible_actions(&["GNOME"], &[Locale::from_string("de_DE")]);
+    /// assert_eq!(actions.len(), 1);
+    /// assert_eq!(actions[0].name, "Neues Fenster");
+    /// ```
+    pub fn visible_actions(
+        &self,
+        current_desktops: &[impl AsRef<str>],
+        locales: &[Locale],
+    ) -> Vec<DesktopAction> {
+        let in_current_desktop = |list: &str| {
+            split_list(list)
+                .iter()
+                .any(|desktop| current_desktops.iter().any(|d| d.as_ref() == desktop))
+        };
+
+        self.actions
+            .iter()
+            .flatten()
+            .filter_map(|id| {
+                let group = self
+                    .additional_groups
+                    .get(&format!("{}{}", ACTION_GROUP_PREFIX, id))?;
+                let name = group.get_localized_string("Name")?;
+
+                if group
+                    .get("OnlyShowIn")
+                    .is_some_and(|l| !in_current_desktop(l))
+                    || group.get("NotShowIn").is_some_and(in_current_desktop)
+                {
+                    return None;
+                }
+
+                Some(DesktopAction {
+                    id: id.clone(),
+                    name: name.get_with_fallbacks(locales).to_string(),
+                    icon: group
+                        .get_icon_string("Icon")
+                        .map(|icon| icon.get_with_fallbacks(locales).to_string()),
+                    exec: group.get("Exec").map(unescape_value),
+                })
+            })
+            .collect()
+    }
+
+    /// Describes the environment variables a launcher exports for this entry.
+    ///
+    /// The desktop file path comes from [`source_path`](Self::source_path),
+    /// made absolute so it stays valid in the launched process.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, DesktopEntryType, LocalizedString};
+    ///
+    /// let mut entry = DesktopEntry::new(DesktopEntryType::Application, LocalizedString::new("Foo"));
+    /// assert!(entry.to_exec_env_description().vars(42).is_empty());
+    ///
+    /// entry.source_path = Some("/usr/share/applications/foo.desktop".into());
+    /// let vars = entry.to_exec_env_description().vars(42);
+    /// assert_eq!(vars[0].1, "/usr/share/applications/foo.desktop");
+    /// ```
+    pub fn to_exec_env_description(&self) -> ExecEnvDescription {
+        ExecEnvDescription {
+            desktop_file: self
+                .source_path
+                .as_ref()
+                .map(|path| std::path::absolute(path).unwrap_or_else(|_| path.clone())),
+        }
+    }
+
+    /// Gets an unknown `string` key of `[Desktop Entry]`, such as an `X-`
+    /// extension key, with escape sequences decoded.
+    ///
+    /// See [`Group::get_string`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nX-GNOME-UsesNotifications=true\nX-Flags=a;b\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(entry.unknown_bool("X-GNOME-UsesNotifications"), Some(true));
+    /// assert_eq!(entry.unknown_string_list("X-Flags").unwrap().len(), 2);
+    /// ```
+    pub fn unknown_string(&self, key: &str) -> Option<String> {
+        KeyValues(&self.unknown_keys).string(key)
+    }
+
+    /// Gets an unknown `boolean` key of `[Desktop Entry]`.
+    ///
+    /// See [`Group::get_bool`].
+    pub fn unknown_bool(&self, key: &str) -> Option<bool> {
+        KeyValues(&self.unknown_keys).bool(key)
+    }
+
+    /// Gets an unknown `numeric` key of `[Desktop Entry]`.
+    ///
+    /// See [`Group::get_numeric`].
+    pub fn unknown_numeric(&self, key: &str) -> Option<f64> {
+        KeyValues(&self.unknown_keys).numeric(key)
+    }
+
+    /// Gets an unknown list key of `[Desktop Entry]`.
+    ///
+    /// See [`Group::get_string_list`].
+    pub fn unknown_string_list(&self, key: &str) -> Option<Vec<String>> {
+        KeyValues(&self.unknown_keys).string_list(key)
+    }
+
+    /// Gets an unknown `localestring` key of `[Desktop Entry]` resolved for a
+    /// locale.
+    ///
+    /// See [`Group::get_localestring`].
+    pub fn unknown_localestring(&self, key: &str, locale: &Locale) -> Option<String> {
+        KeyValues(&self.unknown_keys).localestring(key, locale)
+    }
+
+    /// Sets an unknown key of `[Desktop Entry]` to a raw value, dropping its
+    /// translations, or removes it.
+    #[cfg(any(feature = "gnome", feature = "kde"))]
+    pub(crate) fn set_unknown_raw(&mut self, key: &str, value: Option<String>) {
+        match value {
+            Some(value) => {
+                let entry = Entry {
+                    key: key.to_string(),
+                    locale: None,
+                    value,
+                    span: None,
+                };
+                self.unknown_keys.insert(key.to_string(), vec![entry]);
+            }
+            None => {
+                self.unknown_keys.remove(key);
+            }
+        }
+    }
+
+    /// Gets the group holding the implementation details of an interface.
+    ///
+    /// Interfaces listed in `Implements` may define a group with the same name
+    /// as the interface (e.g., `[org.freedesktop.ImageProvider]`) carrying
+    /// interface-defined keys. Returns `None` if the interface is not listed in
+    /// `Implements` or the file has no such group.
+    ///
+    /// # Specification Reference
+    ///
+    /// Section 9: "Interfaces"
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\n\
+    ///      Implements=org.freedesktop.ImageProvider;\n\n\
+    ///      [org.freedesktop.ImageProvider]\nFormats=png;jpeg;\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let group = entry
+    ///     .interface_group("org.freedesktop.ImageProvider")
+    ///     .unwrap();
+    /// assert_eq!(group.get("Formats"), Some("png;jpeg;"));
+    /// ```
+    pub fn interface_group(&self, interface: &str) -> Option<&Group> {
+        if !self.implements_interface(interface) {
+            return None;
+        }
+        self.additional_groups.get(interface)
+    }
+
+    /// All groups of the entry in their raw key-value form.
+    ///
+    /// Fails if fields were set to values that cannot be serialized (e.g., an
+    /// unknown key with an invalid name).
+    pub(crate) fn raw_groups(&self) -> Result<RawGroups> {
+        let (groups, _) = Parser::new(&self.canonical_string()).parse_groups()?;
+        Ok(groups)
+    }
+
+    /// Removes an additional group, returning it if it existed.
+    ///
+    /// References to the group are removed as well: dropping a
+    /// `[Desktop Action <id>]` group removes `<id>` from `Actions`, and dropping
+    /// an interface group removes the interface from `Implements`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nActions=new;\n\n\
+    ///      [Desktop Action new]\nName=New\nExec=app --new\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert!(entry.remove_group("Desktop Action new").is_some());
+    /// assert_eq!(entry.actions, None);
+    /// ```
+    pub fn remove_group(&mut self, name: &str) -> Option<Group> {
+        let group = self.additional_groups.remove(name)?;
+
+        if let Some(action) = name.strip_prefix(ACTION_GROUP_PREFIX) {
+            Self::remove_reference(&mut self.actions, action);
+        }
+        Self::remove_reference(&mut self.implements, name);
+
+        Some(group)
+    }
+
+    /// Removes an action: its `[Desktop Action <id>]` group and its entry in
+    /// `Actions`, returning the group if it existed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nActions=new;private;\n\n\
+    ///      [Desktop Action new]\nName=New\nExec=app --new\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(entry.remove_action("new").unwrap().get("Name"), Some("New"));
+    /// assert!(entry.remove_action("private").is_none());
+    /// assert_eq!(entry.actions, None);
+    /// ```
+    pub fn remove_action(&mut self, id: &str) -> Option<Group> {
+        Self::remove_reference(&mut self.actions, id);
+        self.remove_group(&format!("{}{}", ACTION_GROUP_PREFIX, id))
+    }
+
+    /// Renames an additional group, updating references to it.
+    ///
+    /// Renaming `[Desktop Action <old>]` to `[Desktop Action <new>]` replaces
+    /// `<old>` with `<new>` in `Actions`, keeping its position. Renaming a group
+    /// into or out of the `Desktop Action` namespace adds or removes the
+    /// corresponding `Actions` entry. Interface groups listed in `Implements`
+    /// are renamed there too.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::MissingGroup`] if `old` does not exist, and
+    /// [`DesktopEntryError::DuplicateGroup`] if `new` is already in use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nActions=new;\n\n\
+    ///      [Desktop Action new]\nName=New\nExec=app --new\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// entry
+    ///     .rename_group("Desktop Action new", "Desktop Action new-window")
+    ///     .unwrap();
+    /// assert_eq!(entry.actions, Some(vec!["new-window".to_string()]));
+    /// ```
+    pub fn rename_group(&mut self, old: &str, new: &str) -> Result<()> {
+        if new == DESKTOP_ENTRY_GROUP || self.additional_groups.contains_key(new) {
+            return Err(DesktopEntryError::DuplicateGroup(new.to_string()));
+        }
+
+        let mut group = self
+            .additional_groups
+            .remove(old)
+            .ok_or_else(|| DesktopEntryError::MissingGroup(old.to_string()))?;
+        group.name = new.to_string();
+        self.additional_groups.insert(new.to_string(), group);
+
+        match (
+            old.strip_prefix(ACTION_GROUP_PREFIX),
+            new.strip_prefix(ACTION_GROUP_PREFIX),
+        ) {
+            (Some(old_action), Some(new_action)) => {
+                Self::rename_reference(&mut self.actions, old_action, new_action);
+            }
+            (Some(old_action), None) => Self::remove_reference(&mut self.actions, old_action),
+            (None, Some(new_action)) => self
+                .actions
+                .get_or_insert_with(Vec::new)
+                .push(new_action.to_string()),
+            (None, None) => {}
+        }
+        Self::rename_reference(&mut self.implements, old, new);
+
+        Ok(())
+    }
+
+    /// Removes `value` from a list key, dropping the key once it is empty.
+    fn remove_reference(list: &mut Option<Vec<String>>, value: &str) {
+        if let Some(values) = list {
+            values.retain(|v| v != value);
+            if values.is_empty() {
+                *list = None;
+            }
+        }
+    }
+
+    /// Replaces `old` with `new` in a list key, keeping its position.
+    fn rename_reference(list: &mut Option<Vec<String>>, old: &str, new: &str) {
+        if let Some(values) = list {
+            for value in values.iter_mut().filter(|v| *v == old) {
+                *value = new.to_string();
+            }
+        }
+    }
+
+    /// Moves the `MimeType` associations into a `mimeapps.list` override.
+    ///
+    /// Returns the content of a `mimeapps.list` file adding the associations
+    /// for `desktop_id` under `[Added Associations]`, one MIME type per line
+    /// in sorted order, or `None` if the entry declares no MIME types. The
+    /// entry itself no longer carries the long `MimeType` line, which keeps
+    /// it reviewable; install the returned file alongside it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Viewer\nExec=viewer %f\nMimeType=image/png;image/gif;\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let overrides = entry.split_mime_associations("viewer.desktop").unwrap();
+    /// assert_eq!(
+    ///     overrides,
+    ///     "[Added Associations]\nimage/gif=viewer.desktop;\nimage/png=viewer.desktop;\n"
+    /// );
+    /// assert!(entry.mime_type.is_none());
+    /// ```
+    pub fn split_mime_associations(&mut self, desktop_id: &str) -> Option<String> {
+        let mut mime_types = self.mime_type.take()?;
+        mime_types.sort();
+        mime_types.dedup();
+
+        let mut output = String::from("[Added Associations]\n");
+        for mime_type in &mime_types {
+            output.push_str(&format!("{}={};\n", mime_type, desktop_id));
+        }
+        Some(output)
+    }
+
+    /// Returns a copy with user-identifying values masked.
+    ///
+    /// Home directories (`$HOME`, `/home/<user>`, `/var/home/<user>`,
+    /// `/Users/<user>`) become `/home/user`, the user names found in them and
+    /// in `$USER`/`$LOGNAME` become `user`, and email addresses become
+    /// `user@example.com`. This covers every string value, additional group,
+    /// unknown key, and comment; the keys, groups, and types are unchanged so
+    /// the copy can be attached to bug reports. The source path is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::DesktopEntry;
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "# Maintained by jdoe@corp.example\n[Desktop Entry]\nType=Application\nName=Tool\nExec=/home/jdoe/bin/tool\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let redacted = entry.redacted();
+    /// assert_eq!(redacted.exec.as_deref(), Some("/home/user/bin/tool"));
+    /// assert_eq!(redacted.comments[0].content, " Maintained by user@example.com");
+    /// ```
+    pub fn redacted(&self) -> DesktopEntry {
+        let redactor = redact::Redactor::for_entry(self);
+        let r = |value: &mut String| *value = redactor.redact(value);
+        let mut entry = self.clone();
+        // Comments inside groups are not redacted, so drop the original text
+        entry.layout = None;
+
+        for string in [
+            Some(&mut entry.name),
+            entry.generic_name.as_mut(),
+            entry.comment.as_mut(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            r(&mut string.default);
+            string.localized.values_mut().for_each(r);
+        }
+        if let Some(icon) = &mut entry.icon {
+            r(&mut icon.default);
+            icon.localized.values_mut().for_each(r);
+        }
+        if let Some(keywords) = &mut entry.keywords {
+            keywords.default.iter_mut().for_each(r);
+            keywords.localized.values_mut().flatten().for_each(r);
+        }
+        for value in [
+            &mut entry.exec,
+            &mut entry.try_exec,
+            &mut entry.path,
+            &mut entry.url,
+            &mut entry.startup_wm_class,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            r(value);
+        }
+
+        let keys = entry
+            .unknown_keys
+            .values_mut()
+            .chain(
+                entry
+                    .additional_groups
+                    .values_mut()
+                    .flat_map(|group| group.entries.values_mut()),
+            )
+            .flatten();
+        for key in keys {
+            r(&mut key.value);
+        }
+        for comment in &mut entry.comments {
+            r(&mut comment.content);
+        }
+        entry.source_path = None;
+
+        entry
+    }
+
+    /// Validates that required fields are present for the entry type.
+    ///
+    /// Keys defined only for applications, such as `Exec` or `Categories`,
+    /// are rejected on links and directories, and `URL` is rejected on
+    /// anything but links. Every ID in `Actions` must have a
+    /// `[Desktop Action <id>]` group with a `Name`, and every action group
+    /// must be listed in `Actions`. Entries with `DBusActivatable=true`
+    /// read from a file must be named after a D-Bus well-known name, as in
+    /// `org.example.App.desktop`.
+    ///
+    /// This is the [`ValidationLevel::Spec`] level of
+    /// [`validate_with`](Self::validate_with), stopping at the first
+    /// violation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if validation fails.
+    pub fn validate(&self) -> Result<()> {
+        match self.spec_violations().into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Validates the entry at a level, reporting every problem found.
+    ///
+    /// - [`ValidationLevel::Spec`] reports the violations of the
+    ///   specification checked by [`validate`](Self::validate), and the
+    ///   [lint](lint::lint) findings of [`Severity::Error`].
+    /// - [`ValidationLevel::Warning`] adds the other lint findings, such as
+    ///   deprecated keys, lists missing their final `;`, or a `Comment`
+    ///   repeating the `Name`.
+    /// - [`ValidationLevel::Pedantic`] adds the findings of
+    ///   [`lint::check_completeness`], such as missing `Keywords` or
+    ///   translations, and icons [not found](lint::check_icons) in the
+    ///   default theme.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, Severity, ValidationLevel};
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Viewer\nComment=Viewer\nExec=viewer\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert!(entry.validate_with(ValidationLevel::Spec).is_empty());
+    /// let warnings = entry.validate_with(ValidationLevel::Warning);
+    /// assert_eq!(warnings[0].code, "comment-equals-name");
+    /// let pedantic = entry.validate_with(ValidationLevel::Pedantic);
+    /// assert!(pedantic.iter().any(|d| d.code == "keywords-missing"));
+    /// ```
+    pub fn validate_with(&self, level: ValidationLevel) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = self
+            .spec_violations()
+            .into_iter()
+            .map(|err| Diagnostic::new(Severity::Error, err.code(), err.to_string()))
+            .collect();
+        diagnostics.extend(lint::lint(self).into_iter().filter(|diagnostic| {
+            level >= ValidationLevel::Warning || diagnostic.severity == Severity::Error
+        }));
+        if level >= ValidationLevel::Pedantic {
+            diagnostics.extend(lint::check_completeness(self));
+            diagnostics.extend(lint::check_icons(self, &[] as &[&str]));
+        }
+        diagnostics
+    }
+
+    /// The violations of the specification checked by
+    /// [`validate`](Self::validate), in order.
+    fn spec_violations(&self) -> Vec<DesktopEntryError> {
+        let mut violations = Vec::new();
+
+        // URL is required for Link type
+        if self.entry_type == DesktopEntryType::Link && self.url.is_none() {
+            violations.push(DesktopEntryError::ValidationError(
+                "URL is required for Link type entries".to_string(),
+            ));
+        }
+
+        // Exec or DBusActivatable is required for Application type
+        if self.entry_type == DesktopEntryType::Application {
+            let has_exec = self.exec.is_some();
+            let is_dbus_activatable = self.dbus_activatable.unwrap_or(false);
+
+            if !has_exec && !is_dbus_activatable {
+                violations.push(DesktopEntryError::ValidationError(
+                    "Either Exec key or DBusActivatable=true is required for Application type"
+                        .to_string(),
+                ));
+            }
+        }
+
+        self.validate_type_keys(&mut violations);
+        self.validate_actions(&mut violations);
+        self.validate_bus_name(&mut violations);
+        violations
+    }
+
+    /// Checks that a D-Bus activatable entry is named after its well-known
+    /// bus name, when its file name is known.
+    ///
+    /// # Specification Reference
+    ///
+    /// Section 8: "D-Bus Activation"
+    fn validate_bus_name(&self, violations: &mut Vec<DesktopEntryError>) {
+        if self.dbus_activatable != Some(true) {
+            return;
+        }
+        let Some(file_name) = self
+            .source_path
+            .as_ref()
+            .and_then(|path| path.file_name()?.to_str())
+        else {
+            return;
+        };
+        let bus_name = file_name.strip_suffix(".desktop").unwrap_or(file_name);
+        if !launch::is_valid_bus_name(bus_name) {
+            violations.push(DesktopEntryError::ValidationError(format!(
+                "DBusActivatable requires the file to be named after a D-Bus well-known \
+                 name, such as org.example.App.desktop, not {}",
+                file_name
+            )));
+        }
+    }
+
+    /// Checks that the entry only sets the keys allowed for its type.
+    ///
+    /// # Specification Reference
+    ///
+    /// Section 6: "Recognized desktop entry keys"
+    fn validate_type_keys(&self, violations: &mut Vec<DesktopEntryError>) {
+        if self.entry_type != DesktopEntryType::Application {
+            for key in self.application_keys() {
+                violations.push(DesktopEntryError::ValidationError(format!(
+                    "{} is only valid for Application type entries, not {}",
+                    key,
+                    self.entry_type.as_str()
+                )));
+            }
+        }
+        if self.entry_type != DesktopEntryType::Link && self.url.is_some() {
+            violations.push(DesktopEntryError::ValidationError(format!(
+                "URL is only valid for Link type entries, not {}",
+                self.entry_type.as_str()
+            )));
+        }
+    }
+
+    /// The keys set on the entry that are only defined for applications.
+    pub(crate) fn application_keys(&self) -> Vec<&'static str> {
+        [
+            ("DBusActivatable", self.dbus_activatable.is_some()),
+            ("TryExec", self.try_exec.is_some()),
+            ("Exec", self.exec.is_some()),
+            ("Path", self.path.is_some()),
+            ("Terminal", self.terminal.is_some()),
+            ("Actions", self.actions.is_some()),
+            ("MimeType", self.mime_type.is_some()),
+            ("Categories", self.categories.is_some()),
+            ("Implements", self.implements.is_some()),
+            ("Keywords", self.keywords.is_some()),
+            ("StartupNotify", self.startup_notify.is_some()),
+            ("StartupWMClass", self.startup_wm_class.is_some()),
+            (
+                "PrefersNonDefaultGPU",
+                self.prefers_non_default_gpu.is_some(),
+            ),
+            ("SingleMainWindow", self.single_main_window.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(key, set)| set.then_some(key))
+        .collect()
+    }
+
+    /// Checks that `Actions` and the `[Desktop Action <id>]` groups agree and
+    /// that every action group has a `Name`.
+    ///
+    /// # Specification Reference
+    ///
+    /// Section 11: "Additional applications actions"
+    fn validate_actions(&self, violations: &mut Vec<DesktopEntryError>) {
+        let actions = self.actions.as_deref().unwrap_or_default();
+
+        for action in actions {
+            let group_name = format!("{}{}", ACTION_GROUP_PREFIX, action);
+            match self.additional_groups.get(&group_name) {
+                None => violations.push(DesktopEntryError::ValidationError(format!(
+                    "Action '{}' is listed in Actions but has no [{}] group",
+                    action, group_name
+                ))),
+                Some(group) if group.get("Name").is_none() => {
+                    violations.push(DesktopEntryError::ValidationError(format!(
+                        "[{}] is missing the required Name key",
+                        group_name
+                    )));
+                }
+                Some(_) => {}
+            }
+        }
+
+        let mut orphans: Vec<&str> = self
+            .additional_groups
+            .keys()
+            .filter_map(|name| name.strip_prefix(ACTION_GROUP_PREFIX))
+            .filter(|id| !actions.iter().any(|action| action == id))
+            .collect();
+        orphans.sort();
+        for orphan in orphans {
+            violations.push(DesktopEntryError::ValidationError(format!(
+                "[{}{}] is not listed in Actions",
+                ACTION_GROUP_PREFIX, orphan
+            )));
+        }
+    }
+}
+
+/// Applies the edits of a [`Fix`] to raw groups.
+fn apply_edits(groups: &mut RawGroups, edits: &[KeyEdit]) -> Result<()> {
+    for edit in edits {
+        let group_name = match edit {
+            KeyEdit::Set { group, .. }
+            | KeyEdit::Remove { group, .. }
+            | KeyEdit::Rename { group, .. } => group,
+        };
+        let group = groups
+            .get_mut(group_name)
+            .ok_or_else(|| DesktopEntryError::MissingGroup(group_name.clone()))?;
+
+        match edit {
+            KeyEdit::Set { key, value, .. } => {
+                let entries = group.entry(key.clone()).or_default();
+                entries.retain(|entry| entry.locale.is_some());
+                entries.insert(
+                    0,
+                    Entry {
+                        key: key.clone(),
+                        locale: None,
+                        value: value.clone(),
+                        span: None,
+                    },
+                );
+            }
+            KeyEdit::Remove { key, .. } => {
+                group
+                    .remove(key)
+                    .ok_or_else(|| DesktopEntryError::MissingRequiredKey(key.clone()))?;
+            }
+            KeyEdit::Rename { from, to, .. } => {
+                if group.contains_key(to) {
+                    return Err(DesktopEntryError::ValidationError(format!(
+                        "Cannot rename {} to existing key {}",
+                        from, to
+                    )));
+                }
+                let mut entries = group
+                    .remove(from)
+                    .ok_or_else(|| DesktopEntryError::MissingRequiredKey(from.clone()))?;
+                for entry in &mut entries {
+                    entry.key = to.clone();
+                }
+                group.insert(to.clone(), entries);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses an entry like [`DesktopEntry::parse`].
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::DesktopEntry;
+///
+/// let entry: DesktopEntry = "[Desktop Entry]\nType=Application\nName=App\nExec=app\n"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(entry.to_string(), entry.serialize());
+/// ```
+impl std::str::FromStr for DesktopEntry {
+    type Err = DesktopEntryError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+/// Formats the entry as a desktop file, like [`DesktopEntry::serialize`].
+impl fmt::Display for DesktopEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.serialize())
+    }
+}
+
+/// Whether a program exists: absolute paths are checked directly, other
+/// names are looked up in `$PATH`.
+pub(crate) fn is_installed(program: &str) -> bool {
+    let program = Path::new(program);
+    if program.is_absolute() {
+        return is_executable(program);
+    }
+    std::env::var_os("PATH").is_some_and(|path| {
+        std::env::split_paths(&path).any(|dir| is_executable(&dir.join(program)))
+    })
+}
+
+/// Whether `path` is a file the current user may execute.
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// Replaces the file at `path` with the output of `write`, through a synced
+/// temporary file in the same directory. An existing file's permissions are
+/// kept.
+pub(crate) fn write_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> io::Result<()> {
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not a file path: {}", path.display()),
+        )
+    })?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = dir.join(temp_name);
+
+    let result = (|| {
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        if let Ok(metadata) = std::fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        let mut writer = io::BufWriter::new(&mut file);
+        write(&mut writer)?;
+        writer.flush()?;
+        drop(writer);
+        file.sync_all()?;
+        std::fs::rename(&temp_path, path)
+    })();
+    if let Err(err) = result {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(err);
+    }
+
+    // Persist the rename itself; not all platforms can sync directories
+    if let Ok(dir) = std::fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Escaping
+// ============================================================================
+
+/// Decodes the escape sequences of a `string` or `localestring` value.
+///
+/// `\s`, `\n`, `\t`, `\r`, and `\\` become a space, newline, tab, carriage
+/// return, and backslash. Other sequences are kept as written, since they
+/// carry meaning at another level (such as `\;` in lists or the quoting of
+/// `Exec`).
+///
+/// # Specification Reference
+///
+/// Section 4: "Possible value types"
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::unescape_value;
+///
+/// assert_eq!(unescape_value(r"\sTwo\nlines"), " Two\nlines");
+/// assert_eq!(unescape_value(r"C:\\Games"), r"C:\Games");
+/// ```
+pub fn unescape_value(value: &str) -> String {
+    unescape(value, false)
+}
+
+/// Decodes the escape sequences of a value, and `\;` too in list items.
+fn unescape(value: &str, list_item: bool) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => output.push(' '),
+            Some('n') => output.push('\n'),
+            Some('t') => output.push('\t'),
+            Some('r') => output.push('\r'),
+            Some('\\') => output.push('\\'),
+            Some(';') if list_item => output.push(';'),
+            Some(other) => {
+                output.push('\\');
+                output.push(other);
+            }
+            None => output.push('\\'),
+        }
+    }
+    output
+}
+
+/// Encodes a value with the escape sequences decoded by [`unescape_value`].
+///
+/// A leading space is written as `\s` so it survives editors and parsers
+/// that trim around the `=`.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::{escape_value, unescape_value};
+///
+/// let value = " Two\nlines";
+/// assert_eq!(escape_value(value), r"\sTwo\nlines");
+/// assert_eq!(unescape_value(&escape_value(value)), value);
+/// ```
+pub fn escape_value(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            ' ' if i == 0 => output.push_str("\\s"),
+            '\n' => output.push_str("\\n"),
+            '\t' => output.push_str("\\t"),
+            '\r' => output.push_str("\\r"),
+            '\\' => output.push_str("\\\\"),
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+/// Splits a list value at its unescaped semicolons, decoding each item,
+/// including the semicolons escaped as `\;`.
+///
+/// Empty items, including the one after a trailing semicolon, are dropped.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::split_list;
+///
+/// assert_eq!(split_list("Audio;Video;"), ["Audio", "Video"]);
+/// assert_eq!(split_list(r"rock\;roll;jazz"), ["rock;roll", "jazz"]);
+/// ```
+pub fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ';' => {
+                items.push(&value[start..i]);
+                start = i + 1;
             }
+            _ => {}
+        }
+    }
+    items.push(&value[start..]);
 
-            if trimmed.starts_with('#') {
-                if current_group.is_none() {
-                    comments.push(Comment {
-                        line_number: line_num,
-                        content: trimmed[1..].to_string(),
-                        is_blank: false,
+    items
+        .into_iter()
+        .filter(|item| !item.is_empty())
+        .map(|item| unescape(item, true))
+        .collect()
+}
+
+/// Encodes a list item with the escape sequences decoded by
+/// [`split_list`].
+pub(crate) fn escape_list_item(item: &str) -> String {
+    escape_value(item).replace(';', "\\;")
+}
+
+/// Joins list items into a list value, ending with a semicolon as the
+/// specification recommends; the inverse of [`split_list`].
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::{join_list, split_list};
+///
+/// let items = ["rock;roll", "jazz"];
+/// assert_eq!(join_list(&items), r"rock\;roll;jazz;");
+/// assert_eq!(split_list(&join_list(&items)), items);
+/// ```
+pub fn join_list(items: &[impl AsRef<str>]) -> String {
+    items
+        .iter()
+        .map(|item| escape_list_item(item.as_ref()) + ";")
+        .collect()
+}
+
+// ============================================================================
+// Parser
+// ============================================================================
+
+/// Raw groups of a parsed file, keyed by group name and then by key.
+pub(crate) type RawGroups = HashMap<String, HashMap<String, Vec<Entry>>>;
+
+/// Options controlling how leniently desktop entry files are parsed.
+///
+/// The defaults match [`DesktopEntry::parse`]. [`ParserOptions::strict`]
+/// rejects anything the specification forbids, while
+/// [`ParserOptions::lenient`] loads as much as possible of the slightly
+/// malformed files found in the wild.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::{DesktopEntry, OrphanKeyPolicy, ParserOptions};
+///
+/// let options = ParserOptions {
+///     keys_before_group: OrphanKeyPolicy::AttachToDesktopEntry,
+///     ..ParserOptions::default()
+/// };
+///
+/// let (entry, diagnostics) = DesktopEntry::parse_with_diagnostics(
+///     "Type=Application\nName=Headerless\nExec=headerless\n",
+///     &options,
+/// )
+/// .unwrap();
+/// assert_eq!(entry.name.default, "Headerless");
+/// assert_eq!(diagnostics.len(), 3);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// What to do with keys appearing before the first group header
+    pub keys_before_group: OrphanKeyPolicy,
+    /// How to handle invalid values of known keys
+    pub strictness: Strictness,
+    /// Fail on keys in `[Desktop Entry]` that are neither defined by the
+    /// spec nor prefixed with `X-`
+    pub reject_unknown_keys: bool,
+    /// Warn about keys in `[Desktop Entry]` that are neither defined by the
+    /// spec, even as deprecated, nor prefixed with `X-`
+    pub warn_unknown_keys: bool,
+    /// Fail on a key appearing twice with the same locale in a group;
+    /// otherwise, such keys are reported as warnings
+    pub reject_duplicate_keys: bool,
+    /// Accept `0` and `1` as boolean values, as written by old generators,
+    /// with a warning; otherwise, they are handled as other invalid values
+    pub accept_legacy_booleans: bool,
+    /// Fail on values containing control characters, which must be written
+    /// as escape sequences such as `\t`
+    pub reject_control_characters: bool,
+    /// Read a `[KDE Desktop Entry]` group, as written by ancient KDE
+    /// versions, as `[Desktop Entry]`, with a warning; it is written back
+    /// as `[Desktop Entry]`
+    pub accept_kde_desktop_entry_group: bool,
+    /// Normalize localized values to NFC after parsing, as
+    /// `DesktopEntry::normalize_unicode` does; ignored without the `unicode`
+    /// feature
+    pub normalize_unicode: bool,
+    /// Do not keep the original text of parsed entries in
+    /// [`DesktopEntry::layout`], which holds a copy of every file; worth
+    /// setting when parsing many files that are not written back
+    pub discard_layout: bool,
+}
+
+impl ParserOptions {
+    /// Options rejecting everything the specification forbids.
+    pub fn strict() -> Self {
+        Self {
+            keys_before_group: OrphanKeyPolicy::Reject,
+            strictness: Strictness::Strict,
+            reject_unknown_keys: true,
+            warn_unknown_keys: true,
+            reject_duplicate_keys: true,
+            accept_legacy_booleans: false,
+            reject_control_characters: true,
+            accept_kde_desktop_entry_group: false,
+            normalize_unicode: false,
+            discard_layout: false,
+        }
+    }
+
+    /// Options loading as much as possible of malformed files.
+    pub fn lenient() -> Self {
+        Self {
+            keys_before_group: OrphanKeyPolicy::AttachToDesktopEntry,
+            strictness: Strictness::Lenient,
+            reject_unknown_keys: false,
+            warn_unknown_keys: true,
+            reject_duplicate_keys: false,
+            accept_legacy_booleans: true,
+            reject_control_characters: false,
+            accept_kde_desktop_entry_group: true,
+            normalize_unicode: false,
+            discard_layout: false,
+        }
+    }
+}
+
+/// Handling of invalid values of known keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strictness {
+    /// Ignore the key, as if it were absent
+    #[default]
+    Lenient,
+    /// Fail with [`DesktopEntryError::InvalidValue`]
+    Strict,
+}
+
+/// Options controlling how [`DesktopEntry::serialize_with`] writes lists.
+///
+/// The format has no line continuations, so long `MimeType` or `Keywords`
+/// values always end up on a single line. Sorting their items and
+/// annotating them with counts keeps diffs of generated entries reviewable.
+/// `Actions` keeps its order since it defines the order of menu items.
+///
+/// [`SerializeOptions::canonical`] makes the output depend only on the
+/// entry's values, so generated files are reproducible.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// Sort the items of list values
+    pub sort_lists: bool,
+    /// Write a `# Key: N items` comment before list values with at least
+    /// this many items
+    pub annotate_lists_from: Option<usize>,
+    /// Ignore the original layout, sort localized variants by locale and
+    /// unknown keys and additional groups by name, and end every list value
+    /// with a semicolon
+    pub canonical: bool,
+    /// Raise a declared `Version` to the
+    /// [`required_spec_version`](DesktopEntry::required_spec_version) of the
+    /// entry, when it uses keys newer than it declares
+    pub bump_version: bool,
+    /// Normalize localized values to NFC before writing them, as
+    /// `DesktopEntry::normalize_unicode` does; ignored without the `unicode`
+    /// feature
+    pub normalize_unicode: bool,
+}
+
+impl SerializeOptions {
+    /// Options producing deterministic, spec-canonical output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, SerializeOptions};
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nName[fr]=Appli\nName=App\nName[de]=Anw\nType=Application\nCategories=Utility\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     entry.serialize_with(&SerializeOptions::canonical()),
+    ///     "[Desktop Entry]\nType=Application\nName=App\nName[de]=Anw\nName[fr]=Appli\nCategories=Utility;\n",
+    /// );
+    /// ```
+    pub fn canonical() -> Self {
+        Self {
+            canonical: true,
+            ..Self::default()
+        }
+    }
+
+    /// Orders the localized variants of a value.
+    fn localized<'a, V>(&self, values: &'a HashMap<Locale, V>) -> Vec<(&'a Locale, &'a V)> {
+        let mut values: Vec<_> = values.iter().collect();
+        if self.canonical {
+            values.sort_by_cached_key(|(locale, _)| locale.to_string_repr());
+        }
+        values
+    }
+
+    /// Orders raw keys and their locale variants.
+    fn entries<'a>(&self, keys: &'a HashMap<String, Vec<Entry>>) -> Vec<&'a Entry> {
+        let mut keys: Vec<_> = keys.iter().collect();
+        if self.canonical {
+            keys.sort_by_key(|(key, _)| *key);
+        }
+        keys.into_iter()
+            .flat_map(|(_, entries)| {
+                let mut entries: Vec<&Entry> = entries.iter().collect();
+                if self.canonical {
+                    entries.sort_by_cached_key(|entry| {
+                        entry.locale.as_ref().map(Locale::to_string_repr)
                     });
                 }
-                continue;
-            }
+                entries
+            })
+            .collect()
+    }
 
-            // Group header
-            if trimmed.starts_with('[') {
-                if !trimmed.ends_with(']') {
-                    return Err(DesktopEntryError::InvalidGroupHeader(
-                        line_num,
-                        line.clone(),
-                    ));
-                }
+    fn write_entry<W: Write>(writer: &mut W, entry: &Entry) -> io::Result<()> {
+        if let Some(locale) = &entry.locale {
+            writeln!(
+                writer,
+                "{}[{}]={}",
+                entry.key,
+                locale.to_string_repr(),
+                entry.value
+            )
+        } else {
+            writeln!(writer, "{}={}", entry.key, entry.value)
+        }
+    }
 
-                let group_name = trimmed[1..trimmed.len() - 1].to_string();
+    fn write_list<W: Write>(&self, writer: &mut W, key: &str, values: &[String]) -> io::Result<()> {
+        if self
+            .annotate_lists_from
+            .is_some_and(|min| values.len() >= min)
+        {
+            writeln!(writer, "# {}: {} items", key, values.len())?;
+        }
+        let mut values: Vec<String> = values.iter().map(|v| escape_list_item(v)).collect();
+        if self.sort_lists {
+            values.sort();
+        }
+        writeln!(writer, "{}={}{}", key, values.join(";"), self.list_end())
+    }
 
-                // Check for duplicate groups
-                if groups.contains_key(&group_name) {
-                    return Err(DesktopEntryError::DuplicateGroup(group_name));
-                }
+    fn list_end(&self) -> &'static str {
+        if self.canonical { ";" } else { "" }
+    }
+}
 
-                groups.insert(group_name.clone(), HashMap::new());
-                current_group = Some(group_name);
-                continue;
-            }
+/// Handling of keys appearing before the first group header.
+///
+/// The spec requires every entry to belong to a group, but broken generators
+/// that omit the `[Desktop Entry]` header are common in the wild.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OrphanKeyPolicy {
+    /// Fail with [`DesktopEntryError::InvalidLine`]
+    #[default]
+    Reject,
+    /// Add the keys to an implicit `[Desktop Entry]` group, reporting a
+    /// warning for each; an explicit `[Desktop Entry]` header later in the
+    /// file continues that group
+    AttachToDesktopEntry,
+    /// Ignore the keys, reporting a warning for each
+    Skip,
+}
 
-            // Key-value pair
-            if let Some(eq_pos) = line.find('=') {
-                let key_part = &line[..eq_pos];
-                let value = &line[eq_pos + 1..];
-
-                // Parse key and locale
-                let (key, locale) = if let Some(bracket_start) = key_part.find('[') {
-                    if let Some(bracket_end) = key_part.find(']') {
-                        let key = key_part[..bracket_start].trim().to_string();
-                        let locale_str = &key_part[bracket_start + 1..bracket_end];
-                        (key, Some(Locale::from_string(locale_str)))
-                    } else {
-                        return Err(DesktopEntryError::InvalidLine(line_num, line.clone()));
-                    }
-                } else {
-                    (key_part.trim().to_string(), None)
-                };
+pub(crate) struct Parser<'a> {
+    content: &'a str,
+    options: ParserOptions,
+    diagnostics: Vec<Diagnostic>,
+    /// Report format errors as diagnostics and skip the offending lines
+    /// instead of failing
+    collect_errors: bool,
+    /// Location of the header of each group
+    header_spans: HashMap<String, Span>,
+    /// Line number of the first line of the content, when parsing part of
+    /// a file
+    first_line: usize,
+}
 
-                // Validate key name (spec: only A-Za-z0-9-)
-                if !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
-                    return Err(DesktopEntryError::InvalidKeyName(line_num, key.clone()));
-                }
+impl<'a> Parser<'a> {
+    pub(crate) fn new(content: &'a str) -> Self {
+        Self::with_options(content, ParserOptions::default())
+    }
 
-                // Add to current group
-                if let Some(group_name) = &current_group {
-                    let group = groups.get_mut(group_name).unwrap();
-                    let entry = Entry {
-                        key: key.clone(),
-                        locale,
-                        value: value.to_string(),
-                    };
-                    group.entry(key).or_insert_with(Vec::new).push(entry);
-                } else {
-                    return Err(DesktopEntryError::InvalidLine(line_num, line.clone()));
-                }
-            } else {
-                return Err(DesktopEntryError::InvalidLine(line_num, line.clone()));
+    fn with_options(content: &'a str, options: ParserOptions) -> Self {
+        Self {
+            content,
+            options,
+            diagnostics: Vec::new(),
+            collect_errors: false,
+            header_spans: HashMap::new(),
+            first_line: 1,
+        }
+    }
+
+    /// Fails with `err`, or records it as a diagnostic when collecting errors.
+    fn recover(
+        diagnostics: &mut Vec<Diagnostic>,
+        collect_errors: bool,
+        err: DesktopEntryError,
+        span: Span,
+    ) -> Result<()> {
+        if !collect_errors {
+            return Err(err);
+        }
+        diagnostics
+            .push(Diagnostic::new(Severity::Error, err.code(), err.to_string()).with_span(span));
+        Ok(())
+    }
+
+    /// Records the error of a failed parse, or else of validating the entry,
+    /// when collecting errors.
+    fn finish_collecting(&mut self, entry: Result<DesktopEntry>) -> Option<DesktopEntry> {
+        let entry = match entry {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                self.diagnostics.push(Diagnostic::new(
+                    Severity::Error,
+                    err.code(),
+                    err.to_string(),
+                ));
+                None
             }
+        };
+        if let Some(Err(err)) = entry.as_ref().map(DesktopEntry::validate) {
+            self.diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                err.code(),
+                err.to_string(),
+            ));
+        }
+        entry
+    }
+
+    fn parse(&mut self) -> Result<DesktopEntry> {
+        let (groups, comments) = self.parse_groups()?;
+        let mut entry = self.build_entry(groups, comments)?;
+        if !self.options.discard_layout {
+            entry.layout = Some(layout::Layout::new(self.content, &self.options));
         }
+        Ok(entry)
+    }
 
+    /// Builds a desktop entry from raw groups, the key-level half of parsing.
+    pub(crate) fn build_entry(
+        &mut self,
+        mut groups: RawGroups,
+        comments: Vec<Comment>,
+    ) -> Result<DesktopEntry> {
         // Must have Desktop Entry group
         let desktop_entry_data = groups
-            .remove("Desktop Entry")
+            .remove(DESKTOP_ENTRY_GROUP)
             .ok_or(DesktopEntryError::MissingDesktopEntryGroup)?;
 
         // Parse Type (required)
@@ -1170,9 +3723,7 @@ impl Parser {
             .and_then(|v| v.first())
             .ok_or_else(|| DesktopEntryError::MissingRequiredKey("Type".to_string()))?;
 
-        let entry_type = DesktopEntryType::from_str(&type_entries.value).ok_or_else(|| {
-            DesktopEntryError::InvalidValue("Type".to_string(), type_entries.value.clone())
-        })?;
+        let entry_type: DesktopEntryType = type_entries.value.parse()?;
 
         // Parse Name (required)
         let name_entries = desktop_entry_data
@@ -1182,9 +3733,10 @@ impl Parser {
         let mut name = LocalizedString::new("");
         for entry in name_entries {
             if let Some(locale) = &entry.locale {
-                name.localized.insert(locale.clone(), entry.value.clone());
+                name.localized
+                    .insert(locale.clone(), unescape_value(&entry.value));
             } else {
-                name.default = entry.value.clone();
+                name.default = unescape_value(&entry.value);
             }
         }
 
@@ -1199,18 +3751,18 @@ impl Parser {
             "GenericName",
             &mut desktop_entry.generic_name,
         );
-        Self::parse_optional_bool(
+        self.parse_bool(
             &desktop_entry_data,
             "NoDisplay",
             &mut desktop_entry.no_display,
-        );
+        )?;
         Self::parse_optional_localized_string(
             &desktop_entry_data,
             "Comment",
             &mut desktop_entry.comment,
         );
         Self::parse_optional_icon_string(&desktop_entry_data, "Icon", &mut desktop_entry.icon);
-        Self::parse_optional_bool(&desktop_entry_data, "Hidden", &mut desktop_entry.hidden);
+        self.parse_bool(&desktop_entry_data, "Hidden", &mut desktop_entry.hidden)?;
         Self::parse_optional_string_list(
             &desktop_entry_data,
             "OnlyShowIn",
@@ -1221,15 +3773,15 @@ impl Parser {
             "NotShowIn",
             &mut desktop_entry.not_show_in,
         );
-        Self::parse_optional_bool(
+        self.parse_bool(
             &desktop_entry_data,
             "DBusActivatable",
             &mut desktop_entry.dbus_activatable,
-        );
+        )?;
         Self::parse_optional_string(&desktop_entry_data, "TryExec", &mut desktop_entry.try_exec);
         Self::parse_optional_string(&desktop_entry_data, "Exec", &mut desktop_entry.exec);
         Self::parse_optional_string(&desktop_entry_data, "Path", &mut desktop_entry.path);
-        Self::parse_optional_bool(&desktop_entry_data, "Terminal", &mut desktop_entry.terminal);
+        self.parse_bool(&desktop_entry_data, "Terminal", &mut desktop_entry.terminal)?;
         Self::parse_optional_string_list(
             &desktop_entry_data,
             "Actions",
@@ -1255,59 +3807,35 @@ impl Parser {
             "Keywords",
             &mut desktop_entry.keywords,
         );
-        Self::parse_optional_bool(
+        self.parse_bool(
             &desktop_entry_data,
             "StartupNotify",
             &mut desktop_entry.startup_notify,
-        );
+        )?;
         Self::parse_optional_string(
             &desktop_entry_data,
             "StartupWMClass",
             &mut desktop_entry.startup_wm_class,
         );
         Self::parse_optional_string(&desktop_entry_data, "URL", &mut desktop_entry.url);
-        Self::parse_optional_bool(
+        self.parse_bool(
             &desktop_entry_data,
             "PrefersNonDefaultGPU",
             &mut desktop_entry.prefers_non_default_gpu,
-        );
-        Self::parse_optional_bool(
+        )?;
+        self.parse_bool(
             &desktop_entry_data,
             "SingleMainWindow",
             &mut desktop_entry.single_main_window,
-        );
+        )?;
 
         // Store unknown keys
-        let known_keys = [
-            "Type",
-            "Name",
-            "Version",
-            "GenericName",
-            "NoDisplay",
-            "Comment",
-            "Icon",
-            "Hidden",
-            "OnlyShowIn",
-            "NotShowIn",
-            "DBusActivatable",
-            "TryExec",
-            "Exec",
-            "Path",
-            "Terminal",
-            "Actions",
-            "MimeType",
-            "Categories",
-            "Implements",
-            "Keywords",
-            "StartupNotify",
-            "StartupWMClass",
-            "URL",
-            "PrefersNonDefaultGPU",
-            "SingleMainWindow",
-        ];
 
         for (key, entries) in desktop_entry_data {
-            if !known_keys.contains(&key.as_str()) {
+            if !KNOWN_KEYS.contains(&key.as_str()) {
+                if self.options.reject_unknown_keys && !key.starts_with("X-") {
+                    return Err(DesktopEntryError::UnknownKey(key));
+                }
                 desktop_entry.unknown_keys.insert(key, entries);
             }
         }
@@ -1317,23 +3845,377 @@ impl Parser {
             let group = Group {
                 name: group_name.clone(),
                 entries: group_data,
+                header_span: self.header_spans.get(&group_name).copied(),
             };
             desktop_entry.additional_groups.insert(group_name, group);
         }
 
+        #[cfg(feature = "unicode")]
+        if self.options.normalize_unicode {
+            desktop_entry.normalize_unicode();
+        }
+
         Ok(desktop_entry)
     }
 
+    /// Splits the file into groups of entries, collecting leading comments.
+    ///
+    /// This is the format-level half of parsing, shared with other files that
+    /// use the desktop entry syntax (e.g., search provider files).
+    pub(crate) fn parse_groups(&mut self) -> Result<(RawGroups, Vec<Comment>)> {
+        let mut groups: RawGroups = HashMap::new();
+        let mut current_group: Option<String> = None;
+        let mut implicit_desktop_entry = false;
+        let mut comments = Vec::new();
+        let mut line_num = self.first_line - 1;
+        // Set after an erroneous group header while collecting errors, so the
+        // keys of that group are skipped rather than reported one by one
+        let mut skipping_group = false;
+        // Line of the first value of each key, to report duplicates
+        let mut first_lines: HashMap<layout::ValueId, usize> = HashMap::new();
+
+        // Parse all lines
+        for line in self.content.lines() {
+            line_num += 1;
+            let trimmed = line.trim();
+
+            // Skip blank lines and comments before first group
+            if trimmed.is_empty() {
+                if current_group.is_none() {
+                    comments.push(Comment {
+                        line_number: line_num,
+                        content: String::new(),
+                        is_blank: true,
+                    });
+                }
+                continue;
+            }
+
+            if let Some(comment) = trimmed.strip_prefix('#') {
+                if current_group.is_none() {
+                    comments.push(Comment {
+                        line_number: line_num,
+                        content: comment.to_string(),
+                        is_blank: false,
+                    });
+                }
+                continue;
+            }
+
+            // Group header
+            if trimmed.starts_with('[') {
+                if !trimmed.ends_with(']') {
+                    Self::recover(
+                        &mut self.diagnostics,
+                        self.collect_errors,
+                        DesktopEntryError::InvalidGroupHeader(line_num, line.to_string()),
+                        Span::line(line_num, line),
+                    )?;
+                    skipping_group = true;
+                    continue;
+                }
+
+                let mut group_name = trimmed[1..trimmed.len() - 1].to_string();
+                let header_span = Span {
+                    line: line_num,
+                    column: line.len() - line.trim_start().len() + 1,
+                    len: trimmed.len(),
+                };
+                if self.options.accept_kde_desktop_entry_group
+                    && group_name == KDE_DESKTOP_ENTRY_GROUP
+                {
+                    self.diagnostics.push(
+                        Diagnostic::new(
+                            Severity::Warning,
+                            "kde-desktop-entry-group",
+                            format!(
+                                "[{}] is deprecated; read as [{}]",
+                                KDE_DESKTOP_ENTRY_GROUP, DESKTOP_ENTRY_GROUP
+                            ),
+                        )
+                        .with_span(Span::line(line_num, line)),
+                    );
+                    group_name = DESKTOP_ENTRY_GROUP.to_string();
+                }
+
+                // An explicit header continues an implicit [Desktop Entry] group
+                if implicit_desktop_entry && group_name == DESKTOP_ENTRY_GROUP {
+                    implicit_desktop_entry = false;
+                    skipping_group = false;
+                    self.header_spans.insert(group_name.clone(), header_span);
+                    current_group = Some(group_name);
+                    continue;
+                }
+
+                // Check for duplicate groups
+                if groups.contains_key(&group_name) {
+                    Self::recover(
+                        &mut self.diagnostics,
+                        self.collect_errors,
+                        DesktopEntryError::DuplicateGroup(group_name),
+                        Span::line(line_num, line),
+                    )?;
+                    skipping_group = true;
+                    continue;
+                }
+
+                groups.insert(group_name.clone(), HashMap::new());
+                self.header_spans.insert(group_name.clone(), header_span);
+                skipping_group = false;
+                current_group = Some(group_name);
+                continue;
+            }
+
+            if skipping_group {
+                continue;
+            }
+
+            // Key-value pair before any group header
+            let is_orphan = current_group.is_none()
+                || (implicit_desktop_entry
+                    && current_group.as_deref() == Some(DESKTOP_ENTRY_GROUP));
+            if is_orphan && line.contains('=') {
+                let action = match self.options.keys_before_group {
+                    OrphanKeyPolicy::Reject => None,
+                    OrphanKeyPolicy::AttachToDesktopEntry => {
+                        if current_group.is_none() {
+                            groups.insert(DESKTOP_ENTRY_GROUP.to_string(), HashMap::new());
+                            current_group = Some(DESKTOP_ENTRY_GROUP.to_string());
+                            implicit_desktop_entry = true;
+                        }
+                        Some(format!("added to [{}]", DESKTOP_ENTRY_GROUP))
+                    }
+                    OrphanKeyPolicy::Skip => Some("skipped".to_string()),
+                };
+                if let Some(action) = action {
+                    self.diagnostics.push(
+                        Diagnostic::new(
+                            Severity::Warning,
+                            "key-before-group",
+                            format!(
+                                "Line {} appears before any group header; {}",
+                                line_num, action
+                            ),
+                        )
+                        .with_span(Span::line(line_num, line)),
+                    );
+                }
+                if self.options.keys_before_group == OrphanKeyPolicy::Skip {
+                    continue;
+                }
+            }
+
+            // Key-value pair
+            let invalid_line = || DesktopEntryError::InvalidLine(line_num, line.to_string());
+            let Some(eq_pos) = line.find('=') else {
+                Self::recover(
+                    &mut self.diagnostics,
+                    self.collect_errors,
+                    invalid_line(),
+                    Span::line(line_num, line),
+                )?;
+                continue;
+            };
+            let key_part = &line[..eq_pos];
+            let value = &line[eq_pos + 1..];
+            let key_span = Span {
+                line: line_num,
+                column: key_part.len() - key_part.trim_start().len() + 1,
+                len: key_part.trim().len(),
+            };
+
+            // Parse key and locale
+            let mut locale_span = None;
+            let (key, locale) = if let Some(bracket_start) = key_part.find('[') {
+                if let Some(bracket_end) = key_part.find(']').filter(|&end| end > bracket_start) {
+                    let key = key_part[..bracket_start].trim().to_string();
+                    let locale_str = &key_part[bracket_start + 1..bracket_end];
+                    locale_span = Some(Span {
+                        line: line_num,
+                        column: bracket_start + 1,
+                        len: bracket_end - bracket_start + 1,
+                    });
+                    (key, Some(Locale::from_string(locale_str)))
+                } else {
+                    Self::recover(
+                        &mut self.diagnostics,
+                        self.collect_errors,
+                        invalid_line(),
+                        Span::line(line_num, line),
+                    )?;
+                    continue;
+                }
+            } else {
+                (key_part.trim().to_string(), None)
+            };
+
+            // Validate key name (spec: only A-Za-z0-9-)
+            if !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                Self::recover(
+                    &mut self.diagnostics,
+                    self.collect_errors,
+                    DesktopEntryError::InvalidKeyName(line_num, key),
+                    key_span,
+                )?;
+                continue;
+            }
+
+            // Add to current group
+            let Some(group_name) = &current_group else {
+                Self::recover(
+                    &mut self.diagnostics,
+                    self.collect_errors,
+                    invalid_line(),
+                    Span::line(line_num, line),
+                )?;
+                continue;
+            };
+            if self.options.warn_unknown_keys
+                && !self.options.reject_unknown_keys
+                && locale.is_none()
+                && group_name == DESKTOP_ENTRY_GROUP
+                && !KNOWN_KEYS.contains(&key.as_str())
+                && !lint::DEPRECATED_KEYS.contains(&key.as_str())
+                && !key.starts_with("X-")
+            {
+                self.diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Warning,
+                        "unknown-key",
+                        format!(
+                            "{} is not defined by the specification; extensions must be \
+                             prefixed with X-",
+                            key
+                        ),
+                    )
+                    .with_span(key_span),
+                );
+            }
+            if self.options.reject_control_characters
+                && let Some(c) = value.chars().find(|c| c.is_control())
+            {
+                Self::recover(
+                    &mut self.diagnostics,
+                    self.collect_errors,
+                    DesktopEntryError::InvalidValue(
+                        key_part.trim().to_string(),
+                        format!("contains the control character U+{:04X}", u32::from(c)),
+                    ),
+                    Span {
+                        line: line_num,
+                        column: eq_pos + 2,
+                        len: value.len(),
+                    },
+                )?;
+                continue;
+            }
+            let id = (group_name.clone(), key.clone(), locale.clone());
+            if let Some(first_line) = first_lines.get(&id) {
+                if self.options.reject_duplicate_keys {
+                    Self::recover(
+                        &mut self.diagnostics,
+                        self.collect_errors,
+                        DesktopEntryError::DuplicateKey(line_num, key),
+                        key_span,
+                    )?;
+                    continue;
+                }
+                self.diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Warning,
+                        "duplicate-key",
+                        format!(
+                            "{} is set again; it was first set at line {}",
+                            key_part.trim(),
+                            first_line
+                        ),
+                    )
+                    .with_span(key_span),
+                );
+            } else {
+                first_lines.insert(id, line_num);
+            }
+            let group = groups.get_mut(group_name).unwrap();
+            let span = EntrySpan {
+                line: Span::line(line_num, line),
+                key: Span {
+                    len: key.len(),
+                    ..key_span
+                },
+                locale: locale_span,
+                value: Span {
+                    line: line_num,
+                    column: eq_pos + 2,
+                    len: value.len(),
+                },
+            };
+            let entry = Entry {
+                key: key.clone(),
+                locale,
+                value: value.to_string(),
+                span: Some(span),
+            };
+            group.entry(key).or_default().push(entry);
+        }
+
+        Ok((groups, comments))
+    }
+
     fn parse_optional_string(
         data: &HashMap<String, Vec<Entry>>,
         key: &str,
         target: &mut Option<String>,
     ) {
-        if let Some(entries) = data.get(key) {
-            if let Some(entry) = entries.first() {
-                *target = Some(entry.value.clone());
+        if let Some(entries) = data.get(key)
+            && let Some(entry) = entries.first()
+        {
+            *target = Some(unescape_value(&entry.value));
+        }
+    }
+
+    /// Parses a boolean key according to the options, reporting legacy
+    /// `0` and `1` values when accepting them.
+    fn parse_bool(
+        &mut self,
+        data: &HashMap<String, Vec<Entry>>,
+        key: &str,
+        target: &mut Option<bool>,
+    ) -> Result<()> {
+        Self::parse_optional_bool(data, key, target);
+        if target.is_some() {
+            return Ok(());
+        }
+        if let Some(entries) = data.get(key)
+            && let Some(entry) = entries.first()
+        {
+            match entry.value.as_str() {
+                value @ ("0" | "1") if self.options.accept_legacy_booleans => {
+                    *target = Some(value == "1");
+                    self.diagnostics.push(Diagnostic::new(
+                        Severity::Warning,
+                        "legacy-boolean",
+                        format!(
+                            "{}={} is deprecated; use {}",
+                            key,
+                            value,
+                            if value == "1" { "true" } else { "false" }
+                        ),
+                    ));
+                }
+                value if self.options.strictness == Strictness::Strict => {
+                    return Err(DesktopEntryError::InvalidValue(
+                        key.to_string(),
+                        format!("'{}' is not a boolean", value),
+                    ));
+                }
+                value => self.diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    "invalid-boolean",
+                    format!("{}={} is not a boolean and was ignored", key, value),
+                )),
             }
         }
+        Ok(())
     }
 
     fn parse_optional_bool(
@@ -1341,14 +4223,14 @@ impl Parser {
         key: &str,
         target: &mut Option<bool>,
     ) {
-        if let Some(entries) = data.get(key) {
-            if let Some(entry) = entries.first() {
-                *target = match entry.value.as_str() {
-                    "true" => Some(true),
-                    "false" => Some(false),
-                    _ => None,
-                };
-            }
+        if let Some(entries) = data.get(key)
+            && let Some(entry) = entries.first()
+        {
+            *target = match entry.value.as_str() {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            };
         }
     }
 
@@ -1357,17 +4239,12 @@ impl Parser {
         key: &str,
         target: &mut Option<Vec<String>>,
     ) {
-        if let Some(entries) = data.get(key) {
-            if let Some(entry) = entries.first() {
-                let list: Vec<String> = entry
-                    .value
-                    .split(';')
-                    .filter(|s| !s.is_empty())
-                    .map(|s| s.to_string())
-                    .collect();
-                if !list.is_empty() {
-                    *target = Some(list);
-                }
+        if let Some(entries) = data.get(key)
+            && let Some(entry) = entries.first()
+        {
+            let list = split_list(&entry.value);
+            if !list.is_empty() {
+                *target = Some(list);
             }
         }
     }
@@ -1383,9 +4260,9 @@ impl Parser {
                 if let Some(locale) = &entry.locale {
                     localized
                         .localized
-                        .insert(locale.clone(), entry.value.clone());
+                        .insert(locale.clone(), unescape_value(&entry.value));
                 } else {
-                    localized.default = entry.value.clone();
+                    localized.default = unescape_value(&entry.value);
                 }
             }
             *target = Some(localized);
@@ -1401,9 +4278,10 @@ impl Parser {
             let mut icon = IconString::new("");
             for entry in entries {
                 if let Some(locale) = &entry.locale {
-                    icon.localized.insert(locale.clone(), entry.value.clone());
+                    icon.localized
+                        .insert(locale.clone(), unescape_value(&entry.value));
                 } else {
-                    icon.default = entry.value.clone();
+                    icon.default = unescape_value(&entry.value);
                 }
             }
             *target = Some(icon);
@@ -1418,12 +4296,7 @@ impl Parser {
         if let Some(entries) = data.get(key) {
             let mut list = LocalizedStringList::new(Vec::new());
             for entry in entries {
-                let values: Vec<String> = entry
-                    .value
-                    .split(';')
-                    .filter(|s| !s.is_empty())
-                    .map(|s| s.to_string())
-                    .collect();
+                let values = split_list(&entry.value);
 
                 if let Some(locale) = &entry.locale {
                     list.localized.insert(locale.clone(), values);
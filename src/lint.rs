@@ -0,0 +1,1220 @@
+//! Lint rules for desktop entries.
+//!
+//! Lints go beyond [`DesktopEntry::validate`]: they flag entries that are
+//! valid but likely wrong. Diagnostics may carry an automatic [`Fix`], which
+//! [`fix`] applies.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use crate::categories::{Category, CategoryKind};
+use crate::discovery;
+use crate::environment::DesktopEnvironment;
+use crate::{
+    ACTION_GROUP_PREFIX, DESKTOP_ENTRY_GROUP, DesktopEntry, Diagnostic, Fix, KeyEdit, Locale,
+    Result, Severity, Span, SpecVersion, exec,
+};
+
+/// Key suggested for holding the application's own version.
+pub const APP_VERSION_KEY: &str = "X-AppVersion";
+
+/// Icon theme every other theme falls back to.
+pub const FALLBACK_ICON_THEME: &str = "hicolor";
+
+/// A rule run by [`lint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    /// The [`Diagnostic::code`] of the rule's findings
+    pub code: &'static str,
+    /// Severity of the findings unless overridden by a [`LintConfig`]
+    pub severity: Severity,
+    /// What the rule checks
+    pub description: &'static str,
+}
+
+/// The rules run by [`lint`], in the order they run.
+pub const RULES: &[Rule] = &[
+    Rule {
+        code: "version-is-app-version",
+        severity: Severity::Warning,
+        description: "Version holds the application's version instead of the spec version",
+    },
+    Rule {
+        code: "version-unknown",
+        severity: Severity::Warning,
+        description: "Version is not a known specification version",
+    },
+    Rule {
+        code: "key-newer-than-version",
+        severity: Severity::Warning,
+        description: "A key added in a later version of the specification than Version declares",
+    },
+    Rule {
+        code: "deprecated-key",
+        severity: Severity::Warning,
+        description: "A key deprecated by the specification, such as Encoding or MiniIcon",
+    },
+    Rule {
+        code: "unknown-key",
+        severity: Severity::Warning,
+        description: "A key neither defined by the specification nor prefixed with X-",
+    },
+    Rule {
+        code: "generic-name-equals-name",
+        severity: Severity::Warning,
+        description: "GenericName repeats Name",
+    },
+    Rule {
+        code: "comment-equals-name",
+        severity: Severity::Warning,
+        description: "Comment repeats Name",
+    },
+    Rule {
+        code: "category-unknown",
+        severity: Severity::Warning,
+        description: "A category neither registered nor prefixed with X-",
+    },
+    Rule {
+        code: "category-missing-main",
+        severity: Severity::Warning,
+        description: "An additional category listed without one of its main categories",
+    },
+    Rule {
+        code: "category-reserved-without-only-show-in",
+        severity: Severity::Error,
+        description: "A reserved category used without OnlyShowIn",
+    },
+    Rule {
+        code: "desktop-environment-unknown",
+        severity: Severity::Warning,
+        description: "OnlyShowIn or NotShowIn names an unregistered desktop without X-",
+    },
+    Rule {
+        code: "desktop-environment-conflict",
+        severity: Severity::Error,
+        description: "A desktop listed in both OnlyShowIn and NotShowIn",
+    },
+    Rule {
+        code: "exec-deprecated-field-code",
+        severity: Severity::Warning,
+        description: "Exec uses one of the deprecated %d, %D, %n, %N, %v, or %m field codes",
+    },
+    Rule {
+        code: "exec-unknown-field-code",
+        severity: Severity::Error,
+        description: "Exec uses a % sequence that is not a field code; % is written as %%",
+    },
+    Rule {
+        code: "exec-multiple-targets",
+        severity: Severity::Error,
+        description: "Exec uses more than one of the %f, %F, %u, and %U field codes",
+    },
+    Rule {
+        code: "exec-target-not-alone",
+        severity: Severity::Error,
+        description: "Exec uses %F or %U inside a larger argument",
+    },
+    Rule {
+        code: "exec-unterminated-quote",
+        severity: Severity::Error,
+        description: "Exec has a quoted argument without its closing quote",
+    },
+    Rule {
+        code: "control-character",
+        severity: Severity::Error,
+        description: "A value contains a control character",
+    },
+    Rule {
+        code: "string-not-ascii",
+        severity: Severity::Warning,
+        description: "A value of type string, such as Exec or StartupWMClass, is not ASCII",
+    },
+    Rule {
+        code: "trailing-whitespace",
+        severity: Severity::Info,
+        description: "A value ends with whitespace",
+    },
+    Rule {
+        code: "list-not-terminated",
+        severity: Severity::Info,
+        description: "A list value does not end with a semicolon",
+    },
+];
+
+/// Selects the rules run by [`lint_with`] and the severity of their
+/// findings.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::{DesktopEntry, Severity};
+/// use xdg_desktop_entry::lint::{self, LintConfig};
+///
+/// let entry = DesktopEntry::parse(
+///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nEncoding=UTF-8\nCategories=Utility\n",
+/// )
+/// .unwrap();
+///
+/// let config = LintConfig::new()
+///     .disable("list-not-terminated")
+///     .severity("deprecated-key", Severity::Info);
+/// let diagnostics = lint::lint_with(&entry, &config);
+///
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].code, "deprecated-key");
+/// assert_eq!(diagnostics[0].severity, Severity::Info);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintConfig {
+    disabled: HashSet<String>,
+    severities: HashMap<String, Severity>,
+}
+
+impl LintConfig {
+    /// A configuration running every rule with its default severity.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Turns a rule off.
+    #[must_use]
+    pub fn disable(mut self, code: &str) -> Self {
+        self.disabled.insert(code.to_string());
+        self
+    }
+
+    /// Turns a rule back on.
+    #[must_use]
+    pub fn enable(mut self, code: &str) -> Self {
+        self.disabled.remove(code);
+        self
+    }
+
+    /// Overrides the severity of a rule's findings.
+    #[must_use]
+    pub fn severity(mut self, code: &str, severity: Severity) -> Self {
+        self.severities.insert(code.to_string(), severity);
+        self
+    }
+
+    /// Whether a rule runs.
+    pub fn is_enabled(&self, code: &str) -> bool {
+        !self.disabled.contains(code)
+    }
+
+    /// Applies the configuration to the findings of the rules.
+    fn apply(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .filter(|diagnostic| self.is_enabled(diagnostic.code))
+            .map(|mut diagnostic| {
+                if let Some(severity) = self.severities.get(diagnostic.code) {
+                    diagnostic.severity = *severity;
+                }
+                diagnostic
+            })
+            .collect()
+    }
+}
+
+/// Runs every lint rule against the entry.
+///
+/// See [`RULES`] for the rules. Rules checking how values are written, such
+/// as `trailing-whitespace`, only apply to entries parsed from text.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::DesktopEntry;
+/// use xdg_desktop_entry::lint;
+///
+/// let entry = DesktopEntry::parse(
+///     "[Desktop Entry]\nType=Application\nName=App\nExec=app\nVersion=104.2\n",
+/// )
+/// .unwrap();
+///
+/// let diagnostics = lint::lint(&entry);
+/// assert_eq!(diagnostics[0].code, "version-is-app-version");
+/// ```
+pub fn lint(entry: &DesktopEntry) -> Vec<Diagnostic> {
+    lint_with(entry, &LintConfig::default())
+}
+
+/// Runs the lint rules enabled by `config` against the entry.
+pub fn lint_with(entry: &DesktopEntry, config: &LintConfig) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    check_version(entry, &mut diagnostics);
+    check_key_versions(entry, &mut diagnostics);
+    check_keys(entry, &mut diagnostics);
+    check_generic_name(entry, &mut diagnostics);
+    check_comment(entry, &mut diagnostics);
+    check_categories(entry, &mut diagnostics);
+    check_desktop_environments(entry, &mut diagnostics);
+    check_field_codes(entry, &mut diagnostics);
+    check_control_characters(entry, &mut diagnostics);
+    check_ascii_strings(entry, &mut diagnostics);
+    check_text(entry, &mut diagnostics);
+    config.apply(diagnostics)
+}
+
+/// Runs the security review rules against every command line of the entry.
+///
+/// These rules are not part of [`lint`]: they flag constructs that are
+/// legitimate in many entries but deserve a second look when auditing
+/// user-writable launcher directories for persistence tricks:
+///
+/// - `exec-shell-command`: a shell invoked with `-c`
+/// - `exec-command-substitution`: `$(...)` or backticks
+/// - `exec-shell-operator`: pipes, redirections, or command chaining
+/// - `exec-world-writable-path`: paths under `/tmp`, `/var/tmp`, or `/dev/shm`
+/// - `exec-environment`: environment manipulation through `env`, `VAR=value`
+///   prefixes, or dynamic linker variables
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::DesktopEntry;
+/// use xdg_desktop_entry::lint;
+///
+/// let entry = DesktopEntry::parse(
+///     "[Desktop Entry]\nType=Application\nName=App\nExec=sh -c \"curl x | sh\"\n",
+/// )
+/// .unwrap();
+///
+/// let codes: Vec<_> = lint::security_review(&entry).iter().map(|d| d.code).collect();
+/// assert_eq!(codes, vec!["exec-shell-command", "exec-shell-operator"]);
+/// ```
+pub fn security_review(entry: &DesktopEntry) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut command_lines: Vec<(String, &str)> = Vec::new();
+
+    if let Some(exec) = &entry.exec {
+        command_lines.push(("Exec".to_string(), exec));
+    }
+    if let Some(try_exec) = &entry.try_exec {
+        command_lines.push(("TryExec".to_string(), try_exec));
+    }
+    for group in action_groups(entry) {
+        if let Some(exec) = group.get("Exec") {
+            command_lines.push((format!("[{}] Exec", group.name), exec));
+        }
+    }
+
+    for (location, command_line) in command_lines {
+        check_command_line(&location, command_line, &mut diagnostics);
+    }
+    diagnostics
+}
+
+/// Checks that the icons named by the entry and its actions exist.
+///
+/// Like [`security_review`], this is not part of [`lint`], as the result
+/// depends on the icons installed on the system. Icon names are looked up in
+/// the given themes, then in [`FALLBACK_ICON_THEME`], in each of the
+/// [`icon_dirs`](discovery::icon_dirs), and finally as unthemed icons at the
+/// top of those directories. Absolute paths are left alone.
+///
+/// Each missing icon is reported as an `icon-not-found` warning.
+///
+/// # Examples
+///
+/// ```no_run
+/// use xdg_desktop_entry::DesktopEntry;
+/// use xdg_desktop_entry::lint;
+///
+/// let entry = DesktopEntry::parse_file("/usr/share/applications/org.gnome.Calculator.desktop")
+///     .unwrap();
+/// for diagnostic in lint::check_icons(&entry, &["Adwaita"]) {
+///     println!("{}", diagnostic);
+/// }
+/// ```
+pub fn check_icons(entry: &DesktopEntry, themes: &[impl AsRef<str>]) -> Vec<Diagnostic> {
+    check_icons_in(entry, themes, &discovery::icon_dirs())
+}
+
+/// Like [`check_icons`], looking up icons in the given base directories.
+pub fn check_icons_in(
+    entry: &DesktopEntry,
+    themes: &[impl AsRef<str>],
+    icon_dirs: &[impl AsRef<Path>],
+) -> Vec<Diagnostic> {
+    let mut themes: Vec<&str> = themes.iter().map(AsRef::as_ref).collect();
+    if !themes.contains(&FALLBACK_ICON_THEME) {
+        themes.push(FALLBACK_ICON_THEME);
+    }
+
+    let mut icons: Vec<(String, &str)> = Vec::new();
+    if let Some(icon) = &entry.icon {
+        icons.push(("Icon".to_string(), &icon.default));
+        let mut localized: Vec<_> = icon.localized.iter().collect();
+        localized.sort_by_key(|(locale, _)| locale.to_string_repr());
+        for (locale, icon) in localized {
+            icons.push((format!("Icon[{}]", locale.to_string_repr()), icon));
+        }
+    }
+    for group in action_groups(entry) {
+        if let Some(icon) = group.get("Icon") {
+            icons.push((format!("[{}] Icon", group.name), icon));
+        }
+    }
+
+    icons
+        .into_iter()
+        .filter(|(_, icon)| !icon.is_empty() && !Path::new(icon).is_absolute())
+        .filter(|(_, icon)| !icon_exists(icon, &themes, icon_dirs))
+        .map(|(location, icon)| {
+            Diagnostic::new(
+                Severity::Warning,
+                "icon-not-found",
+                format!(
+                    "{}: icon '{}' was not found in the {} icon themes",
+                    location,
+                    icon,
+                    themes.join(", ")
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Runs the completeness rules against the entry.
+///
+/// These rules are not part of [`lint`]: they flag optional content that is
+/// missing, which is fine for many entries. They are run by the
+/// [`Pedantic`](crate::ValidationLevel::Pedantic) level of
+/// [`DesktopEntry::validate_with`].
+///
+/// - `keywords-missing`: an application without `Keywords`
+/// - `translation-missing`: `Name`, `GenericName`, `Comment`, or `Keywords`
+///   without a translation for a locale the entry is translated to
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::DesktopEntry;
+/// use xdg_desktop_entry::lint;
+///
+/// let entry = DesktopEntry::parse(
+///     "[Desktop Entry]\nType=Application\nName=Viewer\nName[fr]=Visionneuse\nExec=viewer\n\
+///      Comment=View images\nKeywords=image;photo;\nKeywords[fr]=image;photo;\n",
+/// )
+/// .unwrap();
+///
+/// let diagnostics = lint::check_completeness(&entry);
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].message, "Comment has no translation for fr");
+/// ```
+pub fn check_completeness(entry: &DesktopEntry) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    if entry.entry_type == crate::DesktopEntryType::Application && entry.keywords.is_none() {
+        diagnostics.push(Diagnostic::new(
+            Severity::Info,
+            "keywords-missing",
+            "Keywords is missing; it helps finding the application in searches",
+        ));
+    }
+
+    let translated: [(&str, Option<Vec<&Locale>>); 4] = [
+        ("Name", Some(entry.name.localized.keys().collect())),
+        ("GenericName", locales_of(&entry.generic_name)),
+        ("Comment", locales_of(&entry.comment)),
+        ("Keywords", locales_of(&entry.keywords)),
+    ];
+    let all: BTreeSet<&Locale> = translated
+        .iter()
+        .flat_map(|(_, locales)| locales.iter().flatten().copied())
+        .collect();
+    for (key, locales) in &translated {
+        let Some(locales) = locales else {
+            continue;
+        };
+        for locale in all.iter().filter(|locale| !locales.contains(locale)) {
+            diagnostics.push(Diagnostic::new(
+                Severity::Info,
+                "translation-missing",
+                format!("{} has no translation for {}", key, locale),
+            ));
+        }
+    }
+    diagnostics
+}
+
+fn locales_of<T>(field: &Option<crate::Localized<T>>) -> Option<Vec<&Locale>> {
+    field
+        .as_ref()
+        .map(|localized| localized.localized.keys().collect())
+}
+
+/// File extensions of icons, as listed by the Icon Theme Specification.
+const ICON_EXTENSIONS: &[&str] = &["png", "svg", "xpm"];
+
+/// Whether an icon exists in one of the themes, or unthemed.
+///
+/// Theme directories are organized by size and context, in either order
+/// (`48x48/apps` or `apps/48`), so icons are looked for one and two levels
+/// below each theme directory.
+fn icon_exists(name: &str, themes: &[&str], icon_dirs: &[impl AsRef<Path>]) -> bool {
+    let file_names: Vec<String> = ICON_EXTENSIONS
+        .iter()
+        .map(|ext| format!("{}.{}", name, ext))
+        .collect();
+    let contains_icon = |dir: &Path| file_names.iter().any(|file| dir.join(file).is_file());
+    let subdirs = |dir: &Path| -> Vec<std::path::PathBuf> {
+        std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect()
+    };
+
+    let themed = themes.iter().any(|theme| {
+        icon_dirs.iter().any(|base| {
+            subdirs(&base.as_ref().join(theme))
+                .iter()
+                .any(|dir| contains_icon(dir) || subdirs(dir).iter().any(|dir| contains_icon(dir)))
+        })
+    });
+    themed || icon_dirs.iter().any(|base| contains_icon(base.as_ref()))
+}
+
+/// Shells whose `-c` option runs an arbitrary script.
+const SHELLS: &[&str] = &[
+    "sh", "bash", "dash", "zsh", "ksh", "mksh", "fish", "csh", "tcsh",
+];
+
+/// Directories any user can write to.
+const WORLD_WRITABLE_DIRS: &[&str] = &["/tmp/", "/var/tmp/", "/dev/shm/"];
+
+/// Tokens a shell would treat as pipes, redirections, or command separators.
+const SHELL_OPERATORS: &[&str] = &["|", "||", "&", "&&", ";", ">", ">>", "<", "<<"];
+
+/// Options of `env` taking the next argument as their value.
+const ENV_OPTIONS_WITH_VALUE: &[&str] = &["-u", "--unset", "-C", "--chdir", "-S"];
+
+/// Environment variables that change how every program is loaded.
+const LOADER_VARIABLES: &[&str] = &["LD_PRELOAD", "LD_LIBRARY_PATH", "LD_AUDIT"];
+
+fn check_command_line(location: &str, command_line: &str, diagnostics: &mut Vec<Diagnostic>) {
+    let warn = |code, message: String| {
+        Diagnostic::new(
+            Severity::Warning,
+            code,
+            format!("{}: {} in '{}'", location, message, command_line),
+        )
+    };
+    // Unbalanced quoting is reported elsewhere; fall back to plain splitting
+    // so the rules still see the command.
+    let argv = exec::split_words(command_line).unwrap_or_else(|_| {
+        command_line
+            .split_whitespace()
+            .map(str::to_string)
+            .collect()
+    });
+    let basename = |arg: &str| {
+        Path::new(arg)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(arg)
+            .to_string()
+    };
+
+    // Skip `env` and `VAR=value` prefixes to find the real program
+    let mut program_index = 0;
+    let mut manipulates_environment = false;
+    while let Some(arg) = argv.get(program_index) {
+        let is_env = program_index == 0 && basename(arg) == "env";
+        let is_assignment = arg.contains('=') && !arg.starts_with('-');
+        if is_env || is_assignment {
+            manipulates_environment = true;
+        } else if manipulates_environment && arg.starts_with('-') {
+            if ENV_OPTIONS_WITH_VALUE.contains(&arg.as_str()) {
+                program_index += 1;
+            }
+        } else {
+            break;
+        }
+        program_index += 1;
+    }
+    let program = argv.get(program_index).map(|arg| basename(arg));
+    let script = match program {
+        Some(program) if SHELLS.contains(&program.as_str()) => argv[program_index + 1..]
+            .iter()
+            .position(|arg| arg.starts_with('-') && !arg.starts_with("--") && arg.contains('c'))
+            .and_then(|c| argv.get(program_index + 2 + c)),
+        _ => None,
+    };
+
+    if let Some(script) = script {
+        diagnostics.push(warn(
+            "exec-shell-command",
+            format!("runs a shell script ('{}')", script),
+        ));
+    }
+    if command_line.contains("$(") || command_line.contains('`') {
+        diagnostics.push(warn(
+            "exec-command-substitution",
+            "uses command substitution".to_string(),
+        ));
+    }
+    if argv
+        .iter()
+        .any(|arg| SHELL_OPERATORS.contains(&arg.as_str()))
+        || script.is_some_and(|script| {
+            script.contains('|')
+                || script.contains('>')
+                || script.contains(';')
+                || script.contains("&&")
+        })
+    {
+        diagnostics.push(warn(
+            "exec-shell-operator",
+            "pipes, redirects, or chains commands".to_string(),
+        ));
+    }
+    if let Some(path) = argv.iter().find(|arg| {
+        WORLD_WRITABLE_DIRS
+            .iter()
+            .any(|dir| arg.starts_with(dir) || arg.contains(&format!("={}", dir)))
+    }) {
+        diagnostics.push(warn(
+            "exec-world-writable-path",
+            format!("refers to '{}' in a world-writable directory", path),
+        ));
+    }
+    if manipulates_environment
+        || LOADER_VARIABLES
+            .iter()
+            .any(|variable| command_line.contains(variable))
+    {
+        diagnostics.push(warn(
+            "exec-environment",
+            "manipulates the environment".to_string(),
+        ));
+    }
+}
+
+/// Applies every available automatic fix, returning the fixed diagnostics.
+///
+/// The entry is linted again after each fix, so every fix is built against
+/// the entry as the previous ones left it.
+///
+/// # Errors
+///
+/// Returns an error if a fix cannot be applied; fixes applied before it are
+/// kept.
+pub fn fix(entry: &mut DesktopEntry) -> Result<Vec<Diagnostic>> {
+    let mut fixed = Vec::new();
+    while let Some(diagnostic) = lint(entry)
+        .into_iter()
+        .find(|diagnostic| diagnostic.fix.is_some() && !fixed.contains(diagnostic))
+    {
+        if let Some(fix) = &diagnostic.fix {
+            entry.apply_fix(fix)?;
+        }
+        fixed.push(diagnostic);
+    }
+    Ok(fixed)
+}
+
+/// Flags `Version` values that are not specification versions.
+///
+/// The key is frequently misused for the application's version (e.g.,
+/// "Version=104.2"); such values get a fix moving them to
+/// [`APP_VERSION_KEY`] and declaring the latest specification version.
+fn check_version(entry: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(version) = &entry.version else {
+        return;
+    };
+    if SpecVersion::parse(version).is_some() {
+        return;
+    }
+
+    let components: Vec<_> = version.split('.').collect();
+    let looks_like_spec_version = components.len() == 2
+        && components[0] == "1"
+        && components[1].len() == 1
+        && components[1].chars().all(|c| c.is_ascii_digit());
+
+    if looks_like_spec_version {
+        diagnostics.push(Diagnostic::new(
+            Severity::Warning,
+            "version-unknown",
+            format!("Version {} is not a known specification version", version),
+        ));
+        return;
+    }
+
+    let mut diagnostic = Diagnostic::new(
+        Severity::Warning,
+        "version-is-app-version",
+        format!(
+            "Version={} looks like an application version; Version is the version of the \
+             Desktop Entry Specification the file conforms to",
+            version
+        ),
+    );
+    if !entry.unknown_keys.contains_key(APP_VERSION_KEY) {
+        let group = DESKTOP_ENTRY_GROUP.to_string();
+        diagnostic = diagnostic.with_fix(Fix {
+            description: format!(
+                "Move the value to {} and declare Version={}",
+                APP_VERSION_KEY,
+                SpecVersion::LATEST.as_str()
+            ),
+            edits: vec![
+                KeyEdit::Rename {
+                    group: group.clone(),
+                    from: "Version".to_string(),
+                    to: APP_VERSION_KEY.to_string(),
+                },
+                KeyEdit::Set {
+                    group,
+                    key: "Version".to_string(),
+                    value: SpecVersion::LATEST.as_str().to_string(),
+                },
+            ],
+        });
+    }
+    diagnostics.push(diagnostic);
+}
+
+/// Flags keys added in a later version of the specification than the
+/// declared `Version`, with a fix raising it.
+fn check_key_versions(entry: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(declared) = entry.spec_version() else {
+        return;
+    };
+    let required = entry.required_spec_version();
+    for key in entry.application_keys() {
+        let Some(introduced) = SpecVersion::of_key(key).filter(|v| *v > declared) else {
+            continue;
+        };
+        diagnostics.push(
+            Diagnostic::new(
+                Severity::Warning,
+                "key-newer-than-version",
+                format!(
+                    "{} was added in version {} of the specification, but Version is {}",
+                    key,
+                    introduced.as_str(),
+                    declared.as_str()
+                ),
+            )
+            .with_fix(Fix {
+                description: format!("Declare Version={}", required.as_str()),
+                edits: vec![KeyEdit::Set {
+                    group: DESKTOP_ENTRY_GROUP.to_string(),
+                    key: "Version".to_string(),
+                    value: required.as_str().to_string(),
+                }],
+            }),
+        );
+    }
+}
+
+/// Keys deprecated by the specification (Appendix D).
+pub(crate) const DEPRECATED_KEYS: &[&str] = &[
+    "Encoding",
+    "MiniIcon",
+    "TerminalOptions",
+    "Protocols",
+    "Extensions",
+    "BinaryPattern",
+    "MapNotify",
+    "SwallowTitle",
+    "SwallowExec",
+    "SortOrder",
+    "FilePattern",
+];
+
+/// Keys of `[Desktop Action]` groups defined by the specification.
+const ACTION_KEYS: &[&str] = &["Name", "Icon", "Exec"];
+
+/// Keys holding lists, which should end with a semicolon.
+pub(crate) const LIST_KEYS: &[&str] = &[
+    "Actions",
+    "OnlyShowIn",
+    "NotShowIn",
+    "MimeType",
+    "Categories",
+    "Implements",
+    "Keywords",
+];
+
+/// The action groups of the entry, sorted by name.
+fn action_groups(entry: &DesktopEntry) -> Vec<&crate::Group> {
+    let mut groups: Vec<_> = entry
+        .additional_groups
+        .values()
+        .filter(|group| group.name.starts_with(ACTION_GROUP_PREFIX))
+        .collect();
+    groups.sort_by(|a, b| a.name.cmp(&b.name));
+    groups
+}
+
+/// Flags deprecated keys, with a fix removing them, and keys the
+/// specification does not define, with a fix adding the `X-` prefix unless
+/// the prefixed key already exists.
+fn check_keys(entry: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let mut keys: Vec<_> = entry.unknown_keys.keys().collect();
+    keys.sort();
+    for key in &keys {
+        let taken = entry.unknown_keys.contains_key(&format!("X-{}", key));
+        check_key(DESKTOP_ENTRY_GROUP, key, taken, diagnostics);
+    }
+    for group in action_groups(entry) {
+        let mut keys: Vec<_> = group
+            .entries
+            .keys()
+            .filter(|key| !ACTION_KEYS.contains(&key.as_str()))
+            .collect();
+        keys.sort();
+        for key in keys {
+            let taken = group.entries.contains_key(&format!("X-{}", key));
+            check_key(&group.name, key, taken, diagnostics);
+        }
+    }
+}
+
+fn check_key(group: &str, key: &str, prefixed_taken: bool, diagnostics: &mut Vec<Diagnostic>) {
+    if key.starts_with("X-") {
+        return;
+    }
+    if DEPRECATED_KEYS.contains(&key) {
+        diagnostics.push(
+            Diagnostic::new(
+                Severity::Warning,
+                "deprecated-key",
+                format!("[{}] {} is deprecated", group, key),
+            )
+            .with_fix(Fix {
+                description: format!("Remove {}", key),
+                edits: vec![KeyEdit::Remove {
+                    group: group.to_string(),
+                    key: key.to_string(),
+                }],
+            }),
+        );
+    } else {
+        let mut diagnostic = Diagnostic::new(
+            Severity::Warning,
+            "unknown-key",
+            format!(
+                "[{}] {} is not defined by the specification; extensions must be prefixed \
+                 with X-",
+                group, key
+            ),
+        );
+        if !prefixed_taken {
+            diagnostic = diagnostic.with_fix(Fix {
+                description: format!("Rename {} to X-{}", key, key),
+                edits: vec![KeyEdit::Rename {
+                    group: group.to_string(),
+                    from: key.to_string(),
+                    to: format!("X-{}", key),
+                }],
+            });
+        }
+        diagnostics.push(diagnostic);
+    }
+}
+
+/// Flags a `GenericName` that repeats the `Name`.
+///
+/// When there are no translations to lose, the fix removes the key.
+fn check_generic_name(entry: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(generic_name) = &entry.generic_name else {
+        return;
+    };
+    if generic_name.default != entry.name.default {
+        return;
+    }
+
+    let mut diagnostic = Diagnostic::new(
+        Severity::Warning,
+        "generic-name-equals-name",
+        format!(
+            "GenericName '{}' is the same as Name; it should describe the kind of application \
+             (e.g. \"Web Browser\")",
+            generic_name.default
+        ),
+    );
+    if generic_name.localized.is_empty() {
+        diagnostic = diagnostic.with_fix(Fix {
+            description: "Remove GenericName".to_string(),
+            edits: vec![KeyEdit::Remove {
+                group: DESKTOP_ENTRY_GROUP.to_string(),
+                key: "GenericName".to_string(),
+            }],
+        });
+    }
+    diagnostics.push(diagnostic);
+}
+
+/// Flags a `Comment` that repeats the `Name`.
+///
+/// When there are no translations to lose, the fix removes the key.
+fn check_comment(entry: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(comment) = &entry.comment else {
+        return;
+    };
+    if comment.default != entry.name.default {
+        return;
+    }
+
+    let mut diagnostic = Diagnostic::new(
+        Severity::Warning,
+        "comment-equals-name",
+        format!(
+            "Comment '{}' is the same as Name; it should be a tooltip describing what the \
+             application does",
+            comment.default
+        ),
+    );
+    if comment.localized.is_empty() {
+        diagnostic = diagnostic.with_fix(Fix {
+            description: "Remove Comment".to_string(),
+            edits: vec![KeyEdit::Remove {
+                group: DESKTOP_ENTRY_GROUP.to_string(),
+                key: "Comment".to_string(),
+            }],
+        });
+    }
+    diagnostics.push(diagnostic);
+}
+
+/// Checks `Categories` against the registry of the Desktop Menu
+/// Specification.
+fn check_categories(entry: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(names) = &entry.categories else {
+        return;
+    };
+    let listed = entry.registered_categories();
+
+    for name in names {
+        let Some(category) = Category::parse(name) else {
+            if !name.starts_with("X-") {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    "category-unknown",
+                    format!(
+                        "Category '{}' is not registered; extensions must be prefixed with X-",
+                        name
+                    ),
+                ));
+            }
+            continue;
+        };
+
+        let mains = category.main_categories();
+        if !mains.is_empty() && !mains.iter().any(|main| listed.contains(main)) {
+            let mains: Vec<_> = mains.iter().map(Category::as_str).collect();
+            diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                "category-missing-main",
+                format!(
+                    "Category '{}' should be listed along with one of {}",
+                    name,
+                    mains.join(", ")
+                ),
+            ));
+        }
+        if category.kind() == CategoryKind::Reserved && entry.only_show_in.is_none() {
+            diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                "category-reserved-without-only-show-in",
+                format!(
+                    "Category '{}' is reserved for desktop-specific components and requires \
+                     OnlyShowIn",
+                    name
+                ),
+            ));
+        }
+    }
+}
+
+/// Checks the desktop environments named by `OnlyShowIn` and `NotShowIn`.
+fn check_desktop_environments(entry: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let lists = [
+        ("OnlyShowIn", &entry.only_show_in),
+        ("NotShowIn", &entry.not_show_in),
+    ];
+    for (key, names) in lists {
+        for name in names.iter().flatten() {
+            if !DesktopEnvironment::parse(name).is_registered() && !name.starts_with("X-") {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    "desktop-environment-unknown",
+                    format!(
+                        "{}: desktop environment '{}' is not registered; extensions must be \
+                         prefixed with X-",
+                        key, name
+                    ),
+                ));
+            }
+        }
+    }
+
+    let (Some(only_show_in), Some(not_show_in)) = (&entry.only_show_in, &entry.not_show_in) else {
+        return;
+    };
+    for name in only_show_in
+        .iter()
+        .filter(|name| not_show_in.contains(name))
+    {
+        diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            "desktop-environment-conflict",
+            format!(
+                "Desktop environment '{}' is listed in both OnlyShowIn and NotShowIn",
+                name
+            ),
+        ));
+    }
+}
+
+/// Flags command lines with malformed quoting, unknown or deprecated field
+/// codes, or misplaced or repeated target field codes, as
+/// `desktop-file-validate` does.
+fn check_field_codes(entry: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let mut command_lines: Vec<(String, &str)> = Vec::new();
+    if let Some(exec) = &entry.exec {
+        command_lines.push(("Exec".to_string(), exec));
+    }
+    for group in action_groups(entry) {
+        if let Some(exec) = group.get("Exec") {
+            command_lines.push((format!("[{}] Exec", group.name), exec));
+        }
+    }
+
+    for (location, command_line) in command_lines {
+        let error = |code, message: String| {
+            Diagnostic::new(
+                Severity::Error,
+                code,
+                format!("{}: {} in '{}'", location, message, command_line),
+            )
+        };
+        let Ok(words) = exec::split_words(command_line) else {
+            diagnostics.push(error(
+                "exec-unterminated-quote",
+                "a quoted argument is not closed".to_string(),
+            ));
+            continue;
+        };
+
+        let mut targets = Vec::new();
+        for word in &words {
+            let mut chars = word.chars();
+            while let Some(c) = chars.next() {
+                if c != '%' {
+                    continue;
+                }
+                let code = match chars.next() {
+                    Some('%') => continue,
+                    Some(c) => exec::FieldCode::from_char(c).ok_or(format!("%{}", c)),
+                    None => Err("%".to_string()),
+                };
+                match code {
+                    Err(sequence) => diagnostics.push(error(
+                        "exec-unknown-field-code",
+                        format!("{} is not a field code; write % as %%", sequence),
+                    )),
+                    Ok(exec::FieldCode::Deprecated(c)) => diagnostics.push(Diagnostic::new(
+                        Severity::Warning,
+                        "exec-deprecated-field-code",
+                        format!(
+                            "{}: the %{} field code is deprecated and ignored by launchers",
+                            location, c
+                        ),
+                    )),
+                    Ok(code) if code.is_target() => {
+                        let list = matches!(code, exec::FieldCode::Files | exec::FieldCode::Urls);
+                        if list && word.len() != 2 {
+                            diagnostics.push(error(
+                                "exec-target-not-alone",
+                                format!("%{} must be an argument on its own", code.as_char()),
+                            ));
+                        }
+                        targets.push(format!("%{}", code.as_char()));
+                    }
+                    Ok(_) => {}
+                }
+            }
+        }
+        if targets.len() > 1 {
+            diagnostics.push(error(
+                "exec-multiple-targets",
+                format!(
+                    "only one of %f, %F, %u, and %U may be used, but found {}",
+                    targets.join(", ")
+                ),
+            ));
+        }
+    }
+}
+
+/// Flags values containing control characters, which the specification
+/// forbids; they must be written as escape sequences such as `\t`.
+///
+/// Entries parsed from text are checked as written, others as they would be
+/// serialized.
+fn check_control_characters(entry: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let mut values: Vec<(String, String, Option<Span>)> = Vec::new();
+    if let Some(layout) = &entry.layout {
+        for (index, line) in layout.lines().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('#') || trimmed.starts_with('[') {
+                continue;
+            }
+            if let Some((key_part, value)) = line.split_once('=') {
+                let span = Span {
+                    line: index + 1,
+                    column: key_part.len() + 2,
+                    len: value.len(),
+                };
+                values.push((key_part.trim().to_string(), value.to_string(), Some(span)));
+            }
+        }
+    } else if let Ok(groups) = entry.raw_groups() {
+        let mut entries: Vec<_> = groups
+            .iter()
+            .flat_map(|(group, keys)| keys.values().flatten().map(move |e| (group, e)))
+            .collect();
+        entries.sort_by_key(|(group, e)| (group.as_str(), e.key.as_str(), e.locale.clone()));
+        for (group, e) in entries {
+            let key = match &e.locale {
+                Some(locale) => format!("[{}] {}[{}]", group, e.key, locale),
+                None => format!("[{}] {}", group, e.key),
+            };
+            values.push((key, e.value.clone(), None));
+        }
+    }
+
+    for (key, value, span) in values {
+        let Some(c) = value.chars().find(|c| c.is_control()) else {
+            continue;
+        };
+        let mut diagnostic = Diagnostic::new(
+            Severity::Error,
+            "control-character",
+            format!(
+                "{} contains the control character U+{:04X}",
+                key,
+                u32::from(c)
+            ),
+        );
+        if let Some(span) = span {
+            diagnostic = diagnostic.with_span(span);
+        }
+        diagnostics.push(diagnostic);
+    }
+}
+
+/// Keys of type `string`, which the specification restricts to ASCII.
+const STRING_KEYS: &[&str] = &[
+    "Type",
+    "Version",
+    "OnlyShowIn",
+    "NotShowIn",
+    "TryExec",
+    "Exec",
+    "Path",
+    "Actions",
+    "MimeType",
+    "Categories",
+    "Implements",
+    "StartupWMClass",
+    "URL",
+];
+
+/// Flags values of type `string` containing non-ASCII characters.
+fn check_ascii_strings(entry: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let Ok(groups) = entry.raw_groups() else {
+        return;
+    };
+    let mut values: Vec<(String, &str)> = Vec::new();
+    if let Some(keys) = groups.get(DESKTOP_ENTRY_GROUP) {
+        for key in STRING_KEYS {
+            if let Some(value) = keys.get(*key).and_then(|entries| entries.first()) {
+                values.push((key.to_string(), &value.value));
+            }
+        }
+    }
+    for group in action_groups(entry) {
+        if let Some(exec) = group.get("Exec") {
+            values.push((format!("[{}] Exec", group.name), exec));
+        }
+    }
+
+    for (key, value) in values {
+        if !value.is_ascii() {
+            diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                "string-not-ascii",
+                format!(
+                    "{} is of type string and must be ASCII, but is '{}'",
+                    key, value
+                ),
+            ));
+        }
+    }
+}
+
+/// Flags values ending with whitespace, and list values without a final
+/// semicolon, in the original text of the entry.
+fn check_text(entry: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(layout) = &entry.layout else {
+        return;
+    };
+
+    let mut group: Option<&str> = None;
+    for (index, line) in layout.lines().enumerate() {
+        let line_num = index + 1;
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            group = Some(&trimmed[1..trimmed.len() - 1]);
+            continue;
+        }
+        if trimmed.starts_with('#') {
+            continue;
+        }
+        let Some((key_part, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key_part = key_part.trim();
+        let key = key_part.split('[').next().unwrap_or(key_part);
+
+        let trimmed_value = value.trim_end();
+        if trimmed_value.len() < value.len() {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Info,
+                    "trailing-whitespace",
+                    format!("{} ends with whitespace", key_part),
+                )
+                .with_span(Span {
+                    line: line_num,
+                    column: line.len() - (value.len() - trimmed_value.len()) + 1,
+                    len: value.len() - trimmed_value.len(),
+                }),
+            );
+        }
+        if group.is_none_or(|group| group == DESKTOP_ENTRY_GROUP)
+            && LIST_KEYS.contains(&key)
+            && !trimmed_value.is_empty()
+            && !trimmed_value.ends_with(';')
+        {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Info,
+                    "list-not-terminated",
+                    format!("{} should end with a semicolon", key_part),
+                )
+                .with_span(Span::line(line_num, line)),
+            );
+        }
+    }
+}
@@ -0,0 +1,927 @@
+//! Menus defined by the Desktop Menu Specification.
+//!
+//! A menu file (usually `applications.menu`) describes a tree of menus in
+//! XML: each `<Menu>` names the directories to take desktop entries from,
+//! the `.directory` entry describing it, and `<Include>`/`<Exclude>` rules
+//! selecting entries by desktop file ID or category. Menu files can merge
+//! other files and move submenus around.
+//!
+//! [`MenuFile`] reads a menu file, following its merges, and
+//! [`MenuFile::build`] populates the tree with the installed entries.
+//! `<Layout>`, `<LegacyDir>`, and `<KDELegacyDirs>` are ignored; entries
+//! and submenus are sorted by name.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use xdg_desktop_entry::environment;
+//! use xdg_desktop_entry::menu::{Menu, MenuFile};
+//!
+//! fn print(menu: &Menu, depth: usize) {
+//!     println!("{:indent$}{}", "", menu.display_name(), indent = depth * 2);
+//!     for item in &menu.entries {
+//!         println!("{:indent$}{}", "", item.entry.name.default, indent = depth * 2 + 2);
+//!     }
+//!     for submenu in &menu.submenus {
+//!         print(submenu, depth + 1);
+//!     }
+//! }
+//!
+//! let menu = MenuFile::load_default().unwrap().build(&environment::current_desktop());
+//! print(&menu, 0);
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::discovery::{self, DesktopFileId};
+use crate::{DesktopEntry, DesktopEntryError, Result};
+
+/// Name of the main menu file, without the `$XDG_MENU_PREFIX`.
+pub const APPLICATIONS_MENU: &str = "applications.menu";
+
+/// Maximum depth of nested `<MergeFile>` elements.
+const MAX_MERGE_DEPTH: usize = 16;
+
+/// Maximum depth of nested elements in a menu file.
+const MAX_ELEMENT_DEPTH: usize = 64;
+
+/// A menu file with its merged files, ready to be populated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuFile {
+    root: MenuDef,
+}
+
+impl MenuFile {
+    /// Loads `menus/${XDG_MENU_PREFIX}applications.menu` from the most
+    /// important [configuration directory](discovery::config_dirs) having
+    /// it.
+    ///
+    /// # Errors
+    ///
+    /// Fails with an [`io::ErrorKind::NotFound`](std::io::ErrorKind) error
+    /// if no directory has the file, or with the errors of
+    /// [`load`](Self::load).
+    pub fn load_default() -> Result<Self> {
+        let prefix = std::env::var("XDG_MENU_PREFIX").unwrap_or_default();
+        let name = format!("{}{}", prefix, APPLICATIONS_MENU);
+        let path = discovery::config_dirs()
+            .into_iter()
+            .map(|dir| dir.join("menus").join(&name))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::NotFound, format!("no {} found", name))
+            })?;
+        Self::load(path)
+    }
+
+    /// Loads a menu file and the files it merges.
+    ///
+    /// Relative paths are resolved against the directory of the file.
+    /// Merged files that are missing or invalid are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, or
+    /// [`DesktopEntryError::InvalidMenu`] if it is not a valid menu file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        let mut loader = Loader {
+            visited: vec![path.to_path_buf()],
+        };
+        let root = loader.parse_file(&content, Some(path))?;
+        Ok(Self::finish(root))
+    }
+
+    /// Parses the text of a menu file.
+    ///
+    /// Relative paths are resolved against the current directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::InvalidMenu`] if the text is not a valid
+    /// menu file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::menu::MenuFile;
+    ///
+    /// let menu = MenuFile::parse(
+    ///     "<Menu><Name>Applications</Name>\
+    ///      <Menu><Name>Graphics</Name><Include><Category>Graphics</Category></Include></Menu>\
+    ///      </Menu>",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(menu.name(), "Applications");
+    /// assert!(MenuFile::parse("<Menu><Name>Unclosed</Name>").is_err());
+    /// ```
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut loader = Loader {
+            visited: Vec::new(),
+        };
+        let root = loader.parse_file(content, None)?;
+        Ok(Self::finish(root))
+    }
+
+    /// The name of the root menu.
+    pub fn name(&self) -> &str {
+        &self.root.name
+    }
+
+    /// Populates the menus with the installed entries.
+    ///
+    /// Entries are left out unless
+    /// [`should_show`](DesktopEntry::should_show) in the given desktops,
+    /// while still counting as allocated for `<OnlyUnallocated>` menus.
+    /// Submenus that end up empty, are `<Deleted>`, or whose directory entry
+    /// has `NoDisplay=true` are left out as well.
+    pub fn build(&self, current_desktops: &[impl AsRef<str>]) -> Menu {
+        let mut builder = Builder {
+            current_desktops: current_desktops
+                .iter()
+                .map(|desktop| desktop.as_ref().to_string())
+                .collect(),
+            parsed: HashMap::new(),
+            allocated: HashSet::new(),
+        };
+        let mut menu = builder.populate(&self.root, &[], &[], false);
+        builder.populate_unallocated(&self.root, &[], &[], &mut menu);
+        menu.prune();
+        menu
+    }
+
+    /// Consolidates duplicate submenus and applies `<Move>` elements.
+    fn finish(mut root: MenuDef) -> Self {
+        root.consolidate();
+        root.apply_moves();
+        Self { root }
+    }
+}
+
+/// A populated menu.
+#[derive(Debug, Clone)]
+pub struct Menu {
+    /// Name of the menu, as given by `<Name>`
+    pub name: String,
+    /// The `.directory` entry describing the menu
+    pub directory: Option<DesktopEntry>,
+    /// Entries of the menu, sorted by name
+    pub entries: Vec<MenuItem>,
+    /// Submenus, sorted by name
+    pub submenus: Vec<Menu>,
+}
+
+impl Menu {
+    /// The name to show: the `Name` of the directory entry, or the menu
+    /// name.
+    pub fn display_name(&self) -> &str {
+        self.directory
+            .as_ref()
+            .map_or(&self.name, |directory| &directory.name.default)
+    }
+
+    /// Gets a submenu by its path of `/`-separated names, relative to this
+    /// menu.
+    pub fn find(&self, path: &str) -> Option<&Menu> {
+        path.split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(self, |menu, name| {
+                menu.submenus.iter().find(|submenu| submenu.name == name)
+            })
+    }
+
+    /// Whether the menu has no entries and no submenus.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.submenus.is_empty()
+    }
+
+    /// Drops empty and hidden submenus, and sorts the rest.
+    fn prune(&mut self) {
+        for submenu in &mut self.submenus {
+            submenu.prune();
+        }
+        self.submenus.retain(|submenu| {
+            !submenu.is_empty()
+                && submenu
+                    .directory
+                    .as_ref()
+                    .is_none_or(|directory| directory.no_display != Some(true))
+        });
+        self.entries
+            .sort_by_cached_key(|item| item.entry.name.default.to_lowercase());
+        self.submenus
+            .sort_by_cached_key(|submenu| submenu.display_name().to_lowercase());
+    }
+}
+
+/// An entry of a [`Menu`].
+#[derive(Debug, Clone)]
+pub struct MenuItem {
+    /// Desktop file ID
+    pub id: DesktopFileId,
+    /// Path of the desktop file
+    pub path: PathBuf,
+    /// Parsed entry
+    pub entry: DesktopEntry,
+}
+
+// ============================================================================
+// Menu definitions
+// ============================================================================
+
+/// A `<Menu>` element with its merges resolved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct MenuDef {
+    name: String,
+    /// `<AppDir>` paths, least important first
+    app_dirs: Vec<PathBuf>,
+    /// `<DirectoryDir>` paths, least important first
+    directory_dirs: Vec<PathBuf>,
+    /// `<Directory>` names, least important first
+    directories: Vec<String>,
+    /// `<Include>` (`true`) and `<Exclude>` rules in order
+    rules: Vec<(bool, Rule)>,
+    /// Last of `<OnlyUnallocated>` and `<NotOnlyUnallocated>`
+    only_unallocated: Option<bool>,
+    /// Last of `<Deleted>` and `<NotDeleted>`
+    deleted: Option<bool>,
+    /// `<Move>` elements as old and new paths
+    moves: Vec<(String, String)>,
+    submenus: Vec<MenuDef>,
+}
+
+/// A matching rule of `<Include>` or `<Exclude>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Rule {
+    Filename(String),
+    Category(String),
+    All,
+    And(Vec<Rule>),
+    Or(Vec<Rule>),
+    Not(Vec<Rule>),
+}
+
+impl Rule {
+    fn matches(&self, id: &DesktopFileId, entry: &DesktopEntry) -> bool {
+        match self {
+            Self::Filename(name) => id.as_str() == name,
+            Self::Category(category) => entry
+                .categories
+                .as_ref()
+                .is_some_and(|categories| categories.contains(category)),
+            Self::All => true,
+            Self::And(rules) => rules.iter().all(|rule| rule.matches(id, entry)),
+            Self::Or(rules) => rules.iter().any(|rule| rule.matches(id, entry)),
+            Self::Not(rules) => !rules.iter().any(|rule| rule.matches(id, entry)),
+        }
+    }
+}
+
+impl MenuDef {
+    /// Adds the contents of a merged menu; its name is ignored.
+    fn merge(&mut self, other: MenuDef) {
+        self.app_dirs.extend(other.app_dirs);
+        self.directory_dirs.extend(other.directory_dirs);
+        self.directories.extend(other.directories);
+        self.rules.extend(other.rules);
+        self.only_unallocated = other.only_unallocated.or(self.only_unallocated);
+        self.deleted = other.deleted.or(self.deleted);
+        self.moves.extend(other.moves);
+        self.submenus.extend(other.submenus);
+    }
+
+    /// Merges submenus with the same name, the later ones taking
+    /// precedence, and drops duplicate directories.
+    fn consolidate(&mut self) {
+        let mut submenus: Vec<MenuDef> = Vec::new();
+        for submenu in std::mem::take(&mut self.submenus) {
+            match submenus.iter_mut().find(|known| known.name == submenu.name) {
+                Some(known) => known.merge(submenu),
+                None => submenus.push(submenu),
+            }
+        }
+        self.submenus = submenus;
+        keep_last(&mut self.app_dirs);
+        keep_last(&mut self.directory_dirs);
+        keep_last(&mut self.directories);
+        for submenu in &mut self.submenus {
+            submenu.consolidate();
+        }
+    }
+
+    /// Applies the `<Move>` elements, innermost menus first.
+    fn apply_moves(&mut self) {
+        for submenu in &mut self.submenus {
+            submenu.apply_moves();
+        }
+        let moves = std::mem::take(&mut self.moves);
+        if moves.is_empty() {
+            return;
+        }
+        for (old, new) in moves {
+            if let Some(moved) = self.take_submenu(&old) {
+                let target = self.submenu_mut(&new);
+                let name = target.name.clone();
+                target.merge(moved);
+                target.name = name;
+            }
+        }
+        self.consolidate();
+    }
+
+    /// Removes the submenu at a path.
+    fn take_submenu(&mut self, path: &str) -> Option<MenuDef> {
+        let (parent, name) = match path.rsplit_once('/') {
+            Some((parent, name)) => (self.find_mut(parent)?, name),
+            None => (self, path),
+        };
+        let index = parent
+            .submenus
+            .iter()
+            .position(|submenu| submenu.name == name)?;
+        Some(parent.submenus.remove(index))
+    }
+
+    fn find_mut(&mut self, path: &str) -> Option<&mut MenuDef> {
+        path.split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(self, |menu, name| {
+                menu.submenus
+                    .iter_mut()
+                    .find(|submenu| submenu.name == name)
+            })
+    }
+
+    /// Gets the submenu at a path, creating missing menus.
+    fn submenu_mut(&mut self, path: &str) -> &mut MenuDef {
+        path.split('/')
+            .filter(|name| !name.is_empty())
+            .fold(self, |menu, name| {
+                let index = match menu.submenus.iter().position(|s| s.name == name) {
+                    Some(index) => index,
+                    None => {
+                        menu.submenus.push(MenuDef {
+                            name: name.to_string(),
+                            ..MenuDef::default()
+                        });
+                        menu.submenus.len() - 1
+                    }
+                };
+                &mut menu.submenus[index]
+            })
+    }
+}
+
+/// Drops all but the last occurrence of each item.
+fn keep_last<T: PartialEq>(items: &mut Vec<T>) {
+    let mut index = 0;
+    while index < items.len() {
+        if items[index + 1..].contains(&items[index]) {
+            items.remove(index);
+        } else {
+            index += 1;
+        }
+    }
+}
+
+// ============================================================================
+// Loading
+// ============================================================================
+
+/// Reads menu files, keeping track of the files merged so far.
+struct Loader {
+    visited: Vec<PathBuf>,
+}
+
+impl Loader {
+    fn parse_file(&mut self, content: &str, path: Option<&Path>) -> Result<MenuDef> {
+        let root = parse_xml(content)?;
+        if root.name != "Menu" {
+            return Err(invalid_menu(format!(
+                "root element is <{}>, expected <Menu>",
+                root.name
+            )));
+        }
+        let base = path
+            .and_then(Path::parent)
+            .map_or_else(PathBuf::new, Path::to_path_buf);
+        self.parse_menu(&root, &base, path)
+    }
+
+    fn parse_menu(
+        &mut self,
+        element: &Element,
+        base: &Path,
+        file: Option<&Path>,
+    ) -> Result<MenuDef> {
+        let mut menu = MenuDef::default();
+        for child in &element.children {
+            let text = child.text.trim();
+            match child.name.as_str() {
+                "Name" => menu.name = text.to_string(),
+                "AppDir" => menu.app_dirs.push(base.join(text)),
+                "DefaultAppDirs" => {
+                    menu.app_dirs
+                        .extend(discovery::application_dirs().into_iter().rev());
+                }
+                "DirectoryDir" => menu.directory_dirs.push(base.join(text)),
+                "DefaultDirectoryDirs" => {
+                    menu.directory_dirs
+                        .extend(discovery::directory_dirs().into_iter().rev());
+                }
+                "Directory" => menu.directories.push(text.to_string()),
+                "Include" => menu.rules.push((true, Rule::Or(parse_rules(child)?))),
+                "Exclude" => menu.rules.push((false, Rule::Or(parse_rules(child)?))),
+                "OnlyUnallocated" => menu.only_unallocated = Some(true),
+                "NotOnlyUnallocated" => menu.only_unallocated = Some(false),
+                "Deleted" => menu.deleted = Some(true),
+                "NotDeleted" => menu.deleted = Some(false),
+                "Menu" => menu.submenus.push(self.parse_menu(child, base, file)?),
+                "Move" => {
+                    let old = child.child_text("Old");
+                    let new = child.child_text("New");
+                    if let (Some(old), Some(new)) = (old, new) {
+                        menu.moves.push((old.to_string(), new.to_string()));
+                    }
+                }
+                "MergeFile" => {
+                    let path = if child.attribute("type") == Some("parent") {
+                        file.and_then(parent_file)
+                    } else {
+                        Some(base.join(text))
+                    };
+                    if let Some(merged) = path.and_then(|path| self.merge_file(&path)) {
+                        menu.merge(merged);
+                    }
+                }
+                "MergeDir" => self.merge_dir(&mut menu, &base.join(text)),
+                "DefaultMergeDirs" => {
+                    let name = file
+                        .and_then(Path::file_stem)
+                        .and_then(|stem| stem.to_str())
+                        .unwrap_or("applications");
+                    let prefix = std::env::var("XDG_MENU_PREFIX").unwrap_or_default();
+                    let name = name.strip_prefix(prefix.as_str()).unwrap_or(name);
+                    let merged = format!("menus/{}-merged", name);
+                    let mut dirs = discovery::config_dirs();
+                    dirs.reverse();
+                    for dir in dirs {
+                        self.merge_dir(&mut menu, &dir.join(&merged));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(menu)
+    }
+
+    /// Reads a merged file, skipping files that are missing, invalid, or
+    /// already being merged.
+    fn merge_file(&mut self, path: &Path) -> Option<MenuDef> {
+        if self.visited.len() >= MAX_MERGE_DEPTH || self.visited.iter().any(|known| known == path) {
+            return None;
+        }
+        let content = fs::read_to_string(path).ok()?;
+        self.visited.push(path.to_path_buf());
+        let merged = self.parse_file(&content, Some(path)).ok();
+        self.visited.pop();
+        merged
+    }
+
+    /// Merges the `.menu` files of a directory in name order.
+    fn merge_dir(&mut self, menu: &mut MenuDef, dir: &Path) {
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return;
+        };
+        let mut paths: Vec<PathBuf> = read_dir
+            .flatten()
+            .map(|dir_entry| dir_entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "menu"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            if let Some(merged) = self.merge_file(&path) {
+                menu.merge(merged);
+            }
+        }
+    }
+}
+
+/// The `<Include>` or `<Exclude>` rules of an element.
+fn parse_rules(element: &Element) -> Result<Vec<Rule>> {
+    element
+        .children
+        .iter()
+        .map(|child| {
+            let text = child.text.trim().to_string();
+            Ok(match child.name.as_str() {
+                "Filename" => Rule::Filename(text),
+                "Category" => Rule::Category(text),
+                "All" => Rule::All,
+                "And" => Rule::And(parse_rules(child)?),
+                "Or" => Rule::Or(parse_rules(child)?),
+                "Not" => Rule::Not(parse_rules(child)?),
+                other => return Err(invalid_menu(format!("unknown rule <{}>", other))),
+            })
+        })
+        .collect()
+}
+
+/// The file with the same path below `menus` in the next, less important
+/// configuration directory, for `<MergeFile type="parent">`.
+fn parent_file(file: &Path) -> Option<PathBuf> {
+    let config_dirs = discovery::config_dirs();
+    let (index, relative) = config_dirs
+        .iter()
+        .enumerate()
+        .find_map(|(index, dir)| Some((index, file.strip_prefix(dir.join("menus")).ok()?)))?;
+    config_dirs[index + 1..]
+        .iter()
+        .map(|dir| dir.join("menus").join(relative))
+        .find(|path| path.is_file())
+}
+
+fn invalid_menu(message: impl Into<String>) -> DesktopEntryError {
+    DesktopEntryError::InvalidMenu(message.into())
+}
+
+// ============================================================================
+// Populating
+// ============================================================================
+
+struct Builder {
+    current_desktops: Vec<String>,
+    /// Parsed desktop files, `None` for invalid ones
+    parsed: HashMap<PathBuf, Option<DesktopEntry>>,
+    /// IDs included by menus without `<OnlyUnallocated>`
+    allocated: HashSet<DesktopFileId>,
+}
+
+impl Builder {
+    /// Populates a menu and its submenus, except those with
+    /// `<OnlyUnallocated>`, which are left empty.
+    fn populate(
+        &mut self,
+        def: &MenuDef,
+        app_dirs: &[PathBuf],
+        directory_dirs: &[PathBuf],
+        unallocated_only: bool,
+    ) -> Menu {
+        let app_dirs = [app_dirs, &def.app_dirs].concat();
+        let directory_dirs = [directory_dirs, &def.directory_dirs].concat();
+        let mut menu = Menu {
+            name: def.name.clone(),
+            directory: self.directory(def, &directory_dirs),
+            entries: Vec::new(),
+            submenus: Vec::new(),
+        };
+        if def.only_unallocated != Some(true) && !unallocated_only {
+            let items = self.items(def, &app_dirs);
+            self.allocated
+                .extend(items.iter().map(|item| item.id.clone()));
+            menu.entries = self.visible(items);
+        }
+        menu.submenus = def
+            .submenus
+            .iter()
+            .filter(|submenu| submenu.deleted != Some(true))
+            .map(|submenu| self.populate(submenu, &app_dirs, &directory_dirs, unallocated_only))
+            .collect();
+        menu
+    }
+
+    /// Fills the `<OnlyUnallocated>` menus, once every other menu is
+    /// populated.
+    fn populate_unallocated(
+        &mut self,
+        def: &MenuDef,
+        app_dirs: &[PathBuf],
+        directory_dirs: &[PathBuf],
+        menu: &mut Menu,
+    ) {
+        let app_dirs = [app_dirs, &def.app_dirs].concat();
+        let directory_dirs = [directory_dirs, &def.directory_dirs].concat();
+        if def.only_unallocated == Some(true) {
+            let items = self
+                .items(def, &app_dirs)
+                .into_iter()
+                .filter(|item| !self.allocated.contains(&item.id))
+                .collect();
+            menu.entries = self.visible(items);
+        }
+        let submenus = def
+            .submenus
+            .iter()
+            .filter(|submenu| submenu.deleted != Some(true));
+        for (submenu_def, submenu) in submenus.zip(&mut menu.submenus) {
+            self.populate_unallocated(submenu_def, &app_dirs, &directory_dirs, submenu);
+        }
+    }
+
+    /// The entries selected by the rules of a menu.
+    fn items(&mut self, def: &MenuDef, app_dirs: &[PathBuf]) -> Vec<MenuItem> {
+        // Later directories take precedence
+        let mut pool = BTreeMap::new();
+        for dir in app_dirs {
+            let mut files = Vec::new();
+            discovery::collect_desktop_files(dir, dir, &mut files);
+            pool.extend(files);
+        }
+
+        let mut selected: BTreeMap<DesktopFileId, PathBuf> = BTreeMap::new();
+        for (include, rule) in &def.rules {
+            if *include {
+                for (id, path) in &pool {
+                    if !selected.contains_key(id)
+                        && self
+                            .entry(path)
+                            .is_some_and(|entry| rule.matches(id, entry))
+                    {
+                        selected.insert(id.clone(), path.clone());
+                    }
+                }
+            } else {
+                let excluded: Vec<DesktopFileId> = selected
+                    .iter()
+                    .filter(|(id, path)| {
+                        self.entry(path)
+                            .is_some_and(|entry| rule.matches(id, entry))
+                    })
+                    .map(|(id, _)| id.clone())
+                    .collect();
+                for id in excluded {
+                    selected.remove(&id);
+                }
+            }
+        }
+
+        selected
+            .into_iter()
+            .filter_map(|(id, path)| {
+                let entry = self.entry(&path)?.clone();
+                Some(MenuItem { id, path, entry })
+            })
+            .collect()
+    }
+
+    /// The items to show in the current desktops.
+    fn visible(&self, items: Vec<MenuItem>) -> Vec<MenuItem> {
+        items
+            .into_iter()
+            .filter(|item| item.entry.should_show(&self.current_desktops))
+            .collect()
+    }
+
+    /// The first `<Directory>`, most important first, found in the
+    /// directory directories.
+    fn directory(&mut self, def: &MenuDef, directory_dirs: &[PathBuf]) -> Option<DesktopEntry> {
+        def.directories.iter().rev().find_map(|name| {
+            directory_dirs
+                .iter()
+                .rev()
+                .map(|dir| dir.join(name))
+                .find(|path| path.is_file())
+                .and_then(|path| self.entry(&path).cloned())
+        })
+    }
+
+    fn entry(&mut self, path: &Path) -> Option<&DesktopEntry> {
+        self.parsed
+            .entry(path.to_path_buf())
+            .or_insert_with(|| DesktopEntry::parse_file(path).ok())
+            .as_ref()
+    }
+}
+
+// ============================================================================
+// XML
+// ============================================================================
+
+/// An XML element; text is concatenated and mixed content is not kept in
+/// order, which menu files do not need.
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn child_text(&self, name: &str) -> Option<&str> {
+        self.children
+            .iter()
+            .find(|child| child.name == name)
+            .map(|child| child.text.trim())
+    }
+}
+
+/// Parses an XML document into its root element.
+///
+/// Supports the subset of XML used by menu files: the XML declaration, a
+/// `DOCTYPE`, comments, processing instructions, `CDATA` sections, and the
+/// predefined and numeric character references.
+fn parse_xml(input: &str) -> Result<Element> {
+    let mut parser = XmlParser { input, pos: 0 };
+    parser.skip_misc()?;
+    if parser.rest().starts_with("<!DOCTYPE") {
+        parser.skip_doctype()?;
+        parser.skip_misc()?;
+    }
+    let root = parser.parse_element(1)?;
+    parser.skip_misc()?;
+    if parser.pos < input.len() {
+        return Err(parser.error("unexpected content after the root element"));
+    }
+    Ok(root)
+}
+
+struct XmlParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl XmlParser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn error(&self, message: &str) -> DesktopEntryError {
+        let line = self.input[..self.pos].matches('\n').count() + 1;
+        invalid_menu(format!("{} at line {}", message, line))
+    }
+
+    /// Skips past the next `end`.
+    fn skip_past(&mut self, end: &str, what: &str) -> Result<()> {
+        match self.rest().find(end) {
+            Some(index) => {
+                self.pos += index + end.len();
+                Ok(())
+            }
+            None => Err(self.error(&format!("unterminated {}", what))),
+        }
+    }
+
+    /// Skips whitespace, comments, and processing instructions.
+    fn skip_misc(&mut self) -> Result<()> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<!--") {
+                self.skip_past("-->", "comment")?;
+            } else if self.rest().starts_with("<?") {
+                self.skip_past("?>", "processing instruction")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_doctype(&mut self) -> Result<()> {
+        // Menu files use external DTDs; an internal subset ends with "]>"
+        let end = self.rest().find('>');
+        match (self.rest().find('['), end) {
+            (Some(open), Some(end)) if open < end => self.skip_past("]>", "DOCTYPE"),
+            _ => self.skip_past(">", "DOCTYPE"),
+        }
+    }
+
+    fn parse_name(&mut self) -> Result<String> {
+        let length = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || matches!(c, '>' | '/' | '='))
+            .unwrap_or(self.rest().len());
+        if length == 0 {
+            return Err(self.error("expected a name"));
+        }
+        let name = self.rest()[..length].to_string();
+        self.pos += length;
+        Ok(name)
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos = self.input.len() - self.rest().trim_start().len();
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if !self.rest().starts_with(token) {
+            return Err(self.error(&format!("expected '{}'", token)));
+        }
+        self.pos += token.len();
+        Ok(())
+    }
+
+    fn parse_element(&mut self, depth: usize) -> Result<Element> {
+        if depth > MAX_ELEMENT_DEPTH {
+            return Err(self.error("elements nested too deeply"));
+        }
+        self.expect("<")?;
+        let mut element = Element {
+            name: self.parse_name()?,
+            ..Element::default()
+        };
+
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let name = self.parse_name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => return Err(self.error("expected a quoted attribute value")),
+            };
+            self.pos += 1;
+            let Some(length) = self.rest().find(quote) else {
+                return Err(self.error("unterminated attribute value"));
+            };
+            let value = self.decode(&self.rest()[..length])?;
+            self.pos += length + 1;
+            element.attributes.push((name, value));
+        }
+
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error(&format!("unclosed <{}>", element.name)));
+            }
+            if let Some(rest) = rest.strip_prefix("</") {
+                let name_end = rest.find('>').unwrap_or(rest.len());
+                if rest[..name_end].trim() != element.name {
+                    return Err(self.error(&format!("mismatched end tag for <{}>", element.name)));
+                }
+                self.pos += 2 + name_end;
+                self.expect(">")?;
+                return Ok(element);
+            }
+            if rest.starts_with("<!--") {
+                self.skip_past("-->", "comment")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>", "processing instruction")?;
+            } else if let Some(rest) = rest.strip_prefix("<![CDATA[") {
+                let Some(length) = rest.find("]]>") else {
+                    return Err(self.error("unterminated CDATA section"));
+                };
+                element.text.push_str(&rest[..length]);
+                self.pos += "<![CDATA[".len() + length + 3;
+            } else if rest.starts_with('<') {
+                element.children.push(self.parse_element(depth + 1)?);
+            } else {
+                let length = rest.find('<').unwrap_or(rest.len());
+                let text = self.decode(&rest[..length])?;
+                element.text.push_str(&text);
+                self.pos += length;
+            }
+        }
+    }
+
+    /// Decodes character references.
+    fn decode(&self, text: &str) -> Result<String> {
+        let mut decoded = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('&') {
+            decoded.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find(';') else {
+                return Err(self.error("unterminated character reference"));
+            };
+            let reference = &rest[start + 1..start + end];
+            let c = match reference {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => reference
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| reference.strip_prefix('#').map(str::parse))
+                    .and_then(|code| code.ok())
+                    .and_then(char::from_u32),
+            };
+            let Some(c) = c else {
+                return Err(self.error(&format!("unknown character reference '&{};'", reference)));
+            };
+            decoded.push(c);
+            rest = &rest[start + end + 1..];
+        }
+        decoded.push_str(rest);
+        Ok(decoded)
+    }
+}
@@ -0,0 +1,349 @@
+//! Default applications and MIME type associations.
+//!
+//! Implements the MIME Applications Associations specification: users and
+//! administrators choose default applications and add or remove
+//! associations in `mimeapps.list` files, which are looked up in the XDG
+//! configuration and data directories, optionally prefixed with the name of
+//! the current desktop (e.g. `gnome-mimeapps.list`).
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::mimeapps::MimeAppsList;
+//!
+//! let list = MimeAppsList::parse(
+//!     "[Default Applications]\ntext/plain=org.gnome.TextEditor.desktop;\n\n\
+//!      [Removed Associations]\ntext/plain=vim.desktop;\n",
+//! );
+//!
+//! assert_eq!(list.default_applications["text/plain"], ["org.gnome.TextEditor.desktop"]);
+//! assert_eq!(list.removed_associations["text/plain"], ["vim.desktop"]);
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::Result;
+use crate::discovery::{self, DesktopFileId};
+use crate::environment;
+
+/// Name of the mimeapps file in each directory.
+const MIMEAPPS_LIST: &str = "mimeapps.list";
+
+const DEFAULT_APPLICATIONS_GROUP: &str = "Default Applications";
+const ADDED_ASSOCIATIONS_GROUP: &str = "Added Associations";
+const REMOVED_ASSOCIATIONS_GROUP: &str = "Removed Associations";
+
+/// The contents of a single `mimeapps.list` file.
+///
+/// Each group maps a MIME type to desktop file IDs, in order of preference.
+/// Unknown groups are kept as they are when the file is written back.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MimeAppsList {
+    /// `[Default Applications]`: the preferred application for each type
+    pub default_applications: BTreeMap<String, Vec<String>>,
+    /// `[Added Associations]`: applications to offer in addition to the
+    /// `MimeType` keys of desktop entries
+    pub added_associations: BTreeMap<String, Vec<String>>,
+    /// `[Removed Associations]`: applications not to offer even though their
+    /// desktop entries claim the type
+    pub removed_associations: BTreeMap<String, Vec<String>>,
+    other_groups: Vec<(String, Vec<String>)>,
+}
+
+impl MimeAppsList {
+    /// Parses the contents of a `mimeapps.list` file.
+    ///
+    /// Malformed lines are ignored, like desktop environments do.
+    pub fn parse(content: &str) -> Self {
+        let mut list = Self::default();
+        let mut group: Option<&str> = None;
+
+        for line in content.lines() {
+            let trimmed = line.trim();
+            if let Some(name) = trimmed
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                if ![
+                    DEFAULT_APPLICATIONS_GROUP,
+                    ADDED_ASSOCIATIONS_GROUP,
+                    REMOVED_ASSOCIATIONS_GROUP,
+                ]
+                .contains(&name)
+                {
+                    list.other_groups.push((name.to_string(), Vec::new()));
+                }
+                group = Some(name);
+                continue;
+            }
+
+            let target = match group {
+                Some(DEFAULT_APPLICATIONS_GROUP) => &mut list.default_applications,
+                Some(ADDED_ASSOCIATIONS_GROUP) => &mut list.added_associations,
+                Some(REMOVED_ASSOCIATIONS_GROUP) => &mut list.removed_associations,
+                Some(_) => {
+                    if let Some((_, lines)) = list.other_groups.last_mut()
+                        && !trimmed.is_empty()
+                    {
+                        lines.push(line.to_string());
+                    }
+                    continue;
+                }
+                None => continue,
+            };
+            if trimmed.starts_with('#') {
+                continue;
+            }
+            let Some((mime_type, value)) = trimmed.split_once('=') else {
+                continue;
+            };
+            let ids = target.entry(mime_type.trim().to_string()).or_default();
+            for id in line_ids(value) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+
+        list
+    }
+
+    /// Reads a `mimeapps.list` file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// Serializes the list, with MIME types sorted within each group.
+    pub fn serialize(&self) -> String {
+        let mut output = String::new();
+        for (name, group) in [
+            (DEFAULT_APPLICATIONS_GROUP, &self.default_applications),
+            (ADDED_ASSOCIATIONS_GROUP, &self.added_associations),
+            (REMOVED_ASSOCIATIONS_GROUP, &self.removed_associations),
+        ] {
+            let group: Vec<_> = group.iter().filter(|(_, ids)| !ids.is_empty()).collect();
+            if group.is_empty() {
+                continue;
+            }
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            let _ = writeln!(output, "[{}]", name);
+            for (mime_type, ids) in group {
+                let _ = writeln!(output, "{}={};", mime_type, ids.join(";"));
+            }
+        }
+        for (name, lines) in &self.other_groups {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            let _ = writeln!(output, "[{}]", name);
+            for line in lines {
+                let _ = writeln!(output, "{}", line);
+            }
+        }
+        output
+    }
+
+    /// Writes the list to a file, atomically replacing it.
+    pub fn write_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        crate::write_atomically(path, |writer| writer.write_all(self.serialize().as_bytes()))?;
+        Ok(())
+    }
+}
+
+/// Splits a list of desktop file IDs, skipping empty items.
+fn line_ids(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+}
+
+/// The `mimeapps.list` files in order of precedence.
+///
+/// For each configuration directory and then each `applications` data
+/// directory, the desktop-specific files (`<desktop>-mimeapps.list`, with
+/// the lowercased names from `$XDG_CURRENT_DESKTOP`) come before the generic
+/// `mimeapps.list`. The first path is the user's own file.
+pub fn mimeapps_paths(current_desktops: &[impl AsRef<str>]) -> Vec<PathBuf> {
+    let dirs = discovery::config_dirs()
+        .into_iter()
+        .chain(discovery::application_dirs());
+
+    let mut paths = Vec::new();
+    for dir in dirs {
+        for desktop in current_desktops {
+            let desktop = desktop.as_ref().to_lowercase();
+            paths.push(dir.join(format!("{}-{}", desktop, MIMEAPPS_LIST)));
+        }
+        paths.push(dir.join(MIMEAPPS_LIST));
+    }
+    paths
+}
+
+/// The merged associations of all `mimeapps.list` files.
+///
+/// # Examples
+///
+/// ```no_run
+/// use xdg_desktop_entry::mimeapps::MimeApps;
+///
+/// let mut mime_apps = MimeApps::load();
+/// if let Some(id) = mime_apps.default_app_for("text/plain") {
+///     println!("Text files open with {}", id);
+/// }
+/// mime_apps.set_default_app("image/png", "org.gnome.Loupe.desktop").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct MimeApps {
+    user_path: PathBuf,
+    lists: Vec<(PathBuf, MimeAppsList)>,
+    application_dirs: Vec<PathBuf>,
+}
+
+impl MimeApps {
+    /// Loads the `mimeapps.list` files for the desktops named in
+    /// `$XDG_CURRENT_DESKTOP`, with applications looked up in
+    /// [`application_dirs`](discovery::application_dirs).
+    pub fn load() -> Self {
+        let current_desktops = environment::current_desktop();
+        let user_path = discovery::config_dirs()
+            .into_iter()
+            .next()
+            .unwrap_or_default()
+            .join(MIMEAPPS_LIST);
+        let mut mime_apps = Self::load_from(
+            mimeapps_paths(&current_desktops),
+            discovery::application_dirs(),
+        );
+        mime_apps.user_path = user_path;
+        mime_apps
+    }
+
+    /// Loads the given `mimeapps.list` files, most important first, with
+    /// applications looked up in the given `applications` directories.
+    ///
+    /// Missing or unreadable files are skipped. The first path is the file
+    /// changed by [`set_default_app`](Self::set_default_app).
+    pub fn load_from(paths: Vec<PathBuf>, application_dirs: Vec<PathBuf>) -> Self {
+        let user_path = paths.first().cloned().unwrap_or_default();
+        let lists = paths
+            .into_iter()
+            .filter_map(|path| {
+                let list = MimeAppsList::load(&path).ok()?;
+                Some((path, list))
+            })
+            .collect();
+        Self {
+            user_path,
+            lists,
+            application_dirs,
+        }
+    }
+
+    /// The loaded files with their contents, most important first.
+    pub fn lists(&self) -> &[(PathBuf, MimeAppsList)] {
+        &self.lists
+    }
+
+    /// The default application for a MIME type.
+    ///
+    /// The first installed application listed in `[Default Applications]`
+    /// of the most important file defining one wins. Without a usable
+    /// default, the most preferred of the
+    /// [`added_associations`](Self::added_associations) is used.
+    pub fn default_app_for(&self, mime_type: &str) -> Option<DesktopFileId> {
+        self.lists
+            .iter()
+            .flat_map(|(_, list)| list.default_applications.get(mime_type))
+            .flatten()
+            .map(DesktopFileId::new)
+            .find(|id| self.is_installed(id))
+            .or_else(|| self.added_associations(mime_type).into_iter().next())
+    }
+
+    /// Installed applications added for a MIME type, in order of preference.
+    ///
+    /// An association removed in a file does not apply to the files less
+    /// important than it.
+    pub fn added_associations(&self, mime_type: &str) -> Vec<DesktopFileId> {
+        let mut removed = HashSet::new();
+        let mut added = Vec::new();
+        for (_, list) in &self.lists {
+            for id in list.added_associations.get(mime_type).into_iter().flatten() {
+                let id = DesktopFileId::new(id.as_str());
+                if !removed.contains(&id) && !added.contains(&id) && self.is_installed(&id) {
+                    added.push(id);
+                }
+            }
+            removed.extend(
+                list.removed_associations
+                    .get(mime_type)
+                    .into_iter()
+                    .flatten()
+                    .map(|id| DesktopFileId::new(id.as_str())),
+            );
+        }
+        added
+    }
+
+    /// Whether any file removes the association of an application with a
+    /// MIME type.
+    ///
+    /// Removals always override the `MimeType` key of desktop entries.
+    pub fn is_removed(&self, mime_type: &str, id: &DesktopFileId) -> bool {
+        self.lists.iter().any(|(_, list)| {
+            list.removed_associations
+                .get(mime_type)
+                .is_some_and(|ids| ids.iter().any(|removed| removed == id.as_str()))
+        })
+    }
+
+    /// Makes an application the default for a MIME type in the user's
+    /// `mimeapps.list`, and writes the file.
+    ///
+    /// The application is also added to the associations of the type, and
+    /// any removal of that association in the user's file is dropped.
+    pub fn set_default_app(&mut self, mime_type: &str, id: impl Into<String>) -> Result<()> {
+        let id = id.into();
+        let index = match self
+            .lists
+            .iter()
+            .position(|(path, _)| *path == self.user_path)
+        {
+            Some(index) => index,
+            None => {
+                self.lists
+                    .insert(0, (self.user_path.clone(), MimeAppsList::default()));
+                0
+            }
+        };
+        let list = &mut self.lists[index].1;
+
+        list.default_applications
+            .insert(mime_type.to_string(), vec![id.clone()]);
+        let added = list
+            .added_associations
+            .entry(mime_type.to_string())
+            .or_default();
+        added.retain(|added| *added != id);
+        added.insert(0, id.clone());
+        if let Some(removed) = list.removed_associations.get_mut(mime_type) {
+            removed.retain(|removed| *removed != id);
+        }
+
+        list.write_file(&self.user_path)
+    }
+
+    fn is_installed(&self, id: &DesktopFileId) -> bool {
+        id.resolve_in(&self.application_dirs).is_some()
+    }
+}
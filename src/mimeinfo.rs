@@ -0,0 +1,223 @@
+//! The `mimeinfo.cache` files of `applications` directories.
+//!
+//! `update-desktop-database` records, for each MIME type, the desktop file
+//! IDs of the entries in a directory listing it in their `MimeType` key.
+//! Reading the cache avoids parsing every desktop file to answer
+//! [`Applications::handlers_for_mime`](crate::discovery::Applications::handlers_for_mime).
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::mimeinfo::MimeInfoCache;
+//!
+//! let cache = MimeInfoCache::parse(
+//!     "[MIME Cache]\nimage/png=org.gnome.Loupe.desktop;gimp.desktop;\n",
+//! );
+//!
+//! assert_eq!(cache.handlers("image/png"), ["org.gnome.Loupe.desktop", "gimp.desktop"]);
+//! assert!(cache.handlers("text/plain").is_empty());
+//! ```
+//!
+//! [`MimeInfoCache::update`] regenerates the cache of a directory, like
+//! `update-desktop-database` does.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::discovery::DesktopFileId;
+use crate::{DesktopEntry, Result};
+
+/// Name of the cache file in an `applications` directory.
+pub const MIMEINFO_CACHE: &str = "mimeinfo.cache";
+
+const MIME_CACHE_GROUP: &str = "MIME Cache";
+
+/// The contents of a `mimeinfo.cache` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MimeInfoCache {
+    /// Desktop file IDs of the handlers of each MIME type
+    pub mime_types: BTreeMap<String, Vec<String>>,
+}
+
+impl MimeInfoCache {
+    /// Parses the contents of a `mimeinfo.cache` file.
+    ///
+    /// Lines outside the `[MIME Cache]` group and malformed lines are
+    /// ignored.
+    pub fn parse(content: &str) -> Self {
+        let mut cache = Self::default();
+        let mut in_cache_group = false;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                in_cache_group = name == MIME_CACHE_GROUP;
+                continue;
+            }
+            if !in_cache_group || line.starts_with('#') {
+                continue;
+            }
+            let Some((mime_type, ids)) = line.split_once('=') else {
+                continue;
+            };
+            let handlers = cache.mime_types.entry(mime_type.to_string()).or_default();
+            handlers.extend(ids.split(';').filter(|id| !id.is_empty()).map(String::from));
+        }
+
+        cache
+    }
+
+    /// Reads a `mimeinfo.cache` file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Reads the cache of an `applications` directory, unless it is missing
+    /// or stale.
+    ///
+    /// See [`is_fresh`](Self::is_fresh).
+    pub fn load_fresh(applications_dir: impl AsRef<Path>) -> Option<Self> {
+        let dir = applications_dir.as_ref();
+        if !Self::is_fresh(dir) {
+            return None;
+        }
+        Self::load(dir.join(MIMEINFO_CACHE)).ok()
+    }
+
+    /// Whether the cache of an `applications` directory exists and is at
+    /// least as recent as the desktop files of the directory, and its
+    /// subdirectories with the desktop files in them.
+    ///
+    /// As with `update-desktop-database`, the time of the directory itself is
+    /// left out, since writing the cache updates it.
+    pub fn is_fresh(applications_dir: impl AsRef<Path>) -> bool {
+        let dir = applications_dir.as_ref();
+        let Some(cache_time) = modified(&dir.join(MIMEINFO_CACHE)) else {
+            return false;
+        };
+        newest_modification(dir).is_some_and(|newest| newest <= cache_time)
+    }
+
+    /// Builds the cache of an `applications` directory from its desktop
+    /// files.
+    ///
+    /// Files that fail to parse or are marked `Hidden=true` are skipped, as
+    /// are values of `MimeType` that are not of the form `type/subtype`.
+    pub fn from_dir(applications_dir: impl AsRef<Path>) -> Self {
+        let dir = applications_dir.as_ref();
+        let mut files = Vec::new();
+        crate::discovery::collect_desktop_files(dir, dir, &mut files);
+
+        let mut cache = Self::default();
+        for (id, path) in files {
+            let Ok(entry) = DesktopEntry::parse_file(&path) else {
+                continue;
+            };
+            if entry.hidden == Some(true) {
+                continue;
+            }
+            for mime_type in entry.mime_type.iter().flatten() {
+                if is_valid_mime_type(mime_type) {
+                    cache.add(mime_type, &id);
+                }
+            }
+        }
+        for handlers in cache.mime_types.values_mut() {
+            handlers.sort();
+            handlers.dedup();
+        }
+        cache
+    }
+
+    fn add(&mut self, mime_type: &str, id: &DesktopFileId) {
+        self.mime_types
+            .entry(mime_type.to_string())
+            .or_default()
+            .push(id.to_string());
+    }
+
+    /// Serializes the cache in the format of `update-desktop-database`.
+    pub fn serialize(&self) -> String {
+        let mut output = format!("[{}]\n", MIME_CACHE_GROUP);
+        for (mime_type, handlers) in &self.mime_types {
+            let _ = write!(output, "{}=", mime_type);
+            for handler in handlers {
+                let _ = write!(output, "{};", handler);
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    /// Writes the cache to a file, atomically replacing it.
+    pub fn write_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        crate::write_atomically(path.as_ref(), |writer| {
+            writer.write_all(self.serialize().as_bytes())
+        })?;
+        Ok(())
+    }
+
+    /// Regenerates the `mimeinfo.cache` of an `applications` directory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xdg_desktop_entry::mimeinfo::MimeInfoCache;
+    ///
+    /// let cache = MimeInfoCache::update("/usr/share/applications").unwrap();
+    /// println!("{} MIME types", cache.mime_types.len());
+    /// ```
+    pub fn update(applications_dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = applications_dir.as_ref();
+        let cache = Self::from_dir(dir);
+        cache.write_file(dir.join(MIMEINFO_CACHE))?;
+        Ok(cache)
+    }
+
+    /// The desktop file IDs of the handlers of a MIME type.
+    pub fn handlers(&self, mime_type: &str) -> &[String] {
+        self.mime_types
+            .get(mime_type)
+            .map_or(&[], |handlers| handlers.as_slice())
+    }
+}
+
+/// Whether a value has the `type/subtype` form of a MIME type.
+fn is_valid_mime_type(mime_type: &str) -> bool {
+    mime_type.split_once('/').is_some_and(|(media, subtype)| {
+        !media.is_empty()
+            && !subtype.is_empty()
+            && !subtype.contains('/')
+            && !mime_type.contains(|c: char| c.is_whitespace() || c.is_control())
+    })
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+/// The most recent modification time of the subdirectories of a directory
+/// and of the desktop files in them, not counting the directory itself.
+fn newest_modification(dir: &Path) -> Option<SystemTime> {
+    let mut newest = SystemTime::UNIX_EPOCH;
+    for dir_entry in fs::read_dir(dir).ok()?.flatten() {
+        let path = dir_entry.path();
+        let time = if path.is_dir() {
+            newest_modification(&path)
+                .zip(modified(&path))
+                .map(|(a, b)| a.max(b))
+        } else if path.extension().is_some_and(|ext| ext == "desktop") {
+            modified(&path)
+        } else {
+            continue;
+        };
+        newest = newest.max(time?);
+    }
+    Some(newest)
+}
@@ -0,0 +1,167 @@
+//! Detection of how an application was packaged.
+//!
+//! Software centers and launchers badge applications installed from
+//! Flatpak, Snap, or AppImage. The desktop files those formats export are
+//! recognizable by keys they add and by the command lines they run.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::DesktopEntry;
+//! use xdg_desktop_entry::packaging::PackagingFormat;
+//!
+//! let entry = DesktopEntry::parse(
+//!     "[Desktop Entry]\nType=Application\nName=Firefox\n\
+//!      Exec=env BAMF_DESKTOP_FILE_HINT=/var/lib/snapd/desktop/applications/firefox_firefox.desktop /snap/bin/firefox %u\n\
+//!      X-SnapInstanceName=firefox\n",
+//! )
+//! .unwrap();
+//! assert_eq!(entry.packaging_format(), PackagingFormat::Snap);
+//! ```
+
+use std::fmt;
+use std::path::Path;
+
+use crate::DesktopEntry;
+use crate::exec::ExecString;
+
+/// Key naming the snap instance in desktop files exported by snapd.
+pub const SNAP_INSTANCE_NAME_KEY: &str = "X-SnapInstanceName";
+
+/// Keys added by AppImage desktop integration tools such as appimaged.
+const APPIMAGE_KEYS: &[&str] = &[
+    "X-AppImage-Version",
+    "X-AppImage-Name",
+    "X-AppImage-BuildId",
+];
+
+/// Directory of the snap command wrappers.
+const SNAP_BIN_DIR: &str = "/snap/bin/";
+
+/// The packaging format an application was installed from.
+///
+/// Returned by [`DesktopEntry::packaging_format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PackagingFormat {
+    /// A program installed on the host, e.g. by the distribution.
+    Native,
+    /// A Flatpak application.
+    Flatpak,
+    /// A snap.
+    Snap,
+    /// An AppImage.
+    AppImage,
+    /// The entry runs no program, e.g. a link or a D-Bus activated
+    /// application without an `Exec` key.
+    Unknown,
+}
+
+impl PackagingFormat {
+    /// Guesses the format from where a desktop file is installed, e.g.
+    /// `/var/lib/flatpak/exports/share/applications` or
+    /// `/var/lib/snapd/desktop/applications`.
+    ///
+    /// Returns `None` for other directories, which may hold entries of any
+    /// format.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let path = path.as_ref();
+        if path.ancestors().any(|dir| dir.ends_with("flatpak/exports")) {
+            Some(Self::Flatpak)
+        } else if path.starts_with("/var/lib/snapd/desktop") {
+            Some(Self::Snap)
+        } else {
+            None
+        }
+    }
+
+    /// Lowercase name of the format, e.g. `flatpak`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Native => "native",
+            Self::Flatpak => "flatpak",
+            Self::Snap => "snap",
+            Self::AppImage => "appimage",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for PackagingFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl DesktopEntry {
+    /// Guesses the packaging format of the application.
+    ///
+    /// Flatpak is recognized by [`flatpak_app_id`](Self::flatpak_app_id),
+    /// snaps by `X-SnapInstanceName` or a command in `/snap/bin`, and
+    /// AppImages by `X-AppImage-*` keys or a `.AppImage` program. Commands
+    /// wrapped in `env` are looked through. Other entries with an `Exec` or
+    /// `TryExec` key are [`Native`](PackagingFormat::Native).
+    pub fn packaging_format(&self) -> PackagingFormat {
+        if self.flatpak_app_id().is_some() {
+            return PackagingFormat::Flatpak;
+        }
+        if self.unknown_string(SNAP_INSTANCE_NAME_KEY).is_some() {
+            return PackagingFormat::Snap;
+        }
+        if APPIMAGE_KEYS
+            .iter()
+            .any(|key| self.unknown_string(key).is_some())
+        {
+            return PackagingFormat::AppImage;
+        }
+
+        let programs: Vec<String> = self
+            .exec
+            .as_deref()
+            .and_then(|exec| ExecString::parse(exec).ok())
+            .and_then(|exec| wrapped_program(&exec))
+            .into_iter()
+            .chain(self.try_exec.clone())
+            .collect();
+        if programs.is_empty() {
+            return PackagingFormat::Unknown;
+        }
+        if programs
+            .iter()
+            .any(|program| program.starts_with(SNAP_BIN_DIR))
+        {
+            PackagingFormat::Snap
+        } else if programs.iter().any(|program| is_appimage(program)) {
+            PackagingFormat::AppImage
+        } else {
+            PackagingFormat::Native
+        }
+    }
+}
+
+/// The program a command line runs, looking through `env` and its
+/// options and variable assignments.
+fn wrapped_program(exec: &ExecString) -> Option<String> {
+    if Path::new(exec.program())
+        .file_name()
+        .is_none_or(|name| name != "env")
+    {
+        return Some(exec.program().to_string());
+    }
+    let mut args = exec.args().iter().map(|arg| arg.as_literal());
+    while let Some(arg) = args.next() {
+        match arg? {
+            "-u" | "--unset" | "-C" | "--chdir" => {
+                args.next();
+            }
+            arg if arg.starts_with('-') || arg.contains('=') => {}
+            program => return Some(program.to_string()),
+        }
+    }
+    None
+}
+
+fn is_appimage(program: &str) -> bool {
+    Path::new(program)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("appimage"))
+}
@@ -0,0 +1,345 @@
+//! Surgical edits of desktop files.
+//!
+//! A [`DesktopEntryPatch`] lists edits to apply to the text of a desktop
+//! file. Unlike parsing, editing, and writing back an entry, applying a patch
+//! only touches the lines it edits: every other line, including its line
+//! ending, is kept byte for byte, so the resulting diff is minimal. This
+//! suits configuration management tools, whose changes are reviewed as diffs.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::Locale;
+//! use xdg_desktop_entry::patch::DesktopEntryPatch;
+//!
+//! let content = "[Desktop Entry]\r\nType=Application\r\nName = App\r\n# Keep me\r\n\
+//!                Exec=app\r\nX-Old=1\r\n";
+//!
+//! let patch = DesktopEntryPatch::new()
+//!     .set("Desktop Entry", "Name", "Application")
+//!     .set_localized("Desktop Entry", "Name", Locale::from_string("de"), "Anwendung")
+//!     .remove("Desktop Entry", "X-Old");
+//!
+//! assert_eq!(
+//!     patch.apply_to_str(content).unwrap(),
+//!     "[Desktop Entry]\r\nType=Application\r\nName = Application\r\n\
+//!      Name[de]=Anwendung\r\n# Keep me\r\nExec=app\r\n"
+//! );
+//! ```
+
+use std::path::Path;
+
+use crate::access::{is_valid_group_name, is_valid_key};
+use crate::{DesktopEntryError, Locale, Result, escape_value, write_atomically};
+
+/// An edit of a [`DesktopEntryPatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchOp {
+    /// Sets a value, in place if the key is set, or after the other values
+    /// of the key or at the end of the group otherwise; a missing group is
+    /// added at the end of the file
+    Set {
+        /// Group holding the key
+        group: String,
+        /// Key name
+        key: String,
+        /// Locale of the value, `None` for the unlocalized value
+        locale: Option<Locale>,
+        /// New value, without escape sequences
+        value: String,
+    },
+    /// Removes a key with all of its localized values, if it is set
+    Remove {
+        /// Group holding the key
+        group: String,
+        /// Key name
+        key: String,
+    },
+    /// Renames a group, keeping its keys
+    RenameGroup {
+        /// Current group name
+        from: String,
+        /// New group name
+        to: String,
+    },
+}
+
+/// Edits to apply to the text of a desktop file, in order.
+///
+/// See the [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DesktopEntryPatch {
+    ops: Vec<PatchOp>,
+}
+
+impl DesktopEntryPatch {
+    /// Creates an empty patch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an edit setting the unlocalized value of a key.
+    pub fn set(
+        mut self,
+        group: impl Into<String>,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.ops.push(PatchOp::Set {
+            group: group.into(),
+            key: key.into(),
+            locale: None,
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Adds an edit setting the value of a key for a locale.
+    pub fn set_localized(
+        mut self,
+        group: impl Into<String>,
+        key: impl Into<String>,
+        locale: Locale,
+        value: impl Into<String>,
+    ) -> Self {
+        self.ops.push(PatchOp::Set {
+            group: group.into(),
+            key: key.into(),
+            locale: Some(locale),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Adds an edit removing a key with all of its localized values.
+    pub fn remove(mut self, group: impl Into<String>, key: impl Into<String>) -> Self {
+        self.ops.push(PatchOp::Remove {
+            group: group.into(),
+            key: key.into(),
+        });
+        self
+    }
+
+    /// Adds an edit renaming a group.
+    pub fn rename_group(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.ops.push(PatchOp::RenameGroup {
+            from: from.into(),
+            to: to.into(),
+        });
+        self
+    }
+
+    /// The edits of the patch, in order.
+    pub fn ops(&self) -> &[PatchOp] {
+        &self.ops
+    }
+
+    /// Applies the patch to the text of a desktop file.
+    ///
+    /// New lines use the line ending of the first line of the text.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::InvalidValue`] if an edit names an
+    /// invalid group or key, [`DesktopEntryError::MissingGroup`] if a
+    /// renamed group does not exist, and
+    /// [`DesktopEntryError::DuplicateGroup`] if it is renamed to an existing
+    /// group. Nothing is applied then.
+    pub fn apply_to_str(&self, content: &str) -> Result<String> {
+        let newline = if content
+            .split_once('\n')
+            .is_some_and(|(first, _)| first.ends_with('\r'))
+        {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let mut lines: Vec<String> = content.split_inclusive('\n').map(String::from).collect();
+        for op in &self.ops {
+            apply(&mut lines, op, newline)?;
+        }
+        Ok(lines.concat())
+    }
+}
+
+/// Applies a patch to a desktop file, atomically replacing it, and returns
+/// whether it changed.
+///
+/// The file is left untouched if the patch changes nothing, so applying the
+/// same patch again is a no-op.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or written, or any error
+/// [`DesktopEntryPatch::apply_to_str`] returns.
+pub fn apply_patch_to_file(path: impl AsRef<Path>, patch: &DesktopEntryPatch) -> Result<bool> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)?;
+    let patched = patch.apply_to_str(&content)?;
+    if patched == content {
+        return Ok(false);
+    }
+    write_atomically(path, |writer| writer.write_all(patched.as_bytes()))?;
+    Ok(true)
+}
+
+/// What a line of a desktop file is.
+enum Line<'a> {
+    Header(&'a str),
+    Key(&'a str, Option<Locale>),
+    Other,
+}
+
+fn classify(line: &str) -> Line<'_> {
+    let trimmed = line.trim();
+    if trimmed.starts_with('[') && trimmed.ends_with(']') {
+        return Line::Header(&trimmed[1..trimmed.len() - 1]);
+    }
+    if trimmed.starts_with('#') {
+        return Line::Other;
+    }
+    let Some((key_part, _)) = trimmed.split_once('=') else {
+        return Line::Other;
+    };
+    let key_part = key_part.trim_end();
+    match key_part.split_once('[') {
+        Some((key, locale)) => match locale.strip_suffix(']') {
+            Some(locale) => Line::Key(key.trim_end(), Some(Locale::from_string(locale))),
+            None => Line::Other,
+        },
+        None => Line::Key(key_part, None),
+    }
+}
+
+/// The lines of the first group with this name: its header and the end of
+/// its lines.
+fn find_group(lines: &[String], name: &str) -> Option<(usize, usize)> {
+    let header = lines
+        .iter()
+        .position(|line| matches!(classify(line), Line::Header(group) if group == name))?;
+    let end = lines[header + 1..]
+        .iter()
+        .position(|line| matches!(classify(line), Line::Header(_)))
+        .map_or(lines.len(), |offset| header + 1 + offset);
+    Some((header, end))
+}
+
+fn apply(lines: &mut Vec<String>, op: &PatchOp, newline: &str) -> Result<()> {
+    match op {
+        PatchOp::Set {
+            group,
+            key,
+            locale,
+            value,
+        } => {
+            check_name(group, is_valid_group_name)?;
+            check_name(key, is_valid_key)?;
+            let value = escape_value(value);
+            let Some((header, end)) = find_group(lines, group) else {
+                let mut text = String::new();
+                if !lines.is_empty() {
+                    text.push_str(newline);
+                }
+                text.push_str(&format!("[{}]{}", group, newline));
+                text.push_str(&key_line(key, locale.as_ref(), &value, newline));
+                insert(lines, lines.len(), text, newline);
+                return Ok(());
+            };
+
+            let mut last_of_key = None;
+            let mut last_key = header;
+            for (index, line) in lines.iter_mut().enumerate().take(end).skip(header + 1) {
+                match classify(line) {
+                    Line::Key(k, l) if k == key && l == *locale => {
+                        *line = replace_value(line, &value);
+                        return Ok(());
+                    }
+                    Line::Key(k, _) => {
+                        if k == key {
+                            last_of_key = Some(index);
+                        }
+                        last_key = index;
+                    }
+                    _ => {}
+                }
+            }
+            let position = last_of_key.unwrap_or(last_key) + 1;
+            insert(
+                lines,
+                position,
+                key_line(key, locale.as_ref(), &value, newline),
+                newline,
+            );
+        }
+        PatchOp::Remove { group, key } => {
+            if let Some((header, end)) = find_group(lines, group) {
+                let mut index = header + 1;
+                let mut end = end;
+                while index < end {
+                    if matches!(classify(&lines[index]), Line::Key(k, _) if k == key) {
+                        lines.remove(index);
+                        end -= 1;
+                    } else {
+                        index += 1;
+                    }
+                }
+            }
+        }
+        PatchOp::RenameGroup { from, to } => {
+            check_name(to, is_valid_group_name)?;
+            let (header, _) = find_group(lines, from)
+                .ok_or_else(|| DesktopEntryError::MissingGroup(from.clone()))?;
+            if from != to && find_group(lines, to).is_some() {
+                return Err(DesktopEntryError::DuplicateGroup(to.clone()));
+            }
+            let line = &lines[header];
+            let start = line.find('[').unwrap_or(0);
+            let close = line.rfind(']').unwrap_or(line.len() - 1);
+            lines[header] = format!("{}[{}]{}", &line[..start], to, &line[close + 1..]);
+        }
+    }
+    Ok(())
+}
+
+fn key_line(key: &str, locale: Option<&Locale>, value: &str, newline: &str) -> String {
+    match locale {
+        Some(locale) => format!("{}[{}]={}{}", key, locale, value, newline),
+        None => format!("{}={}{}", key, value, newline),
+    }
+}
+
+/// Replaces the value of a key line, keeping the key as written, the
+/// spacing around `=`, and the line ending.
+fn replace_value(line: &str, value: &str) -> String {
+    let eq = line.find('=').unwrap_or(line.len());
+    let after_eq = &line[eq + 1..];
+    let spacing = after_eq.len() - after_eq.trim_start_matches([' ', '\t']).len();
+    let ending = if line.ends_with("\r\n") {
+        "\r\n"
+    } else if line.ends_with('\n') {
+        "\n"
+    } else {
+        ""
+    };
+    format!("{}{}{}", &line[..eq + 1 + spacing], value, ending)
+}
+
+/// Inserts text at a line index, ending the line before it if it has no
+/// line ending.
+fn insert(lines: &mut Vec<String>, index: usize, text: String, newline: &str) {
+    if index > 0 && !lines[index - 1].ends_with('\n') {
+        lines[index - 1].push_str(newline);
+    }
+    lines.insert(index, text);
+}
+
+fn check_name(name: &str, is_valid: fn(&str) -> bool) -> Result<()> {
+    if is_valid(name) {
+        Ok(())
+    } else {
+        Err(DesktopEntryError::InvalidValue(
+            name.to_string(),
+            "not a valid group or key name".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,414 @@
+//! Gettext catalogs of the translatable strings of an entry.
+//!
+//! Translation workflows keep the translations of desktop files in `.po`
+//! files, as `xgettext` and `msgfmt --desktop` do: the untranslated values
+//! of `Name`, `GenericName`, `Comment`, and `Keywords`, and the names of
+//! actions, are extracted to a template, and the translated catalogs are
+//! merged back as localized keys. `Keywords` are translated as a whole, in
+//! their `a;b;` form.
+//!
+//! Requires the `gettext` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::po::Catalog;
+//! use xdg_desktop_entry::{DesktopEntry, Locale};
+//!
+//! let mut entry = DesktopEntry::parse(
+//!     "[Desktop Entry]\nType=Application\nName=Calculator\nComment=Do math\nExec=calc\n",
+//! )
+//! .unwrap();
+//!
+//! let template = entry.to_catalog().serialize();
+//! assert!(template.contains("msgid \"Calculator\"\nmsgstr \"\"\n"));
+//!
+//! let french = Catalog::parse(
+//!     "msgid \"Calculator\"\nmsgstr \"Calculatrice\"\n\n\
+//!      #, fuzzy\nmsgid \"Do math\"\nmsgstr \"Faire des maths\"\n",
+//! )
+//! .unwrap();
+//! let fr = Locale::from_string("fr");
+//! assert_eq!(entry.apply_catalog(&fr, &french), 1);
+//! assert_eq!(entry.name.get(&fr), "Calculatrice");
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use crate::{
+    ACTION_GROUP_PREFIX, DesktopEntry, DesktopEntryError, Entry, Locale, Result, escape_value,
+    join_list, split_list, unescape_value,
+};
+
+/// Header of generated catalogs.
+const HEADER: &str = "Content-Type: text/plain; charset=UTF-8\nContent-Transfer-Encoding: 8bit\n";
+
+/// A message of a catalog.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    /// Disambiguating context (`msgctxt`), if any
+    pub context: Option<String>,
+    /// The untranslated string (`msgid`)
+    pub id: String,
+    /// The translation (`msgstr`, or `msgstr[0]` for plural forms), empty
+    /// if untranslated
+    pub translation: String,
+    /// Whether the translation is marked fuzzy and needs review
+    pub fuzzy: bool,
+    /// Extracted comments (`#.`), e.g. the key the string comes from
+    pub comments: Vec<String>,
+}
+
+/// A gettext catalog: a `.pot` template or a `.po` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Catalog {
+    /// The header, the translation of the empty `msgid`
+    pub header: String,
+    /// The messages, in file order
+    pub messages: Vec<Message>,
+}
+
+impl Catalog {
+    /// Parses a `.po` or `.pot` file.
+    ///
+    /// Obsolete messages (`#~`) are skipped, and only the first plural form
+    /// of a translation is kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::InvalidPo`] on malformed lines or
+    /// strings.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut catalog = Catalog::default();
+        let mut message = Message::default();
+        let mut field: Option<Field> = None;
+        let mut has_id = false;
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            let error = |reason: &str| {
+                DesktopEntryError::InvalidPo(format!("line {}: {}", index + 1, reason))
+            };
+            if line.is_empty() || line.starts_with("#~") {
+                continue;
+            }
+
+            if let Some(comment) = line.strip_prefix('#') {
+                if field == Some(Field::Translation) {
+                    catalog.push(std::mem::take(&mut message));
+                    field = None;
+                    has_id = false;
+                }
+                if let Some(flags) = comment.strip_prefix(',') {
+                    message.fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+                } else if let Some(extracted) = comment.strip_prefix('.') {
+                    message.comments.push(extracted.trim().to_string());
+                }
+                continue;
+            }
+
+            if line.starts_with('"') {
+                let value = parse_string(line).ok_or_else(|| error("malformed string"))?;
+                match field {
+                    Some(Field::Context) => {
+                        message.context.get_or_insert_default().push_str(&value)
+                    }
+                    Some(Field::Id) => message.id.push_str(&value),
+                    Some(Field::Translation) => message.translation.push_str(&value),
+                    Some(Field::Ignored) => {}
+                    None => return Err(error("string outside of a message")),
+                }
+                continue;
+            }
+
+            let (keyword, rest) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| error("missing string"))?;
+            let value = parse_string(rest.trim()).ok_or_else(|| error("malformed string"))?;
+            let next = match keyword {
+                "msgctxt" => Field::Context,
+                "msgid" => Field::Id,
+                "msgid_plural" => Field::Ignored,
+                "msgstr" | "msgstr[0]" => Field::Translation,
+                keyword if keyword.starts_with("msgstr[") => Field::Ignored,
+                _ => return Err(error("unknown keyword")),
+            };
+            if matches!(next, Field::Context | Field::Id) && field == Some(Field::Translation) {
+                catalog.push(std::mem::take(&mut message));
+                has_id = false;
+            }
+            match next {
+                Field::Context => message.context = Some(value),
+                Field::Id => {
+                    message.id = value;
+                    has_id = true;
+                }
+                Field::Translation if !has_id => return Err(error("msgstr without msgid")),
+                Field::Translation => message.translation = value,
+                Field::Ignored => {}
+            }
+            field = Some(next);
+        }
+
+        if has_id {
+            catalog.push(message);
+        }
+        Ok(catalog)
+    }
+
+    /// Finds the translation of a string without context, skipping fuzzy
+    /// and empty translations.
+    pub fn translation(&self, id: &str) -> Option<&str> {
+        self.messages
+            .iter()
+            .find(|message| message.context.is_none() && message.id == id)
+            .filter(|message| !message.fuzzy && !message.translation.is_empty())
+            .map(|message| message.translation.as_str())
+    }
+
+    /// Writes the catalog in the `.po` format.
+    pub fn serialize(&self) -> String {
+        let mut output = String::new();
+        write_string(&mut output, "msgid", "");
+        write_string(&mut output, "msgstr", &self.header);
+        for message in &self.messages {
+            output.push('\n');
+            for comment in &message.comments {
+                let _ = writeln!(output, "#. {}", comment);
+            }
+            if message.fuzzy {
+                output.push_str("#, fuzzy\n");
+            }
+            if let Some(context) = &message.context {
+                write_string(&mut output, "msgctxt", context);
+            }
+            write_string(&mut output, "msgid", &message.id);
+            write_string(&mut output, "msgstr", &message.translation);
+        }
+        output
+    }
+
+    /// Adds a message, taking the header from the empty `msgid`.
+    fn push(&mut self, message: Message) {
+        if message.id.is_empty() && message.context.is_none() {
+            self.header = message.translation;
+        } else {
+            self.messages.push(message);
+        }
+    }
+}
+
+/// A translatable value: the key it comes from, the value, and its
+/// existing translations.
+type Translatable = (String, String, Vec<(Locale, String)>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Context,
+    Id,
+    Translation,
+    Ignored,
+}
+
+impl DesktopEntry {
+    /// Extracts the translatable strings into a template, with empty
+    /// translations.
+    ///
+    /// Each distinct string appears once, commented with the keys it comes
+    /// from.
+    pub fn to_catalog(&self) -> Catalog {
+        let mut catalog = Catalog {
+            header: HEADER.to_string(),
+            messages: Vec::new(),
+        };
+        let mut seen = HashSet::new();
+        for (key, value, _) in self.translatable() {
+            if !seen.insert(value.clone()) {
+                if let Some(message) = catalog.messages.iter_mut().find(|m| m.id == value) {
+                    message.comments.push(key);
+                }
+                continue;
+            }
+            catalog.messages.push(Message {
+                id: value,
+                comments: vec![key],
+                ..Message::default()
+            });
+        }
+        catalog
+    }
+
+    /// Extracts the translatable strings into a catalog for a locale, filled
+    /// with the translations the entry already has for exactly that locale.
+    pub fn to_catalog_for(&self, locale: &Locale) -> Catalog {
+        let mut catalog = self.to_catalog();
+        let translations: Vec<(String, String)> = self
+            .translatable()
+            .into_iter()
+            .filter_map(|(_, value, translations)| {
+                let translation = translations.into_iter().find(|(l, _)| l == locale)?.1;
+                Some((value, translation))
+            })
+            .collect();
+        for message in &mut catalog.messages {
+            if let Some((_, translation)) = translations.iter().find(|(id, _)| *id == message.id) {
+                message.translation = translation.clone();
+            }
+        }
+        catalog
+    }
+
+    /// Sets the translations of a catalog as localized keys for a locale,
+    /// returning how many values were translated.
+    ///
+    /// Fuzzy and empty translations are skipped; existing translations for
+    /// the locale are replaced.
+    pub fn apply_catalog(&mut self, locale: &Locale, catalog: &Catalog) -> usize {
+        let mut count = 0;
+        let mut translate = |value: &str| {
+            let translation = catalog.translation(value).map(str::to_string);
+            count += usize::from(translation.is_some());
+            translation
+        };
+
+        if let Some(name) = translate(&self.name.default) {
+            self.name.add_localized(locale.clone(), name);
+        }
+        for field in [&mut self.generic_name, &mut self.comment]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(value) = translate(&field.default) {
+                field.add_localized(locale.clone(), value);
+            }
+        }
+        if let Some(keywords) = &mut self.keywords
+            && let Some(value) = translate(&join_list(&keywords.default))
+        {
+            keywords.add_localized(locale.clone(), split_list(&value));
+        }
+        for (name, group) in &mut self.additional_groups {
+            if !name.starts_with(ACTION_GROUP_PREFIX) {
+                continue;
+            }
+            let Some(value) = group.get_string("Name") else {
+                continue;
+            };
+            if let Some(translation) = translate(&value) {
+                let entries = group.entries.entry("Name".to_string()).or_default();
+                entries.retain(|entry| entry.locale.as_ref() != Some(locale));
+                entries.push(Entry {
+                    key: "Name".to_string(),
+                    locale: Some(locale.clone()),
+                    value: escape_value(&translation),
+                    span: None,
+                });
+            }
+        }
+        count
+    }
+
+    /// The translatable values, starting with `[Desktop Entry]`.
+    fn translatable(&self) -> Vec<Translatable> {
+        fn translations<T: Clone>(
+            localized: &crate::Localized<T>,
+            to_string: impl Fn(&T) -> String,
+        ) -> Vec<(Locale, String)> {
+            localized
+                .localized
+                .iter()
+                .map(|(locale, value)| (locale.clone(), to_string(value)))
+                .collect()
+        }
+
+        let mut values = vec![(
+            "Name".to_string(),
+            self.name.default.clone(),
+            translations(&self.name, String::clone),
+        )];
+        for (key, field) in [
+            ("GenericName", &self.generic_name),
+            ("Comment", &self.comment),
+        ] {
+            if let Some(field) = field {
+                values.push((
+                    key.to_string(),
+                    field.default.clone(),
+                    translations(field, String::clone),
+                ));
+            }
+        }
+        if let Some(keywords) = &self.keywords {
+            values.push((
+                "Keywords".to_string(),
+                join_list(&keywords.default),
+                translations(keywords, |list| join_list(list)),
+            ));
+        }
+
+        let mut actions: Vec<_> = self
+            .additional_groups
+            .iter()
+            .filter_map(|(name, group)| {
+                let id = name.strip_prefix(ACTION_GROUP_PREFIX)?;
+                let entries = group.entries.get("Name")?;
+                let default = entries.iter().find(|entry| entry.locale.is_none())?;
+                let translated = entries
+                    .iter()
+                    .filter_map(|entry| Some((entry.locale.clone()?, unescape_value(&entry.value))))
+                    .collect();
+                Some((
+                    format!("{}{}/Name", ACTION_GROUP_PREFIX, id),
+                    unescape_value(&default.value),
+                    translated,
+                ))
+            })
+            .collect();
+        actions.sort_by(|a, b| a.0.cmp(&b.0));
+        values.extend(actions);
+        values
+    }
+}
+
+fn parse_string(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(match chars.next()? {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                '"' => '"',
+                '\\' => '\\',
+                _ => return None,
+            }),
+            '"' => return None,
+            c => value.push(c),
+        }
+    }
+    Some(value)
+}
+
+/// Writes a keyword and its string, splitting multi-line strings after each
+/// newline as `msgmerge` does.
+fn write_string(output: &mut String, keyword: &str, value: &str) {
+    let escape = |part: &str| {
+        part.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\t', "\\t")
+            .replace('\r', "\\r")
+    };
+    let lines: Vec<&str> = value.split_inclusive('\n').collect();
+    if lines.len() <= 1 {
+        let _ = writeln!(output, "{} \"{}\"", keyword, escape(value));
+        return;
+    }
+    let _ = writeln!(output, "{} \"\"", keyword);
+    for line in lines {
+        let _ = writeln!(output, "\"{}\"", escape(line));
+    }
+}
@@ -0,0 +1,138 @@
+//! Masking of user-identifying values for [`DesktopEntry::redacted`].
+
+use crate::DesktopEntry;
+
+const HOME_PREFIXES: &[&str] = &["/home/", "/var/home/", "/Users/"];
+const PLACEHOLDER_USER: &str = "user";
+const PLACEHOLDER_EMAIL: &str = "user@example.com";
+
+/// Replaces home directories, user names, and email addresses in values.
+pub(crate) struct Redactor {
+    home: Option<String>,
+    users: Vec<String>,
+}
+
+impl Redactor {
+    /// Collects the identifying values to mask from the environment and from
+    /// home directory paths appearing in the entry.
+    pub(crate) fn for_entry(entry: &DesktopEntry) -> Self {
+        let home = std::env::var("HOME")
+            .ok()
+            .map(|home| home.trim_end_matches('/').to_string())
+            .filter(|home| !home.is_empty());
+
+        let mut users: Vec<String> = ["USER", "LOGNAME"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .collect();
+        entry.serialize().lines().for_each(|line| {
+            for prefix in HOME_PREFIXES {
+                for (start, _) in line.match_indices(prefix) {
+                    let rest = &line[start + prefix.len()..];
+                    let end = rest
+                        .find(|c: char| c == '/' || c == '"' || c == '\'' || c.is_whitespace())
+                        .unwrap_or(rest.len());
+                    users.push(rest[..end].to_string());
+                }
+            }
+        });
+        users.retain(|user| !user.is_empty() && user != PLACEHOLDER_USER);
+        users.sort();
+        users.dedup();
+
+        Self { home, users }
+    }
+
+    /// Masks a single value.
+    pub(crate) fn redact(&self, value: &str) -> String {
+        let mut value = value.to_string();
+        if let Some(home) = &self.home
+            && home != "/"
+        {
+            value = replace_path(&value, home, &format!("/home/{}", PLACEHOLDER_USER));
+        }
+        for user in &self.users {
+            value = replace_word(&value, user, PLACEHOLDER_USER);
+        }
+        mask_emails(&value)
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Replaces occurrences of `word` not embedded in a longer word.
+fn replace_word(value: &str, word: &str, replacement: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut last = 0;
+    for (start, _) in value.match_indices(word) {
+        let end = start + word.len();
+        if start < last {
+            continue;
+        }
+        let before = value[..start].chars().next_back();
+        let after = value[end..].chars().next();
+        if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+            continue;
+        }
+        output.push_str(&value[last..start]);
+        output.push_str(replacement);
+        last = end;
+    }
+    output.push_str(&value[last..]);
+    output
+}
+
+/// Replaces occurrences of the directory `path`, but not of longer names
+/// starting with it, such as `/home/alice2` for `/home/alice`.
+fn replace_path(value: &str, path: &str, replacement: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut last = 0;
+    for (start, _) in value.match_indices(path) {
+        let end = start + path.len();
+        if value[end..]
+            .chars()
+            .next()
+            .is_some_and(|c| is_word_char(c) || c == '.')
+        {
+            continue;
+        }
+        output.push_str(&value[last..start]);
+        output.push_str(replacement);
+        last = end;
+    }
+    output.push_str(&value[last..]);
+    output
+}
+
+/// Replaces `local@domain.tld` strings with a placeholder address.
+fn mask_emails(value: &str) -> String {
+    let is_local = |c: char| c.is_ascii_alphanumeric() || "._%+-".contains(c);
+    let is_domain = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '-';
+
+    let mut output = String::with_capacity(value.len());
+    let mut last = 0;
+    for (at, _) in value.match_indices('@') {
+        if at < last {
+            continue;
+        }
+        let start = value[..at]
+            .char_indices()
+            .rfind(|&(_, c)| !is_local(c))
+            .map_or(0, |(i, c)| i + c.len_utf8())
+            .max(last);
+        let end = value[at + 1..]
+            .find(|c: char| !is_domain(c))
+            .map_or(value.len(), |i| at + 1 + i);
+        let domain = value[at + 1..end].trim_end_matches('.');
+        if start == at || !domain.contains('.') || domain.starts_with('.') {
+            continue;
+        }
+        output.push_str(&value[last..start]);
+        output.push_str(PLACEHOLDER_EMAIL);
+        last = at + 1 + domain.len();
+    }
+    output.push_str(&value[last..]);
+    output
+}
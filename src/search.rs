@@ -0,0 +1,509 @@
+//! Full-text search over desktop entries.
+//!
+//! A [`SearchIndex`] tokenizes `Name`, `GenericName`, `Comment`, `Keywords`,
+//! and the basename of the `Exec` program of each entry, and matches
+//! queries against the tokens by exact word, prefix, or a small edit
+//! distance. Every query word has to match. Results are ranked by where
+//! the words matched: the name scores above keywords, which score above
+//! the generic name, the program, and the comment, and a query equal to
+//! the whole name ranks first.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::DesktopEntry;
+//! use xdg_desktop_entry::search::{MatchField, SearchIndex};
+//!
+//! let mut index = SearchIndex::new();
+//! index.insert(
+//!     "org.example.Editor.desktop",
+//!     &DesktopEntry::parse(
+//!         "[Desktop Entry]\nType=Application\nName=Text Editor\nKeywords=notepad;write;\nExec=gedit %U\n",
+//!     )
+//!     .unwrap(),
+//! );
+//! index.insert(
+//!     "org.example.Notes.desktop",
+//!     &DesktopEntry::parse(
+//!         "[Desktop Entry]\nType=Application\nName=Notes\nComment=Write down text\nExec=notes\n",
+//!     )
+//!     .unwrap(),
+//! );
+//!
+//! let results = index.search("text");
+//! assert_eq!(results[0].id, "org.example.Editor.desktop");
+//! assert_eq!(results[0].field, MatchField::Name);
+//! assert_eq!(results[1].id, "org.example.Notes.desktop");
+//!
+//! // Prefixes and typos match too
+//! assert_eq!(index.search("edi")[0].id, "org.example.Editor.desktop");
+//! assert_eq!(index.search("notpad")[0].field, MatchField::Keyword);
+//! ```
+
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::path::Path;
+
+use crate::exec::ExecString;
+use crate::{DesktopEntry, Locale, Localized};
+
+/// The part of an entry a query word matched.
+///
+/// Ordered from most to least relevant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MatchField {
+    /// `Name`
+    Name,
+    /// `Keywords`
+    Keyword,
+    /// `GenericName`
+    GenericName,
+    /// Basename of the `Exec` program
+    Exec,
+    /// `Comment`
+    Comment,
+}
+
+impl MatchField {
+    fn weight(self) -> u32 {
+        match self {
+            Self::Name => 100,
+            Self::Keyword => 70,
+            Self::GenericName => 60,
+            Self::Exec => 50,
+            Self::Comment => 30,
+        }
+    }
+}
+
+/// How a query word matched a word of an entry.
+///
+/// Ordered from most to least relevant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MatchKind {
+    /// The words are equal
+    Exact,
+    /// The query word starts the entry's word
+    Prefix,
+    /// The words differ by a few edits
+    Fuzzy,
+}
+
+impl MatchKind {
+    fn weight(self) -> u32 {
+        match self {
+            Self::Exact => 4,
+            Self::Prefix => 3,
+            Self::Fuzzy => 2,
+        }
+    }
+}
+
+/// Score added when the query equals a whole name.
+const EXACT_NAME_BONUS: u32 = 1000;
+
+/// An entry matching a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult<'a> {
+    /// ID the entry was inserted with
+    pub id: &'a str,
+    /// Relevance; higher is better
+    pub score: u32,
+    /// Most relevant field matched by the first query word
+    pub field: MatchField,
+}
+
+/// A word of an indexed entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Posting {
+    document: usize,
+    field: MatchField,
+}
+
+/// An index of desktop entries for searching.
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    /// Locales to index, most preferred first; empty for all locales
+    locales: Vec<Locale>,
+    ids: Vec<String>,
+    /// Normalized whole names of each entry
+    names: Vec<Vec<String>>,
+    words: BTreeMap<String, Vec<Posting>>,
+}
+
+impl SearchIndex {
+    /// Creates an index covering the values of all locales.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an index covering only the values matching a locale chain,
+    /// most preferred first, plus the unlocalized values.
+    ///
+    /// Each localized key contributes the value chosen by
+    /// [`Localized::get_with_fallbacks`].
+    pub fn with_locales(locales: Vec<Locale>) -> Self {
+        Self {
+            locales,
+            ..Self::default()
+        }
+    }
+
+    /// Number of indexed entries.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Whether the index holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Indexes an entry under an ID, typically its desktop file ID.
+    pub fn insert(&mut self, id: impl Into<String>, entry: &DesktopEntry) {
+        let document = self.ids.len();
+        self.ids.push(id.into());
+
+        let names = self.values(&entry.name);
+        self.names
+            .push(names.iter().map(|name| normalize(name)).collect());
+
+        let mut texts: Vec<(MatchField, &str)> = names
+            .into_iter()
+            .map(|name| (MatchField::Name, name.as_str()))
+            .collect();
+        if let Some(keywords) = &entry.keywords {
+            let keywords = self.values(keywords).into_iter().flatten();
+            texts.extend(keywords.map(|keyword| (MatchField::Keyword, keyword.as_str())));
+        }
+        if let Some(generic_name) = &entry.generic_name {
+            let values = self.values(generic_name).into_iter();
+            texts.extend(values.map(|value| (MatchField::GenericName, value.as_str())));
+        }
+        let program = entry.exec.as_deref().and_then(exec_basename);
+        texts.extend(
+            program
+                .as_deref()
+                .map(|program| (MatchField::Exec, program)),
+        );
+        if let Some(comment) = &entry.comment {
+            let values = self.values(comment).into_iter();
+            texts.extend(values.map(|value| (MatchField::Comment, value.as_str())));
+        }
+
+        for (field, text) in texts {
+            for word in words(text) {
+                let postings = self.words.entry(word).or_default();
+                let posting = Posting { document, field };
+                if !postings.contains(&posting) {
+                    postings.push(posting);
+                }
+            }
+        }
+    }
+
+    /// Indexes every entry of an iterator of IDs and entries, such as
+    /// [`AppDatabase::iter`](crate::database::AppDatabase::iter).
+    pub fn extend<'a, I: AsRef<str>>(
+        &mut self,
+        entries: impl IntoIterator<Item = (I, &'a DesktopEntry)>,
+    ) {
+        for (id, entry) in entries {
+            self.insert(id.as_ref(), entry);
+        }
+    }
+
+    /// Finds the entries matching every word of a query, most relevant
+    /// first.
+    ///
+    /// Entries with equal scores are ordered by ID. An empty query matches
+    /// nothing.
+    pub fn search(&self, query: &str) -> Vec<SearchResult<'_>> {
+        let terms = words(query);
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        // Best (score, field) per document for each term
+        let mut totals: Vec<Option<(u32, MatchField)>> =
+            vec![Some((0, MatchField::Name)); self.len()];
+        for (index, term) in terms.iter().enumerate() {
+            let mut best: Vec<Option<(u32, MatchField)>> = vec![None; self.len()];
+            for (word, kind) in self.matching_words(term) {
+                for posting in &self.words[word] {
+                    let score = posting.field.weight() * kind.weight();
+                    let slot = &mut best[posting.document];
+                    if slot.is_none_or(|(best_score, _)| score > best_score) {
+                        *slot = Some((score, posting.field));
+                    }
+                }
+            }
+            for (total, best) in totals.iter_mut().zip(best) {
+                *total = match (*total, best) {
+                    (Some((sum, field)), Some((score, best_field))) => {
+                        Some((sum + score, if index == 0 { best_field } else { field }))
+                    }
+                    _ => None,
+                };
+            }
+        }
+
+        let query = normalize(query);
+        let mut results: Vec<SearchResult<'_>> = totals
+            .into_iter()
+            .enumerate()
+            .filter_map(|(document, total)| {
+                let (mut score, field) = total?;
+                if self.names[document].contains(&query) {
+                    score += EXACT_NAME_BONUS;
+                }
+                Some(SearchResult {
+                    id: &self.ids[document],
+                    score,
+                    field,
+                })
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.id.cmp(b.id)));
+        results
+    }
+
+    /// The indexed words matching a query word, with how they match.
+    fn matching_words<'a>(&'a self, term: &'a str) -> impl Iterator<Item = (&'a str, MatchKind)> {
+        let prefixed = self
+            .words
+            .range::<str, _>((Bound::Included(term), Bound::Unbounded))
+            .take_while(move |(word, _)| word.starts_with(term))
+            .map(move |(word, _)| {
+                let kind = if word == term {
+                    MatchKind::Exact
+                } else {
+                    MatchKind::Prefix
+                };
+                (word.as_str(), kind)
+            });
+        let fuzzy = self
+            .words
+            .keys()
+            .filter(move |word| match_kind(term, word) == Some(MatchKind::Fuzzy))
+            .map(|word| (word.as_str(), MatchKind::Fuzzy));
+        prefixed.chain(fuzzy)
+    }
+
+    /// The values of a localized key covered by this index.
+    fn values<'a, T>(&self, localized: &'a Localized<T>) -> Vec<&'a T> {
+        if self.locales.is_empty() {
+            let mut values = vec![&localized.default];
+            values.extend(localized.localized.values());
+            return values;
+        }
+        chain_values(localized, &self.locales)
+    }
+}
+
+/// How well an entry matches a query, as returned by
+/// [`DesktopEntry::matches_query`].
+///
+/// Compares by score first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MatchScore {
+    /// Relevance; higher is better
+    pub score: u32,
+    /// Most relevant field matched by the first query word
+    pub field: MatchField,
+    /// How the first query word matched that field
+    pub kind: MatchKind,
+}
+
+impl DesktopEntry {
+    /// Checks whether every word of a query matches the name, generic
+    /// name, keywords, or `Exec` program of this entry.
+    ///
+    /// Localized values are taken from the first locale of the chain that
+    /// has a translation, besides the unlocalized value. Words match by
+    /// prefix or with a few typos, ignoring case and, with the `unicode`
+    /// feature, all diacritics (only Latin ones otherwise). The score uses
+    /// the ranking of [`SearchIndex`], so results of several entries can be
+    /// sorted by it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::search::MatchField;
+    /// use xdg_desktop_entry::{DesktopEntry, Locale};
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Calendar\nName[fr]=Calendrier\nKeywords=agenda;événement;\nExec=gnome-calendar\n",
+    /// )
+    /// .unwrap();
+    /// let locales = [Locale::from_string("fr_FR")];
+    ///
+    /// assert_eq!(entry.matches_query("calendri", &locales).unwrap().field, MatchField::Name);
+    /// assert_eq!(entry.matches_query("EVENEMENT", &locales).unwrap().field, MatchField::Keyword);
+    /// assert!(entry.matches_query("calendar mail", &locales).is_none());
+    /// ```
+    pub fn matches_query(&self, query: &str, locales: &[Locale]) -> Option<MatchScore> {
+        let terms = words(query);
+        if terms.is_empty() {
+            return None;
+        }
+
+        let names = chain_values(&self.name, locales);
+        let mut texts: Vec<(MatchField, &str)> = names
+            .iter()
+            .map(|name| (MatchField::Name, name.as_str()))
+            .collect();
+        if let Some(keywords) = &self.keywords {
+            let keywords = chain_values(keywords, locales).into_iter().flatten();
+            texts.extend(keywords.map(|keyword| (MatchField::Keyword, keyword.as_str())));
+        }
+        if let Some(generic_name) = &self.generic_name {
+            let values = chain_values(generic_name, locales).into_iter();
+            texts.extend(values.map(|value| (MatchField::GenericName, value.as_str())));
+        }
+        let program = self.exec.as_deref().and_then(exec_basename);
+        texts.extend(
+            program
+                .as_deref()
+                .map(|program| (MatchField::Exec, program)),
+        );
+        let fields: Vec<(MatchField, Vec<String>)> = texts
+            .into_iter()
+            .map(|(field, text)| (field, words(text)))
+            .collect();
+
+        let mut total = 0;
+        let mut first = None;
+        for term in &terms {
+            let (score, field, kind) = fields
+                .iter()
+                .flat_map(|(field, words)| words.iter().map(move |word| (*field, word)))
+                .filter_map(|(field, word)| {
+                    let kind = match_kind(term, word)?;
+                    Some((field.weight() * kind.weight(), field, kind))
+                })
+                .max_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1)))?;
+            total += score;
+            first.get_or_insert((field, kind));
+        }
+
+        let query = normalize(query);
+        if names.iter().any(|name| normalize(name) == query) {
+            total += EXACT_NAME_BONUS;
+        }
+        let (field, kind) = first?;
+        Some(MatchScore {
+            score: total,
+            field,
+            kind,
+        })
+    }
+}
+
+/// The unlocalized value and the value of the first locale of the chain
+/// with a translation.
+fn chain_values<'a, T>(localized: &'a Localized<T>, locales: &[Locale]) -> Vec<&'a T> {
+    let chosen = locales.iter().find_map(|locale| localized.lookup(locale));
+    let mut values = vec![&localized.default];
+    values.extend(chosen);
+    values
+}
+
+/// How a query word matches a word, if it does.
+fn match_kind(term: &str, word: &str) -> Option<MatchKind> {
+    if word == term {
+        return Some(MatchKind::Exact);
+    }
+    if word.starts_with(term) {
+        return Some(MatchKind::Prefix);
+    }
+    let max_distance = max_edit_distance(term);
+    (max_distance > 0
+        && word.chars().count().abs_diff(term.chars().count()) <= max_distance
+        && edit_distance(word, term) <= max_distance)
+        .then_some(MatchKind::Fuzzy)
+}
+
+/// Folds case and diacritics and collapses whitespace.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(fold)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Splits text into words with case and diacritics folded.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(fold)
+        .collect()
+}
+
+/// Lowercases a word and strips its diacritics.
+#[cfg(feature = "unicode")]
+fn fold(word: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+    use unicode_normalization::char::is_combining_mark;
+
+    word.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Lowercases a word and strips the diacritics of Latin letters.
+#[cfg(not(feature = "unicode"))]
+fn fold(word: &str) -> String {
+    word.chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'à'..='å' | 'ā' | 'ă' | 'ą' => 'a',
+            'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+            'ď' => 'd',
+            'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+            'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+            'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' => 'i',
+            'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+            'ò'..='ö' | 'ō' | 'ŏ' | 'ő' => 'o',
+            'ŕ' | 'ř' => 'r',
+            'ś' | 'ŝ' | 'ş' | 'š' => 's',
+            'ţ' | 'ť' => 't',
+            'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+            'ý' | 'ÿ' => 'y',
+            'ź' | 'ż' | 'ž' => 'z',
+            c => c,
+        })
+        .collect()
+}
+
+/// The file name of the program run by an `Exec` value.
+fn exec_basename(exec: &str) -> Option<String> {
+    let exec = ExecString::parse(exec).ok()?;
+    let name = Path::new(exec.program()).file_name()?.to_str()?;
+    Some(name.to_string())
+}
+
+/// Edits allowed for a fuzzy match of a query word.
+fn max_edit_distance(term: &str) -> usize {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Levenshtein distance between two words.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
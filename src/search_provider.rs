@@ -0,0 +1,180 @@
+//! GNOME Shell search provider declarations.
+//!
+//! Applications plug into the GNOME Shell search by installing a key file in
+//! `$XDG_DATA_DIRS/gnome-shell/search-providers/` that points at their desktop
+//! entry and at the D-Bus object implementing
+//! `org.gnome.Shell.SearchProvider2`:
+//!
+//! ```ini
+//! [Shell Search Provider]
+//! DesktopId=org.example.App.desktop
+//! BusName=org.example.App
+//! ObjectPath=/org/example/App/SearchProvider
+//! Version=2
+//! ```
+//!
+//! The desktop entry itself declares the interface in its `Implements` key.
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::launch::dbus_object_path;
+use crate::{DesktopEntry, DesktopEntryError, Parser, Result};
+
+/// D-Bus interface implemented by GNOME Shell search providers.
+pub const SEARCH_PROVIDER_INTERFACE: &str = "org.gnome.Shell.SearchProvider2";
+
+/// Directory, relative to an XDG data directory, holding search provider files.
+pub const SEARCH_PROVIDERS_DIR: &str = "gnome-shell/search-providers";
+
+/// Name of the group holding the search provider keys.
+const SEARCH_PROVIDER_GROUP: &str = "Shell Search Provider";
+
+/// A GNOME Shell search provider declaration.
+///
+/// # Examples
+///
+/// ```
+/// use xdg_desktop_entry::search_provider::SearchProvider;
+///
+/// let provider = SearchProvider::for_desktop_id("org.example.App.desktop");
+/// assert_eq!(provider.bus_name, "org.example.App");
+/// assert_eq!(provider.object_path, "/org/example/App/SearchProvider");
+/// assert_eq!(provider.file_name(), "org.example.App.search-provider.ini");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchProvider {
+    /// Desktop file ID of the application (e.g., "org.example.App.desktop")
+    pub desktop_id: String,
+    /// Well-known bus name the provider is reachable at
+    pub bus_name: String,
+    /// Object path exporting the search provider interface
+    pub object_path: String,
+    /// Version of the search provider interface (currently 2)
+    pub version: u32,
+    /// Whether the provider is disabled until the user enables it
+    pub default_disabled: bool,
+}
+
+impl SearchProvider {
+    /// Creates a new search provider declaration for interface version 2.
+    pub fn new(
+        desktop_id: impl Into<String>,
+        bus_name: impl Into<String>,
+        object_path: impl Into<String>,
+    ) -> Self {
+        Self {
+            desktop_id: desktop_id.into(),
+            bus_name: bus_name.into(),
+            object_path: object_path.into(),
+            version: 2,
+            default_disabled: false,
+        }
+    }
+
+    /// Creates a declaration following the usual naming conventions.
+    ///
+    /// The bus name is the desktop file ID without its `.desktop` suffix and
+    /// the object path is the application's D-Bus object path with a trailing
+    /// `/SearchProvider`.
+    pub fn for_desktop_id(desktop_id: impl Into<String>) -> Self {
+        let desktop_id = desktop_id.into();
+        let bus_name = desktop_id
+            .strip_suffix(".desktop")
+            .unwrap_or(&desktop_id)
+            .to_string();
+        let object_path = format!("{}/SearchProvider", dbus_object_path(&bus_name));
+        Self::new(desktop_id, bus_name, object_path)
+    }
+
+    /// Parses a search provider file from a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is malformed, lacks the
+    /// `[Shell Search Provider]` group, or misses one of the required
+    /// `DesktopId`, `BusName`, `ObjectPath`, and `Version` keys.
+    pub fn parse(content: &str) -> Result<Self> {
+        let (mut groups, _) = Parser::new(content).parse_groups()?;
+        let data = groups
+            .remove(SEARCH_PROVIDER_GROUP)
+            .ok_or_else(|| DesktopEntryError::MissingGroup(SEARCH_PROVIDER_GROUP.to_string()))?;
+
+        let required = |key: &str| {
+            data.get(key)
+                .and_then(|entries| entries.first())
+                .map(|entry| entry.value.clone())
+                .ok_or_else(|| DesktopEntryError::MissingRequiredKey(key.to_string()))
+        };
+
+        let version = required("Version")?;
+        let mut provider = Self::new(
+            required("DesktopId")?,
+            required("BusName")?,
+            required("ObjectPath")?,
+        );
+        provider.version = version
+            .parse()
+            .map_err(|_| DesktopEntryError::InvalidValue("Version".to_string(), version))?;
+
+        let mut default_disabled = None;
+        Parser::parse_optional_bool(&data, "DefaultDisabled", &mut default_disabled);
+        provider.default_disabled = default_disabled.unwrap_or(false);
+
+        Ok(provider)
+    }
+
+    /// Parses a search provider file from a file path.
+    pub fn parse_file(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+
+    /// Serializes the search provider declaration to a string.
+    pub fn serialize(&self) -> String {
+        let mut output = Vec::new();
+        self.write_to(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    /// Writes the search provider declaration to a writer.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "[{}]", SEARCH_PROVIDER_GROUP)?;
+        writeln!(writer, "DesktopId={}", self.desktop_id)?;
+        writeln!(writer, "BusName={}", self.bus_name)?;
+        writeln!(writer, "ObjectPath={}", self.object_path)?;
+        writeln!(writer, "Version={}", self.version)?;
+        if self.default_disabled {
+            writeln!(writer, "DefaultDisabled=true")?;
+        }
+        Ok(())
+    }
+
+    /// Conventional file name of the declaration (e.g.,
+    /// "org.example.App.search-provider.ini").
+    pub fn file_name(&self) -> String {
+        let stem = self
+            .desktop_id
+            .strip_suffix(".desktop")
+            .unwrap_or(&self.desktop_id);
+        format!("{}.search-provider.ini", stem)
+    }
+
+    /// Whether the desktop entry declares the search provider interface.
+    pub fn is_declared_by(entry: &DesktopEntry) -> bool {
+        entry
+            .implements
+            .as_ref()
+            .is_some_and(|implements| implements.iter().any(|i| i == SEARCH_PROVIDER_INTERFACE))
+    }
+
+    /// Adds the search provider interface to the entry's `Implements` key.
+    pub fn declare_on(entry: &mut DesktopEntry) {
+        if !Self::is_declared_by(entry) {
+            entry
+                .implements
+                .get_or_insert_with(Vec::new)
+                .push(SEARCH_PROVIDER_INTERFACE.to_string());
+        }
+    }
+}
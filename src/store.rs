@@ -0,0 +1,340 @@
+//! A shared registry of the installed applications.
+//!
+//! An [`ApplicationStore`] ties the lower-level pieces of the crate
+//! together: it discovers the desktop files in the `applications`
+//! directories, resolves which file provides each desktop file ID, keeps the
+//! parsed entries in an [`ApplicationCache`], and answers lookups by ID, MIME
+//! type, and category. The store is `Send` and `Sync`, so a single instance
+//! can be shared between threads behind an [`Arc`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use xdg_desktop_entry::store::ApplicationStore;
+//!
+//! let store = Arc::new(ApplicationStore::new());
+//! for app in store.visible() {
+//!     println!("{}: {}", app.id, app.entry.name.default);
+//! }
+//!
+//! let worker = Arc::clone(&store);
+//! std::thread::spawn(move || {
+//!     worker.refresh();
+//! });
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::cache::ApplicationCache;
+use crate::discovery::{self, DesktopFileId};
+use crate::environment::{self, DesktopEnvironment};
+use crate::findings::RefreshSummary;
+use crate::mimeapps::MimeApps;
+use crate::{DesktopEntry, Result};
+
+/// An application of an [`ApplicationStore`].
+///
+/// Cloning is cheap: the entry is shared with the store.
+#[derive(Debug, Clone)]
+pub struct StoredApplication {
+    /// Desktop file ID
+    pub id: DesktopFileId,
+    /// Path of the file providing the ID
+    pub path: PathBuf,
+    /// Parsed entry
+    pub entry: Arc<DesktopEntry>,
+}
+
+/// The installed applications, kept up to date by
+/// [`refresh`](Self::refresh).
+///
+/// Follows the precedence and shadowing rules of
+/// [`ApplicationIter`](discovery::ApplicationIter): each ID is provided by
+/// its file in the most important directory, and IDs whose file is marked
+/// `Hidden=true` or fails to parse are left out. Lookups by ID or MIME type
+/// include entries with `NoDisplay=true`, which can still be launched or
+/// open files; [`visible`](Self::visible) applies
+/// [`should_show`](DesktopEntry::should_show) for menus.
+#[derive(Debug)]
+pub struct ApplicationStore {
+    dirs: Vec<PathBuf>,
+    current_desktops: Vec<DesktopEnvironment>,
+    state: RwLock<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    cache: ApplicationCache,
+    mime_apps: MimeApps,
+    applications: BTreeMap<DesktopFileId, StoredApplication>,
+}
+
+impl ApplicationStore {
+    /// Loads the entries in the
+    /// [`application_dirs`](discovery::application_dirs), the associations
+    /// of [`MimeApps::load`], and the desktops named in
+    /// `$XDG_CURRENT_DESKTOP`.
+    ///
+    /// Starts from the cache at
+    /// [`ApplicationCache::default_path`], so only new or changed files are
+    /// parsed.
+    pub fn new() -> Self {
+        Self::with_cache(
+            discovery::application_dirs(),
+            MimeApps::load(),
+            environment::current_desktop(),
+            ApplicationCache::load_default(),
+        )
+    }
+
+    /// Loads the entries in the given `applications` directories, most
+    /// important first, with the given associations and current desktops.
+    pub fn with_dirs(
+        dirs: Vec<PathBuf>,
+        mime_apps: MimeApps,
+        current_desktops: Vec<DesktopEnvironment>,
+    ) -> Self {
+        Self::with_cache(dirs, mime_apps, current_desktops, ApplicationCache::new())
+    }
+
+    /// Like [`with_dirs`](Self::with_dirs), starting from an existing cache.
+    pub fn with_cache(
+        dirs: Vec<PathBuf>,
+        mime_apps: MimeApps,
+        current_desktops: Vec<DesktopEnvironment>,
+        cache: ApplicationCache,
+    ) -> Self {
+        let store = Self {
+            dirs,
+            current_desktops,
+            state: RwLock::new(State {
+                cache,
+                mime_apps,
+                applications: BTreeMap::new(),
+            }),
+        };
+        store.refresh();
+        store
+    }
+
+    /// Rescans the directories, parsing new and changed files.
+    ///
+    /// The summary lists every desktop file, including shadowed ones.
+    pub fn refresh(&self) -> RefreshSummary {
+        let mut files = Vec::new();
+        for dir in &self.dirs {
+            let mut found = Vec::new();
+            discovery::collect_desktop_files(dir, dir, &mut found);
+            found.sort();
+            files.extend(found);
+        }
+
+        let mut state = self.write();
+        let summary = state
+            .cache
+            .refresh(files.iter().map(|(_, path)| path.as_path()));
+        let reparsed: HashSet<&Path> = summary
+            .added
+            .iter()
+            .chain(&summary.changed)
+            .map(PathBuf::as_path)
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut applications = BTreeMap::new();
+        for (id, path) in files {
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            let Some(entry) = state.cache.get(&path) else {
+                continue;
+            };
+            if entry.hidden == Some(true) {
+                continue;
+            }
+            let entry = match state.applications.get(&id) {
+                Some(known) if known.path == path && !reparsed.contains(path.as_path()) => {
+                    Arc::clone(&known.entry)
+                }
+                _ => Arc::new(entry.clone()),
+            };
+            applications.insert(id.clone(), StoredApplication { id, path, entry });
+        }
+        state.applications = applications;
+        summary
+    }
+
+    /// Replaces the MIME associations, e.g. after a `mimeapps.list` file
+    /// changed.
+    pub fn set_mime_apps(&self, mime_apps: MimeApps) {
+        self.write().mime_apps = mime_apps;
+    }
+
+    /// Saves the parsed entries to a cache file, to be passed to
+    /// [`with_cache`](Self::with_cache) by a later run.
+    ///
+    /// See [`ApplicationCache::save`].
+    pub fn save_cache(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.read().cache.save(path)
+    }
+
+    /// Saves the parsed entries to [`ApplicationCache::default_path`], which
+    /// [`new`](Self::new) starts from.
+    pub fn save_default_cache(&self) -> Result<()> {
+        self.read().cache.save_default()
+    }
+
+    /// The desktops entries are shown in by [`visible`](Self::visible).
+    pub fn current_desktops(&self) -> &[DesktopEnvironment] {
+        &self.current_desktops
+    }
+
+    /// Number of applications, including those not shown in menus.
+    pub fn len(&self) -> usize {
+        self.read().applications.len()
+    }
+
+    /// Whether no application is installed.
+    pub fn is_empty(&self) -> bool {
+        self.read().applications.is_empty()
+    }
+
+    /// Gets an application by desktop file ID.
+    pub fn get(&self, id: &str) -> Option<StoredApplication> {
+        self.read()
+            .applications
+            .get(&DesktopFileId::new(id))
+            .cloned()
+    }
+
+    /// All applications in ID order, including those not shown in menus.
+    pub fn all(&self) -> Vec<StoredApplication> {
+        self.read().applications.values().cloned().collect()
+    }
+
+    /// The applications to show in menus of the current desktops, in ID
+    /// order.
+    pub fn visible(&self) -> Vec<StoredApplication> {
+        self.filter(|entry| entry.should_show(&self.current_desktops))
+    }
+
+    /// The applications able to open a MIME type, most preferred first.
+    ///
+    /// Follows the order of
+    /// [`Applications::handlers_for_mime`](discovery::Applications::handlers_for_mime).
+    pub fn handlers_for_mime(&self, mime_type: &str) -> Vec<StoredApplication> {
+        let state = self.read();
+        let mut handlers: Vec<StoredApplication> = Vec::new();
+
+        let preferred = state
+            .mime_apps
+            .default_app_for(mime_type)
+            .into_iter()
+            .chain(state.mime_apps.added_associations(mime_type));
+        for id in preferred {
+            if let Some(app) = state.applications.get(&id)
+                && !handlers.iter().any(|handler| handler.id == id)
+            {
+                handlers.push(app.clone());
+            }
+        }
+
+        for (id, app) in &state.applications {
+            let claims = app
+                .entry
+                .mime_type
+                .as_ref()
+                .is_some_and(|types| types.iter().any(|t| t == mime_type));
+            if claims
+                && !state.mime_apps.is_removed(mime_type, id)
+                && !handlers.iter().any(|handler| handler.id == *id)
+            {
+                handlers.push(app.clone());
+            }
+        }
+
+        handlers
+    }
+
+    /// The preferred application for a MIME type.
+    pub fn default_for_mime(&self, mime_type: &str) -> Option<StoredApplication> {
+        self.handlers_for_mime(mime_type).into_iter().next()
+    }
+
+    /// The visible applications listing a category in their `Categories`
+    /// key, in ID order.
+    ///
+    /// Takes a registered [`Category`](crate::categories::Category) or any
+    /// category name, such as an `X-` extension.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xdg_desktop_entry::categories::Category;
+    /// use xdg_desktop_entry::store::ApplicationStore;
+    ///
+    /// let store = ApplicationStore::new();
+    /// for app in store.by_category(Category::Graphics) {
+    ///     println!("{}", app.entry.name.default);
+    /// }
+    /// let games = store.by_category("X-Retro");
+    /// ```
+    pub fn by_category(&self, category: impl AsRef<str>) -> Vec<StoredApplication> {
+        let category = category.as_ref();
+        self.filter(|entry| {
+            entry.should_show(&self.current_desktops)
+                && entry
+                    .categories
+                    .as_ref()
+                    .is_some_and(|categories| categories.iter().any(|c| c == category))
+        })
+    }
+
+    /// The applications listing an interface in their `Implements` key, in
+    /// ID order, including those not shown in menus.
+    ///
+    /// Takes an [`Interface`](crate::interface::Interface) or any name.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xdg_desktop_entry::interface::Interface;
+    /// use xdg_desktop_entry::store::ApplicationStore;
+    ///
+    /// let store = ApplicationStore::new();
+    /// let file_manager = Interface::parse("org.freedesktop.FileManager1").unwrap();
+    /// for app in store.implementing(&file_manager) {
+    ///     println!("{}", app.id);
+    /// }
+    /// ```
+    pub fn implementing(&self, interface: impl AsRef<str>) -> Vec<StoredApplication> {
+        let interface = interface.as_ref();
+        self.filter(|entry| entry.implements_interface(interface))
+    }
+
+    fn filter(&self, predicate: impl Fn(&DesktopEntry) -> bool) -> Vec<StoredApplication> {
+        self.read()
+            .applications
+            .values()
+            .filter(|app| predicate(&app.entry))
+            .cloned()
+            .collect()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, State> {
+        self.state.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, State> {
+        self.state.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for ApplicationStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
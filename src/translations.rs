@@ -0,0 +1,255 @@
+//! Listing and editing of translated values.
+
+use std::collections::BTreeSet;
+
+use crate::{DesktopEntry, DesktopEntryError, Locale, Localized, Result};
+
+impl DesktopEntry {
+    /// Sets the untranslated `Name`, keeping its translations.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::InvalidValue`] if the name is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, Locale};
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=Calc\nExec=calc\n",
+    /// )
+    /// .unwrap();
+    /// entry.set_name("Calculator").unwrap();
+    /// entry
+    ///     .set_name_localized(Locale::from_string("fr"), "Calculatrice")
+    ///     .unwrap();
+    /// assert!(entry.set_name("").is_err());
+    ///
+    /// let serialized = entry.serialize();
+    /// assert!(serialized.contains("Name=Calculator\n"));
+    /// assert!(serialized.contains("Name[fr]=Calculatrice\n"));
+    /// ```
+    pub fn set_name(&mut self, name: impl Into<String>) -> Result<()> {
+        let name = name.into();
+        if name.is_empty() {
+            return Err(empty_value("Name"));
+        }
+        self.name.default = name;
+        Ok(())
+    }
+
+    /// Sets the translation of `Name` for a locale.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::InvalidValue`] if the translation is
+    /// empty.
+    pub fn set_name_localized(&mut self, locale: Locale, name: impl Into<String>) -> Result<()> {
+        let name = name.into();
+        if name.is_empty() {
+            return Err(empty_value("Name"));
+        }
+        self.name.add_localized(locale, name);
+        Ok(())
+    }
+
+    /// Sets the untranslated `GenericName`, keeping its translations, or
+    /// removes the key with its translations.
+    pub fn set_generic_name(&mut self, generic_name: Option<&str>) {
+        set_default(&mut self.generic_name, generic_name.map(str::to_string));
+    }
+
+    /// Sets the translation of `GenericName` for a locale.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::MissingRequiredKey`] if there is no
+    /// untranslated `GenericName` to translate.
+    pub fn set_generic_name_localized(
+        &mut self,
+        locale: Locale,
+        generic_name: impl Into<String>,
+    ) -> Result<()> {
+        set_localized(
+            &mut self.generic_name,
+            "GenericName",
+            locale,
+            generic_name.into(),
+        )
+    }
+
+    /// Sets the untranslated `Comment`, keeping its translations, or removes
+    /// the key with its translations.
+    pub fn set_comment(&mut self, comment: Option<&str>) {
+        set_default(&mut self.comment, comment.map(str::to_string));
+    }
+
+    /// Sets the translation of `Comment` for a locale.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::MissingRequiredKey`] if there is no
+    /// untranslated `Comment` to translate.
+    pub fn set_comment_localized(
+        &mut self,
+        locale: Locale,
+        comment: impl Into<String>,
+    ) -> Result<()> {
+        set_localized(&mut self.comment, "Comment", locale, comment.into())
+    }
+
+    /// Sets the untranslated `Keywords`, keeping their translations, or
+    /// removes the key with its translations.
+    pub fn set_keywords(&mut self, keywords: Option<Vec<String>>) {
+        set_default(&mut self.keywords, keywords);
+    }
+
+    /// Sets the translation of `Keywords` for a locale.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::MissingRequiredKey`] if there are no
+    /// untranslated `Keywords` to translate.
+    pub fn set_keywords_localized(&mut self, locale: Locale, keywords: Vec<String>) -> Result<()> {
+        set_localized(&mut self.keywords, "Keywords", locale, keywords)
+    }
+
+    /// Sets the untranslated `Icon`, keeping its translations, or removes the
+    /// key with its translations.
+    pub fn set_icon(&mut self, icon: Option<&str>) {
+        set_default(&mut self.icon, icon.map(str::to_string));
+    }
+
+    /// Sets the translation of `Icon` for a locale.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DesktopEntryError::MissingRequiredKey`] if there is no
+    /// untranslated `Icon` to translate.
+    pub fn set_icon_localized(&mut self, locale: Locale, icon: impl Into<String>) -> Result<()> {
+        set_localized(&mut self.icon, "Icon", locale, icon.into())
+    }
+
+    /// The locales the entry has translations for.
+    ///
+    /// Covers the same keys as
+    /// [`remove_localization`](Self::remove_localization): the localized keys
+    /// of `[Desktop Entry]`, including unknown keys, and those of the other
+    /// groups, such as action names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, Locale};
+    ///
+    /// let entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nName[de]=Anwendung\nExec=app\n\
+    ///      Keywords[pt_BR]=programa;\nKeywords=program;\nActions=new;\n\n\
+    ///      [Desktop Action new]\nName=New\nName[sr@latin]=Novi\nExec=app --new\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let locales: Vec<String> = entry.locales().iter().map(Locale::to_string_repr).collect();
+    /// assert_eq!(locales, ["de", "pt_BR", "sr@latin"]);
+    /// ```
+    pub fn locales(&self) -> BTreeSet<Locale> {
+        let mut locales: BTreeSet<Locale> = self.name.localized.keys().cloned().collect();
+        locales.extend(keys_of(&self.generic_name));
+        locales.extend(keys_of(&self.comment));
+        locales.extend(keys_of(&self.keywords));
+        locales.extend(keys_of(&self.icon));
+
+        let groups = self.additional_groups.values().map(|group| &group.entries);
+        for entries in std::iter::once(&self.unknown_keys).chain(groups) {
+            locales.extend(
+                entries
+                    .values()
+                    .flatten()
+                    .filter_map(|entry| entry.locale.clone()),
+            );
+        }
+        locales
+    }
+
+    /// Removes every value translated for exactly this locale, returning
+    /// whether any was removed.
+    ///
+    /// Covers the localized keys of `[Desktop Entry]`, including unknown
+    /// keys, and those of the other groups, such as action names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::{DesktopEntry, Locale};
+    ///
+    /// let mut entry = DesktopEntry::parse(
+    ///     "[Desktop Entry]\nType=Application\nName=App\nName[de]=Anwendung\nExec=app\n\
+    ///      Comment=Does things\nComment[de]=Macht Dinge\nActions=new;\n\n\
+    ///      [Desktop Action new]\nName=New\nName[de]=Neu\nExec=app --new\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert!(entry.remove_localization(&Locale::from_string("de")));
+    /// assert!(!entry.serialize().contains("[de]"));
+    /// assert!(!entry.remove_localization(&Locale::from_string("de")));
+    /// ```
+    pub fn remove_localization(&mut self, locale: &Locale) -> bool {
+        let mut removed = self.name.localized.remove(locale).is_some();
+        removed |= remove_from(&mut self.generic_name, locale);
+        removed |= remove_from(&mut self.comment, locale);
+        removed |= remove_from(&mut self.keywords, locale);
+        removed |= remove_from(&mut self.icon, locale);
+
+        let groups = self
+            .additional_groups
+            .values_mut()
+            .map(|group| &mut group.entries);
+        for entries in std::iter::once(&mut self.unknown_keys).chain(groups) {
+            for values in entries.values_mut() {
+                let count = values.len();
+                values.retain(|entry| entry.locale.as_ref() != Some(locale));
+                removed |= values.len() != count;
+            }
+            entries.retain(|_, values| !values.is_empty());
+        }
+        removed
+    }
+}
+
+fn set_default<T>(field: &mut Option<Localized<T>>, value: Option<T>) {
+    match (field.as_mut(), value) {
+        (Some(localized), Some(value)) => localized.default = value,
+        (None, Some(value)) => *field = Some(Localized::new(value)),
+        (_, None) => *field = None,
+    }
+}
+
+fn set_localized<T>(
+    field: &mut Option<Localized<T>>,
+    key: &str,
+    locale: Locale,
+    value: T,
+) -> Result<()> {
+    let localized = field
+        .as_mut()
+        .ok_or_else(|| DesktopEntryError::MissingRequiredKey(key.to_string()))?;
+    localized.add_localized(locale, value);
+    Ok(())
+}
+
+fn keys_of<T>(field: &Option<Localized<T>>) -> impl Iterator<Item = Locale> + '_ {
+    field
+        .iter()
+        .flat_map(|localized| localized.localized.keys().cloned())
+}
+
+fn remove_from<T>(field: &mut Option<Localized<T>>, locale: &Locale) -> bool {
+    field
+        .as_mut()
+        .is_some_and(|localized| localized.localized.remove(locale).is_some())
+}
+
+fn empty_value(key: &str) -> DesktopEntryError {
+    DesktopEntryError::InvalidValue(key.to_string(), "must not be empty".to_string())
+}
@@ -0,0 +1,439 @@
+//! Type-specific views of desktop entries.
+//!
+//! [`ApplicationEntry`], [`LinkEntry`] and [`DirectoryEntry`] wrap a
+//! [`DesktopEntry`] of the matching [`DesktopEntryType`] and only expose the
+//! keys the specification allows for that type. An application cannot be
+//! given a `URL`, and a link cannot be given an `Exec` line, because the
+//! setters do not exist.
+//!
+//! # Examples
+//!
+//! ```
+//! use xdg_desktop_entry::LocalizedString;
+//! use xdg_desktop_entry::typed::{ApplicationEntry, LinkEntry};
+//!
+//! let mut app = ApplicationEntry::new(LocalizedString::new("Editor"), "editor %F");
+//! app.set_terminal(Some(false));
+//! assert!(app.as_entry().validate().is_ok());
+//!
+//! let link = LinkEntry::new(LocalizedString::new("Home"), "https://example.com");
+//! let entry = link.into_entry();
+//! assert!(entry.as_link().is_some());
+//! assert!(entry.as_application().is_none());
+//! ```
+
+use std::path::Path;
+
+use crate::discovery;
+use crate::{
+    DesktopEntry, DesktopEntryError, DesktopEntryType, IconString, LocalizedString,
+    LocalizedStringList, Result,
+};
+
+/// A desktop entry of type `Application`.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct ApplicationEntry {
+    inner: DesktopEntry,
+}
+
+/// A desktop entry of type `Link`.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct LinkEntry {
+    inner: DesktopEntry,
+}
+
+/// A desktop entry of type `Directory`.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct DirectoryEntry {
+    inner: DesktopEntry,
+}
+
+/// Defines the conversions and the keys shared by all entry types.
+macro_rules! typed_entry {
+    ($ty:ident, $entry_type:ident, $as_ref:ident, $as_mut:ident, $into:ident) => {
+        impl $ty {
+            /// The underlying entry.
+            pub fn as_entry(&self) -> &DesktopEntry {
+                &self.inner
+            }
+
+            /// Converts back into the underlying entry.
+            pub fn into_entry(self) -> DesktopEntry {
+                self.inner
+            }
+
+            /// Name of the entry.
+            pub fn name(&self) -> &LocalizedString {
+                &self.inner.name
+            }
+
+            /// Sets the name of the entry.
+            pub fn set_name(&mut self, name: LocalizedString) {
+                self.inner.name = name;
+            }
+
+            /// Generic name of the entry.
+            pub fn generic_name(&self) -> Option<&LocalizedString> {
+                self.inner.generic_name.as_ref()
+            }
+
+            /// Sets or clears the generic name.
+            pub fn set_generic_name(&mut self, generic_name: Option<LocalizedString>) {
+                self.inner.generic_name = generic_name;
+            }
+
+            /// Tooltip of the entry.
+            pub fn comment(&self) -> Option<&LocalizedString> {
+                self.inner.comment.as_ref()
+            }
+
+            /// Sets or clears the tooltip.
+            pub fn set_comment(&mut self, comment: Option<LocalizedString>) {
+                self.inner.comment = comment;
+            }
+
+            /// Icon of the entry.
+            pub fn icon(&self) -> Option<&IconString> {
+                self.inner.icon.as_ref()
+            }
+
+            /// Sets or clears the icon.
+            pub fn set_icon(&mut self, icon: Option<IconString>) {
+                self.inner.icon = icon;
+            }
+
+            /// Specification version the entry conforms to.
+            pub fn version(&self) -> Option<&str> {
+                self.inner.version.as_deref()
+            }
+
+            /// Sets or clears the specification version.
+            pub fn set_version(&mut self, version: Option<String>) {
+                self.inner.version = version;
+            }
+
+            /// Whether the entry is hidden from menus.
+            pub fn no_display(&self) -> Option<bool> {
+                self.inner.no_display
+            }
+
+            /// Sets or clears `NoDisplay`.
+            pub fn set_no_display(&mut self, no_display: Option<bool>) {
+                self.inner.no_display = no_display;
+            }
+
+            /// Whether the entry has been deleted.
+            pub fn hidden(&self) -> Option<bool> {
+                self.inner.hidden
+            }
+
+            /// Sets or clears `Hidden`.
+            pub fn set_hidden(&mut self, hidden: Option<bool>) {
+                self.inner.hidden = hidden;
+            }
+
+            /// Desktops that should display the entry.
+            pub fn only_show_in(&self) -> Option<&[String]> {
+                self.inner.only_show_in.as_deref()
+            }
+
+            /// Sets or clears `OnlyShowIn`.
+            pub fn set_only_show_in(&mut self, desktops: Option<Vec<String>>) {
+                self.inner.only_show_in = desktops;
+            }
+
+            /// Desktops that should not display the entry.
+            pub fn not_show_in(&self) -> Option<&[String]> {
+                self.inner.not_show_in.as_deref()
+            }
+
+            /// Sets or clears `NotShowIn`.
+            pub fn set_not_show_in(&mut self, desktops: Option<Vec<String>>) {
+                self.inner.not_show_in = desktops;
+            }
+        }
+
+        impl From<$ty> for DesktopEntry {
+            fn from(entry: $ty) -> Self {
+                entry.inner
+            }
+        }
+
+        impl DesktopEntry {
+            #[doc = concat!("Views the entry as a [`", stringify!($ty), "`] if its type is `", stringify!($entry_type), "`.")]
+            pub fn $as_ref(&self) -> Option<&$ty> {
+                if self.entry_type != DesktopEntryType::$entry_type {
+                    return None;
+                }
+                // SAFETY: the wrapper is `repr(transparent)` over `DesktopEntry`.
+                Some(unsafe { &*(self as *const DesktopEntry as *const $ty) })
+            }
+
+            #[doc = concat!("Mutably views the entry as a [`", stringify!($ty), "`] if its type is `", stringify!($entry_type), "`.")]
+            pub fn $as_mut(&mut self) -> Option<&mut $ty> {
+                if self.entry_type != DesktopEntryType::$entry_type {
+                    return None;
+                }
+                // SAFETY: the wrapper is `repr(transparent)` over `DesktopEntry`.
+                Some(unsafe { &mut *(self as *mut DesktopEntry as *mut $ty) })
+            }
+
+            #[doc = concat!("Converts the entry into a [`", stringify!($ty), "`], or gives it back if its type is not `", stringify!($entry_type), "`.")]
+            #[allow(clippy::result_large_err)]
+            pub fn $into(self) -> std::result::Result<$ty, DesktopEntry> {
+                if self.entry_type == DesktopEntryType::$entry_type {
+                    Ok($ty { inner: self })
+                } else {
+                    Err(self)
+                }
+            }
+        }
+    };
+}
+
+typed_entry!(
+    ApplicationEntry,
+    Application,
+    as_application,
+    as_application_mut,
+    into_application
+);
+typed_entry!(LinkEntry, Link, as_link, as_link_mut, into_link);
+typed_entry!(
+    DirectoryEntry,
+    Directory,
+    as_directory,
+    as_directory_mut,
+    into_directory
+);
+
+impl ApplicationEntry {
+    /// Creates an application launched with the given command line.
+    pub fn new(name: LocalizedString, exec: impl Into<String>) -> Self {
+        let mut inner = DesktopEntry::new(DesktopEntryType::Application, name);
+        inner.exec = Some(exec.into());
+        Self { inner }
+    }
+
+    /// Command line of the application.
+    pub fn exec(&self) -> Option<&str> {
+        self.inner.exec.as_deref()
+    }
+
+    /// Sets or clears the command line.
+    ///
+    /// An application without `Exec` must be `DBusActivatable`.
+    pub fn set_exec(&mut self, exec: Option<String>) {
+        self.inner.exec = exec;
+    }
+
+    /// Program checked for to decide whether the application is installed.
+    pub fn try_exec(&self) -> Option<&str> {
+        self.inner.try_exec.as_deref()
+    }
+
+    /// Sets or clears `TryExec`.
+    pub fn set_try_exec(&mut self, try_exec: Option<String>) {
+        self.inner.try_exec = try_exec;
+    }
+
+    /// Working directory of the application.
+    pub fn path(&self) -> Option<&str> {
+        self.inner.path.as_deref()
+    }
+
+    /// Sets or clears the working directory.
+    pub fn set_path(&mut self, path: Option<String>) {
+        self.inner.path = path;
+    }
+
+    /// Whether the application runs in a terminal.
+    pub fn terminal(&self) -> Option<bool> {
+        self.inner.terminal
+    }
+
+    /// Sets or clears `Terminal`.
+    pub fn set_terminal(&mut self, terminal: Option<bool>) {
+        self.inner.terminal = terminal;
+    }
+
+    /// Whether the application is started through D-Bus activation.
+    pub fn dbus_activatable(&self) -> Option<bool> {
+        self.inner.dbus_activatable
+    }
+
+    /// Sets or clears `DBusActivatable`.
+    pub fn set_dbus_activatable(&mut self, dbus_activatable: Option<bool>) {
+        self.inner.dbus_activatable = dbus_activatable;
+    }
+
+    /// Identifiers of the application's actions.
+    pub fn actions(&self) -> Option<&[String]> {
+        self.inner.actions.as_deref()
+    }
+
+    /// Sets or clears the list of actions.
+    pub fn set_actions(&mut self, actions: Option<Vec<String>>) {
+        self.inner.actions = actions;
+    }
+
+    /// MIME types the application supports.
+    pub fn mime_type(&self) -> Option<&[String]> {
+        self.inner.mime_type.as_deref()
+    }
+
+    /// Sets or clears the supported MIME types.
+    pub fn set_mime_type(&mut self, mime_type: Option<Vec<String>>) {
+        self.inner.mime_type = mime_type;
+    }
+
+    /// Menu categories of the application.
+    pub fn categories(&self) -> Option<&[String]> {
+        self.inner.categories.as_deref()
+    }
+
+    /// Sets or clears the menu categories.
+    pub fn set_categories(&mut self, categories: Option<Vec<String>>) {
+        self.inner.categories = categories;
+    }
+
+    /// Interfaces the application implements.
+    pub fn implements(&self) -> Option<&[String]> {
+        self.inner.implements.as_deref()
+    }
+
+    /// Sets or clears the implemented interfaces.
+    pub fn set_implements(&mut self, implements: Option<Vec<String>>) {
+        self.inner.implements = implements;
+    }
+
+    /// Search keywords of the application.
+    pub fn keywords(&self) -> Option<&LocalizedStringList> {
+        self.inner.keywords.as_ref()
+    }
+
+    /// Sets or clears the search keywords.
+    pub fn set_keywords(&mut self, keywords: Option<LocalizedStringList>) {
+        self.inner.keywords = keywords;
+    }
+
+    /// Whether the application sends startup notifications.
+    pub fn startup_notify(&self) -> Option<bool> {
+        self.inner.startup_notify
+    }
+
+    /// Sets or clears `StartupNotify`.
+    pub fn set_startup_notify(&mut self, startup_notify: Option<bool>) {
+        self.inner.startup_notify = startup_notify;
+    }
+
+    /// WM class the application's windows are expected to have.
+    pub fn startup_wm_class(&self) -> Option<&str> {
+        self.inner.startup_wm_class.as_deref()
+    }
+
+    /// Sets or clears `StartupWMClass`.
+    pub fn set_startup_wm_class(&mut self, wm_class: Option<String>) {
+        self.inner.startup_wm_class = wm_class;
+    }
+
+    /// Whether the application prefers a discrete GPU.
+    pub fn prefers_non_default_gpu(&self) -> Option<bool> {
+        self.inner.prefers_non_default_gpu
+    }
+
+    /// Sets or clears `PrefersNonDefaultGPU`.
+    pub fn set_prefers_non_default_gpu(&mut self, prefers: Option<bool>) {
+        self.inner.prefers_non_default_gpu = prefers;
+    }
+
+    /// Whether the application has a single main window.
+    pub fn single_main_window(&self) -> Option<bool> {
+        self.inner.single_main_window
+    }
+
+    /// Sets or clears `SingleMainWindow`.
+    pub fn set_single_main_window(&mut self, single: Option<bool>) {
+        self.inner.single_main_window = single;
+    }
+}
+
+impl LinkEntry {
+    /// Creates a link to the given URL.
+    pub fn new(name: LocalizedString, url: impl Into<String>) -> Self {
+        let mut inner = DesktopEntry::new(DesktopEntryType::Link, name);
+        inner.url = Some(url.into());
+        Self { inner }
+    }
+
+    /// Target URL of the link.
+    pub fn url(&self) -> Option<&str> {
+        self.inner.url.as_deref()
+    }
+
+    /// Sets the target URL.
+    pub fn set_url(&mut self, url: impl Into<String>) {
+        self.inner.url = Some(url.into());
+    }
+}
+
+impl DirectoryEntry {
+    /// Creates a menu directory entry.
+    pub fn new(name: LocalizedString) -> Self {
+        Self {
+            inner: DesktopEntry::new(DesktopEntryType::Directory, name),
+        }
+    }
+
+    /// Parses a `.directory` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`DesktopEntry::parse_file`], or
+    /// [`DesktopEntryError::InvalidValue`] if the entry is not of type
+    /// `Directory`.
+    pub fn parse_file(path: impl AsRef<Path>) -> Result<Self> {
+        DesktopEntry::parse_file(path)?
+            .into_directory()
+            .map_err(|entry| {
+                DesktopEntryError::InvalidValue(
+                    "Type".to_string(),
+                    format!("expected Directory, found {}", entry.entry_type.as_str()),
+                )
+            })
+    }
+
+    /// Finds a `.directory` file, such as `Graphics.directory`, in the
+    /// [`directory_dirs`](discovery::directory_dirs).
+    ///
+    /// Returns `None` if no directory has a valid entry with that name.
+    pub fn find(name: &str) -> Option<Self> {
+        Self::find_in(name, &discovery::directory_dirs())
+    }
+
+    /// Finds a `.directory` file in the given directories, most important
+    /// first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xdg_desktop_entry::typed::DirectoryEntry;
+    ///
+    /// let graphics = DirectoryEntry::find_in(
+    ///     "Graphics.directory",
+    ///     &["tests/fixtures/menu/directories"],
+    /// )
+    /// .unwrap();
+    /// assert_eq!(graphics.name().default, "Graphics & Art");
+    /// assert!(DirectoryEntry::find_in("Missing.directory", &["tests/fixtures/menu/directories"]).is_none());
+    /// ```
+    pub fn find_in(name: &str, dirs: &[impl AsRef<Path>]) -> Option<Self> {
+        dirs.iter()
+            .map(|dir| dir.as_ref().join(name))
+            .find(|path| path.is_file())
+            .and_then(|path| Self::parse_file(path).ok())
+    }
+}
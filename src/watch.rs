@@ -0,0 +1,185 @@
+//! Live updates of the installed applications.
+//!
+//! An [`ApplicationWatcher`] monitors the `applications` directories and
+//! reports which desktop file IDs appeared, changed, or disappeared. Events
+//! follow the precedence rules of [`ApplicationIter`]: a user override
+//! appearing in a more important directory reports the ID as modified with
+//! the override's entry, and removing the override reports the system entry
+//! again. Files marked `Hidden=true` or failing to parse hide their ID.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::discovery::{self, ApplicationIter, DesktopFileId};
+use crate::{DesktopEntry, Result};
+
+/// A change of an installed application.
+#[derive(Debug, Clone)]
+pub enum ApplicationEvent {
+    /// An application became available
+    Added(DesktopFileId, PathBuf, DesktopEntry),
+    /// The entry providing an application changed, or was replaced by a
+    /// file in another directory
+    Modified(DesktopFileId, PathBuf, DesktopEntry),
+    /// An application is no longer available
+    Removed(DesktopFileId),
+}
+
+/// Watches the `applications` directories for changes.
+///
+/// Directories that do not exist when the watcher is created are not
+/// watched.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use xdg_desktop_entry::watch::{ApplicationEvent, ApplicationWatcher};
+///
+/// let mut watcher = ApplicationWatcher::new().unwrap();
+/// loop {
+///     for event in watcher.wait_timeout(Duration::from_secs(1)) {
+///         match event {
+///             ApplicationEvent::Added(id, _, entry) => println!("+ {}: {}", id, entry.name.default),
+///             ApplicationEvent::Modified(id, _, _) => println!("~ {}", id),
+///             ApplicationEvent::Removed(id) => println!("- {}", id),
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ApplicationWatcher {
+    dirs: Vec<PathBuf>,
+    /// Path of the entry providing each visible ID
+    visible: HashMap<DesktopFileId, PathBuf>,
+    receiver: Receiver<notify::Result<notify::Event>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl ApplicationWatcher {
+    /// Watches the [`application_dirs`](discovery::application_dirs).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform's file watching facility fails.
+    pub fn new() -> Result<Self> {
+        Self::with_dirs(discovery::application_dirs())
+    }
+
+    /// Watches the given `applications` directories, most important first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform's file watching facility fails.
+    pub fn with_dirs(dirs: Vec<PathBuf>) -> Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })
+        .map_err(watch_error)?;
+        for dir in dirs.iter().filter(|dir| dir.is_dir()) {
+            watcher
+                .watch(dir, RecursiveMode::Recursive)
+                .map_err(watch_error)?;
+        }
+
+        let visible = ApplicationIter::with_dirs(dirs.clone())
+            .map(|(id, path, _)| (id, path))
+            .collect();
+        Ok(Self {
+            dirs,
+            visible,
+            receiver,
+            _watcher: watcher,
+        })
+    }
+
+    /// The IDs of the visible applications and the paths of their entries.
+    pub fn applications(&self) -> impl Iterator<Item = (&DesktopFileId, &Path)> {
+        self.visible.iter().map(|(id, path)| (id, path.as_path()))
+    }
+
+    /// Returns the events for the changes seen so far, without blocking.
+    pub fn try_events(&mut self) -> Vec<ApplicationEvent> {
+        let mut changed = BTreeMap::new();
+        while let Ok(event) = self.receiver.try_recv() {
+            self.collect_paths(event, &mut changed);
+        }
+        self.resolve(changed)
+    }
+
+    /// Waits up to `timeout` for changes, then returns their events.
+    ///
+    /// Changes arriving together are reported together, so a file written
+    /// in several steps is usually reported once. The result may be empty
+    /// if the changes did not affect any application.
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Vec<ApplicationEvent> {
+        let mut changed = BTreeMap::new();
+        match self.receiver.recv_timeout(timeout) {
+            Ok(event) => self.collect_paths(event, &mut changed),
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => {
+                return Vec::new();
+            }
+        }
+        while let Ok(event) = self.receiver.try_recv() {
+            self.collect_paths(event, &mut changed);
+        }
+        self.resolve(changed)
+    }
+
+    /// Adds the desktop files touched by an event, grouped by ID.
+    fn collect_paths(
+        &self,
+        event: notify::Result<notify::Event>,
+        changed: &mut BTreeMap<DesktopFileId, Vec<PathBuf>>,
+    ) {
+        let Ok(event) = event else {
+            return;
+        };
+        for path in event.paths {
+            if let Some(id) = self
+                .dirs
+                .iter()
+                .find_map(|dir| DesktopFileId::from_path(&path, dir))
+            {
+                changed.entry(id).or_default().push(path);
+            }
+        }
+    }
+
+    /// Re-resolves the changed IDs and reports the differences.
+    fn resolve(&mut self, changed: BTreeMap<DesktopFileId, Vec<PathBuf>>) -> Vec<ApplicationEvent> {
+        let mut events = Vec::new();
+        for (id, paths) in changed {
+            let current = id.resolve_in(&self.dirs).and_then(|path| {
+                let entry = DesktopEntry::parse_file(&path).ok()?;
+                (entry.hidden != Some(true)).then_some((path, entry))
+            });
+            match (self.visible.remove(&id), current) {
+                (None, Some((path, entry))) => {
+                    self.visible.insert(id.clone(), path.clone());
+                    events.push(ApplicationEvent::Added(id, path, entry));
+                }
+                (Some(old_path), Some((path, entry))) => {
+                    self.visible.insert(id.clone(), path.clone());
+                    // Changes to shadowed files are not reported
+                    if old_path != path || paths.contains(&path) {
+                        events.push(ApplicationEvent::Modified(id, path, entry));
+                    }
+                }
+                (Some(_), None) => events.push(ApplicationEvent::Removed(id)),
+                (None, None) => {}
+            }
+        }
+        events
+    }
+}
+
+fn watch_error(err: notify::Error) -> crate::DesktopEntryError {
+    io::Error::other(err).into()
+}
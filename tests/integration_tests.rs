@@ -127,6 +127,64 @@ fn test_parse_spec_example() {
     assert!(entry.validate().is_ok());
 }
 
+#[test]
+fn test_string_conversions() {
+    let entry: DesktopEntry = std::fs::read_to_string("tests/fixtures/valid/spec_example.desktop")
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(entry.name.default, "Foo Viewer");
+    assert_eq!(format!("{}", entry), entry.serialize());
+    assert!(matches!(
+        "[Desktop Entry]\nName=App\n".parse::<DesktopEntry>(),
+        Err(DesktopEntryError::MissingRequiredKey(_))
+    ));
+
+    for entry_type in [
+        DesktopEntryType::Application,
+        DesktopEntryType::Link,
+        DesktopEntryType::Directory,
+    ] {
+        assert_eq!(
+            entry_type.to_string().parse::<DesktopEntryType>().unwrap(),
+            entry_type
+        );
+    }
+    assert!(matches!(
+        "Service".parse::<DesktopEntryType>(),
+        Err(DesktopEntryError::InvalidValue(key, value)) if key == "Type" && value == "Service"
+    ));
+    assert_eq!(DesktopEntryType::parse("Service"), None);
+}
+
+#[test]
+fn test_entry_equality_and_hash() {
+    use std::collections::HashSet;
+
+    let content = "# Viewer\n[Desktop Entry]\nType=Application\nName=Viewer\nName[de]=Betrachter\n\
+                   Name[fr]=Visionneuse\nExec=viewer\nX-Foo=1\nX-Bar=2\n\n[X-Extra]\nA=1\nB=2\n";
+    let entry = DesktopEntry::parse(content).unwrap();
+    let reordered = DesktopEntry::parse(
+        "# Viewer\n[Desktop Entry]\nName[fr]=Visionneuse\nX-Bar=2\nExec=viewer\nType=Application\n\
+         Name=Viewer\nX-Foo=1\nName[de]=Betrachter\n[X-Extra]\nB=2\nA=1\n",
+    )
+    .unwrap();
+    let mut from_file = entry.clone();
+    from_file.source_path = Some("/usr/share/applications/viewer.desktop".into());
+
+    assert_eq!(entry, reordered);
+    assert_eq!(entry, from_file);
+    let set: HashSet<DesktopEntry> = [entry.clone(), reordered, from_file].into_iter().collect();
+    assert_eq!(set.len(), 1);
+
+    let mut edited = entry.clone();
+    edited.exec = Some("viewer %f".to_string());
+    assert_ne!(entry, edited);
+    let uncommented = DesktopEntry::parse(content.trim_start_matches("# Viewer\n")).unwrap();
+    assert_ne!(entry, uncommented);
+    assert!(entry.semantic_eq(&uncommented));
+}
+
 #[test]
 fn test_missing_desktop_entry_group() {
     let result = DesktopEntry::parse_file("tests/fixtures/invalid/missing_desktop_entry.desktop");
@@ -194,6 +252,26 @@ fn test_locale_parsing() {
     assert_eq!(locale.modifier, Some("euro".to_string()));
 }
 
+#[test]
+fn test_locale_std_traits() {
+    use std::collections::BTreeMap;
+
+    for repr in ["en", "en_US", "sr_YU@Latn", "en_US.UTF-8@euro"] {
+        let locale: Locale = repr.parse().unwrap();
+        assert_eq!(locale, Locale::from_string(repr));
+        assert_eq!(locale.to_string(), repr);
+        assert_eq!(locale.to_string_repr(), repr);
+    }
+    assert_eq!(Locale::default().to_string(), "C");
+
+    let names: BTreeMap<Locale, &str> = [("pt_BR", "Olá"), ("de", "Hallo"), ("pt", "Olá")]
+        .into_iter()
+        .map(|(locale, name)| (locale.parse().unwrap(), name))
+        .collect();
+    let order: Vec<String> = names.keys().map(ToString::to_string).collect();
+    assert_eq!(order, ["de", "pt", "pt_BR"]);
+}
+
 #[test]
 fn test_locale_matching() {
     use xdg_desktop_entry::LocalizedString;
@@ -213,6 +291,75 @@ fn test_locale_matching() {
     assert_eq!(name.get(&Locale::from_string("de")), "Default");
 }
 
+#[test]
+fn test_locale_normalized_matching() {
+    use xdg_desktop_entry::LocalizedString;
+
+    let mut name = LocalizedString::new("Default");
+    name.add_localized(Locale::from_string("de_DE"), "Deutsch".to_string());
+    name.add_localized(Locale::from_string("NO"), "Norsk".to_string());
+    name.add_localized(Locale::from_string("sr_YU@latin"), "Srpski".to_string());
+    name.add_localized(Locale::from_string("pt"), "Português".to_string());
+
+    assert_eq!(name.get(&Locale::from_string("de_de.UTF-8")), "Deutsch");
+    assert_eq!(name.get(&Locale::from_string("DE_DE")), "Deutsch");
+    assert_eq!(name.get(&Locale::from_string("nb_NO.utf8")), "Norsk");
+    assert_eq!(name.get(&Locale::from_string("sr_RS@latin")), "Srpski");
+    assert_eq!(name.get(&Locale::from_string("pt_BR.UTF-8")), "Português");
+    assert_eq!(name.get(&Locale::from_string("sr")), "Default");
+
+    let locale = Locale::from_string("En_gb.ISO-8859-1");
+    assert_eq!(locale.normalized().to_string_repr(), "en_GB");
+    assert_eq!(Locale::from_string("iw_IL").normalized().lang, "he");
+}
+
+#[test]
+fn test_locale_fallback_chain() {
+    use xdg_desktop_entry::{LocalizedString, LocalizedStringList};
+
+    let mut name = LocalizedString::new("Default");
+    name.add_localized(Locale::from_string("fr"), "Français".to_string());
+    name.add_localized(Locale::from_string("en_US"), "American English".to_string());
+
+    let chain = |list: &str| -> Vec<Locale> { list.split(':').map(Locale::from_string).collect() };
+    // Spec matching applies to each locale before moving on
+    assert_eq!(
+        name.get_with_fallbacks(&chain("de:fr_BE:en_US")),
+        "Français"
+    );
+    assert_eq!(
+        name.get_with_fallbacks(&chain("en_US:fr")),
+        "American English"
+    );
+    assert_eq!(name.get_with_fallbacks(&chain("de:it")), "Default");
+    assert_eq!(name.get_with_fallbacks(&[]), "Default");
+
+    let mut keywords = LocalizedStringList::new(vec!["calc".to_string()]);
+    keywords.add_localized(Locale::from_string("de"), vec!["Rechner".to_string()]);
+    assert_eq!(keywords.get_with_fallbacks(&chain("fr:de_AT")), ["Rechner"]);
+    assert_eq!(keywords.get_with_fallbacks(&chain("fr")), ["calc"]);
+}
+
+#[test]
+fn test_localized_generic_value() {
+    use std::path::{Path, PathBuf};
+    use xdg_desktop_entry::Localized;
+
+    let mut help = Localized::<PathBuf>::new("/usr/share/help/C/app");
+    help.add_localized(
+        Locale::from_string("de"),
+        PathBuf::from("/usr/share/help/de/app"),
+    );
+
+    let path: &Path = help.get(&Locale::from_string("de_CH@euro"));
+    assert_eq!(path, Path::new("/usr/share/help/de/app"));
+    assert!(help.lookup(&Locale::from_string("fr")).is_none());
+    assert_eq!(
+        help.get_with_fallbacks(&[Locale::from_string("fr")]),
+        Path::new("/usr/share/help/C/app")
+    );
+}
+
 #[test]
 fn test_serialization_roundtrip() {
     // Parse a file
@@ -321,6 +468,56 @@ fn test_missing_name() {
     }
 }
 
+#[test]
+fn test_actions_validation_failure() {
+    let mut entry =
+        DesktopEntry::parse_file("tests/fixtures/invalid/broken_actions.desktop").unwrap();
+
+    let message = |entry: &DesktopEntry| match entry.validate() {
+        Err(DesktopEntryError::ValidationError(message)) => message,
+        other => panic!("Expected ValidationError, got {:?}", other),
+    };
+    assert!(message(&entry).contains("[Desktop Action new-window] is missing the required Name"));
+
+    entry
+        .additional_groups
+        .get_mut("Desktop Action new-window")
+        .unwrap()
+        .entries
+        .insert(
+            "Name".to_string(),
+            vec![xdg_desktop_entry::Entry {
+                key: "Name".to_string(),
+                locale: None,
+                value: "New Window".to_string(),
+                span: None,
+            }],
+        );
+    assert!(message(&entry).contains("Action 'missing' is listed in Actions"));
+
+    entry.actions = Some(vec!["new-window".to_string()]);
+    assert!(message(&entry).contains("[Desktop Action orphan] is not listed in Actions"));
+
+    entry.remove_group("Desktop Action orphan");
+    entry.validate().unwrap();
+}
+
+#[test]
+fn test_io_error_keeps_kind_and_source() {
+    use std::error::Error;
+
+    let err = DesktopEntry::parse_file("tests/fixtures/does_not_exist.desktop").unwrap_err();
+    let DesktopEntryError::Io(io_err) = &err else {
+        panic!("Expected Io error, got {:?}", err);
+    };
+    assert_eq!(io_err.kind(), std::io::ErrorKind::NotFound);
+
+    let source = err.source().unwrap();
+    let source = source.downcast_ref::<std::io::Error>().unwrap();
+    assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+    assert_eq!(err.clone(), err);
+}
+
 #[test]
 fn test_link_validation_failure() {
     let entry =
@@ -393,6 +590,23 @@ fn test_parse_dbus_app() {
 
     assert_eq!(entry.entry_type, DesktopEntryType::Application);
     assert_eq!(entry.dbus_activatable, Some(true));
+
+    // The file name must be a D-Bus well-known name
+    assert!(matches!(
+        entry.validate(),
+        Err(DesktopEntryError::ValidationError(message)) if message.contains("dbus_app.desktop")
+    ));
+    let mut entry = entry;
+    for (file_name, valid) in [
+        ("org.example.DBusApp.desktop", true),
+        ("org.example.dbus-app_2.desktop", true),
+        ("org.2example.App.desktop", false),
+        ("org..App.desktop", false),
+    ] {
+        entry.source_path = Some(format!("/usr/share/applications/{}", file_name).into());
+        assert_eq!(entry.validate().is_ok(), valid, "{}", file_name);
+    }
+    entry.source_path = None;
     assert!(entry.validate().is_ok());
 }
 
@@ -440,3 +654,4493 @@ fn test_parse_feature_rich() {
 
     assert!(entry.validate().is_ok());
 }
+
+// ============================================================================
+// Group editing tests
+// ============================================================================
+
+#[test]
+fn test_remove_action_group_updates_actions() {
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/full_entry.desktop").unwrap();
+
+    let group = entry.remove_group("Desktop Action new-window").unwrap();
+    assert_eq!(group.name, "Desktop Action new-window");
+    assert!(
+        !entry
+            .additional_groups
+            .contains_key("Desktop Action new-window")
+    );
+    assert_eq!(entry.actions, Some(vec!["preferences".to_string()]));
+
+    assert!(entry.remove_group("Desktop Action new-window").is_none());
+}
+
+#[test]
+fn test_get_and_set_by_path() {
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/full_entry.desktop").unwrap();
+    let de = Locale::from_string("de");
+
+    assert_eq!(entry.get("Type").as_deref(), Some("Application"));
+    assert_eq!(
+        entry.get("Desktop Entry/Type").as_deref(),
+        Some("Application")
+    );
+    assert_eq!(entry.get("X-Missing"), None);
+    assert_eq!(entry.get("X-Missing Group/Name"), None);
+
+    entry.set("StartupWMClass", "full-app").unwrap();
+    assert_eq!(entry.startup_wm_class.as_deref(), Some("full-app"));
+    entry.set("Categories", "Office;Viewer;").unwrap();
+    assert_eq!(
+        entry.categories,
+        Some(vec!["Office".to_string(), "Viewer".to_string()])
+    );
+
+    entry
+        .set("Desktop Action new-window/Name", "Open a New Window")
+        .unwrap();
+    entry
+        .set_localized(
+            "Desktop Action new-window/Name",
+            de.clone(),
+            "Neues Fenster",
+        )
+        .unwrap();
+    assert_eq!(
+        entry.get("Desktop Action new-window/Name").as_deref(),
+        Some("Open a New Window")
+    );
+    assert_eq!(
+        entry
+            .get_localized(
+                "Desktop Action new-window/Name",
+                &Locale::from_string("de_CH")
+            )
+            .as_deref(),
+        Some("Neues Fenster")
+    );
+
+    // Values are escaped, and new groups are created
+    entry
+        .set("X-Vendor Settings/Motd", "line 1\nline 2")
+        .unwrap();
+    assert_eq!(
+        entry.additional_groups["X-Vendor Settings"].get("Motd"),
+        Some("line 1\\nline 2")
+    );
+    assert_eq!(
+        entry.get("X-Vendor Settings/Motd").as_deref(),
+        Some("line 1\nline 2")
+    );
+
+    // Failed edits leave the entry untouched
+    let before = entry.clone();
+    assert!(entry.set("Type", "Service").is_err());
+    assert!(entry.set("Bad Key", "x").is_err());
+    assert!(entry.set("X-[Bad]/Key", "x").is_err());
+    assert_eq!(entry, before);
+}
+
+#[test]
+fn test_remove_keys_and_actions() {
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/full_entry.desktop").unwrap();
+
+    assert!(entry.remove_key("Desktop Entry", "Categories").unwrap());
+    assert_eq!(entry.categories, None);
+    assert!(
+        entry
+            .remove_key("Desktop Action new-window", "Exec")
+            .unwrap()
+    );
+    assert_eq!(entry.get("Desktop Action new-window/Exec"), None);
+    assert!(!entry.remove_key("X-Missing", "Key").unwrap());
+    assert!(matches!(
+        entry.remove_key("Desktop Entry", "Type"),
+        Err(DesktopEntryError::MissingRequiredKey(_))
+    ));
+    assert_eq!(entry.entry_type, DesktopEntryType::Application);
+
+    let group = entry.remove_action("preferences").unwrap();
+    assert_eq!(group.name, "Desktop Action preferences");
+    assert_eq!(entry.actions, Some(vec!["new-window".to_string()]));
+    assert!(!entry.serialize().contains("[Desktop Action preferences]"));
+}
+
+#[test]
+fn test_rename_action_group_updates_actions() {
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/full_entry.desktop").unwrap();
+
+    entry
+        .rename_group("Desktop Action new-window", "Desktop Action open")
+        .unwrap();
+
+    let group = entry.additional_groups.get("Desktop Action open").unwrap();
+    assert_eq!(group.name, "Desktop Action open");
+    assert_eq!(
+        entry.actions,
+        Some(vec!["open".to_string(), "preferences".to_string()])
+    );
+
+    // The renamed group survives a round trip
+    let reparsed = DesktopEntry::parse(&entry.serialize()).unwrap();
+    assert!(
+        reparsed
+            .additional_groups
+            .contains_key("Desktop Action open")
+    );
+}
+
+#[test]
+fn test_rename_group_errors() {
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/full_entry.desktop").unwrap();
+
+    assert_eq!(
+        entry.rename_group("Desktop Action missing", "Desktop Action other"),
+        Err(DesktopEntryError::MissingGroup(
+            "Desktop Action missing".to_string()
+        ))
+    );
+    assert_eq!(
+        entry.rename_group("Desktop Action new-window", "Desktop Action preferences"),
+        Err(DesktopEntryError::DuplicateGroup(
+            "Desktop Action preferences".to_string()
+        ))
+    );
+    assert_eq!(
+        entry.rename_group("Desktop Action new-window", "Desktop Entry"),
+        Err(DesktopEntryError::DuplicateGroup(
+            "Desktop Entry".to_string()
+        ))
+    );
+}
+
+// ============================================================================
+// Interface group tests
+// ============================================================================
+
+#[test]
+fn test_interface_group_lookup() {
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/interface_app.desktop").unwrap();
+
+    let group = entry
+        .interface_group("org.freedesktop.ImageProvider")
+        .unwrap();
+    let de = Locale::from_string("de_DE");
+
+    let name = group.get_localized_string("Name").unwrap();
+    assert_eq!(name.default, "Viewer Images");
+    assert_eq!(name.get(&de), "Betrachterbilder");
+
+    let icon = group.get_icon_string("Icon").unwrap();
+    assert_eq!(icon.default, "image-viewer");
+
+    let formats = group.get_localized_string_list("Formats").unwrap();
+    assert_eq!(formats.default, vec!["png", "jpeg"]);
+    assert_eq!(formats.get(&de), ["png", "jpeg", "gif"]);
+
+    assert_eq!(group.get("Missing"), None);
+    assert!(group.get_localized_string("Missing").is_none());
+
+    // Groups whose interface is not declared in Implements are not interface groups
+    assert!(entry.interface_group("org.example.Unlisted").is_none());
+    assert!(entry.interface_group("org.example.Missing").is_none());
+}
+
+#[test]
+fn test_typed_key_accessors() {
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\n\
+         X-Scale=2\nX-Enabled=yes\nX-Note=a\\tb\nX-Title=Hi\nX-Title[fr]=Salut\n\n\
+         [X-Vendor]\nRatio= 0.5\nWords=one;two;three;\n",
+    )
+    .unwrap();
+
+    assert_eq!(entry.unknown_numeric("X-Scale"), Some(2.0));
+    assert_eq!(entry.unknown_bool("X-Enabled"), None);
+    assert_eq!(entry.unknown_string("X-Note").as_deref(), Some("a\tb"));
+    let fr = Locale::from_string("fr_FR");
+    assert_eq!(
+        entry.unknown_localestring("X-Title", &fr).as_deref(),
+        Some("Salut")
+    );
+    assert_eq!(entry.unknown_string("X-Missing"), None);
+
+    let group = &entry.additional_groups["X-Vendor"];
+    assert_eq!(group.get_numeric("Ratio"), Some(0.5));
+    assert_eq!(group.get_string_list("Words").unwrap().len(), 3);
+    assert_eq!(group.get_bool("Ratio"), None);
+}
+
+#[test]
+fn test_interface_names() {
+    use xdg_desktop_entry::interface::Interface;
+
+    for valid in [
+        "org.freedesktop.FileManager1",
+        "org.gnome.Shell.SearchProvider2",
+        "_private.x",
+    ] {
+        assert!(Interface::is_valid(valid), "{}", valid);
+    }
+    for invalid in [
+        "",
+        "FileManager",
+        "org..Example",
+        "org.freedesktop.",
+        "org.1example",
+        "org.example-app",
+        &format!("org.{}", "a".repeat(252)),
+    ] {
+        assert!(Interface::parse(invalid).is_none(), "{}", invalid);
+    }
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/interface_app.desktop").unwrap();
+    let image_provider = Interface::parse("org.freedesktop.ImageProvider").unwrap();
+    assert_eq!(entry.interfaces(), std::slice::from_ref(&image_provider));
+    assert!(entry.implements_interface(&image_provider));
+    assert!(!entry.implements_interface("org.example.Unlisted"));
+}
+
+// ============================================================================
+// Search provider tests
+// ============================================================================
+
+#[test]
+fn test_parse_search_provider() {
+    use xdg_desktop_entry::search_provider::SearchProvider;
+
+    let provider = SearchProvider::parse_file("tests/fixtures/valid/search_provider.ini").unwrap();
+
+    assert_eq!(provider.desktop_id, "org.example.Notes.desktop");
+    assert_eq!(provider.bus_name, "org.example.Notes");
+    assert_eq!(provider.object_path, "/org/example/Notes/SearchProvider");
+    assert_eq!(provider.version, 2);
+    assert!(provider.default_disabled);
+
+    // Round trip
+    let reparsed = SearchProvider::parse(&provider.serialize()).unwrap();
+    assert_eq!(reparsed, provider);
+}
+
+#[test]
+fn test_search_provider_errors() {
+    use xdg_desktop_entry::search_provider::SearchProvider;
+
+    assert_eq!(
+        SearchProvider::parse("[Other]\nKey=value\n"),
+        Err(DesktopEntryError::MissingGroup(
+            "Shell Search Provider".to_string()
+        ))
+    );
+    assert_eq!(
+        SearchProvider::parse("[Shell Search Provider]\nDesktopId=a.desktop\nVersion=2\n"),
+        Err(DesktopEntryError::MissingRequiredKey("BusName".to_string()))
+    );
+    assert!(matches!(
+        SearchProvider::parse(
+            "[Shell Search Provider]\nDesktopId=a.desktop\nBusName=a\nObjectPath=/a\nVersion=two\n"
+        ),
+        Err(DesktopEntryError::InvalidValue(_, _))
+    ));
+}
+
+#[test]
+fn test_search_provider_declaration() {
+    use xdg_desktop_entry::search_provider::{SEARCH_PROVIDER_INTERFACE, SearchProvider};
+
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/minimal.desktop").unwrap();
+    assert!(!SearchProvider::is_declared_by(&entry));
+
+    SearchProvider::declare_on(&mut entry);
+    SearchProvider::declare_on(&mut entry);
+    assert!(SearchProvider::is_declared_by(&entry));
+    assert_eq!(
+        entry.implements,
+        Some(vec![SEARCH_PROVIDER_INTERFACE.to_string()])
+    );
+}
+
+// ============================================================================
+// Launch method tests
+// ============================================================================
+
+#[test]
+fn test_launch_method_exec_command() {
+    use xdg_desktop_entry::launch::LaunchMethod;
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/full_entry.desktop").unwrap();
+    assert_eq!(
+        entry.effective_launch_method().unwrap(),
+        LaunchMethod::ExecCommand {
+            argv: vec!["full-app".to_string(), "%F".to_string()]
+        }
+    );
+}
+
+#[cfg(feature = "dbus")]
+#[test]
+fn test_launch_dbus_requires_dbus_activatable() {
+    use xdg_desktop_entry::dbus::PlatformData;
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/full_entry.desktop").unwrap();
+    assert!(matches!(
+        entry.launch_dbus(&[] as &[&str], &PlatformData::default()),
+        Err(DesktopEntryError::ValidationError(_))
+    ));
+
+    // The bus name comes from the desktop file name
+    let mut entry =
+        DesktopEntry::parse_file("tests/fixtures/applications/org.example.FooViewer.desktop")
+            .unwrap();
+    entry.source_path = None;
+    assert!(matches!(
+        entry.launch_dbus(&["/tmp/a.png"], &PlatformData::default()),
+        Err(DesktopEntryError::ValidationError(_))
+    ));
+}
+
+#[test]
+fn test_find_terminal_emulator() {
+    use xdg_desktop_entry::launch::{
+        LaunchOptions, TERMINAL_EMULATORS, XDG_TERMINAL_EXEC, find_terminal_emulator,
+    };
+
+    assert_eq!(TERMINAL_EMULATORS[0][0], "x-terminal-emulator");
+    assert!(TERMINAL_EMULATORS.contains(&&["gnome-terminal", "--"][..]));
+
+    // Terminal applications run in the emulator found on this system
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=Top\nExec=top\nTerminal=true\n",
+    )
+    .unwrap();
+    let command = entry
+        .launch_command(&[] as &[&str], &LaunchOptions::default())
+        .unwrap();
+    match find_terminal_emulator() {
+        Some(terminal) if terminal[0] == XDG_TERMINAL_EXEC => {
+            assert_eq!(terminal.len(), 1);
+            assert_eq!(command.get_program(), XDG_TERMINAL_EXEC);
+        }
+        Some(terminal) => assert_eq!(command.get_program(), terminal[0].as_str()),
+        None => assert_eq!(command.get_program(), "xterm"),
+    }
+    assert_eq!(command.get_args().last().unwrap(), "top");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_launch_detached() {
+    use std::time::{Duration, Instant};
+    use xdg_desktop_entry::launch::LaunchOptions;
+
+    let dir = std::env::temp_dir().join(format!("xdg-detached-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let marker = dir.join("started");
+
+    let entry = DesktopEntry::parse(&format!(
+        "[Desktop Entry]\nType=Application\nName=Touch\nExec=touch {}\n",
+        marker.display()
+    ))
+    .unwrap();
+    entry
+        .launch_detached(&[] as &[&str], &LaunchOptions::default())
+        .unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !marker.exists() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert!(marker.exists());
+
+    // A missing program is reported, although it fails in the grandchild
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=Missing\nExec=/nonexistent/xdg-desktop-entry-test\n",
+    )
+    .unwrap();
+    assert!(
+        entry
+            .launch_detached(&[] as &[&str], &LaunchOptions::default())
+            .is_err()
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_launch_in_systemd_scope() {
+    use xdg_desktop_entry::launch::LaunchOptions;
+
+    let options = LaunchOptions {
+        systemd_scope: true,
+        ..LaunchOptions::default()
+    };
+    let mut entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=My App\nExec=/opt/my-app/bin/my-app %f\n",
+    )
+    .unwrap();
+
+    let command = entry.launch_command(&["a.txt"], &options).unwrap();
+    assert_eq!(command.get_program(), "systemd-run");
+    let args: Vec<_> = command
+        .get_args()
+        .map(|arg| arg.to_str().unwrap())
+        .collect();
+    assert_eq!(
+        args[..5],
+        [
+            "--user",
+            "--scope",
+            "--quiet",
+            "--collect",
+            "--slice=app.slice"
+        ]
+    );
+    assert!(args[5].starts_with("--unit=app-my\\x2dapp-") && args[5].ends_with(".scope"));
+    assert_eq!(args[6], "--description=My App");
+    assert_eq!(args[7..], ["--", "/opt/my-app/bin/my-app", "a.txt"]);
+
+    // The desktop file gives the app ID; the scope wraps the environment setup
+    entry.source_path = Some("/opt/my-app/org.example.MyApp.desktop".into());
+    let command = entry.launch_command(&["a.txt"], &options).unwrap();
+    assert_eq!(command.get_program(), "sh");
+    let args: Vec<_> = command
+        .get_args()
+        .map(|arg| arg.to_str().unwrap())
+        .collect();
+    assert_eq!(args[2], "systemd-run");
+    assert!(args[8].starts_with("--unit=app-org.example.MyApp-"));
+}
+
+#[test]
+fn test_launch_action_command() {
+    use xdg_desktop_entry::launch::LaunchOptions;
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/feature_rich.desktop").unwrap();
+    let command = entry
+        .launch_action_command("view", &LaunchOptions::default())
+        .unwrap();
+    assert_eq!(command.get_program(), "sh");
+    let args: Vec<_> = command.get_args().skip(2).collect();
+    assert_eq!(args, ["feature-rich-app", "--view"]);
+    assert_eq!(
+        command.get_current_dir(),
+        Some(std::path::Path::new("/home/user/apps"))
+    );
+
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\nActions=listed;\n\n\
+         [Desktop Action listed]\nName=Listed\n\n\
+         [Desktop Action unlisted]\nName=Unlisted\nExec=app --unlisted\n",
+    )
+    .unwrap();
+    assert!(matches!(
+        entry.launch_action_command("listed", &LaunchOptions::default()),
+        Err(DesktopEntryError::MissingRequiredKey(_))
+    ));
+    assert!(matches!(
+        entry.launch_action_command("unlisted", &LaunchOptions::default()),
+        Err(DesktopEntryError::ValidationError(_))
+    ));
+    assert!(matches!(
+        entry.launch_action("missing", &LaunchOptions::default()),
+        Err(DesktopEntryError::ValidationError(_))
+    ));
+}
+
+#[test]
+fn test_launch_method_dbus_activation() {
+    use xdg_desktop_entry::launch::LaunchMethod;
+
+    let entry =
+        DesktopEntry::parse_file("tests/fixtures/applications/org.example.FooViewer.desktop")
+            .unwrap();
+    assert_eq!(
+        entry.effective_launch_method().unwrap(),
+        LaunchMethod::DBusActivation {
+            name: "org.example.FooViewer".to_string(),
+            fallback: Some(vec![
+                "fooview".to_string(),
+                "--name".to_string(),
+                "Foo $HOME \"Viewer\"".to_string(),
+                "%F".to_string(),
+            ]),
+        }
+    );
+
+    // Without Exec there is nothing to fall back to
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/dbus_app.desktop").unwrap();
+    match entry.effective_launch_method().unwrap() {
+        LaunchMethod::DBusActivation { name, fallback } => {
+            assert_eq!(name, "dbus_app");
+            assert_eq!(fallback, None);
+        }
+        other => panic!("Expected DBusActivation, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_launch_method_none() {
+    use xdg_desktop_entry::launch::LaunchMethod;
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/link_entry.desktop").unwrap();
+    assert_eq!(entry.effective_launch_method().unwrap(), LaunchMethod::None);
+
+    // DBusActivatable entries with no known file name and no Exec
+    let entry = DesktopEntry::parse(
+        &std::fs::read_to_string("tests/fixtures/valid/dbus_app.desktop").unwrap(),
+    )
+    .unwrap();
+    assert_eq!(entry.effective_launch_method().unwrap(), LaunchMethod::None);
+}
+
+#[test]
+fn test_launch_method_unterminated_quote() {
+    let entry =
+        DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=App\nExec=app \"arg\n")
+            .unwrap();
+    assert!(matches!(
+        entry.effective_launch_method(),
+        Err(DesktopEntryError::InvalidValue(_, _))
+    ));
+}
+
+#[test]
+fn test_launch_command_expands_field_codes() {
+    use xdg_desktop_entry::launch::LaunchOptions;
+
+    let mut entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=Viewer\nName[de]=Betrachter\n\
+         Icon=viewer\nExec=viewer %i --name=%c %k %f\nTerminal=true\n",
+    )
+    .unwrap();
+    let options = LaunchOptions {
+        locales: vec![Locale::from_string("de_DE")],
+        terminal: Some(vec!["foot".to_string()]),
+        ..LaunchOptions::default()
+    };
+
+    let command = entry
+        .launch_command(&["file:///tmp/a.png"], &options)
+        .unwrap();
+    assert_eq!(command.get_program(), "foot");
+    let args: Vec<_> = command.get_args().collect();
+    assert_eq!(
+        args,
+        [
+            "viewer",
+            "--icon",
+            "viewer",
+            "--name=Betrachter",
+            "/tmp/a.png"
+        ]
+    );
+
+    // %f accepts a single target; %k expands once the location is known
+    assert!(entry.launch_command(&["a", "b"], &options).is_err());
+    entry.terminal = None;
+    entry.source_path = Some("/usr/share/applications/viewer.desktop".into());
+    let command = entry.launch_command(&[] as &[&str], &options).unwrap();
+    let args: Vec<_> = command.get_args().skip(2).collect();
+    assert_eq!(
+        args,
+        [
+            "viewer",
+            "--icon",
+            "viewer",
+            "--name=Betrachter",
+            "/usr/share/applications/viewer.desktop"
+        ]
+    );
+}
+
+#[test]
+fn test_launch_exports_desktop_file_and_pid() {
+    use std::process::Stdio;
+    use xdg_desktop_entry::launch::LaunchOptions;
+
+    let mut entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=Env\nExec=printenv GIO_LAUNCHED_DESKTOP_FILE GIO_LAUNCHED_DESKTOP_FILE_PID\nPath=/\n",
+    )
+    .unwrap();
+    entry.source_path = Some("/usr/share/applications/env.desktop".into());
+
+    let mut command = entry
+        .launch_command(&[] as &[&str], &LaunchOptions::default())
+        .unwrap();
+    let child = command.stdout(Stdio::piped()).spawn().unwrap();
+    let pid = child.id();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("/usr/share/applications/env.desktop\n{}\n", pid)
+    );
+}
+
+// ============================================================================
+// Application database tests
+// ============================================================================
+
+fn fixture_database() -> xdg_desktop_entry::database::AppDatabase {
+    let mut database = xdg_desktop_entry::database::AppDatabase::new();
+    for name in [
+        "full_entry",
+        "feature_rich",
+        "spec_example",
+        "hidden_app",
+        "link_entry",
+        "minimal",
+    ] {
+        let path = format!("tests/fixtures/valid/{}.desktop", name);
+        database.insert(
+            format!("{}.desktop", name),
+            DesktopEntry::parse_file(path).unwrap(),
+        );
+    }
+    database
+}
+
+#[test]
+fn test_database_mime_conflicts() {
+    let database = fixture_database();
+    let report = database.mime_conflicts();
+
+    assert_eq!(
+        report.contested.get("text/plain"),
+        Some(&vec![
+            "feature_rich.desktop".to_string(),
+            "full_entry.desktop".to_string()
+        ])
+    );
+    assert!(!report.contested.contains_key("image/x-foo"));
+    assert!(report.unhandled_schemes.contains(&"https".to_string()));
+    // Local files are opened through their MIME type, not a scheme handler
+    assert!(!report.unhandled_schemes.contains(&"file".to_string()));
+}
+
+#[test]
+fn test_database_scheme_handlers() {
+    let mut database = fixture_database();
+    let mut browser = DesktopEntry::parse_file("tests/fixtures/valid/minimal.desktop").unwrap();
+    browser.mime_type = Some(vec!["x-scheme-handler/https".to_string()]);
+    database.insert("browser.desktop", browser);
+
+    let report = database.mime_conflicts_for_schemes(&["https", "gopher"]);
+    assert_eq!(report.unhandled_schemes, vec!["gopher".to_string()]);
+
+    // Hidden entries are deleted and handle nothing
+    let mut hidden = database.remove("browser.desktop").unwrap();
+    hidden.hidden = Some(true);
+    database.insert("browser.desktop", hidden);
+    let report = database.mime_conflicts_for_schemes(&["https"]);
+    assert_eq!(report.unhandled_schemes, vec!["https".to_string()]);
+}
+
+#[test]
+fn test_database_export_json() {
+    use xdg_desktop_entry::database::ExportFormat;
+
+    let database = fixture_database();
+    let json = database.export(
+        &Locale::from_string("de_DE"),
+        &["GNOME"],
+        ExportFormat::Json,
+    );
+
+    assert!(
+        json.starts_with(
+            "[\n  {\"id\": \"feature_rich.desktop\", \"name\": \"Funktionsreiche App\""
+        )
+    );
+    assert!(json.contains(
+        "{\"id\": \"minimal.desktop\", \"name\": \"Minimal App\", \"icon\": null, \"exec\": \"minimal-app\", \"categories\": []}"
+    ));
+    assert!(json.contains("\"categories\": [\"Utility\", \"TextEditor\", \"Development\"]"));
+    // Hidden applications and links are not exported
+    assert!(!json.contains("hidden_app.desktop"));
+    assert!(!json.contains("link_entry.desktop"));
+
+    // OnlyShowIn=GNOME;KDE; excludes the entry elsewhere
+    let json = database.export(&Locale::from_string("de_DE"), &["XFCE"], ExportFormat::Json);
+    assert!(!json.contains("feature_rich.desktop"));
+}
+
+#[test]
+fn test_database_export_csv() {
+    use xdg_desktop_entry::database::{AppDatabase, ExportFormat};
+
+    let mut database = AppDatabase::new();
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/full_entry.desktop").unwrap();
+    entry.name.default = "Full, \"Featured\"".to_string();
+    database.insert("full.desktop", entry);
+
+    let csv = database.export(&Locale::from_string("en"), &["GNOME"], ExportFormat::Csv);
+    assert_eq!(
+        csv,
+        "id,name,icon,exec,categories\r\n\
+         full.desktop,\"Full, \"\"Featured\"\"\",text-editor,full-app %F,Utility;TextEditor;Development\r\n"
+    );
+
+    let empty = AppDatabase::new();
+    assert_eq!(
+        empty.export(&Locale::from_string("en"), &["GNOME"], ExportFormat::Json),
+        "[]\n"
+    );
+}
+
+// ============================================================================
+// Application cache tests
+// ============================================================================
+
+#[test]
+fn test_cache_roundtrip() {
+    use xdg_desktop_entry::cache::AppCache;
+
+    let database = fixture_database();
+    let path = std::env::temp_dir().join(format!("xdg-cache-{}.bin", std::process::id()));
+    AppCache::write_file(&database, &path).unwrap();
+
+    let cache = AppCache::open(&path).unwrap();
+    #[cfg(feature = "mmap")]
+    {
+        // SAFETY: nothing else writes the file while it is mapped.
+        let mapped = unsafe { AppCache::open_mapped(&path) }.unwrap();
+        assert_eq!(mapped.len(), database.len());
+        assert_eq!(
+            mapped
+                .get("full_entry.desktop")
+                .unwrap()
+                .get("Desktop Entry", "Exec"),
+            Some("full-app %F")
+        );
+    }
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(cache.len(), database.len());
+
+    let entry = cache.get("full_entry.desktop").unwrap();
+    assert_eq!(entry.id(), "full_entry.desktop");
+    assert_eq!(entry.get("Desktop Entry", "Exec"), Some("full-app %F"));
+    assert_eq!(
+        entry.get_localized("Desktop Entry", "Name", &Locale::from_string("es")),
+        Some("Aplicación Completa")
+    );
+    assert_eq!(
+        entry.get("Desktop Action new-window", "Exec"),
+        Some("full-app --new-window")
+    );
+    assert!(cache.get("missing.desktop").is_none());
+
+    let ids: Vec<_> = cache.entries().map(|entry| entry.id()).collect();
+    let expected: Vec<_> = database.iter().map(|(id, _)| id).collect();
+    assert_eq!(ids, expected);
+
+    let restored = cache.to_database().unwrap();
+    let original = database.get("full_entry.desktop").unwrap();
+    let entry = restored.get("full_entry.desktop").unwrap();
+    assert_eq!(entry.name, original.name);
+    assert_eq!(entry.exec, original.exec);
+    assert_eq!(entry.additional_groups, original.additional_groups);
+}
+
+#[test]
+fn test_cache_rejects_invalid_data() {
+    use xdg_desktop_entry::cache::AppCache;
+
+    let mut bytes = Vec::new();
+    AppCache::write(&fixture_database(), &mut bytes).unwrap();
+
+    assert!(AppCache::from_bytes(b"not a cache".to_vec()).is_err());
+    assert!(AppCache::from_bytes(bytes[..bytes.len() - 1].to_vec()).is_err());
+
+    // Corrupt the offset of the first entry ID
+    let mut corrupt = bytes.clone();
+    corrupt[24..28].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(AppCache::from_bytes(corrupt).is_err());
+
+    assert!(AppCache::from_bytes(bytes).is_ok());
+}
+
+#[test]
+fn test_application_cache_refresh() {
+    use xdg_desktop_entry::cache::ApplicationCache;
+
+    let dir = std::env::temp_dir().join(format!("xdg-app-cache-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let app = dir.join("app.desktop");
+    let broken = dir.join("broken.desktop");
+    std::fs::write(
+        &app,
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\n",
+    )
+    .unwrap();
+    std::fs::write(&broken, "not a desktop entry\n").unwrap();
+
+    let mut cache = ApplicationCache::new();
+    let summary = cache.refresh([&app, &broken]);
+    assert_eq!(summary.added, vec![app.clone()]);
+    assert_eq!(cache.len(), 1);
+    assert!(cache.is_fresh(&app));
+    assert_eq!(
+        cache.get(&app).unwrap().source_path.as_deref(),
+        Some(app.as_path())
+    );
+
+    let cache_path = dir.join("applications.cache");
+    cache.save(&cache_path).unwrap();
+    let mut loaded = ApplicationCache::load(&cache_path).unwrap();
+    assert_eq!(loaded.refresh([&app]).unchanged, 1);
+    assert_eq!(loaded.get(&app).unwrap().name.default, "App");
+
+    std::fs::write(
+        &app,
+        "[Desktop Entry]\nType=Application\nName=Renamed\nExec=app\n",
+    )
+    .unwrap();
+    assert!(!loaded.is_fresh(&app));
+    assert_eq!(loaded.refresh([&app]).changed, vec![app.clone()]);
+    assert_eq!(loaded.get(&app).unwrap().name.default, "Renamed");
+
+    assert!(loaded.invalidate(&app));
+    assert!(loaded.get(&app).is_none());
+    assert_eq!(
+        loaded
+            .refresh(Vec::<std::path::PathBuf>::new())
+            .removed
+            .len(),
+        0
+    );
+
+    std::fs::write(&cache_path, b"garbage").unwrap();
+    assert!(ApplicationCache::load(&cache_path).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// ============================================================================
+// Search tests
+// ============================================================================
+
+#[test]
+fn test_search_index() {
+    use xdg_desktop_entry::search::{MatchField, SearchIndex};
+
+    let database = fixture_database();
+    let mut index = SearchIndex::new();
+    index.extend(database.iter());
+    assert_eq!(index.len(), database.len());
+
+    // A query equal to the name ranks first, across all locales
+    let results = index.search("feature rich app");
+    assert_eq!(results[0].id, "feature_rich.desktop");
+    assert!(results[0].score >= 1000);
+    assert_eq!(index.search("werkzeug")[0].field, MatchField::GenericName);
+    assert_eq!(index.search("feature-rich")[0].id, "feature_rich.desktop");
+    let results = index.search("fooview");
+    assert_eq!(
+        (results[0].id, results[0].field),
+        ("spec_example.desktop", MatchField::Exec)
+    );
+    // Every word has to match
+    assert!(index.search("feature nonexistent").is_empty());
+    assert!(index.search("  ").is_empty());
+
+    // Names outrank comments: "application" names full_entry and hidden_app
+    let results = index.search("applic");
+    assert!(
+        results
+            .windows(2)
+            .all(|pair| pair[0].score >= pair[1].score)
+    );
+    assert_eq!(results[0].field, MatchField::Name);
+
+    // Restricted to a locale chain, other translations are not indexed
+    let mut english = SearchIndex::with_locales(vec![Locale::from_string("en_US")]);
+    english.extend(database.iter());
+    assert!(english.search("werkzeug").is_empty());
+    let mut german = SearchIndex::with_locales(vec![Locale::from_string("de_DE")]);
+    german.extend(database.iter());
+    assert_eq!(german.search("werkzeug")[0].id, "feature_rich.desktop");
+}
+
+#[test]
+fn test_matches_query() {
+    use xdg_desktop_entry::search::{MatchField, MatchKind};
+
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=Café Menu\nName[de]=Speisekarte\nGenericName=Menu Editor\nKeywords=food;Kochen;\nExec=/usr/bin/cafe-menu %f\n",
+    )
+    .unwrap();
+    let german = [Locale::from_string("de_DE")];
+
+    let exact = entry.matches_query("CAFE MENU", &[]).unwrap();
+    assert_eq!(exact.field, MatchField::Name);
+    assert_eq!(exact.kind, MatchKind::Exact);
+    let prefix = entry.matches_query("caf", &[]).unwrap();
+    assert_eq!(prefix.kind, MatchKind::Prefix);
+    assert!(exact > prefix);
+
+    assert_eq!(
+        entry.matches_query("speise", &german).unwrap().field,
+        MatchField::Name
+    );
+    assert!(entry.matches_query("speise", &[]).is_none());
+    assert_eq!(
+        entry.matches_query("editor", &[]).unwrap().field,
+        MatchField::GenericName
+    );
+    assert_eq!(
+        entry.matches_query("food", &[]).unwrap().field,
+        MatchField::Keyword
+    );
+    assert_eq!(
+        entry.matches_query("cafe-menu", &[]).unwrap().field,
+        MatchField::Name
+    );
+    assert_eq!(
+        entry.matches_query("kochn", &[]).unwrap().kind,
+        MatchKind::Fuzzy
+    );
+    assert!(entry.matches_query("", &[]).is_none());
+    assert!(entry.matches_query("cafe browser", &[]).is_none());
+}
+
+// ============================================================================
+// Lint tests
+// ============================================================================
+
+#[test]
+fn test_spec_version() {
+    use xdg_desktop_entry::SpecVersion;
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/feature_rich.desktop").unwrap();
+    assert_eq!(entry.spec_version(), Some(SpecVersion::V1_5));
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/minimal.desktop").unwrap();
+    assert_eq!(entry.spec_version(), None);
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/app_version.desktop").unwrap();
+    assert_eq!(entry.spec_version(), None);
+
+    assert!(SpecVersion::V1_0 < SpecVersion::LATEST);
+}
+
+#[test]
+fn test_lint_version_misuse() {
+    use xdg_desktop_entry::{Severity, lint};
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/app_version.desktop").unwrap();
+    let diagnostics = lint::lint(&entry);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].code, "version-is-app-version");
+    assert!(diagnostics[0].fix.is_some());
+
+    // Plausible but unknown spec versions are flagged without a fix
+    let mut entry = entry;
+    entry.version = Some("1.9".to_string());
+    let diagnostics = lint::lint(&entry);
+    assert_eq!(diagnostics[0].code, "version-unknown");
+    assert!(diagnostics[0].fix.is_none());
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/feature_rich.desktop").unwrap();
+    // Its Version is fine; only the unregistered Productivity category is
+    // reported
+    let codes: Vec<_> = lint::lint(&entry).iter().map(|d| d.code).collect();
+    assert_eq!(codes, ["category-unknown"]);
+}
+
+#[test]
+fn test_lint_fix_version_misuse() {
+    use xdg_desktop_entry::{SpecVersion, lint};
+
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/app_version.desktop").unwrap();
+    let fixed = lint::fix(&mut entry).unwrap();
+
+    assert_eq!(fixed.len(), 1);
+    assert_eq!(entry.spec_version(), Some(SpecVersion::LATEST));
+    assert_eq!(entry.unknown_keys["X-AppVersion"][0].value, "104.2");
+    assert_eq!(entry.exec, Some("versioned-app %u".to_string()));
+    assert_eq!(
+        entry.source_path.as_deref(),
+        Some(std::path::Path::new(
+            "tests/fixtures/valid/app_version.desktop"
+        ))
+    );
+    assert!(lint::lint(&entry).is_empty());
+}
+
+#[test]
+fn test_lint_fix_overlapping_fixes() {
+    use xdg_desktop_entry::lint;
+
+    // Both fixes would move a value to X-AppVersion; only the first does
+    let mut entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\nVersion=104.2\nAppVersion=2\n",
+    )
+    .unwrap();
+    let fixed = lint::fix(&mut entry).unwrap();
+    assert_eq!(fixed.len(), 1);
+
+    let serialized = entry.serialize();
+    assert!(serialized.contains("=104.2\n"), "{}", serialized);
+    assert!(serialized.contains("=2\n"), "{}", serialized);
+    let remaining: Vec<_> = lint::lint(&entry).iter().map(|d| d.code).collect();
+    assert_eq!(remaining.len(), 1);
+}
+
+#[test]
+fn test_lint_rules() {
+    use xdg_desktop_entry::lint::{self, LintConfig, RULES};
+    use xdg_desktop_entry::{Severity, Span};
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/legacy_lints.desktop").unwrap();
+    let diagnostics = lint::lint(&entry);
+    let found: Vec<_> = diagnostics
+        .iter()
+        .map(|d| (d.code, d.message.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("deprecated-key", "[Desktop Entry] Encoding is deprecated"),
+            ("deprecated-key", "[Desktop Entry] MiniIcon is deprecated"),
+            (
+                "deprecated-key",
+                "[Desktop Action open] SwallowTitle is deprecated"
+            ),
+            (
+                "unknown-key",
+                "[Desktop Action open] Tooltip is not defined by the specification; \
+                 extensions must be prefixed with X-"
+            ),
+            (
+                "generic-name-equals-name",
+                "GenericName 'Legacy Viewer' is the same as Name; it should describe the kind \
+                 of application (e.g. \"Web Browser\")"
+            ),
+            (
+                "exec-deprecated-field-code",
+                "Exec: the %m field code is deprecated and ignored by launchers"
+            ),
+            (
+                "exec-deprecated-field-code",
+                "[Desktop Action open] Exec: the %d field code is deprecated and ignored by launchers"
+            ),
+            ("trailing-whitespace", "Icon ends with whitespace"),
+            (
+                "list-not-terminated",
+                "Categories should end with a semicolon"
+            ),
+        ]
+    );
+    assert_eq!(
+        diagnostics[7].span,
+        Some(Span {
+            line: 7,
+            column: 19,
+            len: 1
+        })
+    );
+    assert_eq!(diagnostics[8].severity, Severity::Info);
+    assert!(diagnostics.iter().all(|d| {
+        RULES
+            .iter()
+            .any(|rule| rule.code == d.code && rule.severity == d.severity)
+    }));
+
+    let config = LintConfig::new()
+        .disable("deprecated-key")
+        .disable("unknown-key")
+        .severity("list-not-terminated", Severity::Error);
+    let codes: Vec<_> = lint::lint_with(&entry, &config)
+        .iter()
+        .map(|d| (d.code, d.severity))
+        .collect();
+    assert_eq!(codes.len(), 5);
+    assert_eq!(codes[4], ("list-not-terminated", Severity::Error));
+
+    // Deprecated keys, unprefixed keys, and the redundant GenericName are
+    // fixable
+    let mut entry = entry;
+    let fixed = lint::fix(&mut entry).unwrap();
+    assert_eq!(fixed.len(), 5);
+    assert_eq!(
+        entry.additional_groups["Desktop Action open"].get("X-Tooltip"),
+        Some("Open a file")
+    );
+    assert!(entry.generic_name.is_none());
+    assert!(!entry.unknown_keys.contains_key("Encoding"));
+    assert!(entry.unknown_keys.contains_key("X-Legacy-Mode"));
+    assert!(
+        !entry.additional_groups["Desktop Action open"]
+            .entries
+            .contains_key("SwallowTitle")
+    );
+}
+
+#[test]
+fn test_validation_levels() {
+    use xdg_desktop_entry::{Severity, ValidationLevel};
+
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Link\nName=Docs\nName[de]=Doku\nComment=Docs\n\
+         Exec=docs\nCategories=Screensaver;\nEncoding=UTF-8\nIcon=/usr/share/docs.png\n",
+    )
+    .unwrap();
+    let codes = |level| -> Vec<&'static str> {
+        entry
+            .validate_with(level)
+            .iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect()
+    };
+
+    let spec = entry.validate_with(ValidationLevel::Spec);
+    assert!(spec.iter().all(|d| d.severity == Severity::Error));
+    assert_eq!(
+        codes(ValidationLevel::Spec),
+        [
+            "validation-error",
+            "validation-error",
+            "validation-error",
+            "category-reserved-without-only-show-in"
+        ]
+    );
+    assert_eq!(spec[0].message, entry.validate().unwrap_err().to_string());
+
+    let warning = codes(ValidationLevel::Warning);
+    assert!(warning.starts_with(&codes(ValidationLevel::Spec)[..3]));
+    assert!(warning.contains(&"deprecated-key"));
+    assert!(warning.contains(&"comment-equals-name"));
+    assert!(!warning.contains(&"translation-missing"));
+
+    // Links need no keywords, and the icon is an absolute path
+    let pedantic = entry.validate_with(ValidationLevel::Pedantic);
+    let extra: Vec<_> = pedantic[warning.len()..]
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(extra, ["Comment has no translation for de"]);
+}
+
+#[test]
+fn test_unknown_key_warnings() {
+    use xdg_desktop_entry::lint;
+    use xdg_desktop_entry::{ParserOptions, Span};
+
+    let content = "[Desktop Entry]\nType=Application\nName=App\nExec=app\nFoo=1\nX-Foo=2\nEncoding=UTF-8\nBar=3\n";
+    let (_, diagnostics) =
+        DesktopEntry::parse_with_diagnostics(content, &ParserOptions::default()).unwrap();
+    assert!(diagnostics.is_empty());
+
+    let (mut entry, diagnostics) =
+        DesktopEntry::parse_with_diagnostics(content, &ParserOptions::lenient()).unwrap();
+    let found: Vec<_> = diagnostics.iter().map(|d| (d.code, d.span)).collect();
+    assert_eq!(
+        found,
+        [
+            (
+                "unknown-key",
+                Some(Span {
+                    line: 5,
+                    column: 1,
+                    len: 3
+                })
+            ),
+            (
+                "unknown-key",
+                Some(Span {
+                    line: 8,
+                    column: 1,
+                    len: 3
+                })
+            ),
+        ]
+    );
+
+    // Bar is renamed; Foo is not, as X-Foo is taken
+    lint::fix(&mut entry).unwrap();
+    assert_eq!(entry.unknown_string("X-Bar").as_deref(), Some("3"));
+    assert_eq!(entry.unknown_string("Foo").as_deref(), Some("1"));
+    assert!(!entry.unknown_keys.contains_key("Encoding"));
+}
+
+#[test]
+fn test_control_characters_and_ascii_strings() {
+    use xdg_desktop_entry::lint;
+    use xdg_desktop_entry::{DesktopEntryError, ParserOptions, Severity, Span};
+
+    let content = "[Desktop Entry]\nType=Application\nName=App\x07\nExec=caf\u{e9}\nStartupWMClass=App\u{e9}\nComment=Caf\u{e9}\n";
+    let entry = DesktopEntry::parse(content).unwrap();
+    let found: Vec<_> = lint::lint(&entry)
+        .into_iter()
+        .filter(|d| d.code == "control-character" || d.code == "string-not-ascii")
+        .map(|d| (d.code, d.severity, d.span))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (
+                "control-character",
+                Severity::Error,
+                Some(Span {
+                    line: 3,
+                    column: 6,
+                    len: 4
+                })
+            ),
+            ("string-not-ascii", Severity::Warning, None),
+            ("string-not-ascii", Severity::Warning, None),
+        ]
+    );
+
+    let result = DesktopEntry::parse_with_diagnostics(content, &ParserOptions::strict());
+    assert!(matches!(result, Err(DesktopEntryError::InvalidValue(key, _)) if key == "Name"));
+}
+
+#[test]
+fn test_kde_desktop_entry_group() {
+    use xdg_desktop_entry::{ParserOptions, Span};
+
+    let content = "# Old file\n[KDE Desktop Entry]\nType=Application\nName=Konsole\nExec=konsole\n";
+    assert!(DesktopEntry::parse(content).is_err());
+    assert!(DesktopEntry::parse_with(content, &ParserOptions::strict()).is_err());
+
+    let (mut entry, diagnostics) =
+        DesktopEntry::parse_with_diagnostics(content, &ParserOptions::lenient()).unwrap();
+    assert_eq!(entry.name.default, "Konsole");
+    let found: Vec<_> = diagnostics.iter().map(|d| (d.code, d.span)).collect();
+    assert_eq!(
+        found,
+        [(
+            "kde-desktop-entry-group",
+            Some(Span {
+                line: 2,
+                column: 1,
+                len: 19
+            })
+        )]
+    );
+
+    // Written back with the standard header, keeping the layout
+    assert_eq!(
+        entry.serialize(),
+        "# Old file\n[Desktop Entry]\nType=Application\nName=Konsole\nExec=konsole\n"
+    );
+    entry.terminal = Some(false);
+    assert!(
+        entry
+            .serialize()
+            .starts_with("# Old file\n[Desktop Entry]\nType=Application\n")
+    );
+    assert!(!entry.serialize().contains("KDE"));
+}
+
+#[test]
+fn test_escaped_semicolons_in_lists() {
+    use xdg_desktop_entry::LocalizedString;
+
+    let content = "[Desktop Entry]\nType=Application\nName=App\nExec=app\n\
+                   Keywords=rock\\;roll;jazz;back\\\\;\nX-Pairs=a\\;b;c;\n";
+    let entry = DesktopEntry::parse(content).unwrap();
+    assert_eq!(
+        entry.keywords.as_ref().unwrap().default,
+        ["rock;roll", "jazz", "back\\"]
+    );
+    assert_eq!(entry.unknown_string_list("X-Pairs").unwrap(), ["a;b", "c"]);
+
+    let mut entry = DesktopEntry::new(DesktopEntryType::Application, LocalizedString::new("App"));
+    entry.categories = Some(vec!["Audio;Video".to_string(), "Player".to_string()]);
+    let serialized = entry.serialize();
+    assert!(serialized.contains("Categories=Audio\\;Video;Player\n"));
+    let reparsed = DesktopEntry::parse(&serialized).unwrap();
+    assert_eq!(reparsed.categories, entry.categories);
+}
+
+#[test]
+fn test_public_escaping_helpers() {
+    use xdg_desktop_entry::{escape_value, join_list, split_list, unescape_value};
+
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\nX-Paths=C:\\\\a;\\sb\\;c;\n",
+    )
+    .unwrap();
+    let raw = &entry.unknown_keys["X-Paths"][0].value;
+    let items = split_list(raw);
+    assert_eq!(items, ["C:\\a", " b;c"]);
+    assert_eq!(&join_list(&items), raw);
+    assert_eq!(unescape_value(&escape_value("\ttab ")), "\ttab ");
+}
+
+#[test]
+fn test_lint_exec_field_codes() {
+    use xdg_desktop_entry::{Severity, lint};
+
+    let lint_exec = |exec: &str| {
+        let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/minimal.desktop").unwrap();
+        entry.exec = Some(exec.to_string());
+        lint::lint(&entry)
+            .into_iter()
+            .filter(|d| d.code.starts_with("exec-"))
+            .map(|d| (d.code, d.severity))
+            .collect::<Vec<_>>()
+    };
+
+    assert!(lint_exec("app --progress=100%% %U").is_empty());
+    assert_eq!(
+        lint_exec("app %x %"),
+        [
+            ("exec-unknown-field-code", Severity::Error),
+            ("exec-unknown-field-code", Severity::Error),
+        ]
+    );
+    assert_eq!(
+        lint_exec("app %f %u"),
+        [("exec-multiple-targets", Severity::Error)]
+    );
+    assert_eq!(
+        lint_exec("app --files=%F %D"),
+        [
+            ("exec-target-not-alone", Severity::Error),
+            ("exec-deprecated-field-code", Severity::Warning),
+        ]
+    );
+    assert_eq!(
+        lint_exec("app \"unterminated %f"),
+        [("exec-unterminated-quote", Severity::Error)]
+    );
+}
+
+#[test]
+fn test_spec_version_key_gating() {
+    use xdg_desktop_entry::{SerializeOptions, SpecVersion, lint};
+
+    let content = "[Desktop Entry]\nVersion=1.1\nType=Application\nName=App\nExec=app\n\
+                   Keywords=app;\nPrefersNonDefaultGPU=true\nSingleMainWindow=true\n";
+    let mut entry = DesktopEntry::parse(content).unwrap();
+    assert_eq!(entry.required_spec_version(), SpecVersion::V1_5);
+
+    let messages: Vec<_> = lint::lint(&entry)
+        .into_iter()
+        .filter(|d| d.code == "key-newer-than-version")
+        .map(|d| d.message)
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "PrefersNonDefaultGPU was added in version 1.4 of the specification, but Version is 1.1",
+            "SingleMainWindow was added in version 1.5 of the specification, but Version is 1.1",
+        ]
+    );
+
+    // Only a declared, older version is bumped
+    let bump = SerializeOptions {
+        bump_version: true,
+        ..SerializeOptions::default()
+    };
+    assert!(entry.serialize_with(&bump).contains("Version=1.5\n"));
+    assert!(entry.serialize().contains("Version=1.1\n"));
+    entry.version = None;
+    assert!(!entry.serialize_with(&bump).contains("Version="));
+
+    entry.version = Some("1.1".to_string());
+    lint::fix(&mut entry).unwrap();
+    assert_eq!(entry.spec_version(), Some(SpecVersion::V1_5));
+    assert!(
+        lint::lint(&entry)
+            .iter()
+            .all(|d| d.code != "key-newer-than-version")
+    );
+}
+
+#[test]
+fn test_lint_categories() {
+    use xdg_desktop_entry::categories::{Category, CategoryKind};
+    use xdg_desktop_entry::{Severity, lint};
+
+    let lint_categories = |categories: &str, only_show_in: bool| {
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName=App\nExec=app\nCategories={}\n{}",
+            categories,
+            if only_show_in {
+                "OnlyShowIn=GNOME;\n"
+            } else {
+                ""
+            }
+        );
+        let entry = DesktopEntry::parse(&content).unwrap();
+        lint::lint(&entry)
+            .into_iter()
+            .map(|d| (d.code, d.severity))
+            .collect::<Vec<_>>()
+    };
+
+    assert!(lint_categories("Network;WebBrowser;X-Custom;", false).is_empty());
+    assert_eq!(
+        lint_categories("Network;Browser;", false),
+        [("category-unknown", Severity::Warning)]
+    );
+    assert_eq!(
+        lint_categories("Utility;WebBrowser;", false),
+        [("category-missing-main", Severity::Warning)]
+    );
+    // Audio and Video are main categories, but belong with AudioVideo
+    assert_eq!(
+        lint_categories("Audio;Player;", false),
+        [("category-missing-main", Severity::Warning)]
+    );
+    assert_eq!(
+        lint_categories("Utility;TrayIcon;", false),
+        [("category-reserved-without-only-show-in", Severity::Error)]
+    );
+    assert!(lint_categories("Utility;TrayIcon;", true).is_empty());
+
+    assert!(
+        Category::ALL
+            .iter()
+            .all(|category| Category::parse(category.as_str()) == Some(*category))
+    );
+    let main = Category::ALL
+        .iter()
+        .filter(|category| category.kind() == CategoryKind::Main)
+        .count();
+    assert_eq!(main, 13);
+}
+
+#[test]
+fn test_lint_desktop_environments() {
+    use xdg_desktop_entry::lint;
+
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\n\
+         OnlyShowIn=GNOME;Gnome;X-Phosh;\nNotShowIn=KDE;GNOME;\n",
+    )
+    .unwrap();
+    let found: Vec<_> = lint::lint(&entry)
+        .into_iter()
+        .map(|d| (d.code, d.message))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (
+                "desktop-environment-unknown",
+                "OnlyShowIn: desktop environment 'Gnome' is not registered; extensions must be \
+                 prefixed with X-"
+                    .to_string()
+            ),
+            (
+                "desktop-environment-conflict",
+                "Desktop environment 'GNOME' is listed in both OnlyShowIn and NotShowIn"
+                    .to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_check_icons() {
+    use xdg_desktop_entry::lint;
+
+    let icon_dirs = ["tests/fixtures/icons"];
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\nIcon=editor\n\
+         Icon[de]=viewer\nActions=legacy;missing;\n\n\
+         [Desktop Action legacy]\nName=Legacy\nIcon=legacy\n\n\
+         [Desktop Action missing]\nName=Missing\nIcon=missing-icon\n",
+    )
+    .unwrap();
+
+    // hicolor is always searched; viewer is only in the Custom theme
+    let diagnostics = lint::check_icons_in(&entry, &[] as &[&str], &icon_dirs);
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "Icon[de]: icon 'viewer' was not found in the hicolor icon themes",
+            "[Desktop Action missing] Icon: icon 'missing-icon' was not found in the hicolor icon themes",
+        ]
+    );
+    assert!(diagnostics.iter().all(|d| d.code == "icon-not-found"));
+
+    let diagnostics = lint::check_icons_in(&entry, &["Custom"], &icon_dirs);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].message.contains("'missing-icon'"));
+
+    // Absolute paths are not looked up in themes
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\nIcon=/opt/app/icon.png\n",
+    )
+    .unwrap();
+    assert!(lint::check_icons_in(&entry, &["Custom"], &icon_dirs).is_empty());
+}
+
+#[test]
+fn test_apply_fix_errors() {
+    use xdg_desktop_entry::{Fix, KeyEdit};
+
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/minimal.desktop").unwrap();
+    let remove_type = Fix {
+        description: "Remove Type".to_string(),
+        edits: vec![KeyEdit::Remove {
+            group: "Desktop Entry".to_string(),
+            key: "Type".to_string(),
+        }],
+    };
+    assert_eq!(
+        entry.apply_fix(&remove_type),
+        Err(DesktopEntryError::MissingRequiredKey("Type".to_string()))
+    );
+    assert_eq!(entry.name.default, "Minimal App");
+
+    let missing_group = Fix {
+        description: "Edit a missing group".to_string(),
+        edits: vec![KeyEdit::Remove {
+            group: "Desktop Action missing".to_string(),
+            key: "Name".to_string(),
+        }],
+    };
+    assert!(matches!(
+        entry.apply_fix(&missing_group),
+        Err(DesktopEntryError::MissingGroup(_))
+    ));
+}
+
+#[test]
+fn test_should_show() {
+    use xdg_desktop_entry::environment::{DesktopEnvironment, parse_current_desktop};
+
+    let hidden = DesktopEntry::parse_file("tests/fixtures/valid/hidden_app.desktop").unwrap();
+    assert!(!hidden.should_show(&["GNOME"]));
+
+    let mut entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\nNotShowIn=KDE;\n",
+    )
+    .unwrap();
+    assert!(entry.should_show(&["GNOME"]));
+    assert!(!entry.should_show(&["X-Cinnamon", "KDE"]));
+    assert!(!entry.should_show(&[DesktopEnvironment::Kde]));
+    assert!(entry.should_show(&[DesktopEnvironment::Other("X-Phosh".to_string())]));
+    assert!(!entry.should_show(&parse_current_desktop("ubuntu:KDE")));
+    assert!(entry.should_show(&parse_current_desktop("ubuntu:GNOME")));
+
+    assert!(entry.try_exec_available());
+    entry.try_exec = Some("sh".to_string());
+    assert!(entry.try_exec_available());
+    entry.try_exec = Some("/nonexistent/xdg-desktop-entry-test".to_string());
+    assert!(!entry.try_exec_available());
+    assert!(!entry.should_show_in_current_desktop(true));
+}
+
+// ============================================================================
+// Action tests
+// ============================================================================
+
+#[test]
+fn test_visible_actions() {
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/desktop_actions.desktop").unwrap();
+
+    let actions = entry.visible_actions(&["ubuntu", "GNOME"], &[]);
+    let ids: Vec<_> = actions.iter().map(|action| action.id.as_str()).collect();
+    assert_eq!(ids, vec!["compose"]);
+    assert_eq!(actions[0].name, "Compose Message");
+    assert_eq!(actions[0].icon, Some("mail-message-new".to_string()));
+    assert_eq!(actions[0].exec, Some("mail-client --compose".to_string()));
+
+    let actions = entry.visible_actions(&["KDE"], &[]);
+    let ids: Vec<_> = actions.iter().map(|action| action.id.as_str()).collect();
+    assert_eq!(ids, vec!["compose", "contacts", "calendar"]);
+}
+
+#[test]
+fn test_visible_actions_locale_chain() {
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/desktop_actions.desktop").unwrap();
+    let no_desktops: &[&str] = &[];
+
+    let actions = entry.visible_actions(no_desktops, &[Locale::from_string("fr_CA")]);
+    assert_eq!(actions[0].name, "Nouveau courriel");
+    assert_eq!(actions[0].icon, Some("mail-message-new-fr".to_string()));
+
+    // The first locale with a translation wins
+    let locales = [Locale::from_string("de"), Locale::from_string("fr_BE")];
+    let actions = entry.visible_actions(no_desktops, &locales);
+    assert_eq!(actions[0].name, "Nouveau message");
+}
+
+// ============================================================================
+// Unicode normalization tests
+// ============================================================================
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_normalize_unicode() {
+    let decomposed = "Cafe\u{301}";
+    let composed = "Caf\u{e9}";
+    let content = format!(
+        "[Desktop Entry]\nType=Application\nName={0}\nName[fr]={0}\nComment={0}\n\
+         Keywords={0};Tea;\nExec=cafe\nX-Tagline[fr]={0}\nX-Raw={0}\nActions=order;\n\n\
+         [Desktop Action order]\nName={0}\nExec=cafe --order\n",
+        decomposed
+    );
+    let mut entry = DesktopEntry::parse(&content).unwrap();
+    entry.normalize_unicode();
+
+    assert_eq!(entry.name.default, composed);
+    assert_eq!(entry.name.get(&Locale::from_string("fr")), composed);
+    assert_eq!(entry.comment.as_ref().unwrap().default, composed);
+    assert_eq!(entry.keywords.as_ref().unwrap().default[0], composed);
+    assert_eq!(entry.unknown_keys["X-Tagline"][0].value, composed);
+    assert_eq!(
+        entry.additional_groups["Desktop Action order"].get("Name"),
+        Some(composed)
+    );
+
+    // Keys that are not localizable are left alone
+    assert_eq!(entry.unknown_keys["X-Raw"][0].value, decomposed);
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_normalize_unicode_options() {
+    use xdg_desktop_entry::{ParserOptions, SerializeOptions};
+
+    let content = "[Desktop Entry]\nType=Application\nName=Cafe\u{301}\nExec=cafe\n";
+    let options = ParserOptions {
+        normalize_unicode: true,
+        ..ParserOptions::default()
+    };
+    assert_eq!(
+        DesktopEntry::parse_with(content, &options)
+            .unwrap()
+            .name
+            .default,
+        "Caf\u{e9}"
+    );
+
+    // Serializing keeps the layout and rewrites only the normalized values
+    let entry = DesktopEntry::parse(content).unwrap();
+    assert_eq!(entry.name.default, "Cafe\u{301}");
+    let options = SerializeOptions {
+        normalize_unicode: true,
+        ..SerializeOptions::default()
+    };
+    assert_eq!(
+        entry.serialize_with(&options),
+        content.replace("Cafe\u{301}", "Caf\u{e9}")
+    );
+    assert_eq!(entry.serialize(), content);
+}
+
+#[test]
+fn test_security_review() {
+    use xdg_desktop_entry::lint;
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/risky_exec.desktop").unwrap();
+    let diagnostics = lint::security_review(&entry);
+    let found: Vec<_> = diagnostics
+        .iter()
+        .map(|d| (d.code, d.message.starts_with("Exec")))
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            ("exec-shell-command", true),
+            ("exec-shell-operator", true),
+            ("exec-world-writable-path", true),
+            ("exec-environment", true),
+            ("exec-command-substitution", false),
+            ("exec-world-writable-path", false),
+        ]
+    );
+    assert!(diagnostics[4].message.contains("Desktop Action update"));
+
+    // Ordinary command lines pass, and the review is not part of regular lints
+    for fixture in ["full_entry", "feature_rich", "spec_example"] {
+        let path = format!("tests/fixtures/valid/{}.desktop", fixture);
+        let entry = DesktopEntry::parse_file(path).unwrap();
+        assert!(lint::security_review(&entry).is_empty());
+    }
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/risky_exec.desktop").unwrap();
+    assert!(lint::lint(&entry).is_empty());
+}
+
+#[test]
+fn test_security_review_environment_prefix() {
+    use xdg_desktop_entry::lint;
+
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=GDK_BACKEND=x11 app %U\n",
+    )
+    .unwrap();
+    let codes: Vec<_> = lint::security_review(&entry)
+        .iter()
+        .map(|d| d.code)
+        .collect();
+    assert_eq!(codes, vec!["exec-environment"]);
+
+    // The values of env options are not taken for the program
+    for exec in [
+        "env -u DISPLAY sh -c 'app'",
+        "env --unset DISPLAY -C /opt sh -c 'app'",
+        "env --chdir /opt LANG=C sh -c 'app'",
+    ] {
+        let entry = DesktopEntry::parse(&format!(
+            "[Desktop Entry]\nType=Application\nName=App\nExec={}\n",
+            exec
+        ))
+        .unwrap();
+        let codes: Vec<_> = lint::security_review(&entry)
+            .iter()
+            .map(|d| d.code)
+            .collect();
+        assert_eq!(
+            codes,
+            vec!["exec-shell-command", "exec-environment"],
+            "{}",
+            exec
+        );
+    }
+}
+
+// ============================================================================
+// Lenient parsing tests
+// ============================================================================
+
+#[test]
+fn test_keys_before_group_rejected_by_default() {
+    let content = std::fs::read_to_string("tests/fixtures/lenient/missing_header.desktop").unwrap();
+    assert!(matches!(
+        DesktopEntry::parse(&content),
+        Err(DesktopEntryError::InvalidLine(2, _))
+    ));
+}
+
+#[test]
+fn test_keys_before_group_attached() {
+    use xdg_desktop_entry::{OrphanKeyPolicy, ParserOptions};
+
+    let content = std::fs::read_to_string("tests/fixtures/lenient/missing_header.desktop").unwrap();
+    let options = ParserOptions {
+        keys_before_group: OrphanKeyPolicy::AttachToDesktopEntry,
+        ..ParserOptions::default()
+    };
+    let (entry, diagnostics) = DesktopEntry::parse_with_diagnostics(&content, &options).unwrap();
+
+    assert_eq!(entry.name.default, "Headerless App");
+    assert_eq!(entry.exec, Some("headerless".to_string()));
+    // The explicit header continues the implicit group
+    assert!(entry.icon.is_some());
+    assert!(entry.additional_groups.contains_key("Desktop Action new"));
+    assert_eq!(diagnostics.len(), 3);
+    assert!(diagnostics.iter().all(|d| d.code == "key-before-group"));
+    assert!(diagnostics[0].message.starts_with("Line 2"));
+}
+
+#[test]
+fn test_keys_before_group_skipped() {
+    use xdg_desktop_entry::{OrphanKeyPolicy, ParserOptions};
+
+    let content = std::fs::read_to_string("tests/fixtures/lenient/missing_header.desktop").unwrap();
+    let options = ParserOptions {
+        keys_before_group: OrphanKeyPolicy::Skip,
+        ..ParserOptions::default()
+    };
+
+    // Skipping the keys loses the required Type
+    assert_eq!(
+        DesktopEntry::parse_with(&content, &options).unwrap_err(),
+        DesktopEntryError::MissingRequiredKey("Type".to_string())
+    );
+
+    let content = "X-Generator=broken\n[Desktop Entry]\nType=Application\nName=App\nExec=app\n";
+    let (entry, diagnostics) = DesktopEntry::parse_with_diagnostics(content, &options).unwrap();
+    assert!(entry.unknown_keys.is_empty());
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn test_duplicate_key_warnings() {
+    use xdg_desktop_entry::{ParserOptions, Severity, Span};
+
+    let content = "[Desktop Entry]\nType=Application\nName=App\nName[de]=Anw\nExec=app\n\
+                   Name[de]=Anwendung\nActions=new;\n\n[Desktop Action new]\nName=New\n  Name =Newer\n";
+    let (_, diagnostics) =
+        DesktopEntry::parse_with_diagnostics(content, &ParserOptions::default()).unwrap();
+    assert_eq!(diagnostics.len(), 2);
+    assert!(
+        diagnostics
+            .iter()
+            .all(|d| d.severity == Severity::Warning && d.code == "duplicate-key")
+    );
+    assert_eq!(
+        diagnostics[0].message,
+        "Name[de] is set again; it was first set at line 4"
+    );
+    assert_eq!(
+        diagnostics[1].span,
+        Some(Span {
+            line: 11,
+            column: 3,
+            len: 4
+        })
+    );
+
+    let (_, diagnostics) = DesktopEntry::parse_collecting(content, &ParserOptions::strict());
+    let lines: Vec<_> = diagnostics
+        .iter()
+        .map(|d| (d.code, d.severity, d.span.map(|span| span.line)))
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            ("duplicate-key", Severity::Error, Some(6)),
+            ("duplicate-key", Severity::Error, Some(11)),
+        ]
+    );
+}
+
+#[test]
+fn test_parser_options_strict_and_lenient() {
+    use xdg_desktop_entry::{ParserOptions, Strictness};
+
+    let content = "[Desktop Entry]\nType=Application\nName=Old\nExec=old\nTerminal=1\n\
+                   Encoding=UTF-8\nX-Vendor=yes\nName=Older\n";
+
+    // The defaults accept the duplicate and drop the legacy boolean
+    let entry = DesktopEntry::parse_with(content, &ParserOptions::default()).unwrap();
+    assert_eq!(entry.name.default, "Older");
+    assert_eq!(entry.terminal, None);
+    assert!(entry.unknown_keys.contains_key("Encoding"));
+
+    let (entry, diagnostics) =
+        DesktopEntry::parse_with_diagnostics(content, &ParserOptions::lenient()).unwrap();
+    assert_eq!(entry.terminal, Some(true));
+    let legacy: Vec<_> = diagnostics
+        .iter()
+        .filter(|d| d.code == "legacy-boolean")
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(legacy, ["Terminal=1 is deprecated; use true"]);
+    let (_, diagnostics) =
+        DesktopEntry::parse_with_diagnostics(content, &ParserOptions::default()).unwrap();
+    assert!(diagnostics.iter().any(|d| d.code == "invalid-boolean"));
+
+    let strict = ParserOptions::strict();
+    assert_eq!(
+        DesktopEntry::parse_with(content, &strict).unwrap_err(),
+        DesktopEntryError::DuplicateKey(8, "Name".to_string())
+    );
+    let content = content.replace("Name=Older\n", "");
+    assert!(matches!(
+        DesktopEntry::parse_with(&content, &strict),
+        Err(DesktopEntryError::InvalidValue(key, _)) if key == "Terminal"
+    ));
+    let content = content.replace("Terminal=1", "Terminal=true");
+    assert_eq!(
+        DesktopEntry::parse_with(&content, &strict).unwrap_err(),
+        DesktopEntryError::UnknownKey("Encoding".to_string())
+    );
+
+    let options = ParserOptions {
+        strictness: Strictness::Strict,
+        ..ParserOptions::default()
+    };
+    let entry = DesktopEntry::parse_with(&content, &options).unwrap();
+    assert_eq!(entry.terminal, Some(true));
+    assert!(entry.unknown_keys.contains_key("X-Vendor"));
+}
+
+#[test]
+fn test_parse_collecting_reports_every_problem() {
+    use xdg_desktop_entry::{ParserOptions, Span};
+
+    let content = "[Desktop Entry]\n\
+                   Type=Application\n\
+                   Name=Broken\n\
+                   Actions=missing;\n\
+                   bad_key=1\n\
+                   [Broken Group\n\
+                   Ignored=yes\n\
+                   [Desktop Entry]\n\
+                   Also=ignored\n\
+                   [Other]\n\
+                   Key[de=x\n";
+    let (entry, diagnostics) = DesktopEntry::parse_collecting(content, &ParserOptions::default());
+
+    let codes: Vec<_> = diagnostics.iter().map(|d| d.code).collect();
+    assert_eq!(
+        codes,
+        [
+            "invalid-key-name",
+            "invalid-group-header",
+            "duplicate-group",
+            "invalid-line",
+            "validation-error"
+        ]
+    );
+    assert_eq!(
+        diagnostics[0].span,
+        Some(Span {
+            line: 5,
+            column: 1,
+            len: 7
+        })
+    );
+    assert_eq!(diagnostics[1].span.unwrap().line, 6);
+    assert_eq!(diagnostics[2].span.unwrap().line, 8);
+    assert!(
+        diagnostics[2]
+            .to_string()
+            .starts_with("8:1: error[duplicate-group]")
+    );
+
+    let entry = entry.unwrap();
+    assert!(entry.additional_groups.contains_key("Other"));
+    assert!(!entry.unknown_keys.contains_key("Also"));
+
+    // Entries that cannot be built are reported without an entry
+    let (entry, diagnostics) = DesktopEntry::parse_collecting(
+        "[Desktop Entry]\nName=No Type\n",
+        &ParserOptions::default(),
+    );
+    assert!(entry.is_none());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "missing-required-key");
+}
+
+#[test]
+fn test_exec_env_description() {
+    use xdg_desktop_entry::launch::{DESKTOP_FILE_ENV, DESKTOP_FILE_PID_ENV};
+
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/minimal.desktop").unwrap();
+    let description = entry.to_exec_env_description();
+    let desktop_file = description.desktop_file.as_ref().unwrap();
+    assert!(desktop_file.is_absolute());
+    assert!(desktop_file.ends_with("tests/fixtures/valid/minimal.desktop"));
+
+    let vars = description.vars(1234);
+    assert_eq!(vars[0].0, DESKTOP_FILE_ENV);
+    assert_eq!(vars[0].1, desktop_file.to_string_lossy());
+    assert_eq!(
+        vars[1],
+        (DESKTOP_FILE_PID_ENV.to_string(), "1234".to_string())
+    );
+}
+
+// ============================================================================
+// Findings index tests
+// ============================================================================
+
+#[test]
+fn test_findings_index_revalidates_only_changed_files() {
+    use xdg_desktop_entry::findings::FindingsIndex;
+
+    let dir = std::env::temp_dir().join(format!("xdg-findings-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let good = dir.join("good.desktop");
+    let bad = dir.join("bad.desktop");
+    std::fs::write(
+        &good,
+        "[Desktop Entry]\nType=Application\nName=Good\nExec=good\n",
+    )
+    .unwrap();
+    std::fs::write(&bad, "[Desktop Entry]\nName=Bad\n").unwrap();
+
+    let mut index = FindingsIndex::new();
+    let summary = index.refresh([&good, &bad]).unwrap();
+    assert_eq!(summary.added.len(), 2);
+    assert!(index.get(&good).unwrap().findings.is_empty());
+    assert_eq!(index.get(&bad).unwrap().findings[0].code, "parse-error");
+
+    let summary = index.refresh([&good, &bad]).unwrap();
+    assert!(summary.added.is_empty() && summary.changed.is_empty());
+    assert_eq!(summary.unchanged, 2);
+
+    std::fs::write(
+        &bad,
+        "[Desktop Entry]\nType=Application\nName=Bad\nExec=bad\nVersion=2.3\n",
+    )
+    .unwrap();
+    let summary = index.refresh([&good, &bad]).unwrap();
+    assert_eq!(summary.changed, vec![bad.clone()]);
+    assert_eq!(
+        index.get(&bad).unwrap().findings[0].code,
+        "version-is-app-version"
+    );
+
+    let summary = index.refresh([&bad]).unwrap();
+    assert_eq!(summary.removed, vec![good.clone()]);
+
+    let saved = dir.join("findings.idx");
+    index.save(&saved).unwrap();
+    let loaded = FindingsIndex::load(&saved).unwrap();
+    assert_eq!(loaded, index);
+
+    // Paths that are not UTF-8 are saved byte for byte
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        let odd = dir.join(std::ffi::OsStr::from_bytes(b"caf\xe9 \\x41\t.desktop"));
+        std::fs::copy(&bad, &odd).unwrap();
+        index.refresh([&bad, &odd]).unwrap();
+        index.save(&saved).unwrap();
+        let loaded = FindingsIndex::load(&saved).unwrap();
+        assert_eq!(loaded, index);
+        assert!(loaded.get(&odd).is_some());
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// ============================================================================
+// Typed entry tests
+// ============================================================================
+
+#[test]
+fn test_typed_entry_views() {
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/minimal.desktop").unwrap();
+    assert!(entry.as_link().is_none());
+    assert!(entry.as_directory().is_none());
+
+    let app = entry.as_application_mut().unwrap();
+    app.set_terminal(Some(true));
+    app.set_categories(Some(vec!["Utility".to_string()]));
+    assert_eq!(entry.terminal, Some(true));
+    assert_eq!(
+        entry.as_application().unwrap().categories(),
+        Some(&["Utility".to_string()][..])
+    );
+
+    let entry = entry.into_link().unwrap_err();
+    let app = entry.into_application().unwrap();
+    assert!(app.exec().is_some());
+}
+
+#[test]
+fn test_typed_entry_constructors_are_valid() {
+    use xdg_desktop_entry::LocalizedString;
+    use xdg_desktop_entry::typed::{ApplicationEntry, DirectoryEntry, LinkEntry};
+
+    let app = ApplicationEntry::new(LocalizedString::new("App"), "app");
+    let link = LinkEntry::new(LocalizedString::new("Link"), "https://example.com");
+    let dir = DirectoryEntry::new(LocalizedString::new("Games"));
+
+    for entry in [app.into_entry(), link.into_entry(), dir.into_entry()] {
+        entry.validate().unwrap();
+        let reparsed = DesktopEntry::parse(&entry.serialize()).unwrap();
+        assert_eq!(reparsed.entry_type, entry.entry_type);
+    }
+}
+
+#[test]
+fn test_directory_entry() {
+    use xdg_desktop_entry::DesktopEntryError;
+    use xdg_desktop_entry::typed::DirectoryEntry;
+
+    let dir = DirectoryEntry::parse_file("tests/fixtures/valid/directory_entry.desktop").unwrap();
+    assert_eq!(dir.icon().unwrap().default, "folder-custom");
+    dir.as_entry().validate().unwrap();
+    assert!(matches!(
+        DirectoryEntry::parse_file("tests/fixtures/valid/minimal.desktop"),
+        Err(DesktopEntryError::InvalidValue(_, _))
+    ));
+
+    let dirs = [
+        "tests/fixtures/menu/missing",
+        "tests/fixtures/menu/directories",
+    ];
+    let secret = DirectoryEntry::find_in("Secret.directory", &dirs).unwrap();
+    assert_eq!(secret.no_display(), Some(true));
+
+    // Application-only keys and URL are invalid on directories
+    for key in [
+        "Exec=app",
+        "Categories=Graphics;",
+        "Terminal=false",
+        "URL=https://example.com",
+    ] {
+        let entry = DesktopEntry::parse(&format!(
+            "[Desktop Entry]\nType=Directory\nName=Games\n{}\n",
+            key
+        ))
+        .unwrap();
+        let key = key.split('=').next().unwrap();
+        match entry.validate() {
+            Err(DesktopEntryError::ValidationError(message)) => {
+                assert!(message.starts_with(key), "{}", message)
+            }
+            other => panic!("{} was accepted: {:?}", key, other),
+        }
+    }
+    let app = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\nURL=https://example.com\n",
+    )
+    .unwrap();
+    assert!(app.validate().is_err());
+}
+
+// ============================================================================
+// Serializer option tests
+// ============================================================================
+
+#[test]
+fn test_serialize_with_sorted_annotated_lists() {
+    use xdg_desktop_entry::SerializeOptions;
+
+    let content = std::fs::read_to_string("tests/fixtures/valid/full_entry.desktop").unwrap();
+    let entry = DesktopEntry::parse(&content).unwrap();
+    let options = SerializeOptions {
+        sort_lists: true,
+        annotate_lists_from: Some(2),
+        ..SerializeOptions::default()
+    };
+    let serialized = entry.serialize_with(&options);
+
+    let reparsed = DesktopEntry::parse(&serialized).unwrap();
+    let mut categories = entry.categories.clone().unwrap();
+    categories.sort();
+    assert_eq!(reparsed.categories, Some(categories.clone()));
+    assert!(serialized.contains(&format!("# Categories: {} items\n", categories.len())));
+    // Generated comments are not carried over into the parsed entry
+    assert_eq!(reparsed.comments, entry.comments);
+    assert_eq!(
+        entry.serialize_with(&SerializeOptions::default()),
+        entry.serialize()
+    );
+}
+
+#[test]
+fn test_split_mime_associations() {
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/feature_rich.desktop").unwrap();
+    let mime_types = entry.mime_type.clone().unwrap();
+
+    let overrides = entry
+        .split_mime_associations("feature-rich.desktop")
+        .unwrap();
+    assert!(entry.mime_type.is_none());
+    assert_eq!(overrides.lines().count(), mime_types.len() + 1);
+    for mime_type in &mime_types {
+        assert!(overrides.contains(&format!("{}=feature-rich.desktop;", mime_type)));
+    }
+    assert!(
+        entry
+            .split_mime_associations("feature-rich.desktop")
+            .is_none()
+    );
+}
+
+// ============================================================================
+// Redaction tests
+// ============================================================================
+
+#[test]
+fn test_redacted_masks_identifying_values() {
+    let content = "# Contact: Alice <alice.smith@mail.example.org>\n\
+                   [Desktop Entry]\n\
+                   Type=Application\n\
+                   Name=Notes of alice\n\
+                   Exec=\"/home/alice/My Apps/notes\" --profile alice\n\
+                   Path=/home/alice/work\n\
+                   Icon=/home/alice/.icons/notes.png\n\
+                   Categories=Office;\n\
+                   Actions=open;\n\
+                   \n\
+                   [Desktop Action open]\n\
+                   Name=Open\n\
+                   Exec=notes --open /home/alice/notes.txt\n";
+    let entry = DesktopEntry::parse(content).unwrap();
+    let redacted = entry.redacted();
+
+    assert_eq!(
+        redacted.exec.as_deref(),
+        Some("\"/home/user/My Apps/notes\" --profile user")
+    );
+    assert_eq!(redacted.path.as_deref(), Some("/home/user/work"));
+    assert_eq!(
+        redacted.icon.as_ref().unwrap().default,
+        "/home/user/.icons/notes.png"
+    );
+    assert_eq!(redacted.name.default, "Notes of user");
+    assert_eq!(
+        redacted.comments[0].content,
+        " Contact: Alice <user@example.com>"
+    );
+    assert_eq!(
+        redacted.additional_groups["Desktop Action open"].get("Exec"),
+        Some("notes --open /home/user/notes.txt")
+    );
+    // Structure is unchanged
+    assert_eq!(redacted.categories, entry.categories);
+    assert_eq!(redacted.actions, entry.actions);
+    assert!(!redacted.serialize().contains("alice"));
+}
+
+#[test]
+fn test_redacted_masks_emails_next_to_non_ascii_text() {
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\n\
+         Comment=Contact «john@example.com» ou é@x.fr\n",
+    )
+    .unwrap();
+    assert_eq!(
+        entry.redacted().comment.unwrap().default,
+        "Contact «user@example.com» ou é@x.fr"
+    );
+}
+
+#[test]
+fn test_redacted_home_does_not_match_longer_names() {
+    let Some(home) = std::env::var("HOME")
+        .ok()
+        .map(|home| home.trim_end_matches('/').to_string())
+        .filter(|home| !home.is_empty())
+    else {
+        return;
+    };
+    let entry = DesktopEntry::parse(&format!(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app {home}/x {home}\nPath={home}2/x\n"
+    ))
+    .unwrap();
+    let redacted = entry.redacted();
+    assert_eq!(
+        redacted.exec.as_deref(),
+        Some("app /home/user/x /home/user")
+    );
+    assert!(!redacted.path.unwrap().contains("user2"));
+}
+
+// ============================================================================
+// JSON tests
+// ============================================================================
+
+#[test]
+fn test_json_round_trip() {
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/full_entry.desktop").unwrap();
+    let json = entry.to_json();
+
+    assert!(json.starts_with("{\n  \"version\": 1,\n  \"groups\": {\n    \"Desktop Entry\": {"));
+    let restored = DesktopEntry::from_json(&json).unwrap();
+    assert!(restored.layout.is_none());
+    assert_eq!(restored.name, entry.name);
+    assert_eq!(restored.categories, entry.categories);
+    assert_eq!(restored.additional_groups, entry.additional_groups);
+    assert_eq!(restored.to_json(), json);
+}
+
+#[test]
+fn test_from_json_unicode_and_errors() {
+    let json = r#"{"version": 1, "groups": {"Desktop Entry": {
+        "Type": {"": "Application"},
+        "Name": {"": "Caf\u00e9 \ud83d\ude00", "fr": "Caf\u00e9"},
+        "Exec": {"": "cafe %U"}
+    }}}"#;
+    let entry = DesktopEntry::from_json(json).unwrap();
+    assert_eq!(entry.name.default, "Café 😀");
+    assert_eq!(entry.name.localized.len(), 1);
+
+    for invalid in [
+        "",
+        "[]",
+        r#"{"version": 2, "groups": {}}"#,
+        r#"{"version": 1, "groups": {"Desktop Entry": {"Name": {"": "a\nb"}}}}"#,
+        r#"{"version": 1, "groups": {"Desktop] Entry": {}}}"#,
+        r#"{"version": 1, "groups": {}} trailing"#,
+    ] {
+        assert!(
+            matches!(
+                DesktopEntry::from_json(invalid),
+                Err(DesktopEntryError::InvalidJson(_))
+            ),
+            "{invalid:?}"
+        );
+    }
+}
+
+#[test]
+fn test_from_json_nesting_limit() {
+    for nested in ["[".repeat(200_000), "{\"a\":".repeat(200_000)] {
+        assert!(matches!(
+            DesktopEntry::from_json(&nested),
+            Err(DesktopEntryError::InvalidJson(message)) if message.contains("nested")
+        ));
+    }
+}
+
+// ============================================================================
+// Event parser tests
+// ============================================================================
+
+#[test]
+fn test_entry_events() {
+    use xdg_desktop_entry::events::{EntryEvent, EntryEvents};
+
+    let content = std::fs::read_to_string("tests/fixtures/valid/full_entry.desktop").unwrap();
+    let entry = DesktopEntry::parse(&content).unwrap();
+
+    let mut groups = Vec::new();
+    let mut name_locales = 0;
+    for event in EntryEvents::new(&content) {
+        match event.unwrap() {
+            EntryEvent::GroupStart { name, span } => {
+                assert_eq!(
+                    content.lines().nth(span.line - 1).unwrap().trim(),
+                    format!("[{name}]")
+                );
+                groups.push(name);
+            }
+            EntryEvent::KeyValue {
+                key: "Name",
+                locale: Some(_),
+                ..
+            } if groups.len() == 1 => name_locales += 1,
+            _ => {}
+        }
+    }
+    assert_eq!(groups.len(), entry.additional_groups.len() + 1);
+    assert_eq!(name_locales, entry.name.localized.len());
+
+    let events: Vec<_> =
+        EntryEvents::new("# Comment\nName=Orphan\n[Group\n[Group]\nBad Key=1\nKey[de]=Wert\n")
+            .collect();
+    assert!(matches!(
+        events[0],
+        Ok(EntryEvent::Comment {
+            content: " Comment",
+            ..
+        })
+    ));
+    assert!(matches!(
+        events[1],
+        Err(DesktopEntryError::InvalidLine(2, _))
+    ));
+    assert!(matches!(
+        events[2],
+        Err(DesktopEntryError::InvalidGroupHeader(3, _))
+    ));
+    assert!(matches!(
+        events[4],
+        Err(DesktopEntryError::InvalidKeyName(5, _))
+    ));
+    assert!(matches!(
+        events[5],
+        Ok(EntryEvent::KeyValue {
+            key: "Key",
+            locale: Some("de"),
+            value: "Wert",
+            ..
+        })
+    ));
+}
+
+// ============================================================================
+// Borrowed parsing tests
+// ============================================================================
+
+#[test]
+fn test_desktop_entry_ref_matches_owned_parse() {
+    use std::borrow::Cow;
+    use xdg_desktop_entry::borrowed::DesktopEntryRef;
+
+    for fixture in [
+        "full_entry",
+        "feature_rich",
+        "desktop_actions",
+        "spec_example",
+    ] {
+        let content =
+            std::fs::read_to_string(format!("tests/fixtures/valid/{fixture}.desktop")).unwrap();
+        let borrowed = DesktopEntryRef::parse(&content).unwrap();
+        let owned = borrowed.to_owned_entry().unwrap();
+
+        assert_eq!(borrowed.entry_type(), owned.entry_type, "{fixture}");
+        assert_eq!(
+            borrowed.get_string("Exec").as_deref(),
+            owned.exec.as_deref()
+        );
+        assert_eq!(borrowed.groups().len(), owned.additional_groups.len() + 1);
+        let name = borrowed.name();
+        assert_eq!(name.default, owned.name.default);
+        for (locale, value) in &owned.name.localized {
+            assert_eq!(name.get(locale), value, "{fixture}");
+        }
+    }
+
+    let entry = DesktopEntryRef::parse(
+        "[Desktop Entry]\nType=Link\nName=Docs\nURL=https://example.org\nComment=A\\sB\n",
+    )
+    .unwrap();
+    assert!(matches!(entry.get_string("URL"), Some(Cow::Borrowed(_))));
+    assert_eq!(entry.get_string("Comment").as_deref(), Some("A B"));
+    assert!(matches!(
+        DesktopEntryRef::parse("[Desktop Entry]\nType=Application\n"),
+        Err(DesktopEntryError::MissingRequiredKey(_))
+    ));
+    assert!(matches!(
+        DesktopEntryRef::parse("[Desktop Entry]\nType=Link\nName=A\n[Desktop Entry]\n"),
+        Err(DesktopEntryError::DuplicateGroup(_))
+    ));
+}
+
+// ============================================================================
+// Escape sequence tests
+// ============================================================================
+
+#[test]
+fn test_string_values_are_unescaped() {
+    let entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=Two\\nLines\nComment=\\sTab\\there \\\\ C:\\\\dir\nExec=app\n",
+    )
+    .unwrap();
+    assert_eq!(entry.name.default, "Two\nLines");
+    assert_eq!(
+        entry.comment.as_ref().unwrap().default,
+        " Tab\there \\ C:\\dir"
+    );
+}
+
+#[test]
+fn test_escaped_values_round_trip() {
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/minimal.desktop").unwrap();
+    entry.name.default = " Leading space\r\nand a \\ backslash".to_string();
+    entry.exec = Some("app --sep '\t'".to_string());
+
+    let serialized = entry.serialize();
+    assert!(serialized.contains("Name=\\sLeading space\\r\\nand a \\\\ backslash\n"));
+    assert!(serialized.contains("Exec=app --sep '\\t'\n"));
+
+    let reparsed = DesktopEntry::parse(&serialized).unwrap();
+    assert_eq!(reparsed.name, entry.name);
+    assert_eq!(reparsed.exec, entry.exec);
+}
+
+// ============================================================================
+// Exec parsing tests
+// ============================================================================
+
+#[test]
+fn test_parsed_exec_field_codes() {
+    use xdg_desktop_entry::exec::{ArgPart, FieldCode};
+
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/minimal.desktop").unwrap();
+    entry.exec = Some("app %i --title=%c \"100%% done\" %u %d".to_string());
+
+    let exec = entry.parsed_exec().unwrap();
+    assert_eq!(exec.program(), "app");
+    assert_eq!(exec.args()[0].as_field_code(), Some(FieldCode::Icon));
+    assert_eq!(
+        exec.args()[1].parts(),
+        &[
+            ArgPart::Text("--title=".to_string()),
+            ArgPart::Code(FieldCode::Name)
+        ]
+    );
+    assert_eq!(exec.args()[2].as_literal(), Some("100% done"));
+    assert_eq!(
+        exec.field_codes(),
+        vec![
+            FieldCode::Icon,
+            FieldCode::Name,
+            FieldCode::Url,
+            FieldCode::Deprecated('d')
+        ]
+    );
+    assert!(exec.accepts_urls() && !exec.accepts_files() && !exec.accepts_multiple());
+}
+
+#[test]
+fn test_parsed_exec_errors() {
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/minimal.desktop").unwrap();
+
+    for exec in ["app %x", "app %", "app --files=%F", "app %f %U", "%f", ""] {
+        entry.exec = Some(exec.to_string());
+        assert!(
+            matches!(
+                entry.parsed_exec(),
+                Err(DesktopEntryError::InvalidValue(key, _)) if key == "Exec"
+            ),
+            "{:?} should be rejected",
+            exec
+        );
+    }
+
+    entry.exec = None;
+    assert_eq!(
+        entry.parsed_exec().unwrap_err(),
+        DesktopEntryError::MissingRequiredKey("Exec".to_string())
+    );
+}
+
+#[test]
+fn test_exec_program() {
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/minimal.desktop").unwrap();
+
+    for (exec, program) in [
+        ("firefox %u", Some("firefox")),
+        ("  /usr/bin/gimp-2.10 %U", Some("/usr/bin/gimp-2.10")),
+        (
+            r#""/opt/100%% Tools/run" --flag"#,
+            Some("/opt/100% Tools/run"),
+        ),
+        (r#""/opt/\$weird/app""#, Some("/opt/$weird/app")),
+        ("app%k --mode=%c", Some("app")),
+        ("%f", None),
+        ("", None),
+        ("\"/opt/broken", None),
+    ] {
+        entry.exec = Some(exec.to_string());
+        assert_eq!(entry.exec_program().as_deref(), program, "{:?}", exec);
+    }
+
+    entry.exec = None;
+    assert_eq!(entry.exec_program(), None);
+}
+
+#[test]
+fn test_exec_split_and_quote_args() {
+    use xdg_desktop_entry::exec::{FieldCode, quote_arg, split_args};
+
+    let args = [
+        "/opt/My App/run",
+        "C:\\Temp",
+        "$PATH",
+        "`id`",
+        "100%",
+        "say \"hi\"",
+    ];
+    let mut entry = DesktopEntry::parse_file("tests/fixtures/valid/minimal.desktop").unwrap();
+    let quoted: Vec<String> = args.iter().map(|arg| quote_arg(arg)).collect();
+    entry.exec = Some(format!("{} %F", quoted.join(" ")));
+
+    // Both layers of escaping survive a round trip through the file
+    let serialized = entry.serialize();
+    assert!(serialized.contains(r#"Exec="/opt/My App/run" "C:\\\\Temp" "\\$PATH""#));
+    let reparsed = DesktopEntry::parse(&serialized).unwrap();
+    let split = split_args(reparsed.exec.as_deref().unwrap()).unwrap();
+    let literals: Vec<_> = split.iter().filter_map(|arg| arg.as_literal()).collect();
+    assert_eq!(literals, args);
+    assert_eq!(
+        split.last().unwrap().as_field_code(),
+        Some(FieldCode::Files)
+    );
+
+    assert!(split_args("app \"unterminated").is_err());
+    assert!(split_args("app %x").is_err());
+}
+
+// ============================================================================
+// Discovery tests
+// ============================================================================
+
+#[test]
+fn test_application_iter_precedence_and_shadowing() {
+    use std::path::PathBuf;
+    use xdg_desktop_entry::discovery::ApplicationIter;
+
+    let dirs = vec![
+        PathBuf::from("tests/fixtures/discovery/home/applications"),
+        PathBuf::from("tests/fixtures/discovery/system/applications"),
+    ];
+    let found: Vec<(String, PathBuf, String)> = ApplicationIter::with_dirs(dirs)
+        .map(|(id, path, entry)| (id.to_string(), path, entry.name.default))
+        .collect();
+
+    // Home entries come first and shadow system ones; the hidden
+    // removed.desktop hides the system copy as well
+    let home = "tests/fixtures/discovery/home/applications";
+    let system = "tests/fixtures/discovery/system/applications";
+    assert_eq!(
+        found,
+        vec![
+            (
+                "foo.desktop".to_string(),
+                PathBuf::from(home).join("foo.desktop"),
+                "Foo (user)".to_string()
+            ),
+            (
+                "kde4-kate.desktop".to_string(),
+                PathBuf::from(home).join("kde4/kate.desktop"),
+                "Kate".to_string()
+            ),
+            (
+                "bar.desktop".to_string(),
+                PathBuf::from(system).join("bar.desktop"),
+                "Bar".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_desktop_file_id_path_conversion() {
+    use std::path::PathBuf;
+    use xdg_desktop_entry::discovery::DesktopFileId;
+
+    let home = PathBuf::from("tests/fixtures/discovery/home/applications");
+    let system = PathBuf::from("tests/fixtures/discovery/system/applications");
+
+    let kate = home.join("kde4/kate.desktop");
+    let id = DesktopFileId::from_path(&kate, &home).unwrap();
+    assert_eq!(id, DesktopFileId::new("kde4-kate.desktop"));
+    assert_eq!(id.resolve_in(&[&home, &system]), Some(kate.clone()));
+    assert_eq!(
+        id.resolve_in(&[&system]),
+        Some(system.join("kde4-kate.desktop"))
+    );
+
+    assert_eq!(DesktopFileId::from_path(&kate, &system), None);
+    assert_eq!(
+        DesktopFileId::new("missing.desktop").resolve_in(&[&home, &system]),
+        None
+    );
+
+    // IDs cannot point outside the directories
+    let absolute = std::fs::canonicalize(system.join("bar.desktop")).unwrap();
+    for id in [
+        "../applications/bar.desktop",
+        "..-applications-bar.desktop",
+        ".-bar.desktop",
+        "..",
+        absolute.to_str().unwrap(),
+    ] {
+        let id = DesktopFileId::new(id);
+        assert_eq!(id.resolve_in(&[&home, &system]), None, "{}", id);
+        assert!(id.resolve_all_in(&[&home, &system]).is_empty(), "{}", id);
+    }
+    assert_eq!(
+        DesktopFileId::new("org.example.App.desktop").bus_name(),
+        "org.example.App"
+    );
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_discovery_matches_blocking() {
+    use std::path::PathBuf;
+    use xdg_desktop_entry::asynchronous;
+    use xdg_desktop_entry::discovery::ApplicationIter;
+
+    let dirs = vec![
+        PathBuf::from("tests/fixtures/discovery/home/applications"),
+        PathBuf::from("tests/fixtures/discovery/system/applications"),
+    ];
+    let found: Vec<_> = asynchronous::find_desktop_entries_in(dirs.clone())
+        .await
+        .into_iter()
+        .map(|(id, path, entry)| (id, path, entry.name.default))
+        .collect();
+    let expected: Vec<_> = ApplicationIter::with_dirs(dirs)
+        .map(|(id, path, entry)| (id, path, entry.name.default))
+        .collect();
+    assert_eq!(found, expected);
+
+    let path = "tests/fixtures/valid/full_entry.desktop";
+    let entry = DesktopEntry::parse_file_async(path).await.unwrap();
+    assert_eq!(entry.exec.as_deref(), Some("full-app %F"));
+    assert_eq!(entry.source_path, Some(PathBuf::from(path)));
+    assert!(matches!(
+        DesktopEntry::parse_file_async("tests/fixtures/missing.desktop").await,
+        Err(DesktopEntryError::Io(_))
+    ));
+
+    #[cfg(feature = "dbus")]
+    assert!(matches!(
+        entry
+            .launch_dbus_async(&[] as &[&str], &Default::default())
+            .await,
+        Err(DesktopEntryError::ValidationError(_))
+    ));
+}
+
+#[cfg(feature = "watch")]
+#[test]
+fn test_application_watcher_precedence() {
+    use std::time::{Duration, Instant};
+    use xdg_desktop_entry::watch::{ApplicationEvent, ApplicationWatcher};
+
+    fn next_event(watcher: &mut ApplicationWatcher) -> ApplicationEvent {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if let Some(event) = watcher.wait_timeout(Duration::from_millis(200)).pop() {
+                // Let the rest of the write arrive before the next step
+                std::thread::sleep(Duration::from_millis(100));
+                watcher.try_events();
+                return event;
+            }
+        }
+        panic!("no application event");
+    }
+
+    let base = std::env::temp_dir().join(format!("xdg-watch-{}", std::process::id()));
+    let home = base.join("home/applications");
+    let system = base.join("system/applications");
+    std::fs::create_dir_all(&home).unwrap();
+    std::fs::create_dir_all(&system).unwrap();
+    let entry = |name: &str| format!("[Desktop Entry]\nType=Application\nName={name}\nExec=app\n");
+
+    let mut watcher = ApplicationWatcher::with_dirs(vec![home.clone(), system.clone()]).unwrap();
+    assert_eq!(watcher.applications().count(), 0);
+
+    std::fs::write(system.join("app.desktop"), entry("System")).unwrap();
+    match next_event(&mut watcher) {
+        ApplicationEvent::Added(id, path, entry) => {
+            assert_eq!(id.as_str(), "app.desktop");
+            assert_eq!(path, system.join("app.desktop"));
+            assert_eq!(entry.name.default, "System");
+        }
+        event => panic!("unexpected {event:?}"),
+    }
+
+    std::fs::write(home.join("app.desktop"), entry("User")).unwrap();
+    match next_event(&mut watcher) {
+        ApplicationEvent::Modified(_, path, entry) => {
+            assert_eq!(path, home.join("app.desktop"));
+            assert_eq!(entry.name.default, "User");
+        }
+        event => panic!("unexpected {event:?}"),
+    }
+
+    std::fs::remove_file(home.join("app.desktop")).unwrap();
+    match next_event(&mut watcher) {
+        ApplicationEvent::Modified(_, _, entry) => assert_eq!(entry.name.default, "System"),
+        event => panic!("unexpected {event:?}"),
+    }
+
+    std::fs::remove_file(system.join("app.desktop")).unwrap();
+    assert!(matches!(
+        next_event(&mut watcher),
+        ApplicationEvent::Removed(id) if id.as_str() == "app.desktop"
+    ));
+    std::fs::remove_dir_all(&base).unwrap();
+}
+
+// ============================================================================
+// Formatter tests
+// ============================================================================
+
+#[test]
+fn test_format_str() {
+    use xdg_desktop_entry::format::{FormatOptions, format_str};
+
+    let content = "\n# Generated\n\n[X-Extra]\nb=2\n\n\
+                   [Desktop Action two]\nName=Two\nExec=app 2\n\
+                   [Desktop Entry]\n\
+                   Type = Application\n\
+                   # The name\n\
+                   Name=App\n\
+                   Keywords[de]=spiel\n\
+                   Exec=app\n\
+                   Keywords[da]=leg\n\
+                   Keywords=game\n\
+                   Tip=1\nX-Tip=2\nHint=3\n\
+                   MimeType=text/plain\\;\n\
+                   Actions=one;two\n\
+                   # trailing\n\
+                   [Desktop Action one]\nName=One\nExec=app 1\n";
+
+    let formatted = format_str(content, &FormatOptions::default()).unwrap();
+    assert_eq!(
+        formatted,
+        "# Generated\n\n\
+         [Desktop Entry]\n\
+         Type=Application\n\
+         # The name\n\
+         Name=App\n\
+         Keywords=game;\n\
+         Keywords[de]=spiel;\n\
+         Keywords[da]=leg;\n\
+         Exec=app\n\
+         Tip=1\nX-Tip=2\nHint=3\n\
+         MimeType=text/plain\\;;\n\
+         Actions=one;two;\n\
+         # trailing\n\n\
+         [Desktop Action one]\nName=One\nExec=app 1\n\n\
+         [Desktop Action two]\nName=Two\nExec=app 2\n\n\
+         [X-Extra]\nb=2\n"
+    );
+    // Formatting is idempotent
+    assert_eq!(
+        format_str(&formatted, &FormatOptions::default()).unwrap(),
+        formatted
+    );
+
+    let options = FormatOptions {
+        sort_keys: true,
+        prefix_unknown_keys: true,
+    };
+    let formatted = format_str(content, &options).unwrap();
+    assert!(formatted.contains(
+        "Exec=app\nKeywords=game;\nKeywords[da]=leg;\nKeywords[de]=spiel;\n\
+         MimeType=text/plain\\;;\n# The name\nName=App\nTip=1\nType=Application\n\
+         X-Hint=3\nX-Tip=2\n"
+    ));
+
+    assert_eq!(
+        format_str("Name=App\n", &FormatOptions::default()).unwrap_err(),
+        DesktopEntryError::InvalidLine(1, "Name=App".to_string())
+    );
+    assert!(format_str("[Desktop Entry]\nno equals\n", &FormatOptions::default()).is_err());
+}
+
+// ============================================================================
+// Patch tests
+// ============================================================================
+
+#[test]
+fn test_apply_patch_to_file() {
+    use xdg_desktop_entry::patch::{DesktopEntryPatch, apply_patch_to_file};
+
+    let content = "# Managed by hand\n[Desktop Entry]\nType=Application\n\
+                   Name=App\nName[fr]=Appli\n\n# Launcher\nExec = app %U\n\
+                   X-Old=1\nX-Old[de]=1\n\n[Desktop Action new]\nName=New\nExec=app --new";
+    let path = std::env::temp_dir().join(format!("xdg-patch-{}.desktop", std::process::id()));
+    std::fs::write(&path, content).unwrap();
+
+    let patch = DesktopEntryPatch::new()
+        .set("Desktop Entry", "Exec", "app --fresh %U")
+        .set_localized(
+            "Desktop Entry",
+            "Name",
+            Locale::from_string("de"),
+            "Anwendung",
+        )
+        .set("Desktop Entry", "Comment", "A;B")
+        .remove("Desktop Entry", "X-Old")
+        .rename_group("Desktop Action new", "Desktop Action create")
+        .set("Desktop Action create", "Icon", "new")
+        .set("X-Extra", "Enabled", "true");
+    assert_eq!(patch.ops().len(), 7);
+
+    assert!(apply_patch_to_file(&path, &patch).unwrap());
+    let patched = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        patched,
+        "# Managed by hand\n[Desktop Entry]\nType=Application\n\
+         Name=App\nName[fr]=Appli\nName[de]=Anwendung\n\n# Launcher\nExec = app --fresh %U\n\
+         Comment=A;B\n\n[Desktop Action create]\nName=New\nExec=app --new\nIcon=new\n\
+         \n[X-Extra]\nEnabled=true\n"
+    );
+    DesktopEntry::parse(&patched).unwrap();
+
+    // The group is already renamed, so the same patch fails, leaving the file
+    // untouched; edits already applied change nothing
+    assert!(matches!(
+        apply_patch_to_file(&path, &patch),
+        Err(DesktopEntryError::MissingGroup(_))
+    ));
+    let idempotent = DesktopEntryPatch::new()
+        .set("Desktop Entry", "Exec", "app --fresh %U")
+        .remove("Desktop Entry", "X-Old");
+    assert!(!apply_patch_to_file(&path, &idempotent).unwrap());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), patched);
+
+    let invalid = DesktopEntryPatch::new().set("Desktop Entry", "Bad Key", "1");
+    assert!(matches!(
+        apply_patch_to_file(&path, &invalid),
+        Err(DesktopEntryError::InvalidValue(..))
+    ));
+    let duplicate = DesktopEntryPatch::new().rename_group("X-Extra", "Desktop Entry");
+    assert_eq!(
+        duplicate.apply_to_str(&patched).unwrap_err(),
+        DesktopEntryError::DuplicateGroup("Desktop Entry".to_string())
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+// ============================================================================
+// Span tests
+// ============================================================================
+
+#[test]
+fn test_parse_spans() {
+    use xdg_desktop_entry::Span;
+
+    let content = "# Header\n[Desktop Entry]\nType=Application\nName=App\n  X-Mode [fr] = rapide\n\
+                   \n  [Desktop Action new]\nName=New\nExec=app --new\n";
+    let entry = DesktopEntry::parse(content).unwrap();
+    let text = |span: Span| &content[span.byte_range(content).unwrap()];
+
+    let group = &entry.additional_groups["Desktop Action new"];
+    let header = group.header_span.unwrap();
+    assert_eq!(
+        header,
+        Span {
+            line: 7,
+            column: 3,
+            len: 20
+        }
+    );
+    assert_eq!(text(header), "[Desktop Action new]");
+    assert_eq!(entry.group_span("Desktop Action new"), Some(header));
+    assert_eq!(
+        text(entry.group_span("Desktop Entry").unwrap()),
+        "[Desktop Entry]"
+    );
+    assert_eq!(entry.group_span("Missing"), None);
+
+    let exec = group.entries["Exec"][0].span.unwrap();
+    assert_eq!(exec.line, Span::line(9, "Exec=app --new"));
+    assert_eq!(text(exec.key), "Exec");
+    assert_eq!(exec.locale, None);
+    assert_eq!(text(exec.value), "app --new");
+
+    let mode = entry.unknown_keys["X-Mode"][0].span.unwrap();
+    assert_eq!(mode.key.line, 5);
+    assert_eq!(text(mode.key), "X-Mode");
+    assert_eq!(text(mode.locale.unwrap()), "[fr]");
+    assert_eq!(text(mode.value), " rapide");
+    assert_eq!(
+        entry.entry_span("Desktop Entry", "X-Mode", Some(&Locale::from_string("fr"))),
+        Some(mode)
+    );
+    assert!(entry.entry_span("Desktop Entry", "X-Mode", None).is_none());
+    assert_eq!(
+        text(
+            entry
+                .entry_span("Desktop Entry", "Type", None)
+                .unwrap()
+                .value
+        ),
+        "Application"
+    );
+
+    // Spans are not compared, and are unknown without a layout
+    let mut built = entry.clone();
+    built.layout = None;
+    assert!(built.entry_span("Desktop Entry", "Type", None).is_none());
+    let mut moved = group.clone();
+    moved.header_span = None;
+    for entries in moved.entries.values_mut() {
+        entries.iter_mut().for_each(|entry| entry.span = None);
+    }
+    assert_eq!(&moved, group);
+
+    assert_eq!(
+        Span {
+            line: 20,
+            column: 1,
+            len: 1
+        }
+        .byte_range(content),
+        None
+    );
+    let kde = DesktopEntry::parse_with(
+        "[KDE Desktop Entry]\nType=Application\nName=App\n",
+        &xdg_desktop_entry::ParserOptions::lenient(),
+    )
+    .unwrap();
+    assert_eq!(
+        kde.group_span("Desktop Entry"),
+        Some(Span {
+            line: 1,
+            column: 1,
+            len: 19
+        })
+    );
+}
+
+// ============================================================================
+// Incremental parsing tests
+// ============================================================================
+
+#[test]
+fn test_document_edits_match_full_parse() {
+    use xdg_desktop_entry::ParserOptions;
+    use xdg_desktop_entry::document::{Document, TextEdit};
+
+    fn check(document: &Document) {
+        let (entry, diagnostics) =
+            DesktopEntry::parse_collecting(document.text(), document.options());
+        assert_eq!(document.diagnostics(), diagnostics, "{:?}", document.text());
+        assert_eq!(
+            document.entry().map(DesktopEntry::to_json),
+            entry.as_ref().map(DesktopEntry::to_json),
+            "{:?}",
+            document.text()
+        );
+        let Some(entry) = entry else {
+            return;
+        };
+        for (group, key, locale) in [
+            ("Desktop Entry", "Name", None),
+            ("Desktop Entry", "Name", Some(Locale::from_string("de"))),
+            ("Desktop Action new", "Exec", None),
+            ("X-Tail", "Key", None),
+        ] {
+            assert_eq!(
+                document.entry_span(group, key, locale.as_ref()),
+                entry.entry_span(group, key, locale.as_ref()),
+                "{:?}",
+                document.text()
+            );
+            assert_eq!(document.group_span(group), entry.group_span(group));
+        }
+    }
+
+    let content = "# Header\n[Desktop Entry]\nType=Application\nName=App\nName[de]=Anwendung\n\
+                   Exec=app\nActions=new;\n\n[Desktop Action new]\nName=New\nExec=app --new\n\n\
+                   [X-Tail]\nKey=value\nBad Key=1\n";
+    for options in [ParserOptions::default(), ParserOptions::lenient()] {
+        let mut document = Document::parse(content, &options);
+        check(&document);
+
+        let edits: Vec<(&str, &str)> = vec![
+            // Edits within a line
+            ("Name=App", "Name=Editor"),
+            ("Exec=app\n", "Exec=app %U\n"),
+            // Adding and removing lines shifts the following groups
+            (
+                "Name[de]=Anwendung\n",
+                "Name[de]=Anwendung\nComment=Edits\nComment=Twice\n",
+            ),
+            ("Comment=Edits\n", ""),
+            // Breaking and restoring a group header
+            ("[Desktop Action new]", "[Desktop Action new"),
+            ("[Desktop Action new\n", "[Desktop Action new]\n"),
+            ("[Desktop Action new]\n", "Desktop Action new]\n"),
+            ("Desktop Action new]\n", "[Desktop Action new]\n"),
+            // A duplicate group, and joining a header to the line before
+            ("[X-Tail]", "[X-Tail]\nKey=first\n\n[X-Tail]"),
+            ("\n\n[X-Tail]\nKey=value", "[X-Tail]\nKey=value"),
+            // Keys before the first group header
+            ("# Header\n", "# Header\nOrphan=1\n"),
+            ("Orphan=1\n", ""),
+            // Removing the Type key makes the entry invalid
+            ("Type=Application\n", ""),
+            ("[Desktop Entry]\n", "[Desktop Entry]\nType=Application\n"),
+        ];
+        for (old, new) in edits {
+            let start = document
+                .text()
+                .find(old)
+                .unwrap_or_else(|| panic!("{:?} not in {:?}", old, document.text()));
+            document.edit(&TextEdit::new(start..start + old.len(), new));
+            check(&document);
+        }
+
+        // Typing a new group character by character at the end
+        for c in "\n[X-New]\nA=b".chars() {
+            let end = document.text().len();
+            document.edit(&TextEdit::new(end..end, c.to_string()));
+            check(&document);
+        }
+
+        // Clearing the text and typing it back
+        let len = document.text().len();
+        document.edit(&TextEdit::new(0..len, ""));
+        check(&document);
+        document.edit(&TextEdit::new(0..0, content));
+        check(&document);
+        assert_eq!(document.text(), content);
+    }
+}
+
+#[test]
+fn test_closing_bracket_before_locale_is_an_invalid_line() {
+    use xdg_desktop_entry::ParserOptions;
+    use xdg_desktop_entry::document::{Document, TextEdit};
+
+    let content = "[Desktop Entry]\nType=Application\nName=App\nExec=app\nX]a[b=c\n";
+    assert!(matches!(
+        DesktopEntry::parse(content),
+        Err(DesktopEntryError::InvalidLine(5, _))
+    ));
+    let (entry, diagnostics) = DesktopEntry::parse_collecting(content, &ParserOptions::default());
+    assert!(entry.is_some());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "invalid-line");
+
+    // Typed one character at a time in an editor
+    let mut document = Document::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\n",
+        &ParserOptions::default(),
+    );
+    for c in "X]a[b=c".chars() {
+        let end = document.text().len();
+        document.edit(&TextEdit::new(end..end, c.to_string()));
+    }
+    assert_eq!(document.diagnostics(), diagnostics);
+}
+
+// ============================================================================
+// Layout preservation tests
+// ============================================================================
+
+#[test]
+fn test_unmodified_entries_serialize_unchanged() {
+    for name in [
+        "desktop_actions",
+        "feature_rich",
+        "full_entry",
+        "spec_example",
+        "with_comments",
+    ] {
+        let path = format!("tests/fixtures/valid/{}.desktop", name);
+        let content = std::fs::read_to_string(&path).unwrap();
+        let entry = DesktopEntry::parse(&content).unwrap();
+        assert_eq!(entry.serialize(), content, "{}", path);
+    }
+}
+
+#[test]
+fn test_layout_rewrites_only_changed_lines() {
+    let content = "\
+# Header
+[Desktop Entry]
+Type=Application
+Name=Editor
+Name[de]=Bearbeiter
+# The command
+Exec=editor %F
+X-Custom=keep
+Categories=Utility;
+
+[Desktop Action new]
+Name=New Window
+Exec=editor --new
+
+[X-Vendor]
+Foo=bar
+";
+    let mut entry = DesktopEntry::parse(content).unwrap();
+    entry.name.default = "Text Editor".to_string();
+    entry.categories = None;
+    entry.terminal = Some(false);
+    entry.additional_groups.remove("X-Vendor");
+
+    assert_eq!(
+        entry.serialize(),
+        "\
+# Header
+[Desktop Entry]
+Type=Application
+Name=Text Editor
+Name[de]=Bearbeiter
+# The command
+Exec=editor %F
+X-Custom=keep
+Terminal=false
+
+[Desktop Action new]
+Name=New Window
+Exec=editor --new
+"
+    );
+
+    entry.layout = None;
+    assert!(!entry.serialize().contains("# The command"));
+}
+
+#[test]
+fn test_layout_keeps_spacing_after_equals() {
+    use xdg_desktop_entry::ParserOptions;
+
+    let content = "[Desktop Entry]\nType=Application\nName = App\nExec=app\nX-Mode =\tfast\n";
+    let mut entry = DesktopEntry::parse(content).unwrap();
+    entry.name.default = "App2".to_string();
+    entry.unknown_keys.get_mut("X-Mode").unwrap()[0].value = "slow".to_string();
+    assert_eq!(
+        entry.serialize(),
+        "[Desktop Entry]\nType=Application\nName = App2\nExec=app\nX-Mode =\tslow\n"
+    );
+
+    let options = ParserOptions {
+        discard_layout: true,
+        ..ParserOptions::default()
+    };
+    let entry = DesktopEntry::parse_with(content, &options).unwrap();
+    assert!(entry.layout.is_none());
+}
+
+#[test]
+fn test_layout_keeps_invalid_values() {
+    for line in [
+        "Terminal=yes",
+        "NoDisplay=1",
+        "StartupNotify=True",
+        "NoDisplay = false",
+    ] {
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName=App\n{}\nExec=app\n",
+            line
+        );
+        let entry = DesktopEntry::parse(&content).unwrap();
+        assert_eq!(entry.serialize(), content);
+
+        // Editing another key keeps the line
+        let mut entry = entry;
+        entry.exec = Some("app --new".to_string());
+        assert_eq!(
+            entry.serialize(),
+            content.replace("Exec=app", "Exec=app --new")
+        );
+    }
+
+    // Setting the key rewrites the invalid line
+    let content = "[Desktop Entry]\nType=Application\nName=App\nTerminal=yes\nExec=app\n";
+    let mut entry = DesktopEntry::parse(content).unwrap();
+    entry.terminal = Some(true);
+    assert_eq!(
+        entry.serialize(),
+        content.replace("Terminal=yes", "Terminal=true")
+    );
+}
+
+#[test]
+fn test_canonical_serialization_is_deterministic() {
+    use xdg_desktop_entry::SerializeOptions;
+
+    let content = std::fs::read_to_string("tests/fixtures/valid/full_entry.desktop").unwrap();
+    let canonical = DesktopEntry::parse(&content)
+        .unwrap()
+        .serialize_with(&SerializeOptions::canonical());
+
+    for _ in 0..5 {
+        let entry = DesktopEntry::parse(&content).unwrap();
+        assert_eq!(
+            entry.serialize_with(&SerializeOptions::canonical()),
+            canonical
+        );
+        let reparsed = DesktopEntry::parse(&canonical).unwrap();
+        assert_eq!(
+            reparsed.serialize_with(&SerializeOptions::canonical()),
+            canonical
+        );
+    }
+    for line in canonical.lines() {
+        if line.starts_with("Categories=") || line.starts_with("MimeType=") {
+            assert!(line.ends_with(';'), "{}", line);
+        }
+    }
+}
+
+// ============================================================================
+// File writing tests
+// ============================================================================
+
+#[test]
+fn test_write_file_replaces_atomically() {
+    let dir = std::env::temp_dir().join(format!("xdg-write-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("app.desktop");
+    std::fs::write(
+        &path,
+        "[Desktop Entry]\nType=Application\nName=Old\nExec=app\n",
+    )
+    .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let mut entry = DesktopEntry::parse_file(&path).unwrap();
+    entry.name.default = "New".to_string();
+    entry.write_file(&path).unwrap();
+
+    assert_eq!(DesktopEntry::parse_file(&path).unwrap().name.default, "New");
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    assert!(entry.write_file(dir.join("missing/app.desktop")).is_err());
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// ============================================================================
+// Builder tests
+// ============================================================================
+
+#[test]
+fn test_builder_produces_valid_entries() {
+    let entry = DesktopEntry::builder(DesktopEntryType::Application)
+        .name("Editor")
+        .localized_name(Locale::from_string("de"), "Bearbeiter")
+        .exec("editor %F")
+        .categories(["Utility", "TextEditor"])
+        .mime_types(vec!["text/plain".to_string()])
+        .terminal(false)
+        .action("new-window", "New Window", Some("editor --new-window"))
+        .build()
+        .unwrap();
+
+    let reparsed = DesktopEntry::parse(&entry.serialize()).unwrap();
+    assert_eq!(reparsed.name.get(&Locale::from_string("de")), "Bearbeiter");
+    assert_eq!(reparsed.terminal, Some(false));
+    let actions = reparsed.visible_actions(&["GNOME"], &[]);
+    assert_eq!(actions[0].exec.as_deref(), Some("editor --new-window"));
+    assert!(reparsed.validate().is_ok());
+
+    let link = DesktopEntry::builder(DesktopEntryType::Link)
+        .name("Home")
+        .url("https://example.com")
+        .build()
+        .unwrap();
+    assert_eq!(link.url.as_deref(), Some("https://example.com"));
+
+    assert!(matches!(
+        DesktopEntry::builder(DesktopEntryType::Application)
+            .exec("app")
+            .build(),
+        Err(DesktopEntryError::MissingRequiredKey(key)) if key == "Name"
+    ));
+    assert!(matches!(
+        DesktopEntry::builder(DesktopEntryType::Application)
+            .name("App")
+            .build(),
+        Err(DesktopEntryError::ValidationError(_))
+    ));
+}
+
+// ============================================================================
+// MIME association tests
+// ============================================================================
+
+fn fixture_mime_apps(user_dir: &std::path::Path) -> xdg_desktop_entry::mimeapps::MimeApps {
+    use xdg_desktop_entry::mimeapps::MimeApps;
+
+    let fixtures = std::path::Path::new("tests/fixtures/mimeapps");
+    MimeApps::load_from(
+        vec![
+            user_dir.join("gnome-mimeapps.list"),
+            user_dir.join("mimeapps.list"),
+            fixtures.join("applications/gnome-mimeapps.list"),
+            fixtures.join("applications/mimeapps.list"),
+        ],
+        vec![fixtures.join("applications")],
+    )
+}
+
+#[test]
+fn test_mimeapps_merging() {
+    use xdg_desktop_entry::discovery::DesktopFileId;
+
+    let mime_apps = fixture_mime_apps(std::path::Path::new("tests/fixtures/mimeapps/config"));
+    let ids = |ids: &[&str]| -> Vec<DesktopFileId> {
+        ids.iter().map(|id| DesktopFileId::new(*id)).collect()
+    };
+
+    // The desktop-specific file wins, skipping applications that are not installed
+    assert_eq!(
+        mime_apps.default_app_for("text/plain"),
+        Some(DesktopFileId::new("editor.desktop"))
+    );
+    assert_eq!(
+        mime_apps.default_app_for("image/png"),
+        Some(DesktopFileId::new("viewer.desktop"))
+    );
+    assert_eq!(mime_apps.default_app_for("video/mp4"), None);
+
+    // Removals apply to less important files
+    assert_eq!(
+        mime_apps.added_associations("text/plain"),
+        ids(&["viewer.desktop"])
+    );
+    assert_eq!(
+        mime_apps.added_associations("image/png"),
+        ids(&["editor.desktop", "viewer.desktop"])
+    );
+    assert!(mime_apps.is_removed("text/plain", &DesktopFileId::new("vim.desktop")));
+    assert_eq!(mime_apps.lists().len(), 3);
+}
+
+#[test]
+fn test_mimeapps_set_default_app() {
+    use xdg_desktop_entry::discovery::DesktopFileId;
+    use xdg_desktop_entry::mimeapps::MimeAppsList;
+
+    let dir = std::env::temp_dir().join(format!("xdg-mimeapps-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(
+        "tests/fixtures/mimeapps/config/mimeapps.list",
+        dir.join("mimeapps.list"),
+    )
+    .unwrap();
+
+    let mut mime_apps = fixture_mime_apps(&dir);
+    assert_eq!(
+        mime_apps.default_app_for("text/plain"),
+        Some(DesktopFileId::new("vim.desktop"))
+    );
+
+    mime_apps
+        .set_default_app("text/plain", "editor.desktop")
+        .unwrap();
+    let editor = Some(DesktopFileId::new("editor.desktop"));
+    assert_eq!(mime_apps.default_app_for("text/plain"), editor);
+    assert_eq!(
+        fixture_mime_apps(&dir).default_app_for("text/plain"),
+        editor
+    );
+
+    // The first path is the user's file
+    let written = MimeAppsList::load(dir.join("gnome-mimeapps.list")).unwrap();
+    assert_eq!(
+        written.default_applications["text/plain"],
+        ["editor.desktop"]
+    );
+    assert_eq!(written.added_associations["text/plain"], ["editor.desktop"]);
+    assert_eq!(MimeAppsList::parse(&written.serialize()), written);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_handlers_for_mime() {
+    use xdg_desktop_entry::discovery::Applications;
+
+    let applications = Applications::with_dirs(
+        vec!["tests/fixtures/mimeapps/applications".into()],
+        fixture_mime_apps(std::path::Path::new("tests/fixtures/mimeapps/config")),
+    );
+    let handlers = |mime_type: &str| -> Vec<String> {
+        applications
+            .handlers_for_mime(mime_type)
+            .into_iter()
+            .map(|(id, _, _)| id.to_string())
+            .collect()
+    };
+
+    // vim.desktop claims text/plain but the association is removed
+    assert_eq!(handlers("text/plain"), ["editor.desktop", "viewer.desktop"]);
+    assert_eq!(handlers("image/png"), ["viewer.desktop", "editor.desktop"]);
+    assert_eq!(handlers("image/jpeg"), ["viewer.desktop"]);
+    assert!(handlers("video/mp4").is_empty());
+}
+
+#[test]
+fn test_handlers_for_scheme() {
+    use xdg_desktop_entry::DesktopEntryError;
+    use xdg_desktop_entry::discovery::Applications;
+
+    let applications = Applications::with_dirs(
+        vec!["tests/fixtures/mimeapps/applications".into()],
+        fixture_mime_apps(std::path::Path::new("tests/fixtures/mimeapps/config")),
+    );
+
+    let handlers: Vec<String> = applications
+        .handlers_for_scheme("https")
+        .into_iter()
+        .map(|(id, _, _)| id.to_string())
+        .collect();
+    assert_eq!(handlers, ["browser.desktop"]);
+
+    let (id, _, entry) = applications.default_handler_for_scheme("HTTP").unwrap();
+    assert_eq!(id.as_str(), "browser.desktop");
+    assert_eq!(entry.name.default, "Browser");
+    assert!(applications.default_handler_for_scheme("mailto").is_none());
+
+    let kind = |uri: &str| match applications.open_uri(uri) {
+        Err(DesktopEntryError::Io(err)) => err.kind(),
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    };
+    assert_eq!(
+        kind("mailto:someone@example.com"),
+        std::io::ErrorKind::NotFound
+    );
+    assert_eq!(
+        kind("/home/user/file.txt"),
+        std::io::ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn test_handlers_for_mime_uses_fresh_cache() {
+    use std::time::{Duration, SystemTime};
+    use xdg_desktop_entry::discovery::Applications;
+    use xdg_desktop_entry::mimeapps::MimeApps;
+    use xdg_desktop_entry::mimeinfo::{MIMEINFO_CACHE, MimeInfoCache};
+
+    let dir = std::env::temp_dir().join(format!("xdg-mimeinfo-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for app in ["editor", "viewer"] {
+        let name = format!("{}.desktop", app);
+        std::fs::copy(
+            format!("tests/fixtures/mimeapps/applications/{}", name),
+            dir.join(&name),
+        )
+        .unwrap();
+    }
+    // The cache claims a type the viewer entry itself does not list
+    std::fs::write(
+        dir.join(MIMEINFO_CACHE),
+        "[MIME Cache]\nimage/gif=viewer.desktop;\ntext/plain=editor.desktop;\n",
+    )
+    .unwrap();
+    assert!(MimeInfoCache::is_fresh(&dir));
+
+    let applications = Applications::with_dirs(
+        vec![dir.clone()],
+        MimeApps::load_from(Vec::new(), vec![dir.clone()]),
+    );
+    let handlers = |mime_type: &str| -> Vec<String> {
+        applications
+            .handlers_for_mime(mime_type)
+            .into_iter()
+            .map(|(id, _, _)| id.to_string())
+            .collect()
+    };
+    assert_eq!(handlers("image/gif"), ["viewer.desktop"]);
+
+    // Editing an entry makes the cache stale, so the directory is scanned
+    let file = std::fs::File::options()
+        .append(true)
+        .open(dir.join("viewer.desktop"))
+        .unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+    assert!(!MimeInfoCache::is_fresh(&dir));
+    assert!(handlers("image/gif").is_empty());
+    assert_eq!(handlers("image/png"), ["viewer.desktop"]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_mimeinfo_cache_update() {
+    use xdg_desktop_entry::mimeinfo::{MIMEINFO_CACHE, MimeInfoCache};
+
+    let dir = std::env::temp_dir().join(format!("xdg-mimeinfo-update-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("kde4")).unwrap();
+    for app in ["editor", "viewer", "vim"] {
+        let name = format!("{}.desktop", app);
+        std::fs::copy(
+            format!("tests/fixtures/mimeapps/applications/{}", name),
+            dir.join(&name),
+        )
+        .unwrap();
+    }
+    std::fs::write(
+        dir.join("kde4/okular.desktop"),
+        "[Desktop Entry]\nType=Application\nName=Okular\nExec=okular\nMimeType=application/pdf;not-a-type;\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("removed.desktop"),
+        "[Desktop Entry]\nType=Application\nName=Removed\nExec=removed\nHidden=true\nMimeType=text/plain;\n",
+    )
+    .unwrap();
+
+    let cache = MimeInfoCache::update(&dir).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(dir.join(MIMEINFO_CACHE)).unwrap(),
+        "[MIME Cache]\n\
+         application/pdf=kde4-okular.desktop;\n\
+         image/jpeg=viewer.desktop;\n\
+         image/png=viewer.desktop;\n\
+         text/plain=editor.desktop;vim.desktop;\n\
+         text/x-c=editor.desktop;\n"
+    );
+    assert!(MimeInfoCache::is_fresh(&dir));
+    assert_eq!(MimeInfoCache::load_fresh(&dir), Some(cache));
+
+    // Renaming the cache into the directory may update its time after the
+    // time of the cache; only the desktop files and subdirectories count
+    let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+    std::fs::File::open(&dir)
+        .unwrap()
+        .set_modified(later)
+        .unwrap();
+    assert!(MimeInfoCache::is_fresh(&dir));
+    std::fs::File::open(dir.join("kde4"))
+        .unwrap()
+        .set_modified(later)
+        .unwrap();
+    assert!(!MimeInfoCache::is_fresh(&dir));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// ============================================================================
+// Application store tests
+// ============================================================================
+
+#[test]
+fn test_application_store() {
+    use std::sync::Arc;
+    use xdg_desktop_entry::categories::Category;
+    use xdg_desktop_entry::environment::DesktopEnvironment;
+    use xdg_desktop_entry::interface::Interface;
+    use xdg_desktop_entry::mimeapps::MimeApps;
+    use xdg_desktop_entry::store::ApplicationStore;
+
+    let root = std::env::temp_dir().join(format!("xdg-app-store-{}", std::process::id()));
+    let home = root.join("home");
+    let system = root.join("system");
+    std::fs::create_dir_all(&home).unwrap();
+    std::fs::create_dir_all(&system).unwrap();
+    let write = |path: std::path::PathBuf, keys: &str| {
+        std::fs::write(
+            path,
+            format!("[Desktop Entry]\nType=Application\nExec=app\n{}", keys),
+        )
+        .unwrap();
+    };
+    write(
+        system.join("paint.desktop"),
+        "Name=Paint\nCategories=Graphics;\n",
+    );
+    write(
+        home.join("paint.desktop"),
+        "Name=My Paint\nCategories=Graphics;\n",
+    );
+    write(system.join("gone.desktop"), "Name=Gone\n");
+    write(home.join("gone.desktop"), "Name=Gone\nHidden=true\n");
+    write(
+        system.join("helper.desktop"),
+        "Name=Helper\nNoDisplay=true\nImplements=org.freedesktop.FileManager1;\n",
+    );
+    write(
+        system.join("kde-only.desktop"),
+        "Name=KDE Tool\nCategories=Graphics;\nOnlyShowIn=KDE;\n",
+    );
+
+    let store = Arc::new(ApplicationStore::with_dirs(
+        vec![home.clone(), system.clone()],
+        MimeApps::load_from(Vec::new(), Vec::new()),
+        vec![DesktopEnvironment::Gnome],
+    ));
+    let ids = |apps: Vec<xdg_desktop_entry::store::StoredApplication>| -> Vec<String> {
+        apps.into_iter().map(|app| app.id.to_string()).collect()
+    };
+
+    assert_eq!(
+        ids(store.all()),
+        ["helper.desktop", "kde-only.desktop", "paint.desktop"]
+    );
+    assert_eq!(ids(store.visible()), ["paint.desktop"]);
+    assert_eq!(
+        ids(store.by_category(Category::Graphics)),
+        ["paint.desktop"]
+    );
+    assert_eq!(ids(store.by_category("Graphics")), ["paint.desktop"]);
+    assert!(store.by_category(Category::Office).is_empty());
+    let file_manager = Interface::parse("org.freedesktop.FileManager1").unwrap();
+    assert_eq!(ids(store.implementing(&file_manager)), ["helper.desktop"]);
+    assert!(store.implementing("org.example.Missing").is_empty());
+    let paint = store.get("paint.desktop").unwrap();
+    assert_eq!(paint.entry.name.default, "My Paint");
+    assert_eq!(paint.path, home.join("paint.desktop"));
+    assert!(store.get("gone.desktop").is_none());
+
+    // Removing the override falls back to the system entry
+    std::fs::remove_file(home.join("paint.desktop")).unwrap();
+    let worker = Arc::clone(&store);
+    let summary = std::thread::spawn(move || worker.refresh()).join().unwrap();
+    assert_eq!(summary.removed, [home.join("paint.desktop")]);
+    assert_eq!(
+        store.get("paint.desktop").unwrap().entry.name.default,
+        "Paint"
+    );
+    assert_eq!(store.len(), 3);
+
+    // Unchanged entries are shared between refreshes
+    let helper = store.get("helper.desktop").unwrap();
+    assert_eq!(store.refresh().unchanged, 5);
+    assert!(Arc::ptr_eq(
+        &helper.entry,
+        &store.get("helper.desktop").unwrap().entry
+    ));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_application_store_handlers_for_mime() {
+    use xdg_desktop_entry::discovery::Applications;
+    use xdg_desktop_entry::store::ApplicationStore;
+
+    let dirs = vec!["tests/fixtures/mimeapps/applications".into()];
+    let mime_apps = fixture_mime_apps(std::path::Path::new("tests/fixtures/mimeapps/config"));
+    let store = ApplicationStore::with_dirs(dirs.clone(), mime_apps.clone(), Vec::new());
+    let applications = Applications::with_dirs(dirs, mime_apps);
+
+    for mime_type in ["text/plain", "image/png", "image/jpeg", "video/mp4"] {
+        let from_store: Vec<String> = store
+            .handlers_for_mime(mime_type)
+            .into_iter()
+            .map(|app| app.id.to_string())
+            .collect();
+        let expected: Vec<String> = applications
+            .handlers_for_mime(mime_type)
+            .into_iter()
+            .map(|(id, _, _)| id.to_string())
+            .collect();
+        assert_eq!(from_store, expected, "{}", mime_type);
+    }
+    assert_eq!(
+        store.default_for_mime("image/png").unwrap().id.as_str(),
+        "viewer.desktop"
+    );
+}
+
+// ============================================================================
+// Menu tests
+// ============================================================================
+
+#[test]
+fn test_menu_file_build() {
+    use xdg_desktop_entry::menu::{Menu, MenuFile};
+
+    let menu_file = MenuFile::load("tests/fixtures/menu/applications.menu").unwrap();
+    assert_eq!(menu_file.name(), "Applications");
+
+    let names = |menu: &Menu| -> Vec<String> {
+        menu.entries
+            .iter()
+            .map(|item| item.entry.name.default.clone())
+            .collect()
+    };
+    let menu = menu_file.build(&["GNOME"]);
+    let submenus: Vec<&str> = menu.submenus.iter().map(Menu::display_name).collect();
+    // Secret is hidden by its directory entry, Removed is deleted, and
+    // Empty has no entries
+    assert_eq!(submenus, ["Graphics & Art", "Other", "Work & Office"]);
+    assert!(menu.entries.is_empty());
+
+    // The merged file adds misc.desktop and excludes viewer.desktop
+    let graphics = menu.find("Graphics").unwrap();
+    assert_eq!(names(graphics), ["Misc", "Paint"]);
+    assert_eq!(
+        graphics
+            .directory
+            .as_ref()
+            .unwrap()
+            .icon
+            .as_ref()
+            .unwrap()
+            .default,
+        "applications-graphics"
+    );
+
+    let office = menu.find("Work & Office").unwrap();
+    assert_eq!(names(office), ["Calc", "Writer"]);
+    assert_eq!(office.entries[0].id.as_str(), "office-calc.desktop");
+
+    assert_eq!(
+        names(menu.find("Other").unwrap()),
+        ["Image Viewer", "Notes"]
+    );
+    assert!(menu.find("Office").is_none());
+
+    let kde = menu_file.build(&["KDE"]);
+    assert_eq!(
+        names(kde.find("Other").unwrap()),
+        ["Image Viewer", "KDE Tool", "Notes"]
+    );
+}
+
+#[test]
+fn test_menu_file_errors() {
+    use xdg_desktop_entry::DesktopEntryError;
+    use xdg_desktop_entry::menu::MenuFile;
+
+    for invalid in [
+        "",
+        "<Menu><Name>A</Name>",
+        "<Menu><Name>A</Menu></Name>",
+        "<Other/>",
+        "<Menu><Include><Unknown/></Include></Menu>",
+        "<Menu><Name>&bogus;</Name></Menu>",
+        "<Menu/><Menu/>",
+    ] {
+        assert!(
+            matches!(
+                MenuFile::parse(invalid),
+                Err(DesktopEntryError::InvalidMenu(_))
+            ),
+            "{}",
+            invalid
+        );
+    }
+    let menu = MenuFile::parse("<Menu><Name>A &#x26; B<![CDATA[ <C>]]></Name></Menu>").unwrap();
+    assert_eq!(menu.name(), "A & B <C>");
+}
+
+#[test]
+fn test_menu_file_nesting_limit() {
+    use xdg_desktop_entry::DesktopEntryError;
+    use xdg_desktop_entry::menu::MenuFile;
+
+    let nested = |depth: usize| {
+        format!(
+            "<Menu><Name>A</Name><Include>{}{}</Include></Menu>",
+            "<Not>".repeat(depth),
+            "</Not>".repeat(depth)
+        )
+    };
+    assert!(MenuFile::parse(&nested(8)).is_ok());
+    assert!(matches!(
+        MenuFile::parse(&nested(100_000)),
+        Err(DesktopEntryError::InvalidMenu(_))
+    ));
+}
+
+// ============================================================================
+// Extension key tests
+// ============================================================================
+
+#[cfg(feature = "gnome")]
+#[test]
+fn test_gnome_ext() {
+    use xdg_desktop_entry::gnome::GnomeExt;
+
+    let mut entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=Tracker\nExec=tracker\n\
+         X-GNOME-Autostart-enabled=true\nX-GNOME-Autostart-Phase=Initialization\n\
+         X-GNOME-Autostart-Delay=soon\nX-GNOME-AutoRestart=yes\n",
+    )
+    .unwrap();
+    assert_eq!(entry.gnome_autostart_enabled(), Some(true));
+    assert_eq!(
+        entry.gnome_autostart_phase().as_deref(),
+        Some("Initialization")
+    );
+    // Values of the wrong type read as missing
+    assert_eq!(entry.gnome_autostart_delay(), None);
+    assert_eq!(entry.gnome_auto_restart(), None);
+    assert_eq!(entry.gnome_uses_notifications(), None);
+
+    entry.set_gnome_autostart_delay(Some(10));
+    entry.set_gnome_single_window(Some(true));
+    entry.set_gnome_hidden_under_systemd(Some(true));
+    entry.set_gnome_autostart_phase(None);
+    entry.set_purism_form_factors(Some(&["Workstation", "Mobile"]));
+
+    let reparsed = DesktopEntry::parse(&entry.serialize()).unwrap();
+    assert_eq!(reparsed.gnome_autostart_delay(), Some(10));
+    assert_eq!(reparsed.gnome_single_window(), Some(true));
+    assert_eq!(reparsed.gnome_hidden_under_systemd(), Some(true));
+    assert_eq!(reparsed.gnome_autostart_phase(), None);
+    assert_eq!(
+        reparsed.unknown_string("X-Purism-FormFactor").as_deref(),
+        Some("Workstation;Mobile;")
+    );
+}
+
+#[cfg(feature = "kde")]
+#[test]
+fn test_kde_ext() {
+    use xdg_desktop_entry::kde::KdeExt;
+
+    let mut entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=Partition Manager\nExec=partitionmanager\n\
+         X-KDE-SubstituteUID=true\nX-KDE-StartupNotify=false\nInitialPreference=high\n",
+    )
+    .unwrap();
+    assert_eq!(entry.kde_substitute_uid(), Some(true));
+    assert_eq!(entry.kde_username(), None);
+    assert_eq!(entry.kde_startup_notify(), Some(false));
+    assert_eq!(entry.initial_preference(), None);
+    assert!(!entry.wants_non_default_gpu());
+
+    entry.set_kde_username(Some("root"));
+    entry.set_kde_protocols(Some(&["file", "smb"]));
+    entry.set_initial_preference(Some(-2));
+    entry.set_kde_startup_notify(None);
+    entry.set_kde_run_on_discrete_gpu(Some(true));
+    assert!(entry.wants_non_default_gpu());
+
+    // PrefersNonDefaultGPU takes precedence over the KDE key
+    entry.prefers_non_default_gpu = Some(false);
+    assert!(!entry.wants_non_default_gpu());
+
+    let reparsed = DesktopEntry::parse(&entry.serialize()).unwrap();
+    assert_eq!(reparsed.kde_username().as_deref(), Some("root"));
+    assert_eq!(reparsed.kde_protocols().unwrap(), ["file", "smb"]);
+    assert_eq!(reparsed.initial_preference(), Some(-2));
+    assert_eq!(reparsed.kde_startup_notify(), None);
+    assert_eq!(reparsed.kde_run_on_discrete_gpu(), Some(true));
+}
+
+// ============================================================================
+// Packaging tests
+// ============================================================================
+
+#[test]
+fn test_flatpak_app_id() {
+    let parse = |exec: &str, extra: &str| {
+        DesktopEntry::parse(&format!(
+            "[Desktop Entry]\nType=Application\nName=App\nExec={exec}\n{extra}"
+        ))
+        .unwrap()
+    };
+
+    let gimp = parse(
+        "/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=gimp --file-forwarding org.gimp.GIMP @@ %U @@",
+        "",
+    );
+    assert_eq!(gimp.flatpak_app_id().as_deref(), Some("org.gimp.GIMP"));
+    assert!(gimp.uses_flatpak_file_forwarding());
+
+    let by_ref = parse("flatpak run app/org.test.App/x86_64/stable %f", "");
+    assert_eq!(by_ref.flatpak_app_id().as_deref(), Some("org.test.App"));
+    assert!(!by_ref.uses_flatpak_file_forwarding());
+
+    // X-Flatpak takes precedence over the Exec line
+    let keyed = parse("flatpak run org.test.Other", "X-Flatpak=org.test.App\n");
+    assert_eq!(keyed.flatpak_app_id().as_deref(), Some("org.test.App"));
+    let invalid_key = parse("flatpak run org.test.Other", "X-Flatpak=not an id\n");
+    assert_eq!(
+        invalid_key.flatpak_app_id().as_deref(),
+        Some("org.test.Other")
+    );
+
+    assert_eq!(parse("gimp %U", "").flatpak_app_id(), None);
+    assert_eq!(
+        parse("flatpak install org.test.App", "").flatpak_app_id(),
+        None
+    );
+    assert_eq!(parse("flatpak run --user", "").flatpak_app_id(), None);
+}
+
+#[test]
+fn test_packaging_format() {
+    use xdg_desktop_entry::packaging::PackagingFormat;
+
+    let parse = |keys: &str| {
+        DesktopEntry::parse(&format!(
+            "[Desktop Entry]\nType=Application\nName=App\n{keys}"
+        ))
+        .unwrap()
+    };
+
+    assert_eq!(
+        parse("Exec=/usr/bin/flatpak run org.test.App\n").packaging_format(),
+        PackagingFormat::Flatpak
+    );
+    assert_eq!(
+        parse("Exec=/snap/bin/app %U\n").packaging_format(),
+        PackagingFormat::Snap
+    );
+    assert_eq!(
+        parse("Exec=env BAMF_DESKTOP_FILE_HINT=/x.desktop /snap/bin/app\n").packaging_format(),
+        PackagingFormat::Snap
+    );
+    assert_eq!(
+        parse("Exec=app\nX-SnapInstanceName=app_beta\n").packaging_format(),
+        PackagingFormat::Snap
+    );
+    assert_eq!(
+        parse("Exec=/home/user/Apps/Tool-x86_64.AppImage %F\n").packaging_format(),
+        PackagingFormat::AppImage
+    );
+    assert_eq!(
+        parse("Exec=AppRun\nX-AppImage-Version=1.2\n").packaging_format(),
+        PackagingFormat::AppImage
+    );
+    assert_eq!(
+        parse("Exec=env -u DISPLAY /usr/bin/app\n").packaging_format(),
+        PackagingFormat::Native
+    );
+    assert_eq!(
+        parse("Exec=env -u SNAP /snap/bin/app\n").packaging_format(),
+        PackagingFormat::Snap
+    );
+    assert_eq!(
+        parse("TryExec=app\n").packaging_format(),
+        PackagingFormat::Native
+    );
+    assert_eq!(
+        parse("DBusActivatable=true\n").packaging_format(),
+        PackagingFormat::Unknown
+    );
+    assert_eq!(PackagingFormat::AppImage.to_string(), "appimage");
+
+    assert_eq!(
+        PackagingFormat::from_path(
+            "/var/lib/flatpak/exports/share/applications/org.test.App.desktop"
+        ),
+        Some(PackagingFormat::Flatpak)
+    );
+    assert_eq!(
+        PackagingFormat::from_path("/var/lib/snapd/desktop/applications/app_app.desktop"),
+        Some(PackagingFormat::Snap)
+    );
+    assert_eq!(
+        PackagingFormat::from_path("/usr/share/applications/app.desktop"),
+        None
+    );
+}
+
+#[cfg(feature = "appimage")]
+#[test]
+fn test_appimage_extract() {
+    use xdg_desktop_entry::appimage;
+
+    let path = "tests/fixtures/appimage/Test-x86_64.AppImage";
+    let app = appimage::extract(path).unwrap();
+    let absolute = std::path::absolute(path).unwrap();
+    let absolute = absolute.to_str().unwrap();
+
+    assert_eq!(app.desktop_file_name, "test-app.desktop");
+    assert_eq!(app.entry.name.default, "Test App");
+    assert_eq!(
+        app.entry.exec.as_deref(),
+        Some(format!("{absolute} --open %F").as_str())
+    );
+    assert_eq!(app.entry.try_exec.as_deref(), Some(absolute));
+    assert_eq!(
+        app.entry.visible_actions(&[] as &[&str], &[])[0]
+            .exec
+            .as_deref(),
+        Some(format!("{absolute} --new-window").as_str())
+    );
+    assert_eq!(
+        app.entry.packaging_format(),
+        xdg_desktop_entry::packaging::PackagingFormat::AppImage
+    );
+
+    let icon = app.icon.unwrap();
+    assert_eq!(icon.file_name, "test-app.png");
+    assert!(icon.data.starts_with(b"\x89PNG"));
+
+    // Not an AppImage
+    assert!(appimage::extract("tests/fixtures/menu/applications.menu").is_err());
+
+    // A section header table past the end of the address space
+    let mut header = [0u8; 64];
+    header[..6].copy_from_slice(b"\x7fELF\x02\x01");
+    header[0x28..0x30].copy_from_slice(&u64::MAX.to_le_bytes());
+    header[0x3A..0x3C].copy_from_slice(&64u16.to_le_bytes());
+    header[0x3C..0x3E].copy_from_slice(&2u16.to_le_bytes());
+    let crafted = std::env::temp_dir().join(format!("xdg-appimage-{}", std::process::id()));
+    std::fs::write(&crafted, header).unwrap();
+    let err = appimage::extract(&crafted).unwrap_err();
+    std::fs::remove_file(&crafted).unwrap();
+    assert!(err.to_string().contains("out of range"), "{}", err);
+}
+
+#[test]
+fn test_install_and_uninstall() {
+    use xdg_desktop_entry::install::{self, InstallOptions};
+
+    let base = std::env::temp_dir().join(format!("xdg-install-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&base);
+    std::fs::create_dir_all(&base).unwrap();
+    let source = base.join("editor.desktop");
+    std::fs::write(
+        &source,
+        "[Desktop Entry]\nType=Application\nName=Editor\nExec=editor %F\n\
+         Categories=Utility;TextEditor;\nMimeType=text/plain;\nX-Build-Id=42\n",
+    )
+    .unwrap();
+
+    let options = InstallOptions {
+        dir: Some(base.join("applications")),
+        vendor: Some("example".to_string()),
+        add_categories: vec!["Development".to_string()],
+        remove_categories: vec!["Utility".to_string()],
+        add_mime_types: vec!["text/x-rust".to_string()],
+        mode: Some(0o600),
+        rebuild_mime_cache: true,
+        ..InstallOptions::default()
+    }
+    .set_key("NoDisplay", "true")
+    .remove_key("X-Build-Id")
+    .remove_key("X-Missing");
+
+    let path = install::install(&source, &options).unwrap();
+    assert_eq!(path, base.join("applications/example-editor.desktop"));
+    let installed = DesktopEntry::parse_file(&path).unwrap();
+    assert_eq!(installed.no_display, Some(true));
+    assert!(!installed.unknown_keys.contains_key("X-Build-Id"));
+    assert_eq!(installed.categories.unwrap(), ["TextEditor", "Development"]);
+    assert_eq!(installed.mime_type.unwrap(), ["text/plain", "text/x-rust"]);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    let cache = std::fs::read_to_string(base.join("applications/mimeinfo.cache")).unwrap();
+    assert!(cache.contains("text/x-rust=example-editor.desktop;"));
+
+    // The vendor prefix is not doubled
+    let reinstalled = install::install(&path, &options).unwrap();
+    assert_eq!(reinstalled, path);
+
+    // Invalid results and file names are rejected
+    let invalid = InstallOptions {
+        dir: Some(base.join("applications")),
+        ..InstallOptions::default()
+    }
+    .remove_key("Name");
+    assert!(install::install(&source, &invalid).is_err());
+    let entry = DesktopEntry::parse_file(&source).unwrap();
+    assert!(install::install_entry(&entry, "editor.txt", &invalid).is_err());
+
+    assert_eq!(
+        install::uninstall("example-editor.desktop", &options).unwrap(),
+        path
+    );
+    assert!(!path.exists());
+    let cache = std::fs::read_to_string(base.join("applications/mimeinfo.cache")).unwrap();
+    assert!(!cache.contains("example-editor.desktop"));
+    assert!(install::uninstall("example-editor.desktop", &options).is_err());
+
+    // IDs cannot reach files outside the directory
+    let victim = base.join("victim.desktop");
+    std::fs::copy(&source, &victim).unwrap();
+    for id in ["../victim.desktop", "..-victim.desktop"] {
+        assert!(install::uninstall(id, &options).is_err(), "{}", id);
+    }
+    assert!(victim.exists());
+
+    std::fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn test_create_override() {
+    use xdg_desktop_entry::KeyEdit;
+    use xdg_desktop_entry::discovery::DesktopFileId;
+
+    let base = std::env::temp_dir().join(format!("xdg-override-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&base);
+    let system = base.join("system/applications");
+    let user = base.join("user/applications");
+    std::fs::create_dir_all(system.join("kde4")).unwrap();
+    std::fs::write(
+        system.join("kde4/browser.desktop"),
+        "[Desktop Entry]\nType=Application\nName=Browser\nExec=browser \"--profile=a b\" %u\n",
+    )
+    .unwrap();
+
+    let entry = DesktopEntry::parse_file(system.join("kde4/browser.desktop")).unwrap();
+    let id = DesktopFileId::from_path(system.join("kde4/browser.desktop"), &system).unwrap();
+    let edit = entry
+        .append_exec_args_edit(&["--new-window", "a;b"])
+        .unwrap();
+    let hide = KeyEdit::Set {
+        group: "Desktop Entry".to_string(),
+        key: "Hidden".to_string(),
+        value: "true".to_string(),
+    };
+    let path = entry.create_override_in(&id, &user, &[edit, hide]).unwrap();
+    assert_eq!(path, user.join("kde4-browser.desktop"));
+
+    // The override shadows the system entry
+    assert_eq!(id.resolve_in(&[&user, &system]).unwrap(), path);
+    let shadow = DesktopEntry::parse_file(&path).unwrap();
+    assert_eq!(shadow.hidden, Some(true));
+    let exec = shadow.parsed_exec().unwrap();
+    let args: Vec<_> = exec
+        .args()
+        .iter()
+        .filter_map(|arg| arg.as_literal())
+        .collect();
+    assert_eq!(args, ["--profile=a b", "--new-window", "a;b"]);
+    assert!(exec.accepts_urls());
+
+    // Entries that were not loaded from an installed file have no ID
+    let unsaved =
+        DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=A\nExec=a\n").unwrap();
+    assert!(unsaved.create_override(&[]).is_err());
+    assert!(unsaved.append_exec_args_edit(&["-x"]).is_ok());
+
+    std::fs::remove_dir_all(&base).unwrap();
+}
+
+// ============================================================================
+// Comparison tests
+// ============================================================================
+
+#[test]
+fn test_semantic_eq_and_diff() {
+    use xdg_desktop_entry::diff::{ChangeKind, EntryChange};
+
+    let a = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nName[fr]=Appli\nExec=app\nX-Foo=1\n\n\
+         [Desktop Action new]\nName=New\nExec=app --new\n",
+    )
+    .unwrap();
+    let reordered = DesktopEntry::parse(
+        "# comment\n\n[Desktop Action new]\nExec=app --new\nName=New\n\n\
+         [Desktop Entry]\nX-Foo=1\nName[fr]=Appli\nExec=app\nName=App\nType=Application\n",
+    )
+    .unwrap();
+    assert!(a.semantic_eq(&reordered));
+    assert!(a.diff(&reordered).is_empty());
+
+    let b = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nName[fr]=Application\nExec=app\n\n\
+         [Desktop Action new]\nName=New\nExec=app --new-window\n",
+    )
+    .unwrap();
+    assert!(!a.semantic_eq(&b));
+    assert_eq!(
+        a.diff(&b),
+        [
+            EntryChange {
+                group: "Desktop Action new".to_string(),
+                key: "Exec".to_string(),
+                locale: None,
+                kind: ChangeKind::Changed {
+                    old: "app --new".to_string(),
+                    new: "app --new-window".to_string(),
+                },
+            },
+            EntryChange {
+                group: "Desktop Entry".to_string(),
+                key: "Name".to_string(),
+                locale: Some(Locale::from_string("fr")),
+                kind: ChangeKind::Changed {
+                    old: "Appli".to_string(),
+                    new: "Application".to_string(),
+                },
+            },
+            EntryChange {
+                group: "Desktop Entry".to_string(),
+                key: "X-Foo".to_string(),
+                locale: None,
+                kind: ChangeKind::Removed("1".to_string()),
+            },
+        ]
+    );
+    assert!(matches!(b.diff(&a)[2].kind, ChangeKind::Added(_)));
+}
+
+#[test]
+fn test_merged_entries() {
+    use xdg_desktop_entry::discovery::{Applications, DesktopFileId, MergeMode};
+    use xdg_desktop_entry::mimeapps::MimeApps;
+
+    let base = std::env::temp_dir().join(format!("xdg-merge-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&base);
+    let user = base.join("user/applications");
+    let system = base.join("system/applications");
+    std::fs::create_dir_all(&user).unwrap();
+    std::fs::create_dir_all(&system).unwrap();
+    std::fs::write(
+        system.join("app.desktop"),
+        "[Desktop Entry]\nType=Application\nName=App\nName[de]=Anwendung\nExec=app\nCategories=Utility;\n",
+    )
+    .unwrap();
+    // A partial override changing the name only
+    std::fs::write(user.join("app.desktop"), "[Desktop Entry]\nName=My App\n").unwrap();
+    std::fs::write(
+        system.join("gone.desktop"),
+        "[Desktop Entry]\nType=Application\nName=Gone\nExec=gone\n",
+    )
+    .unwrap();
+    std::fs::write(user.join("gone.desktop"), "[Desktop Entry]\nHidden=true\n").unwrap();
+
+    let applications = Applications::with_dirs(
+        vec![user.clone(), system.clone()],
+        MimeApps::load_from(Vec::new(), Vec::new()),
+    );
+    let id = DesktopFileId::new("app.desktop");
+    let (path, entry) = applications.get_merged(&id, MergeMode::Overlay).unwrap();
+    assert_eq!(path, user.join("app.desktop"));
+    assert_eq!(entry.name.default, "My App");
+    assert!(entry.name.localized.is_empty());
+    assert_eq!(entry.exec.as_deref(), Some("app"));
+    assert_eq!(entry.source_path.as_deref(), Some(path.as_path()));
+    // The partial file is not a valid entry on its own
+    assert!(applications.get(&id).is_none());
+
+    let gone = DesktopFileId::new("gone.desktop");
+    assert!(applications.get_merged(&gone, MergeMode::Overlay).is_none());
+    assert!(applications.get(&gone).is_none());
+    assert_eq!(gone.resolve_all_in(&[&user, &system]).len(), 2);
+
+    // Merging parsed entries
+    let system_entry = DesktopEntry::parse_file(system.join("app.desktop")).unwrap();
+    let full =
+        DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=Other\nExec=other\n").unwrap();
+    let shadowed = system_entry.merged_with(&full).unwrap();
+    assert!(shadowed.semantic_eq(&full));
+    let overlaid = system_entry
+        .merged_with_mode(&full, MergeMode::Overlay)
+        .unwrap();
+    assert_eq!(overlaid.exec.as_deref(), Some("other"));
+    assert_eq!(overlaid.categories.unwrap(), ["Utility"]);
+
+    std::fs::remove_dir_all(&base).unwrap();
+}
+
+// ============================================================================
+// Translation tests
+// ============================================================================
+
+#[test]
+fn test_localized_field_setters() {
+    let mut entry =
+        DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=Viewer\nExec=viewer\n")
+            .unwrap();
+    let de = Locale::from_string("de");
+
+    // Translations need an untranslated value
+    assert!(matches!(
+        entry.set_comment_localized(de.clone(), "Bilder ansehen"),
+        Err(DesktopEntryError::MissingRequiredKey(_))
+    ));
+    entry.set_comment(Some("View images"));
+    entry
+        .set_comment_localized(de.clone(), "Bilder ansehen")
+        .unwrap();
+    entry.set_generic_name(Some("Image Viewer"));
+    entry
+        .set_generic_name_localized(de.clone(), "Bildbetrachter")
+        .unwrap();
+    entry.set_keywords(Some(vec!["photo".to_string()]));
+    entry
+        .set_keywords_localized(de.clone(), vec!["Foto".to_string(), "Bild".to_string()])
+        .unwrap();
+    entry.set_icon(Some("viewer"));
+    entry.set_icon_localized(de.clone(), "viewer-de").unwrap();
+    assert!(entry.set_name_localized(de.clone(), "").is_err());
+    entry.set_name_localized(de.clone(), "Betrachter").unwrap();
+
+    let reparsed = DesktopEntry::parse(&entry.serialize()).unwrap();
+    assert_eq!(reparsed.name.get(&de), "Betrachter");
+    assert_eq!(
+        reparsed.comment.as_ref().unwrap().get(&de),
+        "Bilder ansehen"
+    );
+    assert_eq!(
+        reparsed.generic_name.as_ref().unwrap().get(&de),
+        "Bildbetrachter"
+    );
+    assert_eq!(
+        reparsed.keywords.as_ref().unwrap().get(&de),
+        ["Foto", "Bild"]
+    );
+    assert_eq!(reparsed.icon.as_ref().unwrap().get(&de), "viewer-de");
+
+    // Changing the untranslated value keeps the translations
+    entry.set_comment(Some("Look at images"));
+    assert_eq!(entry.comment.as_ref().unwrap().get(&de), "Bilder ansehen");
+    entry.set_icon(None);
+    assert!(entry.icon.is_none());
+
+    assert!(entry.remove_localization(&de));
+    assert_eq!(entry.name.get(&de), "Viewer");
+    assert!(entry.comment.as_ref().unwrap().localized.is_empty());
+    assert!(!entry.serialize().contains("[de]"));
+}
+
+#[test]
+fn test_entry_locales() {
+    let entry = DesktopEntry::parse_file("tests/fixtures/valid/full_entry.desktop").unwrap();
+    let locales = entry.locales();
+    for locale in entry.name.localized.keys() {
+        assert!(locales.contains(locale));
+    }
+
+    let mut entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\nIcon=app\nIcon[fr_CA]=app-ca\n\
+         X-Subtitle=Sub\nX-Subtitle[ja]=サブ\n",
+    )
+    .unwrap();
+    let locales: Vec<_> = entry.locales().into_iter().collect();
+    assert_eq!(
+        locales,
+        [Locale::from_string("fr_CA"), Locale::from_string("ja")]
+    );
+    entry.remove_localization(&Locale::from_string("ja"));
+    assert_eq!(entry.locales().len(), 1);
+    assert!(
+        DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=App\nExec=app\n")
+            .unwrap()
+            .locales()
+            .is_empty()
+    );
+}
+
+#[cfg(feature = "gettext")]
+#[test]
+fn test_gettext_catalog_round_trip() {
+    use xdg_desktop_entry::po::Catalog;
+
+    let mut entry = DesktopEntry::parse(
+        "[Desktop Entry]\nType=Application\nName=Viewer\nName[de]=Betrachter\n\
+         GenericName=Image Viewer\nComment=View \"images\"\nKeywords=photo;picture;\n\
+         Exec=viewer %f\nActions=open;\n\n[Desktop Action open]\nName=Viewer\nExec=viewer\n",
+    )
+    .unwrap();
+
+    let template = entry.to_catalog();
+    let ids: Vec<&str> = template.messages.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        [
+            "Viewer",
+            "Image Viewer",
+            "View \"images\"",
+            "photo;picture;"
+        ]
+    );
+    assert_eq!(
+        template.messages[0].comments,
+        ["Name", "Desktop Action open/Name"]
+    );
+    assert_eq!(Catalog::parse(&template.serialize()).unwrap(), template);
+
+    let de = Locale::from_string("de");
+    let german = entry.to_catalog_for(&de);
+    assert_eq!(german.translation("Viewer"), Some("Betrachter"));
+    assert_eq!(german.translation("Image Viewer"), None);
+
+    let po = "# French\nmsgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n\n\
+              msgid \"Viewer\"\nmsgstr \"Visionneuse\"\n\n\
+              msgid \"View \\\"images\\\"\"\nmsgstr \"\"\n\"Voir des \"\n\"images\"\n\n\
+              #, fuzzy\nmsgid \"Image Viewer\"\nmsgstr \"Visionneur\"\n\n\
+              msgid \"photo;picture;\"\nmsgstr \"photo;image;\"\n\n\
+              #~ msgid \"Old\"\n#~ msgstr \"Vieux\"\n";
+    let french = Catalog::parse(po).unwrap();
+    assert!(french.header.starts_with("Content-Type"));
+    assert_eq!(french.messages.len(), 4);
+
+    let fr = Locale::from_string("fr");
+    assert_eq!(entry.apply_catalog(&fr, &french), 4);
+    let serialized = entry.serialize();
+    assert!(serialized.contains("Name[fr]=Visionneuse\n"));
+    assert!(serialized.contains("Comment[fr]=Voir des images\n"));
+    assert_eq!(
+        entry.keywords.as_ref().unwrap().get(&fr),
+        &["photo", "image"]
+    );
+    assert!(!serialized.contains("GenericName[fr]"));
+    let action = serialized.split("[Desktop Action open]").nth(1).unwrap();
+    assert!(action.contains("Name[fr]=Visionneuse\n"));
+
+    assert!(matches!(
+        Catalog::parse("msgstr \"orphan\"\n"),
+        Err(DesktopEntryError::InvalidPo(_))
+    ));
+}
+
+#[cfg(feature = "encoding")]
+#[test]
+fn test_parse_legacy_encodings() {
+    use xdg_desktop_entry::encoding::{DetectedEncoding, EncodingPolicy, decode};
+    use xdg_desktop_entry::{DesktopEntryError, Locale};
+
+    // Name[ru] is KOI8-R, Comment[de] is Latin-1, and the rest is UTF-8
+    let mut content =
+        b"[Desktop Entry]\nEncoding=Legacy-Mixed\nType=Application\nName=Editor\n".to_vec();
+    content.extend_from_slice(b"Name[ru_RU.KOI8-R]=\xf0\xd2\xc1\xd7\xcb\xc1\n");
+    content.extend_from_slice(b"Comment=Edit files \xe2\x9c\x8e\n");
+    content.extend_from_slice(b"Comment[de]=Dateien \xe4ndern\nExec=editor\n");
+
+    assert!(matches!(
+        DesktopEntry::parse_bytes(&content, EncodingPolicy::default()),
+        Err(DesktopEntryError::InvalidUtf8)
+    ));
+
+    let policy = EncodingPolicy::LegacyMixed(encoding_rs::WINDOWS_1252);
+    let (entry, detected) = DesktopEntry::parse_bytes(&content, policy).unwrap();
+    assert_eq!(detected, DetectedEncoding::Mixed);
+    assert!(detected.is_legacy());
+    assert_eq!(entry.name.get(&Locale::from_string("ru_RU")), "Правка");
+    let comment = entry.comment.as_ref().unwrap();
+    assert_eq!(comment.default, "Edit files \u{270e}");
+    assert_eq!(comment.get(&Locale::from_string("de")), "Dateien ändern");
+
+    let (_, detected) = decode(b"[Desktop Entry]\n", policy).unwrap();
+    assert_eq!(detected, DetectedEncoding::Utf8);
+}